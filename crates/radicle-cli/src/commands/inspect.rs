@@ -9,8 +9,10 @@ use std::str::FromStr;
 use anyhow::Context as _;
 use chrono::prelude::*;
 
+use radicle::git::fmt::{Qualified, RefString};
+use radicle::identity::crefs::GetCanonicalRefs as _;
 use radicle::identity::RepoId;
-use radicle::identity::{DocAt, Identity};
+use radicle::identity::{CanonicalRefs, Doc, DocAt, Identity};
 use radicle::node::policy::SeedingPolicy;
 use radicle::node::AliasStore as _;
 use radicle::storage::git::{Repository, Storage};
@@ -116,6 +118,10 @@ pub fn run(args: Args, ctx: impl term::Context) -> anyhow::Result<()> {
             let (_, doc) = repo(rid, storage)?;
             println!("{}", term::format::visibility(doc.visibility()));
         }
+        Target::Explain(refname) => {
+            let (_, doc) = repo(rid, storage)?;
+            explain(&doc, &refname)?;
+        }
         Target::History => {
             let (repo, _) = repo(rid, storage)?;
             let identity = Identity::load(&repo)?;
@@ -193,6 +199,50 @@ fn repo(rid: RepoId, storage: &Storage) -> anyhow::Result<(Repository, DocAt)> {
     Ok((repo, doc))
 }
 
+/// Explain how the canonical reference rules of `doc` apply to `refname`.
+fn explain(doc: &Doc, refname: &RefString) -> anyhow::Result<()> {
+    let refname = Qualified::from_refstr(refname).ok_or_else(|| {
+        anyhow::anyhow!("reference must be fully-qualified, eg. 'refs/heads/master'")
+    })?;
+    let crefs = match doc.canonical_refs()? {
+        Some(crefs) => crefs,
+        None => CanonicalRefs::from_iter([doc.default_branch_rule()?]),
+    };
+    let explanation = crefs.rules().explain(&refname);
+    let Some((selected, rule)) = explanation.selected() else {
+        term::info!(
+            "No canonical reference rules match {}",
+            term::format::tertiary(&refname)
+        );
+        return Ok(());
+    };
+
+    for (pattern, candidate) in explanation.candidates() {
+        let threshold = format!("threshold {}", candidate.threshold());
+        if *pattern == selected {
+            println!(
+                "{} {} {}",
+                term::format::positive("*"),
+                term::format::positive(pattern),
+                term::format::dim(threshold)
+            );
+        } else {
+            println!("  {} {}", pattern, term::format::dim(threshold));
+        }
+    }
+    println!();
+    println!(
+        "{} is canonical when {} of the following agree:",
+        term::format::tertiary(&refname),
+        rule.threshold(),
+    );
+    for did in rule.allowed().iter() {
+        println!("{}", term::format::tertiary(did));
+    }
+
+    Ok(())
+}
+
 fn refs(repo: &radicle::storage::git::Repository) -> anyhow::Result<()> {
     let mut refs = Vec::new();
     for r in repo.references()? {
@@ -1,5 +1,8 @@
 use clap::Parser;
 
+use radicle::git;
+use radicle::git::fmt::RefString;
+
 const ABOUT: &str = "Inspect a Radicle repository";
 const LONG_ABOUT: &str = r#"Inspects the given path or RID. If neither is specified,
 the current repository is inspected.
@@ -43,10 +46,31 @@ pub(super) struct TargetArgs {
     /// Inspect the repository's visibility
     #[arg(long)]
     pub(super) visibility: bool,
+
+    /// Explain which canonical reference rules match the given reference,
+    /// and which one of them applies
+    #[arg(
+        long,
+        value_name = "REFNAME",
+        value_parser = parse_refstr,
+        conflicts_with_all = [
+            "delegates",
+            "history",
+            "identity",
+            "payload",
+            "policy",
+            "refs",
+            "rid",
+            "sigrefs",
+            "visibility",
+        ],
+    )]
+    pub(super) explain: Option<RefString>,
 }
 
 pub(super) enum Target {
     Delegates,
+    Explain(RefString),
     History,
     Identity,
     Payload,
@@ -59,6 +83,9 @@ pub(super) enum Target {
 
 impl From<TargetArgs> for Target {
     fn from(args: TargetArgs) -> Self {
+        if let Some(refname) = args.explain {
+            return Target::Explain(refname);
+        }
         match (
             args.delegates,
             args.history,
@@ -85,6 +112,10 @@ impl From<TargetArgs> for Target {
     }
 }
 
+fn parse_refstr(refstr: &str) -> Result<RefString, git::fmt::Error> {
+    RefString::try_from(refstr)
+}
+
 #[derive(Debug, Parser)]
 #[command(about = ABOUT, long_about = LONG_ABOUT, disable_version_flag = true)]
 pub struct Args {
@@ -95,3 +126,23 @@ pub struct Args {
     #[clap(flatten)]
     pub(super) target: TargetArgs,
 }
+
+#[cfg(test)]
+mod test {
+    use super::Args;
+    use clap::error::ErrorKind;
+    use clap::Parser;
+
+    #[test]
+    fn should_parse_explain() {
+        let args = Args::try_parse_from(["inspect", "--explain", "refs/heads/master"]);
+        assert!(args.is_ok())
+    }
+
+    #[test]
+    fn should_not_parse_explain_with_other_target() {
+        let err = Args::try_parse_from(["inspect", "--explain", "refs/heads/master", "--refs"])
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ArgumentConflict);
+    }
+}
@@ -68,13 +68,17 @@ pub fn run(args: Args, ctx: impl term::Context) -> anyhow::Result<()> {
                 table.print();
             }
         }
-        Command::Events { timeout, count } => {
+        Command::Events {
+            timeout,
+            count,
+            json,
+        } => {
             let count = count.unwrap_or(usize::MAX);
             let timeout = timeout
                 .map(time::Duration::from_secs)
                 .unwrap_or(time::Duration::MAX);
 
-            events::run(node, count, timeout)?;
+            events::run(node, count, timeout, json)?;
         }
         Command::Routing { rid, nid, json } => {
             let store = profile.database()?;
@@ -134,6 +134,12 @@ pub(super) enum Command {
         /// Exit after <COUNT> events
         #[arg(long, short = 'n')]
         count: Option<usize>,
+
+        /// Output one JSON record per line, with the event type, the time
+        /// the event was received by `rad`, and the node, repository and
+        /// object IDs involved
+        #[arg(long)]
+        json: bool,
     },
 
     /// Show the routing table
@@ -298,6 +298,13 @@ pub fn status(node: &Node, profile: &Profile) -> anyhow::Result<()> {
             listen.join(", ")
         );
     }
+    let uptime = match node.uptime() {
+        Ok(uptime) => term::format::duration(uptime),
+        // Older nodes don't report their uptime.
+        Err(e) if e.is_unknown_command() => term::Paint::new(String::from("unavailable")),
+        Err(e) => return Err(e.into()),
+    };
+    term::info!("Uptime: {}", term::format::dim(uptime));
 
     let sessions = sessions(node)?;
     if let Some(table) = sessions {
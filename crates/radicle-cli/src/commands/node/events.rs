@@ -1,17 +1,96 @@
+use std::io::{self, Write};
 use std::time;
 
-use radicle::node::{Event, Handle};
+use localtime::LocalTime;
+use serde::{Deserialize, Serialize};
 
-pub fn run<H>(node: H, count: usize, timeout: time::Duration) -> anyhow::Result<()>
+use radicle::git::Oid;
+use radicle::node::{Event, Handle, Timestamp};
+use radicle::prelude::{NodeId, RepoId};
+
+/// An event, as output by `rad node events --json`.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Record {
+    /// The event type, eg. `refsFetched`.
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// When the event was received by `rad`, in milliseconds since the epoch.
+    ///
+    /// Events don't carry the time they occurred at on the node, so this is
+    /// later than that, by however long the event took to reach us.
+    pub timestamp: Timestamp,
+    /// The other node involved in the event.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nid: Option<NodeId>,
+    /// The repository the event is about.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rid: Option<RepoId>,
+    /// The Git object the event is about.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub oid: Option<Oid>,
+    /// The event itself.
+    pub event: Event,
+}
+
+impl Record {
+    /// Create a record of an event received by `rad` at the given time.
+    pub fn new(event: Event, timestamp: Timestamp) -> Result<Self, serde_json::Error> {
+        let kind = match serde_json::to_value(&event)? {
+            serde_json::Value::Object(mut obj) => match obj.remove("type") {
+                Some(serde_json::Value::String(kind)) => kind,
+                _ => String::new(),
+            },
+            _ => String::new(),
+        };
+
+        Ok(Self {
+            kind,
+            timestamp,
+            nid: event.nid().copied(),
+            rid: event.rid().copied(),
+            oid: event.oid().copied(),
+            event,
+        })
+    }
+}
+
+pub fn run<H>(node: H, count: usize, timeout: time::Duration, json: bool) -> anyhow::Result<()>
 where
     H: Handle<Event = Result<Event, <H as Handle>::Error>>,
 {
     let events = node.subscribe(timeout)?;
+
+    write(
+        events,
+        count,
+        json,
+        || LocalTime::now().into(),
+        &mut io::stdout().lock(),
+    )
+}
+
+/// Write up to `count` events to the output, one per line.
+fn write<E>(
+    events: impl IntoIterator<Item = Result<Event, E>>,
+    count: usize,
+    json: bool,
+    now: impl Fn() -> Timestamp,
+    output: &mut impl Write,
+) -> anyhow::Result<()>
+where
+    E: std::error::Error + Send + Sync + 'static,
+{
     for (i, event) in events.into_iter().enumerate() {
         let event = event?;
-        let obj = serde_json::to_string(&event)?;
 
-        println!("{obj}");
+        if json {
+            serde_json::to_writer(&mut *output, &Record::new(event, now())?)?;
+        } else {
+            serde_json::to_writer(&mut *output, &event)?;
+        }
+        writeln!(output)?;
+        output.flush()?;
 
         // Only output up to `count` events.
         if i + 1 >= count {
@@ -21,3 +100,67 @@ where
 
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use std::convert::Infallible;
+
+    use super::*;
+
+    #[test]
+    fn test_json() {
+        let nid: NodeId = "z6MknSLrJoTcukLrE435hVNQT4JUhbvWLX4kUzqkEStBU8Vi"
+            .parse()
+            .unwrap();
+        let rid: RepoId = "rad:z42hL2jL4XNk6K8oHQaSWfMgCL7ji".parse().unwrap();
+        let at: Oid = "f2de534b5e81d7c6e2dcaf58c3dd91573c0a0354".parse().unwrap();
+        let events = [
+            Event::PeerConnected { nid },
+            Event::RefsSynced {
+                remote: nid,
+                rid,
+                at,
+            },
+            Event::PeerDisconnected {
+                nid,
+                reason: String::from("timeout"),
+            },
+        ];
+        let mut output = Vec::new();
+
+        write(
+            events.into_iter().map(Ok::<_, Infallible>),
+            2,
+            true,
+            || Timestamp::try_from(1671125284000).unwrap(),
+            &mut output,
+        )
+        .unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        let records = output
+            .lines()
+            .map(|line| serde_json::from_str::<Record>(line).unwrap())
+            .collect::<Vec<_>>();
+
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].kind, "peerConnected");
+        assert_eq!(*records[0].timestamp, 1671125284000);
+        assert_eq!(records[0].nid, Some(nid));
+        assert_eq!(records[0].rid, None);
+        assert_eq!(records[0].oid, None);
+        assert!(matches!(records[0].event, Event::PeerConnected { .. }));
+
+        assert_eq!(records[1].kind, "refsSynced");
+        assert_eq!(records[1].nid, Some(nid));
+        assert_eq!(records[1].rid, Some(rid));
+        assert_eq!(records[1].oid, Some(at));
+        assert!(matches!(records[1].event, Event::RefsSynced { .. }));
+
+        // Fields that don't apply are omitted.
+        let line = output.lines().next().unwrap();
+        let obj = serde_json::from_str::<serde_json::Value>(line).unwrap();
+        assert!(obj.get("rid").is_none());
+        assert!(obj.get("oid").is_none());
+    }
+}
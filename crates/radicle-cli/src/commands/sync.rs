@@ -647,17 +647,25 @@ fn print_announcer_result(result: &sync::AnnouncerResult, verbose: bool) {
                 for node in timed_out {
                     term::warning(format!("{} timed out", term::format::node_id_human(node)));
                 }
+                print_failed(result.failed());
             }
         }
         sync::AnnouncerResult::NoNodes(result) => {
             term::info!("Announcement could not sync with anymore seeds.");
             if verbose {
-                print_synced(result.synced())
+                print_synced(result.synced());
+                print_failed(result.failed());
             }
         }
     }
 }
 
+fn print_failed(failed: &BTreeMap<NodeId, sync::announce::FailureReason>) {
+    for (node, reason) in failed.iter() {
+        term::warning(format!("{}: {reason}", term::format::node_id_human(node)));
+    }
+}
+
 fn print_synced(synced: &BTreeMap<NodeId, sync::announce::SyncStatus>) {
     for (node, status) in synced.iter() {
         let mut message = format!("🌱 Synced with {}", term::format::node_id_human(node));
@@ -53,7 +53,7 @@ pub fn addr_compact(address: &Address) -> Paint<String> {
 
 /// Format a git Oid.
 pub fn oid(oid: impl Into<radicle::git::Oid>) -> Paint<String> {
-    Paint::new(format!("{:.7}", oid.into()))
+    Paint::new(oid.into().abbreviated().to_string())
 }
 
 /// Wrap parenthesis around styled input, eg. `"input"` -> `"(input)"`.
@@ -108,6 +108,23 @@ pub fn timestamp(time: impl Into<LocalTime>) -> Paint<String> {
     Paint::new(fmt.convert(duration.into()))
 }
 
+/// Format a duration, eg. `2d 3h 5m`. Seconds are only shown for durations
+/// shorter than an hour.
+pub fn duration(duration: std::time::Duration) -> Paint<String> {
+    let secs = duration.as_secs();
+    let (days, hours, mins) = (secs / 86_400, secs / 3_600 % 24, secs / 60 % 60);
+    let duration = if days > 0 {
+        format!("{days}d {hours}h {mins}m")
+    } else if hours > 0 {
+        format!("{hours}h {mins}m")
+    } else if mins > 0 {
+        format!("{mins}m {}s", secs % 60)
+    } else {
+        format!("{secs}s")
+    };
+    Paint::new(duration)
+}
+
 pub fn bytes(size: usize) -> Paint<String> {
     const KB: usize = 1024;
     const MB: usize = 1024usize.pow(2);
@@ -381,6 +398,20 @@ mod test {
     use super::*;
     use html::strip_comments;
 
+    #[test]
+    fn test_duration() {
+        use std::time::Duration;
+
+        assert_eq!(duration(Duration::ZERO).to_string(), "0s");
+        assert_eq!(duration(Duration::from_secs(59)).to_string(), "59s");
+        assert_eq!(duration(Duration::from_secs(61)).to_string(), "1m 1s");
+        assert_eq!(duration(Duration::from_secs(3_600)).to_string(), "1h 0m");
+        assert_eq!(
+            duration(Duration::from_secs(2 * 86_400 + 3 * 3_600 + 5 * 60 + 7)).to_string(),
+            "2d 3h 5m"
+        );
+    }
+
     #[test]
     fn test_strip_comments() {
         let test = "\
@@ -0,0 +1,88 @@
+//! Procedural macros backing the `*_checked!` macros of
+//! `radicle-git-ref-format`, enabled by its feature flag `proc-macro`.
+//!
+//! Each macro in this crate takes a single string literal and expands to
+//! nothing if the literal is valid, and to a [`compile_error`] otherwise.
+//! The conversion itself is left to the declarative macros in
+//! `radicle-git-ref-format`, so that this crate does not need to know how
+//! to construct the types it validates.
+//!
+//! This crate is not meant to be used directly.
+
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, LitStr};
+
+use git_ref_format_core::refspec::{PatternStr, PatternString, QualifiedPattern};
+use git_ref_format_core::{Component, Error, Qualified, RefString};
+
+/// Validate that the literal is a valid [`RefString`].
+#[proc_macro]
+pub fn validate_refname(input: TokenStream) -> TokenStream {
+    let lit = parse_macro_input!(input as LitStr);
+    validate(&lit, "a valid reference name", |s| {
+        RefString::try_from(s).map(|_| ()).map_err(Some)
+    })
+}
+
+/// Validate that the literal is a valid [`Qualified`].
+#[proc_macro]
+pub fn validate_qualified(input: TokenStream) -> TokenStream {
+    let lit = parse_macro_input!(input as LitStr);
+    validate(&lit, "of the form 'refs/<category>/<name>'", |s| {
+        let refstring = RefString::try_from(s).map_err(Some)?;
+        Qualified::from_refstr(refstring).map(|_| ()).ok_or(None)
+    })
+}
+
+/// Validate that the literal is a valid [`Component`].
+#[proc_macro]
+pub fn validate_component(input: TokenStream) -> TokenStream {
+    let lit = parse_macro_input!(input as LitStr);
+    validate(&lit, "a valid component (cannot contain '/')", |s| {
+        let refstring = RefString::try_from(s).map_err(Some)?;
+        Component::from_refstr(refstring).map(|_| ()).ok_or(None)
+    })
+}
+
+/// Validate that the literal is a valid [`PatternString`].
+#[proc_macro]
+pub fn validate_pattern(input: TokenStream) -> TokenStream {
+    let lit = parse_macro_input!(input as LitStr);
+    validate(&lit, "a valid refspec pattern", |s| {
+        PatternString::try_from(s).map(|_| ()).map_err(Some)
+    })
+}
+
+/// Validate that the literal is a valid [`QualifiedPattern`].
+#[proc_macro]
+pub fn validate_qualified_pattern(input: TokenStream) -> TokenStream {
+    let lit = parse_macro_input!(input as LitStr);
+    validate(&lit, "a valid qualified refspec pattern", |s| {
+        let pattern = PatternStr::try_from_str(s).map_err(Some)?;
+        QualifiedPattern::from_patternstr(pattern)
+            .map(|_| ())
+            .ok_or(None)
+    })
+}
+
+/// Run `check` on the value of `lit`, and turn a failure into a compile error
+/// pointing at `lit`. The error returned by `check` is optional, for checks
+/// that do not report a reason.
+fn validate<F>(lit: &LitStr, expected: &str, check: F) -> TokenStream
+where
+    F: FnOnce(&str) -> Result<(), Option<Error>>,
+{
+    let value = lit.value();
+    match check(&value) {
+        Ok(()) => TokenStream::new(),
+        Err(reason) => {
+            let message = match reason {
+                Some(reason) => format!("literal `{value}` must be {expected}: {reason}"),
+                None => format!("literal `{value}` must be {expected}"),
+            };
+            syn::Error::new(lit.span(), message)
+                .to_compile_error()
+                .into()
+        }
+    }
+}
@@ -0,0 +1,3 @@
+fn main() {
+    let _ = radicle_git_ref_format::component_checked!("a/b");
+}
@@ -0,0 +1,3 @@
+fn main() {
+    let _ = radicle_git_ref_format::qualified_pattern_checked!("a/*/b");
+}
@@ -0,0 +1,15 @@
+use radicle_git_ref_format::{
+    component_checked, pattern_checked, qualified_checked, qualified_pattern_checked,
+    refname_checked,
+};
+
+fn main() {
+    assert_eq!(refname_checked!("refs/heads/main").as_str(), "refs/heads/main");
+    assert_eq!(qualified_checked!("refs/tags/v1.0.0").as_str(), "refs/tags/v1.0.0");
+    assert_eq!(component_checked!("main").as_str(), "main");
+    assert_eq!(pattern_checked!("a/*/c").as_str(), "a/*/c");
+    assert_eq!(
+        qualified_pattern_checked!("refs/remotes/origin/*").as_str(),
+        "refs/remotes/origin/*"
+    );
+}
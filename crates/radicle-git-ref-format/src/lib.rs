@@ -24,9 +24,68 @@
 //! conjunction with testing: If all generated objects are used in tests, and
 //! these tests are run, then the guarantees are equally strong. Consumers that
 //! do not or cannot test their code should not use the macros then.
+//!
+//! ## Checked Macros
+//!
+//! For consumers that prefer compile time validation over fewer dependencies,
+//! the feature flag `proc-macro` provides [`refname_checked`],
+//! [`qualified_checked`], [`component_checked`], [`pattern_checked`] and
+//! [`qualified_pattern_checked`]. These validate their argument using a
+//! procedural macro, and fail to compile if it is invalid, regardless of
+//! whether debug assertions are enabled. In turn, they pull in `syn` and its
+//! dependencies at build time.
 
 pub use git_ref_format_core::*;
 
+/// Join `components` into a [`RefString`], separating them with `/`.
+///
+/// Returns `None` if `components` is empty, as the empty string is not a valid
+/// reference name.
+pub fn join<'a, I>(components: I) -> Option<RefString>
+where
+    I: IntoIterator<Item = Component<'a>>,
+{
+    let mut components = components.into_iter();
+    let first = components.next()?.to_ref_string();
+    Some(components.fold(first, |refname, component| refname.join(component)))
+}
+
+/// Iterate over the [`Component`]s of `refname`, i.e. the parts separated by
+/// `/`.
+///
+/// This is equivalent to [`RefStr::components`], without having to name the
+/// iterator type.
+pub fn components(refname: &RefStr) -> impl Iterator<Item = Component<'_>> {
+    refname.components()
+}
+
+/// Strip the components of `prefix` from the start of `refname`, eg.
+/// `refs/heads` from `refs/heads/feature/x`, leaving `feature/x`.
+///
+/// Unlike stripping a string prefix, this only matches whole components, ie.
+/// `refs/head` is not a prefix of `refs/heads/main`. Returns `None` if
+/// `refname` does not start with `prefix`, or if nothing would be left.
+pub fn strip_prefix(refname: &RefStr, prefix: &RefStr) -> Option<RefString> {
+    let mut components = refname.components();
+    for expected in prefix.components() {
+        if components.next()? != expected {
+            return None;
+        }
+    }
+    join(components)
+}
+
+/// Move `refname` to another `category`, keeping the rest of its components,
+/// eg. `refs/heads/feature/x` to `refs/rad/feature/x`.
+pub fn reparent(refname: &Qualified, category: Component) -> Qualified<'static> {
+    let (refs, _, name, rest) = refname.non_empty_components();
+    let refname = join([refs, category, name].into_iter().chain(rest))
+        .expect("there is at least one component");
+
+    Qualified::from_refstr(refname)
+        .expect("a reference with three or more components starting with `refs` is qualified")
+}
+
 /// Create a [`git_ref_format_core::RefString`] from a string literal.
 ///
 /// Similar to [`core::debug_assert`], an optimized build will not validate
@@ -36,10 +95,10 @@ pub use git_ref_format_core::*;
 #[macro_export]
 macro_rules! refname {
     ($arg:literal) => {{
-        use $crate::RefString;
-
         #[cfg(debug_assertions)]
         {
+            use $crate::RefString;
+
             RefString::try_from($arg).expect(core::concat!(
                 "literal `",
                 $arg,
@@ -49,12 +108,7 @@ macro_rules! refname {
 
         #[cfg(not(debug_assertions))]
         {
-            extern crate alloc;
-
-            use alloc::string::String;
-
-            let s: String = $arg.to_owned();
-            unsafe { core::mem::transmute::<_, RefString>(s) }
+            $crate::__refname_unchecked!($arg)
         }
     }};
 }
@@ -68,10 +122,10 @@ macro_rules! refname {
 #[macro_export]
 macro_rules! qualified {
     ($arg:literal) => {{
-        use $crate::Qualified;
-
         #[cfg(debug_assertions)]
         {
+            use $crate::Qualified;
+
             Qualified::from_refstr($crate::refname!($arg)).expect(core::concat!(
                 "literal `",
                 $arg,
@@ -81,21 +135,7 @@ macro_rules! qualified {
 
         #[cfg(not(debug_assertions))]
         {
-            extern crate alloc;
-
-            use core::mem::transmute;
-
-            use alloc::borrow::Cow;
-            use alloc::string::String;
-
-            use $crate::{RefStr, RefString};
-
-            let s: String = $arg.to_owned();
-            let refstring: RefString = unsafe { transmute(s) };
-            let cow: Cow<'_, RefStr> = Cow::Owned(refstring);
-            let qualified: Qualified = unsafe { transmute(cow) };
-
-            qualified
+            $crate::__qualified_unchecked!($arg)
         }
     }};
 }
@@ -109,10 +149,10 @@ macro_rules! qualified {
 #[macro_export]
 macro_rules! component {
     ($arg:literal) => {{
-        use $crate::Component;
-
         #[cfg(debug_assertions)]
         {
+            use $crate::Component;
+
             Component::from_refstr($crate::refname!($arg)).expect(core::concat!(
                 "literal `",
                 $arg,
@@ -122,21 +162,7 @@ macro_rules! component {
 
         #[cfg(not(debug_assertions))]
         {
-            extern crate alloc;
-
-            use core::mem::transmute;
-
-            use alloc::borrow::Cow;
-            use alloc::string::String;
-
-            use $crate::{RefStr, RefString};
-
-            let s: String = $arg.to_owned();
-            let refstring: RefString = unsafe { transmute(s) };
-            let cow: Cow<'_, RefStr> = Cow::Owned(refstring);
-            let component: Component = unsafe { transmute(cow) };
-
-            component
+            $crate::__component_unchecked!($arg)
         }
     }};
 }
@@ -150,10 +176,10 @@ macro_rules! component {
 #[macro_export]
 macro_rules! pattern {
     ($arg:literal) => {{
-        use $crate::refspec::PatternString;
-
         #[cfg(debug_assertions)]
         {
+            use $crate::refspec::PatternString;
+
             PatternString::try_from($arg).expect(core::concat!(
                 "literal `",
                 $arg,
@@ -163,12 +189,7 @@ macro_rules! pattern {
 
         #[cfg(not(debug_assertions))]
         {
-            extern crate alloc;
-
-            use alloc::string::String;
-
-            let s: String = $arg.to_owned();
-            unsafe { core::mem::transmute::<_, PatternString>(s) }
+            $crate::__pattern_unchecked!($arg)
         }
     }};
 }
@@ -182,13 +203,11 @@ macro_rules! pattern {
 #[macro_export]
 macro_rules! qualified_pattern {
     ($arg:literal) => {{
-        use $crate::refspec::QualifiedPattern;
-
         #[cfg(debug_assertions)]
         {
             use core::concat;
 
-            use $crate::refspec::PatternStr;
+            use $crate::refspec::{PatternStr, QualifiedPattern};
 
             let pattern = PatternStr::try_from_str($arg).expect(concat!(
                 "literal `",
@@ -205,27 +224,277 @@ macro_rules! qualified_pattern {
 
         #[cfg(not(debug_assertions))]
         {
-            extern crate alloc;
+            $crate::__qualified_pattern_unchecked!($arg)
+        }
+    }};
+}
 
-            use core::mem::transmute;
+/// Implementation detail of [`refname`] and [`refname_checked`].
+///
+/// Performs an unsafe conversion, the caller must validate the argument.
+#[doc(hidden)]
+#[cfg(any(feature = "macro", feature = "proc-macro", test))]
+#[macro_export]
+macro_rules! __refname_unchecked {
+    ($arg:literal) => {{
+        extern crate alloc;
 
-            use alloc::borrow::Cow;
-            use alloc::string::String;
+        use alloc::string::String;
 
-            use $crate::refspec::{PatternStr, PatternString};
+        use $crate::RefString;
 
-            let s: String = $arg.to_owned();
-            let pattern: PatternString = unsafe { transmute(s) };
-            let cow: Cow<'_, PatternStr> = Cow::Owned(pattern);
-            let qualified: QualifiedPattern = unsafe { transmute(cow) };
+        let s: String = String::from($arg);
+        unsafe { core::mem::transmute::<_, RefString>(s) }
+    }};
+}
 
-            qualified
-        }
+/// Implementation detail of [`qualified`] and [`qualified_checked`].
+///
+/// Performs an unsafe conversion, the caller must validate the argument.
+#[doc(hidden)]
+#[cfg(any(feature = "macro", feature = "proc-macro", test))]
+#[macro_export]
+macro_rules! __qualified_unchecked {
+    ($arg:literal) => {{
+        extern crate alloc;
+
+        use core::mem::transmute;
+
+        use alloc::borrow::Cow;
+        use alloc::string::String;
+
+        use $crate::{Qualified, RefStr, RefString};
+
+        let s: String = String::from($arg);
+        let refstring: RefString = unsafe { transmute(s) };
+        let cow: Cow<'_, RefStr> = Cow::Owned(refstring);
+        let qualified: Qualified = unsafe { transmute(cow) };
+
+        qualified
+    }};
+}
+
+/// Implementation detail of [`component`] and [`component_checked`].
+///
+/// Performs an unsafe conversion, the caller must validate the argument.
+#[doc(hidden)]
+#[cfg(any(feature = "macro", feature = "proc-macro", test))]
+#[macro_export]
+macro_rules! __component_unchecked {
+    ($arg:literal) => {{
+        extern crate alloc;
+
+        use core::mem::transmute;
+
+        use alloc::borrow::Cow;
+        use alloc::string::String;
+
+        use $crate::{Component, RefStr, RefString};
+
+        let s: String = String::from($arg);
+        let refstring: RefString = unsafe { transmute(s) };
+        let cow: Cow<'_, RefStr> = Cow::Owned(refstring);
+        let component: Component = unsafe { transmute(cow) };
+
+        component
+    }};
+}
+
+/// Implementation detail of [`pattern`] and [`pattern_checked`].
+///
+/// Performs an unsafe conversion, the caller must validate the argument.
+#[doc(hidden)]
+#[cfg(any(feature = "macro", feature = "proc-macro", test))]
+#[macro_export]
+macro_rules! __pattern_unchecked {
+    ($arg:literal) => {{
+        extern crate alloc;
+
+        use alloc::string::String;
+
+        use $crate::refspec::PatternString;
+
+        let s: String = String::from($arg);
+        unsafe { core::mem::transmute::<_, PatternString>(s) }
+    }};
+}
+
+/// Implementation detail of [`qualified_pattern`] and
+/// [`qualified_pattern_checked`].
+///
+/// Performs an unsafe conversion, the caller must validate the argument.
+#[doc(hidden)]
+#[cfg(any(feature = "macro", feature = "proc-macro", test))]
+#[macro_export]
+macro_rules! __qualified_pattern_unchecked {
+    ($arg:literal) => {{
+        extern crate alloc;
+
+        use core::mem::transmute;
+
+        use alloc::borrow::Cow;
+        use alloc::string::String;
+
+        use $crate::refspec::{PatternStr, PatternString, QualifiedPattern};
+
+        let s: String = String::from($arg);
+        let pattern: PatternString = unsafe { transmute(s) };
+        let cow: Cow<'_, PatternStr> = Cow::Owned(pattern);
+        let qualified: QualifiedPattern = unsafe { transmute(cow) };
+
+        qualified
+    }};
+}
+
+#[doc(hidden)]
+#[cfg(feature = "proc-macro")]
+pub mod __private {
+    pub use radicle_git_ref_format_macro::{
+        validate_component, validate_pattern, validate_qualified, validate_qualified_pattern,
+        validate_refname,
+    };
+}
+
+/// Create a [`git_ref_format_core::RefString`] from a string literal, which
+/// is validated at compile time.
+///
+/// Unlike [`refname`], an invalid literal is a compile error in all builds.
+#[cfg(feature = "proc-macro")]
+#[macro_export]
+macro_rules! refname_checked {
+    ($arg:literal) => {{
+        $crate::__private::validate_refname!($arg);
+        $crate::__refname_unchecked!($arg)
+    }};
+}
+
+/// Create a [`git_ref_format_core::Qualified`] from a string literal, which
+/// is validated at compile time.
+///
+/// Unlike [`qualified`], an invalid literal is a compile error in all builds.
+#[cfg(feature = "proc-macro")]
+#[macro_export]
+macro_rules! qualified_checked {
+    ($arg:literal) => {{
+        $crate::__private::validate_qualified!($arg);
+        $crate::__qualified_unchecked!($arg)
+    }};
+}
+
+/// Create a [`git_ref_format_core::Component`] from a string literal, which
+/// is validated at compile time.
+///
+/// Unlike [`component`], an invalid literal is a compile error in all builds.
+#[cfg(feature = "proc-macro")]
+#[macro_export]
+macro_rules! component_checked {
+    ($arg:literal) => {{
+        $crate::__private::validate_component!($arg);
+        $crate::__component_unchecked!($arg)
+    }};
+}
+
+/// Create a [`git_ref_format_core::refspec::PatternString`] from a string
+/// literal, which is validated at compile time.
+///
+/// Unlike [`pattern`], an invalid literal is a compile error in all builds.
+#[cfg(feature = "proc-macro")]
+#[macro_export]
+macro_rules! pattern_checked {
+    ($arg:literal) => {{
+        $crate::__private::validate_pattern!($arg);
+        $crate::__pattern_unchecked!($arg)
+    }};
+}
+
+/// Create a [`git_ref_format_core::refspec::QualifiedPattern`] from a string
+/// literal, which is validated at compile time.
+///
+/// Unlike [`qualified_pattern`], an invalid literal is a compile error in all
+/// builds.
+#[cfg(feature = "proc-macro")]
+#[macro_export]
+macro_rules! qualified_pattern_checked {
+    ($arg:literal) => {{
+        $crate::__private::validate_qualified_pattern!($arg);
+        $crate::__qualified_pattern_unchecked!($arg)
     }};
 }
 
 #[cfg(test)]
 mod test {
+    extern crate alloc;
+
+    #[test]
+    fn join() {
+        let joined = crate::join([
+            crate::component!("refs"),
+            crate::component!("remotes"),
+            crate::component!("origin"),
+            crate::component!("main"),
+        ]);
+        assert_eq!(joined, Some(crate::refname!("refs/remotes/origin/main")));
+        assert_eq!(
+            crate::join([crate::component!("main")]),
+            Some(crate::refname!("main"))
+        );
+        assert_eq!(crate::join([]), None);
+    }
+
+    #[test]
+    fn components() {
+        let refname = crate::refname!("refs/remotes/origin/main");
+        let components = crate::components(&refname);
+        assert_eq!(crate::join(components), Some(refname.clone()));
+        assert_eq!(
+            crate::components(&refname).collect::<alloc::vec::Vec<_>>(),
+            [
+                crate::component!("refs"),
+                crate::component!("remotes"),
+                crate::component!("origin"),
+                crate::component!("main"),
+            ]
+        );
+    }
+
+    #[test]
+    fn strip_prefix() {
+        let refname = crate::refname!("refs/heads/feature/x");
+        assert_eq!(
+            crate::strip_prefix(&refname, &crate::refname!("refs/heads")),
+            Some(crate::refname!("feature/x"))
+        );
+        assert_eq!(
+            crate::strip_prefix(&refname, &crate::refname!("refs/heads/feature")),
+            Some(crate::refname!("x"))
+        );
+        assert_eq!(
+            crate::strip_prefix(&refname, &crate::refname!("refs/head")),
+            None
+        );
+        assert_eq!(
+            crate::strip_prefix(&refname, &crate::refname!("refs/rad")),
+            None
+        );
+        assert_eq!(crate::strip_prefix(&refname, &refname), None);
+    }
+
+    #[test]
+    fn reparent() {
+        let refname = crate::qualified!("refs/heads/feature/x");
+        assert_eq!(
+            crate::reparent(&refname, crate::component!("rad")),
+            crate::qualified!("refs/rad/feature/x")
+        );
+        assert_eq!(
+            crate::reparent(
+                &crate::qualified!("refs/heads/main"),
+                crate::component!("tags")
+            ),
+            crate::qualified!("refs/tags/main")
+        );
+    }
+
     #[test]
     fn refname() {
         let _ = crate::refname!("refs/heads/main");
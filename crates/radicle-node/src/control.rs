@@ -209,6 +209,22 @@ where
                 return Err(CommandError::Runtime(e));
             }
         },
+        Command::Block { rid } => match handle.block(rid) {
+            Ok(result) => {
+                CommandResult::updated(result).to_writer(writer)?;
+            }
+            Err(e) => {
+                return Err(CommandError::Runtime(e));
+            }
+        },
+        Command::Unblock { rid } => match handle.unblock(rid) {
+            Ok(result) => {
+                CommandResult::updated(result).to_writer(writer)?;
+            }
+            Err(e) => {
+                return Err(CommandError::Runtime(e));
+            }
+        },
         Command::Follow { nid, alias } => match handle.follow(nid, alias) {
             Ok(result) => {
                 CommandResult::updated(result).to_writer(writer)?;
@@ -272,6 +288,22 @@ where
 
             CommandResult::Okay(debug).to_writer(writer)?;
         }
+        Command::Metrics => {
+            let metrics = handle.metrics()?;
+
+            CommandResult::Okay(metrics).to_writer(writer)?;
+        }
+        Command::Uptime => {
+            let uptime = handle.uptime()?;
+
+            CommandResult::Okay(uptime).to_writer(writer)?;
+        }
+        Command::SetLogLevel { level } => {
+            if let Err(e) = handle.set_log_level(level.into()) {
+                return Err(CommandError::Runtime(e));
+            }
+            CommandResult::ok().to_writer(writer)?;
+        }
         Command::Shutdown => {
             log::debug!(target: "control", "Shutdown requested..");
             // Channel might already be disconnected if shutdown
@@ -279,6 +311,16 @@ where
             handle.shutdown().ok();
             CommandResult::ok().to_writer(writer).ok();
         }
+        Command::Unknown => {
+            let command = json::from_str::<json::Value>(input)?
+                .get("command")
+                .and_then(json::Value::as_str)
+                .unwrap_or_default()
+                .to_owned();
+            log::debug!(target: "control", "Unknown command `{command}` received..");
+
+            CommandResult::<()>::unknown_command(command).to_writer(writer)?;
+        }
     }
     Ok(())
 }
@@ -386,9 +428,62 @@ mod tests {
         assert!(handle.unseed(proj).unwrap());
         assert!(!handle.unseed(proj).unwrap());
 
+        assert!(handle.seed(proj, Scope::default()).unwrap());
+        assert!(handle.block(proj).unwrap());
+        assert!(!handle.block(proj).unwrap());
+        assert!(handle.unblock(proj).unwrap());
+        assert!(!handle.unblock(proj).unwrap());
+
         assert!(handle.follow(peer, Some(Alias::new("alice"))).unwrap());
         assert!(!handle.follow(peer, Some(Alias::new("alice"))).unwrap());
         assert!(handle.unfollow(peer).unwrap());
         assert!(!handle.unfollow(peer).unwrap());
     }
+
+    #[test]
+    fn test_set_log_level() {
+        let tmp = tempfile::tempdir().unwrap();
+        let socket = tmp.path().join("node.sock");
+        let listener = Listener::bind(&socket).unwrap();
+        let service = test::handle::Handle::default();
+        let mut handle = Node::new(&socket);
+
+        thread::spawn({
+            let service = service.clone();
+
+            move || listen(listener, service)
+        });
+
+        // Wait for node to be online.
+        while !handle.is_running() {}
+
+        handle.set_log_level(log::Level::Trace).unwrap();
+        assert_eq!(*service.log_level.lock().unwrap(), Some(log::Level::Trace));
+
+        handle.set_log_level(log::Level::Warn).unwrap();
+        assert_eq!(*service.log_level.lock().unwrap(), Some(log::Level::Warn));
+    }
+
+    #[test]
+    fn test_unknown_command() {
+        let tmp = tempfile::tempdir().unwrap();
+        let socket = tmp.path().join("node.sock");
+        let listener = Listener::bind(&socket).unwrap();
+
+        thread::spawn(move || listen(listener, test::handle::Handle::default()));
+
+        let mut stream = loop {
+            if let Ok(stream) = Stream::connect(&socket) {
+                break stream;
+            }
+        };
+        writeln!(&mut stream, "{}", json::json!({ "command": "frobnicate" })).unwrap();
+
+        let line = BufReader::new(stream).lines().next().unwrap().unwrap();
+        let result = json::from_str::<CommandResult<()>>(&line).unwrap();
+        let CommandResult::UnknownCommand { command, .. } = result else {
+            panic!("expected an unknown command, got `{line}`");
+        };
+        assert_eq!(command, "frobnicate");
+    }
 }
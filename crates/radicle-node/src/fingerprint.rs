@@ -18,6 +18,11 @@
 //! If the user deletes the fingerprint file, the node will not be able
 //! to detect a possible change of the secret key. The consequences of
 //! doing this are unclear.
+//!
+//! To change the secret key, the fingerprint can instead be rotated with
+//! [`Fingerprint::rotate`], given both the old and the new secret key.
+//! Past fingerprints are appended to a log next to the fingerprint file
+//! (usually at `.radicle/node/fingerprint.log`), see [`Fingerprint::history`].
 
 use thiserror::Error;
 
@@ -47,6 +52,12 @@ pub enum Error {
 
     #[error("fingerprint file is not valid UTF-8: {0}")]
     Utf8(#[from] std::str::Utf8Error),
+
+    #[error("no fingerprint found to rotate")]
+    NotFound,
+
+    #[error("secret key does not match the stored fingerprint {0}")]
+    Mismatch(Fingerprint),
 }
 
 impl Fingerprint {
@@ -68,31 +79,79 @@ impl Fingerprint {
         home: &Home,
         secret_key: &impl std::ops::Deref<Target = crypto::SecretKey>,
     ) -> Result<(), Error> {
-        let public_key = crypto::PublicKey(secret_key.deref().public_key());
         let mut file = std::fs::OpenOptions::new()
             .create_new(true)
             .write(true)
             .open(path(home))?;
         {
             use std::io::Write as _;
-            file.write_all(crypto::ssh::fmt::fingerprint(&public_key).as_ref())?;
+            file.write_all(Self::of(secret_key).0.as_bytes())?;
+        }
+
+        Ok(())
+    }
+
+    /// Rotate the fingerprint of the node from the old key to the new key.
+    ///
+    /// The old key must match the stored fingerprint. The stored fingerprint is
+    /// appended to the fingerprint log before being replaced.
+    pub fn rotate(
+        home: &Home,
+        old_key: &impl std::ops::Deref<Target = crypto::SecretKey>,
+        new_key: &impl std::ops::Deref<Target = crypto::SecretKey>,
+    ) -> Result<(), Error> {
+        use std::io::Write as _;
+
+        let old = Self::read(home)?.ok_or(Error::NotFound)?;
+        if old.verify(old_key) == FingerprintVerification::Mismatch {
+            return Err(Error::Mismatch(old));
         }
+        let mut log = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(log_path(home))?;
+        writeln!(log, "{old}")?;
+        log.sync_all()?;
+
+        // Replace the fingerprint atomically, so that it is never missing.
+        let tmp = path(home).with_extension("tmp");
+        std::fs::write(&tmp, Self::of(new_key).0.as_bytes())?;
+        std::fs::rename(tmp, path(home))?;
 
         Ok(())
     }
 
+    /// Return the past fingerprints of the node, oldest first.
+    pub fn history(home: &Home) -> Result<Vec<Fingerprint>, Error> {
+        match std::fs::read(log_path(home)) {
+            Ok(contents) => Ok(std::str::from_utf8(contents.as_ref())?
+                .lines()
+                .filter(|line| !line.is_empty())
+                .map(|line| Fingerprint(line.to_string()))
+                .collect()),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(err) => Err(Error::Io(err)),
+        }
+    }
+
     /// Verify that the fingerprint of given public key matches self.
     pub fn verify(
         &self,
         secret_key: &impl std::ops::Deref<Target = crypto::SecretKey>,
     ) -> FingerprintVerification {
-        let public_key = crypto::PublicKey(secret_key.deref().public_key());
-        if crypto::ssh::fmt::fingerprint(&public_key) == self.0 {
+        if Self::of(secret_key) == *self {
             FingerprintVerification::Match
         } else {
             FingerprintVerification::Mismatch
         }
     }
+
+    /// Compute the fingerprint of the public key of the given secret key.
+    fn of(secret_key: &impl std::ops::Deref<Target = crypto::SecretKey>) -> Self {
+        let public_key = crypto::PublicKey(secret_key.deref().public_key());
+
+        Self(crypto::ssh::fmt::fingerprint(&public_key))
+    }
 }
 
 /// Return the location of the node fingerprint.
@@ -100,6 +159,11 @@ fn path(home: &Home) -> std::path::PathBuf {
     home.node().join("fingerprint")
 }
 
+/// Return the location of the log of past node fingerprints.
+fn log_path(home: &Home) -> std::path::PathBuf {
+    home.node().join("fingerprint.log")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -131,4 +195,59 @@ mod tests {
         // Note that `fp` has not changed since it was initialized from `secret`.
         assert_eq!(fp.verify(&other_secret), FingerprintVerification::Mismatch);
     }
+
+    #[test]
+    fn rotation() {
+        let tmp = tempfile::tempdir().unwrap();
+        let home = Home::new(tmp.path()).unwrap();
+
+        let store = Keystore::new(&home.keys());
+        store.init("test 1", None, crypto::Seed::default()).unwrap();
+        let old = store.secret_key(None).unwrap().unwrap();
+
+        Fingerprint::init(&home, &old).unwrap();
+        let old_fp = Fingerprint::read(&home).unwrap().unwrap();
+        assert!(Fingerprint::history(&home).unwrap().is_empty());
+
+        std::fs::remove_dir_all(home.keys()).unwrap();
+        store.init("test 1", None, crypto::Seed::default()).unwrap();
+        let new = store.secret_key(None).unwrap().unwrap();
+
+        Fingerprint::rotate(&home, &old, &new).unwrap();
+
+        let fp = Fingerprint::read(&home).unwrap().unwrap();
+        assert_eq!(fp.verify(&new), FingerprintVerification::Match);
+        assert_eq!(fp.verify(&old), FingerprintVerification::Mismatch);
+        assert_eq!(Fingerprint::history(&home).unwrap(), vec![old_fp]);
+    }
+
+    #[test]
+    fn rotation_wrong_key() {
+        let tmp = tempfile::tempdir().unwrap();
+        let home = Home::new(tmp.path()).unwrap();
+
+        let store = Keystore::new(&home.keys());
+        store.init("test 1", None, crypto::Seed::default()).unwrap();
+        let secret = store.secret_key(None).unwrap().unwrap();
+
+        // Rotating without a stored fingerprint fails.
+        assert!(matches!(
+            Fingerprint::rotate(&home, &secret, &secret),
+            Err(Error::NotFound)
+        ));
+        Fingerprint::init(&home, &secret).unwrap();
+
+        std::fs::remove_dir_all(home.keys()).unwrap();
+        store.init("test 1", None, crypto::Seed::default()).unwrap();
+        let other = store.secret_key(None).unwrap().unwrap();
+
+        assert!(matches!(
+            Fingerprint::rotate(&home, &other, &other),
+            Err(Error::Mismatch(_))
+        ));
+        // Nothing was changed.
+        let fp = Fingerprint::read(&home).unwrap().unwrap();
+        assert_eq!(fp.verify(&secret), FingerprintVerification::Match);
+        assert!(Fingerprint::history(&home).unwrap().is_empty());
+    }
 }
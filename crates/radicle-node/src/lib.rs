@@ -4,6 +4,9 @@
 #![allow(clippy::byte_char_slices)]
 
 pub mod fingerprint;
+#[cfg(feature = "structured-logger")]
+pub mod logfmt;
+pub mod passphrase;
 pub mod reactor;
 pub mod runtime;
 
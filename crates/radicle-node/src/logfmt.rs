@@ -0,0 +1,148 @@
+//! Writer for the structured logger, that renders records in the `logfmt`
+//! format, eg.
+//!
+//! ```text
+//! level=info target=node msg="Node ID is z6Mk..." timestamp=1700000000000
+//! ```
+//!
+//! The level, target and message of a record come first, followed by its
+//! structured key-values in lexicographic order.
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::io;
+use std::sync::Mutex;
+
+use log::kv::{Key, Value};
+
+/// Keys set by the structured logger for every record.
+const LEVEL: &str = "level";
+const TARGET: &str = "target";
+const MESSAGE: &str = "message";
+
+/// Create a new `logfmt` writer, writing one record per line.
+pub fn new_writer<W: io::Write + Send + Sync + 'static>(
+    writer: W,
+) -> Box<dyn structured_logger::Writer> {
+    Box::new(Writer(Mutex::new(writer)))
+}
+
+/// A `logfmt` writer, see [`new_writer`].
+struct Writer<W>(Mutex<W>);
+
+impl<W: io::Write + Send + Sync + 'static> structured_logger::Writer for Writer<W> {
+    fn write_log(&self, record: &BTreeMap<Key, Value>) -> Result<(), io::Error> {
+        let mut line = format(record);
+        line.push('\n');
+
+        let mut writer = self
+            .0
+            .lock()
+            .map_err(|_| io::Error::other("logfmt writer lock poisoned"))?;
+        writer.write_all(line.as_bytes())
+    }
+}
+
+/// Render a record as a `logfmt` line, without trailing newline.
+pub fn format(record: &BTreeMap<Key, Value>) -> String {
+    let mut line = String::with_capacity(256);
+    let mut field = |key: &str, value: &Value| {
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(key);
+        line.push('=');
+        quote(&mut line, &value.to_string());
+    };
+
+    if let Some(level) = record.get(&Key::from(LEVEL)) {
+        field(
+            LEVEL,
+            &Value::from(level.to_string().to_lowercase().as_str()),
+        );
+    }
+    if let Some(target) = record.get(&Key::from(TARGET)) {
+        field(TARGET, target);
+    }
+    if let Some(msg) = record.get(&Key::from(MESSAGE)) {
+        field("msg", msg);
+    }
+    for (key, value) in record {
+        if ![LEVEL, TARGET, MESSAGE].contains(&key.as_str()) {
+            field(key.as_str(), value);
+        }
+    }
+    line
+}
+
+/// Append a value to the line, quoting and escaping it if necessary.
+fn quote(line: &mut String, value: &str) {
+    let needs_quotes = value.is_empty()
+        || value
+            .chars()
+            .any(|c| c.is_whitespace() || c.is_control() || c == '=' || c == '"');
+    if !needs_quotes {
+        line.push_str(value);
+        return;
+    }
+    line.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => line.push_str("\\\""),
+            '\\' => line.push_str("\\\\"),
+            '\n' => line.push_str("\\n"),
+            '\r' => line.push_str("\\r"),
+            '\t' => line.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(line, "\\u{{{:x}}}", c as u32);
+            }
+            c => line.push(c),
+        }
+    }
+    line.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record<'a>(fields: &[(&'a str, Value<'a>)]) -> BTreeMap<Key<'a>, Value<'a>> {
+        fields
+            .iter()
+            .map(|(k, v)| (Key::from(*k), v.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_format() {
+        let record = record(&[
+            ("level", Value::from("INFO")),
+            ("message", Value::from("Node ID is z6Mk")),
+            ("target", Value::from("node")),
+            ("addr", Value::from("127.0.0.1:8776")),
+            ("alias", Value::from("alice and bob")),
+            ("timestamp", Value::from(1700000000000u64)),
+        ]);
+
+        assert_eq!(
+            format(&record),
+            "level=info target=node msg=\"Node ID is z6Mk\" \
+             addr=127.0.0.1:8776 alias=\"alice and bob\" timestamp=1700000000000"
+        );
+    }
+
+    #[test]
+    fn test_quote() {
+        let quoted = |value: &str| {
+            let mut line = String::new();
+            quote(&mut line, value);
+            line
+        };
+
+        assert_eq!(quoted("plain"), "plain");
+        assert_eq!(quoted(""), "\"\"");
+        assert_eq!(quoted("a=b"), "\"a=b\"");
+        assert_eq!(quoted("say \"hi\""), "\"say \\\"hi\\\"\"");
+        assert_eq!(quoted("two\nlines"), "\"two\\nlines\"");
+        assert_eq!(quoted("back\\ slash"), "\"back\\\\ slash\"");
+    }
+}
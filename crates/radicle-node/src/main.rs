@@ -12,6 +12,8 @@ use radicle::profile;
 
 use radicle_node::crypto::ssh::keystore::{Keystore, MemorySigner};
 use radicle_node::fingerprint::{Fingerprint, FingerprintVerification};
+use radicle_node::passphrase;
+use radicle_node::runtime::{ControlMode, RuntimeOptions};
 use radicle_node::{Runtime, VERSION};
 #[cfg(unix)]
 use radicle_signals as signals;
@@ -37,7 +39,8 @@ Options
                   (default: info)
     --log-logger  (radicle | structured | systemd)  Set logger implementation
                   (default: radicle)
-    --log-format  json                              Set log format for logger implementation
+    --log-format  (json | logfmt)                   Set log format for logger implementation
+                  (default: json)
     --version                                       Print program version
     --help                                          Print help
 "#;
@@ -81,6 +84,8 @@ impl FromStr for Logger {
 enum LogFormat {
     #[cfg(feature = "structured-logger")]
     Json,
+    #[cfg(feature = "structured-logger")]
+    Logfmt,
 }
 
 impl FromStr for LogFormat {
@@ -90,6 +95,8 @@ impl FromStr for LogFormat {
         match s {
             #[cfg(feature = "structured-logger")]
             "json" => Ok(LogFormat::Json),
+            #[cfg(feature = "structured-logger")]
+            "logfmt" => Ok(LogFormat::Logfmt),
             _ => Err("unknown log format"),
         }
     }
@@ -193,6 +200,8 @@ enum ExecutionError {
     Runtime(#[from] radicle_node::runtime::Error),
     #[error(transparent)]
     Fingerprint(#[from] radicle_node::fingerprint::Error),
+    #[error(transparent)]
+    Passphrase(#[from] passphrase::Error),
     #[error("failed to load secret key '{path}': not found")]
     SecretNotFound { path: PathBuf },
     #[error("failed to load secret '{path}': {source}")]
@@ -218,17 +227,19 @@ fn execute(options: Options) -> Result<(), ExecutionError> {
         log::set_max_level(level.to_level_filter());
     }
 
-    let config = options.config.unwrap_or_else(|| home.config());
-    let mut config = profile::Config::load(&config)?;
+    let config_path = options.config.unwrap_or_else(|| home.config());
+    let mut config = profile::Config::load(&config_path)?;
 
-    if options.log.level.is_none() {
-        log::set_max_level(log::Level::from(config.node.log).to_level_filter());
-    } else {
+    if let Some(level) = options.log.level {
         // It might seem counter-intuitive at first, as there
         // always is a log level in the configuration, but the command
         // line argument has precedence, and if it is present, the
-        // log level has been already set above. Thus, we have nothing
-        // to do in this case.
+        // log level has been already set above. We only record it in
+        // the node configuration, so that the node reports it, and
+        // reloading the configuration doesn't change it.
+        config.node.log = level.into();
+    } else {
+        log::set_max_level(log::Level::from(config.node.log).to_level_filter());
     }
 
     log::info!(target: "node", "Starting node..");
@@ -239,36 +250,53 @@ fn execute(options: Options) -> Result<(), ExecutionError> {
 
     #[cfg(all(feature = "systemd", target_os = "linux"))]
     let passphrase = passphrase.or_else(|| {
+        use radicle_systemd::credential::{resolve, CredentialError};
+
         const ID: &str = "xyz.radicle.node.passphrase";
-        match radicle_systemd::credential::path(ID) {
+        match resolve(ID, &[]) {
+            Err(CredentialError::DirectoryUnset | CredentialError::NotFound { .. }) => None,
             Err(err) => {
                 log::warn!(target: "node", "Failed to obtain path of the passphrase file via systemd credential with '{ID}': {err}");
                 None
             },
-            Ok(Some(ref path)) => match std::fs::read_to_string(path) {
+            Ok(ref path) => match std::fs::read_to_string(path) {
                 Ok(passphrase) => Some(passphrase.into()),
                 Err(err) => {
                     log::warn!(target: "node", "Failed to read passphrase from '{}': {err}", path.display());
                     None
                 }
             }
-            Ok(None) => None,
         }
     });
 
     let passphrase = passphrase.or_else(profile::env::passphrase);
 
+    let passphrase = match passphrase {
+        Some(passphrase) => Some(passphrase),
+        None => profile::env::passphrase_command()
+            .or_else(|| config.node.passphrase_command.clone())
+            .map(|cmd| {
+                log::info!(target: "node", "Running passphrase command..");
+                passphrase::command(&cmd)
+            })
+            .transpose()?
+            .flatten(),
+    };
+
     let secret_path = options.secret;
 
     #[cfg(all(feature = "systemd", target_os = "linux"))]
     let secret_path = secret_path.or_else(|| {
+        use radicle_systemd::credential::{resolve, CredentialError};
+
         const ID: &str = "xyz.radicle.node.secret";
-        match radicle_systemd::credential::path(ID) {
+        match resolve(ID, &[]) {
+            Err(CredentialError::DirectoryUnset | CredentialError::NotFound { .. }) => None,
             Err(err) => {
                 log::warn!(target: "node", "Failed to obtain path of the secret key via systemd credential with ID '{ID}': {err}");
                 None
             },
-            Ok(path) => path
+            Ok(path) => Some(path)
         }
     });
 
@@ -335,7 +363,12 @@ fn execute(options: Options) -> Result<(), ExecutionError> {
         log::debug!(target: "node", "Removing existing control socket..");
         std::fs::remove_file(home.socket()).ok();
     }
-    Runtime::init(home, config.node, listen, signals, signer)?.run()?;
+    let options = RuntimeOptions {
+        control: ControlMode::Socket(home.socket()),
+        config: Some(config_path),
+        log_level: options.log.level,
+    };
+    Runtime::init(home, config.node, listen, signals, signer, options)?.run()?;
 
     Ok(())
 }
@@ -351,6 +384,7 @@ fn initialize_logging(options: &LogOptions) -> Result<(), Box<dyn std::error::Er
 
                 let writer = match options.format.unwrap_or(LogFormat::Json) {
                     LogFormat::Json => json::new_writer(io::stdout()),
+                    LogFormat::Logfmt => radicle_node::logfmt::new_writer(io::stdout()),
                 };
 
                 Box::new(Builder::new().with_default_writer(writer).build())
@@ -362,20 +396,23 @@ fn initialize_logging(options: &LogOptions) -> Result<(), Box<dyn std::error::Er
 
                 #[derive(Error, Debug)]
                 enum JournalError {
-                    #[error("journald not connected")]
-                    NotConnected,
+                    #[error("journald not connected: {0}")]
+                    NotConnected(JournalStatus),
                     #[error("journald i/o: {0}")]
                     Io(#[from] io::Error),
                 }
 
-                if !connected() {
-                    return Err(Box::new(JournalError::NotConnected));
+                let status = status();
+                if !status.is_connected() {
+                    return Err(Box::new(JournalError::NotConnected(status)));
                 }
 
                 const SYSLOG_IDENTIFIER: &str = "radicle-node";
                 logger::<&str, &str, _>(SYSLOG_IDENTIFIER.to_string(), []).map_err(Box::new)?
             }
-            Logger::Radicle => Box::new(radicle::logger::Logger::new(level)),
+            // Filtering is left to `log::max_level`, which may be adjusted
+            // at runtime, e.g. via `SIGUSR1` and `SIGUSR2`.
+            Logger::Radicle => Box::new(radicle::logger::Logger::new(log::Level::Trace)),
         }
     };
 
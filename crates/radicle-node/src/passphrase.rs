@@ -0,0 +1,96 @@
+//! Obtain the passphrase of the node secret key by running a command.
+//!
+//! This allows the passphrase to be fetched from an external secrets
+//! manager, instead of being stored in the environment. The command is
+//! run by the shell, and its standard output, with surrounding whitespace
+//! removed, is used as the passphrase.
+//!
+//! The command is taken from the environment variable
+//! [`RAD_PASSPHRASE_CMD`](radicle::profile::env::RAD_PASSPHRASE_CMD), or the
+//! configuration option `node.passphraseCommand`.
+use std::io;
+use std::process::{Command, ExitStatus, Stdio};
+
+use radicle::crypto::ssh::Passphrase;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("failed to run passphrase command '{command}': {source}")]
+    Io { command: String, source: io::Error },
+
+    #[error("passphrase command '{command}' failed with {status}")]
+    Status { command: String, status: ExitStatus },
+
+    #[error("output of passphrase command '{command}' is not valid UTF-8")]
+    Utf8 { command: String },
+}
+
+/// Run the given command and return its output as passphrase.
+///
+/// An empty output is treated as no passphrase.
+pub fn command(command: &str) -> Result<Option<Passphrase>, Error> {
+    let output = shell(command)
+        .stdin(Stdio::null())
+        .stderr(Stdio::inherit())
+        .output()
+        .map_err(|source| Error::Io {
+            command: command.to_owned(),
+            source,
+        })?;
+
+    if !output.status.success() {
+        return Err(Error::Status {
+            command: command.to_owned(),
+            status: output.status,
+        });
+    }
+    let passphrase =
+        Passphrase::from(String::from_utf8(output.stdout).map_err(|_| Error::Utf8 {
+            command: command.to_owned(),
+        })?);
+    let passphrase = passphrase.trim();
+
+    if passphrase.is_empty() {
+        log::trace!(target: "node", "Treating empty passphrase as no passphrase.");
+        return Ok(None);
+    }
+    Ok(Some(Passphrase::from(passphrase.to_owned())))
+}
+
+#[cfg(unix)]
+fn shell(command: &str) -> Command {
+    let mut cmd = Command::new("sh");
+    cmd.arg("-c").arg(command);
+    cmd
+}
+
+#[cfg(windows)]
+fn shell(command: &str) -> Command {
+    let mut cmd = Command::new("cmd");
+    cmd.arg("/C").arg(command);
+    cmd
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command() {
+        let passphrase = command("echo '  hunter2 '").unwrap().unwrap();
+        assert_eq!(passphrase.as_str(), "hunter2");
+
+        assert!(command("printf '\\n'").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_command_failure() {
+        let err = command("echo hunter2; exit 3").unwrap_err();
+        assert!(matches!(err, Error::Status { ref status, .. } if status.code() == Some(3)));
+        assert_eq!(
+            err.to_string(),
+            "passphrase command 'echo hunter2; exit 3' failed with exit status: 3"
+        );
+    }
+}
@@ -34,6 +34,13 @@ impl Listener {
         Ok(Self(TcpListener::bind(addr)?))
     }
 
+    /// Wraps a listener that was bound elsewhere, e.g. received via socket
+    /// activation.
+    pub fn from_std(listener: std::net::TcpListener) -> Result<Self> {
+        listener.set_nonblocking(true)?;
+        Ok(Self(TcpListener::from_std(listener)))
+    }
+
     /// Returns the local [`std::net::SocketAddr`] on which self accepts
     /// connections.
     pub fn local_addr(&self) -> std::net::SocketAddr {
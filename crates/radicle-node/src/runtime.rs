@@ -3,7 +3,8 @@ pub mod thread;
 
 use std::fmt::Debug;
 use std::path::PathBuf;
-use std::{fs, io, net};
+use std::sync::{Arc, RwLock};
+use std::{fs, io, net, time};
 
 #[cfg(unix)]
 use std::os::unix::net::UnixListener as Listener;
@@ -24,6 +25,7 @@ use radicle::node::address;
 use radicle::node::address::Store as _;
 use radicle::node::notifications;
 use radicle::node::policy::config as policy;
+use radicle::node::policy::config::SeedingPolicy;
 use radicle::node::Event;
 use radicle::node::UserAgent;
 use radicle::profile::Home;
@@ -36,7 +38,7 @@ use crate::reactor::Reactor;
 use crate::service::gossip;
 use crate::wire::Wire;
 use crate::worker;
-use crate::{service, LocalTime};
+use crate::{service, LocalDuration, LocalTime};
 
 pub use handle::Error as HandleError;
 pub use handle::Handle;
@@ -45,6 +47,20 @@ pub use node::events::Emitter;
 /// Maximum pending worker tasks allowed.
 pub const MAX_PENDING_TASKS: usize = 1024;
 
+/// Time to wait, on top of the shutdown grace period, for peers to be disconnected.
+const SHUTDOWN_TIMEOUT_MARGIN: time::Duration = time::Duration::from_secs(1);
+
+/// Name of the file descriptor of the control socket, when passed via
+/// socket activation, i.e. `FileDescriptorName=` in the socket unit.
+#[cfg(all(feature = "systemd", target_os = "linux"))]
+const CONTROL_FD_NAME: &str = "control";
+
+/// Name of the file descriptors of the listeners for peer connections, when
+/// passed via socket activation. More than one socket may have this name, e.g.
+/// one for IPv4 and one for IPv6.
+#[cfg(all(feature = "systemd", target_os = "linux"))]
+const PEER_FD_NAME: &str = "peer";
+
 /// A client error.
 #[derive(Error, Debug)]
 pub enum Error {
@@ -93,6 +109,79 @@ pub enum Error {
     GitVersion(#[from] git::VersionError),
 }
 
+/// Raise (positive `step`) or lower (negative `step`) the verbosity of `level`,
+/// saturating at [`log::LevelFilter::Error`] and [`log::LevelFilter::Trace`],
+/// so that logging is never turned off entirely.
+#[cfg(unix)]
+fn verbosity(level: log::LevelFilter, step: isize) -> log::LevelFilter {
+    let levels = [
+        log::LevelFilter::Error,
+        log::LevelFilter::Warn,
+        log::LevelFilter::Info,
+        log::LevelFilter::Debug,
+        log::LevelFilter::Trace,
+    ];
+    let current = levels.iter().position(|l| *l == level).unwrap_or(0);
+    let next = current.saturating_add_signed(step).min(levels.len() - 1);
+
+    levels[next]
+}
+
+/// Reload the node configuration from the file at `path`, applying what can be
+/// applied while the node runs, and logging what requires a restart.
+///
+/// A log level given on the command line takes precedence over the configured
+/// one, as on startup.
+#[cfg(unix)]
+fn reload(
+    handle: &Handle,
+    path: &std::path::Path,
+    log_level: Option<log::Level>,
+    policy: &RwLock<SeedingPolicy>,
+) {
+    #[cfg(all(feature = "systemd", target_os = "linux"))]
+    if let Err(err) = radicle_systemd::notify::reloading() {
+        log::warn!(target: "node", "Failed to notify systemd of reloading: {err}");
+    }
+
+    match radicle::profile::Config::load(path) {
+        Ok(config) => {
+            let mut node = config.node;
+            // As on startup, preferred seeds are persistent peers.
+            node.connect.extend(config.preferred_seeds);
+
+            if let Some(level) = log_level {
+                node.log = level.into();
+            }
+            let seeding_policy = node.seeding_policy.into();
+
+            match handle.reload(node) {
+                Ok(restart) => {
+                    // Fetch workers check the default policy on their own.
+                    if let Ok(mut policy) = policy.write() {
+                        *policy = seeding_policy;
+                    }
+                    for field in restart {
+                        log::warn!(target: "node", "Configuration field `node.{field}` changed; ignored until the node restarts");
+                    }
+                    log::info!(target: "node", "Configuration reloaded from {}", path.display());
+                }
+                Err(e) => {
+                    log::error!(target: "node", "Failed to reload configuration: {e}");
+                }
+            }
+        }
+        Err(e) => {
+            log::error!(target: "node", "Failed to reload configuration: {e}");
+        }
+    }
+
+    #[cfg(all(feature = "systemd", target_os = "linux"))]
+    if let Err(err) = radicle_systemd::notify::ready() {
+        log::warn!(target: "node", "Failed to notify systemd of readiness: {err}");
+    }
+}
+
 impl From<service::Error> for Error {
     fn from(e: service::Error) -> Self {
         Self::Service(Box::new(e))
@@ -107,17 +196,75 @@ pub enum ControlSocket {
     Received(Listener),
 }
 
+/// Listeners received via socket activation.
+#[derive(Default)]
+struct Received {
+    /// Listener for the control socket.
+    control: Option<Listener>,
+    /// Listeners for peer connections.
+    peers: Vec<reactor::Listener>,
+}
+
+/// How the node can be controlled by other processes or threads.
+pub enum ControlMode {
+    /// Accept commands on the control socket at the given path, unless one is
+    /// received via socket activation.
+    Socket(PathBuf),
+    /// Send a [`Control`] on the given channel once the node runs, for it to
+    /// be controlled from within the same process.
+    InProcess(chan::Sender<Control>),
+    /// Don't accept commands from outside the runtime.
+    Disabled,
+}
+
+/// Options for [`Runtime::init`].
+pub struct RuntimeOptions {
+    /// How the node can be controlled.
+    pub control: ControlMode,
+    /// Configuration file to reload on `SIGHUP`. If not set, the signal is
+    /// ignored.
+    pub config: Option<PathBuf>,
+    /// Log level given on the command line, which takes precedence over the
+    /// configured one when reloading the configuration.
+    pub log_level: Option<log::Level>,
+}
+
+/// In-process control of a running node, see [`ControlMode::InProcess`].
+#[derive(Clone)]
+pub struct Control {
+    /// Handle to the node, to issue commands with.
+    pub handle: Handle,
+}
+
+/// The node's control interface, set up according to [`ControlMode`].
+pub enum ControlInterface {
+    /// Commands are accepted on a control socket.
+    Socket(ControlSocket),
+    /// A [`Control`] is sent on the channel once the node runs.
+    InProcess(chan::Sender<Control>),
+    /// Commands are not accepted from outside the runtime.
+    Disabled,
+}
+
 /// Holds join handles to the client threads, as well as a client handle.
 pub struct Runtime {
     pub id: NodeId,
     pub home: Home,
-    pub control: ControlSocket,
+    pub control: ControlInterface,
     pub handle: Handle,
     pub storage: Storage,
     pub reactor: Reactor,
     pub pool: worker::Pool,
     pub local_addrs: Vec<net::SocketAddr>,
     pub signals: chan::Receiver<Signal>,
+    /// How long to wait for active fetches to finish on termination.
+    pub shutdown_grace: time::Duration,
+    /// Configuration file to reload on `SIGHUP`.
+    pub config_path: Option<PathBuf>,
+    /// Log level given on the command line, see [`RuntimeOptions::log_level`].
+    pub log_level: Option<log::Level>,
+    /// Default seeding policy, shared with the worker pool.
+    pub policy: Arc<RwLock<SeedingPolicy>>,
 }
 
 impl Runtime {
@@ -130,6 +277,7 @@ impl Runtime {
         listen: Vec<net::SocketAddr>,
         signals: chan::Receiver<Signal>,
         signer: Device<G>,
+        options: RuntimeOptions,
     ) -> Result<Runtime, Error>
     where
         G: crypto::signature::Signer<crypto::Signature>
@@ -141,12 +289,18 @@ impl Runtime {
         let id = *signer.public_key();
         let alias = config.alias.clone();
         let network = config.network;
+        let shutdown_grace = LocalDuration::from(config.limits.shutdown_grace).into();
         let rng = fastrand::Rng::new();
         let clock = LocalTime::now();
         let timestamp = clock.into();
         let storage = Storage::open(home.storage(), git::UserInfo { alias, key: id })?;
         let policy = config.seeding_policy.into();
 
+        #[cfg(all(feature = "systemd", target_os = "linux"))]
+        let received = Self::receive_listeners();
+        #[cfg(not(all(feature = "systemd", target_os = "linux")))]
+        let received = Received::default();
+
         for (key, _) in &config.extra {
             log::warn!(target: "node", "Unused or deprecated configuration attribute {key:?}");
         }
@@ -154,6 +308,7 @@ impl Runtime {
         log::info!(target: "node", "Opening policy database..");
         let policies = home.policies_mut()?;
         let policies = policy::Config::new(policy, policies);
+        let default_policy = Arc::new(RwLock::new(policy));
         let notifications = home.notifications_mut()?;
         let mut cobs_cache = cob::cache::Store::open(home.cobs().join(cob::cache::COBS_DB_FILE))?;
 
@@ -234,6 +389,14 @@ impl Runtime {
             local_addrs.push(local_addr);
             wire.listen(listener);
         }
+
+        for listener in received.peers {
+            let local_addr = listener.local_addr();
+
+            log::info!(target: "node", "Received listener for {local_addr}.");
+            local_addrs.push(local_addr);
+            wire.listen(listener);
+        }
         let reactor = Reactor::new(wire, thread::name(&id, "service"))?;
         let handle = Handle::new(home.clone(), reactor.controller(), emitter);
 
@@ -254,11 +417,20 @@ impl Runtime {
                 capacity: config.workers.into(),
                 storage: storage.clone(),
                 fetch,
-                policy,
+                policy: default_policy.clone(),
                 policies_db: home.node().join(node::POLICIES_DB_FILE),
             },
         )?;
-        let control = Self::bind(home.socket())?;
+        let control = match options.control {
+            ControlMode::Socket(path) => {
+                ControlInterface::Socket(Self::bind(path, received.control)?)
+            }
+            ControlMode::InProcess(sender) => ControlInterface::InProcess(sender),
+            ControlMode::Disabled => {
+                log::info!(target: "node", "Control socket disabled..");
+                ControlInterface::Disabled
+            }
+        };
 
         Ok(Runtime {
             id,
@@ -270,35 +442,91 @@ impl Runtime {
             pool,
             signals,
             local_addrs,
+            shutdown_grace,
+            config_path: options.config,
+            log_level: options.log_level,
+            policy: default_policy,
         })
     }
 
     pub fn run(self) -> Result<(), Error> {
         let home = self.home;
-        let (listener, remove) = match self.control {
-            ControlSocket::Bound(listener, path) => (listener, Some(path)),
-            ControlSocket::Received(listener) => (listener, None),
-        };
 
         log::info!(target: "node", "Running node {} in {}..", self.id, home.path().display());
 
-        thread::spawn(&self.id, "control", {
-            let handle = self.handle.clone();
-            || control::listen(listener, handle)
-        });
+        let remove = match self.control {
+            ControlInterface::Socket(socket) => {
+                let (listener, remove) = match socket {
+                    ControlSocket::Bound(listener, path) => (listener, Some(path)),
+                    ControlSocket::Received(listener) => (listener, None),
+                };
+                thread::spawn(&self.id, "control", {
+                    let handle = self.handle.clone();
+                    || control::listen(listener, handle)
+                });
+                remove
+            }
+            ControlInterface::InProcess(sender) => {
+                // The receiver may be gone, in which case the node runs uncontrolled.
+                sender
+                    .send(Control {
+                        handle: self.handle.clone(),
+                    })
+                    .ok();
+                None
+            }
+            ControlInterface::Disabled => None,
+        };
+
+        #[cfg(all(feature = "systemd", target_os = "linux"))]
+        if let Err(err) = radicle_systemd::notify::ready() {
+            log::warn!(target: "node", "Failed to notify systemd of readiness: {err}");
+        }
 
         #[cfg(unix)]
         let _signals = thread::spawn(&self.id, "signals", move || loop {
             use radicle::node::Handle as _;
 
             match self.signals.recv() {
-                Ok(Signal::Terminate | Signal::Interrupt) => {
+                Ok(Signal::Terminate) => {
                     log::info!(target: "node", "Termination signal received; shutting down..");
+
+                    // Give in-flight fetches a chance to complete before tearing down.
+                    match self.handle.drain(self.shutdown_grace) {
+                        Ok(done) => {
+                            done.recv_timeout(self.shutdown_grace + SHUTDOWN_TIMEOUT_MARGIN)
+                                .ok();
+                        }
+                        Err(e) => {
+                            log::warn!(target: "node", "Failed to drain connections: {e}");
+                        }
+                    }
                     self.handle.shutdown().ok();
                     break;
                 }
-                Ok(Signal::Hangup) => {
-                    log::debug!(target: "node", "Hangup signal (SIGHUP) received; ignoring..");
+                Ok(Signal::Interrupt) => {
+                    log::info!(target: "node", "Interrupt signal received; shutting down..");
+                    self.handle.shutdown().ok();
+                    break;
+                }
+                Ok(Signal::Hangup) => match &self.config_path {
+                    Some(path) => {
+                        log::info!(target: "node", "Hangup signal (SIGHUP) received; reloading configuration..");
+                        reload(&self.handle, path, self.log_level, &self.policy);
+                    }
+                    None => {
+                        log::debug!(target: "node", "Hangup signal (SIGHUP) received; ignoring..");
+                    }
+                },
+                Ok(Signal::User1) => {
+                    let level = verbosity(log::max_level(), 1);
+                    log::set_max_level(level);
+                    log::info!(target: "node", "User signal (SIGUSR1) received; log level set to {level}");
+                }
+                Ok(Signal::User2) => {
+                    let level = verbosity(log::max_level(), -1);
+                    log::info!(target: "node", "User signal (SIGUSR2) received; log level set to {level}");
+                    log::set_max_level(level);
                 }
                 Ok(Signal::WindowChanged) => {}
                 Err(e) => {
@@ -322,17 +550,41 @@ impl Runtime {
         Ok(())
     }
 
+    /// Receive the listeners passed by systemd, once. Only file descriptors
+    /// named [`CONTROL_FD_NAME`] or [`PEER_FD_NAME`] are used, all others are
+    /// left alone, since their purpose is unknown.
     #[cfg(all(feature = "systemd", target_os = "linux"))]
-    fn receive_listener() -> Option<Listener> {
-        let fd = match radicle_systemd::listen::fd("control") {
-            Ok(Some(fd)) => fd,
-            Ok(None) => return None,
+    fn receive_listeners() -> Received {
+        let mut received = Received::default();
+        let fds = match radicle_systemd::listen::take_named_fds() {
+            Ok(fds) => fds,
             Err(err) => {
-                log::error!(target: "node", "Error receiving listener from systemd: {err}");
-                return None;
+                log::error!(target: "node", "Error receiving listeners from systemd: {err}");
+                return received;
             }
         };
 
+        for (name, fd) in fds {
+            match name.as_deref() {
+                Some(CONTROL_FD_NAME) if received.control.is_none() => {
+                    received.control = Self::receive_listener(fd);
+                }
+                Some(PEER_FD_NAME) => {
+                    received.peers.extend(Self::receive_peer_listener(fd));
+                }
+                Some(name) => {
+                    log::warn!(target: "node", "Ignoring file descriptor {fd} named '{name}' received from systemd.");
+                }
+                None => {
+                    log::warn!(target: "node", "Ignoring unnamed file descriptor {fd} received from systemd; set `FileDescriptorName=` to '{CONTROL_FD_NAME}' or '{PEER_FD_NAME}'.");
+                }
+            }
+        }
+        received
+    }
+
+    #[cfg(all(feature = "systemd", target_os = "linux"))]
+    fn receive_listener(fd: std::os::fd::RawFd) -> Option<Listener> {
         let socket: socket2::Socket = unsafe { std::os::fd::FromRawFd::from_raw_fd(fd) };
 
         let domain = match socket.domain() {
@@ -351,13 +603,37 @@ impl Runtime {
         Some(Listener::from(socket))
     }
 
-    fn bind(path: PathBuf) -> Result<ControlSocket, Error> {
-        #[cfg(all(feature = "systemd", target_os = "linux"))]
-        {
-            if let Some(listener) = Self::receive_listener() {
-                log::info!(target: "node", "Received control socket.");
-                return Ok(ControlSocket::Received(listener));
+    /// Receive a listener for peer connections passed by systemd, which must
+    /// be a TCP socket.
+    #[cfg(all(feature = "systemd", target_os = "linux"))]
+    fn receive_peer_listener(fd: std::os::fd::RawFd) -> Option<reactor::Listener> {
+        let socket: socket2::Socket = unsafe { std::os::fd::FromRawFd::from_raw_fd(fd) };
+
+        match socket.domain() {
+            Ok(socket2::Domain::IPV4 | socket2::Domain::IPV6) => {}
+            Ok(_) => {
+                log::error!(target: "node", "Dropping listener received from systemd: Domain is neither AF_INET nor AF_INET6.");
+                return None;
             }
+            Err(err) => {
+                log::error!(target: "node", "Error receiving listener from systemd when inspecting domain of socket: {err}");
+                return None;
+            }
+        }
+
+        match reactor::Listener::from_std(socket.into()) {
+            Ok(listener) => Some(listener),
+            Err(err) => {
+                log::error!(target: "node", "Error receiving listener from systemd: {err}");
+                None
+            }
+        }
+    }
+
+    fn bind(path: PathBuf, received: Option<Listener>) -> Result<ControlSocket, Error> {
+        if let Some(listener) = received {
+            log::info!(target: "node", "Received control socket.");
+            return Ok(ControlSocket::Received(listener));
         }
 
         log::info!(target: "node", "Binding control socket {}..", &path.display());
@@ -45,6 +45,9 @@ pub enum Error {
     /// An I/O error occurred.
     #[error(transparent)]
     Io(#[from] std::io::Error),
+    /// The command isn't supported by the handle.
+    #[error(transparent)]
+    Unsupported(#[from] radicle::node::UnsupportedCommand),
 }
 
 impl From<chan::RecvError> for Error {
@@ -120,6 +123,27 @@ impl Handle {
         self.controller.cmd(wire::Control::Flush { remote, stream })
     }
 
+    /// Stop accepting new connections and fetches, and disconnect from peers once active
+    /// fetches have finished, or `grace` has elapsed. The returned channel is notified once
+    /// all peers are disconnected.
+    pub fn drain(&self, grace: time::Duration) -> Result<chan::Receiver<()>, io::Error> {
+        let (done, receiver) = chan::bounded(1);
+        self.controller
+            .cmd(wire::Control::Shutdown { grace, done })?;
+
+        Ok(receiver)
+    }
+
+    /// Reload the node configuration, applying what can be applied while the
+    /// node runs. Returns the changed fields that require a restart.
+    pub fn reload(&self, config: radicle::node::Config) -> Result<Vec<&'static str>, Error> {
+        let (sender, receiver) = chan::bounded(1);
+        self.controller
+            .cmd(wire::Control::Reload(Box::new(config), sender))?;
+
+        receiver.recv().map_err(Error::from)
+    }
+
     pub(crate) fn command(&self, cmd: service::Command) -> Result<(), io::Error> {
         self.controller.cmd(wire::Control::User(cmd))
     }
@@ -259,6 +283,18 @@ impl radicle::node::Handle for Handle {
         receiver.recv().map_err(Error::from)
     }
 
+    fn block(&mut self, id: RepoId) -> Result<bool, Error> {
+        let (sender, receiver) = chan::bounded(1);
+        self.command(service::Command::Block(id, sender))?;
+        receiver.recv().map_err(Error::from)
+    }
+
+    fn unblock(&mut self, id: RepoId) -> Result<bool, Error> {
+        let (sender, receiver) = chan::bounded(1);
+        self.command(service::Command::Unblock(id, sender))?;
+        receiver.recv().map_err(Error::from)
+    }
+
     fn announce_refs_for(
         &mut self,
         id: RepoId,
@@ -392,8 +428,41 @@ impl radicle::node::Handle for Handle {
         self.command(service::Command::QueryState(query, err_sender))?;
         err_receiver.recv()??;
 
-        let debug = receiver.recv()?;
+        let mut debug = receiver.recv()?;
+
+        let (sender, receiver) = chan::bounded(1);
+        self.controller.cmd(wire::Control::Snapshot(sender))?;
+        debug["wire"] = json!(receiver.recv()?);
 
         Ok(debug)
     }
+
+    fn uptime(&self) -> Result<time::Duration, Self::Error> {
+        let (sender, receiver) = chan::bounded(1);
+        self.controller.cmd(wire::Control::Uptime(sender))?;
+
+        Ok(receiver.recv()?)
+    }
+
+    fn metrics(&self) -> Result<serde_json::Value, Self::Error> {
+        let (sender, receiver) = chan::bounded(1);
+        let query: Arc<QueryState> = Arc::new(move |state| {
+            let metrics = json!(state.metrics().snapshot());
+            sender.send(metrics).ok();
+
+            Ok(())
+        });
+        let (err_sender, err_receiver) = chan::bounded(1);
+        self.command(service::Command::QueryState(query, err_sender))?;
+        err_receiver.recv()??;
+
+        let metrics = receiver.recv()?;
+
+        Ok(metrics)
+    }
+
+    fn set_log_level(&mut self, level: log::Level) -> Result<(), Self::Error> {
+        self.command(service::Command::SetLogLevel(level))
+            .map_err(Error::from)
+    }
 }
@@ -36,8 +36,9 @@ use radicle::{cob, explorer};
 use radicle::{git, web};
 
 use crate::node::NodeId;
+use crate::runtime::{ControlMode, Handle, RuntimeOptions};
 use crate::storage::git::transport;
-use crate::{runtime, runtime::Handle, service, Runtime};
+use crate::{runtime, service, Runtime};
 
 /// Test environment.
 pub struct Environment {
@@ -516,6 +517,11 @@ where
 {
     /// Spawn a node in its own thread.
     pub fn spawn(self) -> NodeHandle<G> {
+        let options = RuntimeOptions {
+            control: ControlMode::Socket(self.home.socket()),
+            config: None,
+            log_level: None,
+        };
         let listen = vec![([0, 0, 0, 0], 0).into()];
         let (_, signals) = chan::bounded(1);
         let rt = Runtime::init(
@@ -524,6 +530,7 @@ where
             listen,
             signals,
             self.signer.clone(),
+            options,
         )
         .unwrap();
         let addr = *rt.local_addrs.first().unwrap();
@@ -17,7 +17,9 @@ use radicle::node::NodeId;
 pub struct Handle {
     pub updates: Arc<Mutex<Vec<(RepoId, PublicKey)>>>,
     pub seeding: Arc<Mutex<HashSet<RepoId>>>,
+    pub blocked: Arc<Mutex<HashSet<RepoId>>>,
     pub following: Arc<Mutex<HashSet<NodeId>>>,
+    pub log_level: Arc<Mutex<Option<log::Level>>>,
 }
 
 impl radicle::node::Handle for Handle {
@@ -84,6 +86,16 @@ impl radicle::node::Handle for Handle {
         Ok(self.seeding.lock().unwrap().remove(&id))
     }
 
+    fn block(&mut self, id: RepoId) -> Result<bool, Self::Error> {
+        self.seeding.lock().unwrap().remove(&id);
+
+        Ok(self.blocked.lock().unwrap().insert(id))
+    }
+
+    fn unblock(&mut self, id: RepoId) -> Result<bool, Self::Error> {
+        Ok(self.blocked.lock().unwrap().remove(&id))
+    }
+
     fn follow(&mut self, id: NodeId, _alias: Option<Alias>) -> Result<bool, Self::Error> {
         Ok(self.following.lock().unwrap().insert(id))
     }
@@ -135,4 +147,18 @@ impl radicle::node::Handle for Handle {
     fn debug(&self) -> Result<serde_json::Value, Self::Error> {
         Ok(serde_json::Value::Null)
     }
+
+    fn metrics(&self) -> Result<serde_json::Value, Self::Error> {
+        Ok(serde_json::Value::Null)
+    }
+
+    fn uptime(&self) -> Result<std::time::Duration, Self::Error> {
+        Ok(std::time::Duration::ZERO)
+    }
+
+    fn set_log_level(&mut self, level: log::Level) -> Result<(), Self::Error> {
+        *self.log_level.lock().unwrap() = Some(level);
+
+        Ok(())
+    }
 }
@@ -32,11 +32,13 @@ use radicle::rad;
 use radicle::storage::{ReadStorage as _, RemoteRepository as _, SignRepository as _};
 use radicle::test::fixtures;
 use radicle::Storage;
+use radicle_signals::Signal;
 
 use crate::node::device::Device;
 use crate::node::NodeId;
+use crate::runtime::{ControlMode, Handle, RuntimeOptions};
 use crate::storage::git::transport;
-use crate::{runtime, runtime::Handle, service, Runtime};
+use crate::{runtime, service, Runtime};
 
 /// A node that can be run.
 pub struct Node<G> {
@@ -80,6 +82,8 @@ pub struct NodeHandle<G: 'static> {
     pub addr: net::SocketAddr,
     pub thread: ManuallyDrop<thread::JoinHandle<Result<(), runtime::Error>>>,
     pub handle: ManuallyDrop<Handle>,
+    /// Delivers signals to the node, as if sent to the process.
+    pub signals: chan::Sender<Signal>,
 }
 
 impl<G: 'static> Drop for NodeHandle<G> {
@@ -459,15 +463,26 @@ impl Node<MockSigner> {
 impl<G: cyphernet::Ecdh<Pk = NodeId> + Signer<Signature> + Clone + Debug> Node<G> {
     /// Spawn a node in its own thread.
     pub fn spawn(self) -> NodeHandle<G> {
+        let options = RuntimeOptions {
+            control: ControlMode::Socket(self.home.socket()),
+            config: None,
+            log_level: None,
+        };
+        self.spawn_with(options)
+    }
+
+    /// Spawn a node in its own thread, with the given runtime options.
+    pub fn spawn_with(self, options: RuntimeOptions) -> NodeHandle<G> {
         let alias = self.config.alias.clone();
         let listen = vec![([0, 0, 0, 0], 0).into()];
-        let (_, signals) = chan::bounded(1);
+        let (notify, signals) = chan::bounded(1);
         let rt = Runtime::init(
             self.home.clone(),
             self.config,
             listen,
             signals,
             self.signer.clone(),
+            options,
         )
         .unwrap();
         let addr = *rt.local_addrs.first().unwrap();
@@ -484,6 +499,7 @@ impl<G: cyphernet::Ecdh<Pk = NodeId> + Signer<Signature> + Clone + Debug> Node<G
             addr,
             handle,
             thread,
+            signals: notify,
         }
     }
 
@@ -427,6 +427,17 @@ fn test_seeding() {
     assert!(!alice.policies().is_seeding(&proj_id).unwrap());
 }
 
+#[test]
+fn test_set_log_level() {
+    let mut alice = Peer::new("alice", [7, 7, 7, 7]);
+    let level = log::max_level();
+
+    alice.command(Command::SetLogLevel(log::Level::Trace));
+    assert_eq!(log::max_level(), log::LevelFilter::Trace);
+
+    log::set_max_level(level);
+}
+
 #[test]
 fn test_inventory_relay_bad_timestamp() {
     let mut alice = Peer::new("alice", [7, 7, 7, 7]);
@@ -599,6 +610,31 @@ fn test_announcement_rebroadcast_timestamp_filtered() {
     assert_eq!(relayed, second);
 }
 
+#[test]
+fn test_announcement_rebroadcast_blocked_filtered() {
+    let mut alice = Peer::new("alice", [7, 7, 7, 7]);
+    let bob = Peer::new("bob", [8, 8, 8, 8]);
+    let eve = Peer::new("eve", [9, 9, 9, 9]);
+    let seeded = arbitrary::gen::<RepoId>(1);
+    let blocked = arbitrary::gen::<RepoId>(1);
+
+    alice.seed(&seeded, policy::Scope::All).unwrap();
+    alice.block(&blocked).unwrap();
+    alice.connect_to(&bob);
+    alice.receive(bob.id(), bob.node_announcement());
+    alice.receive(bob.id(), bob.refs_announcement(seeded));
+    alice.receive(bob.id(), bob.refs_announcement(blocked));
+
+    // Eve subscribes to everything, but Alice leaves out the repository she blocks.
+    alice.connect_from(&eve);
+    alice.receive(eve.id(), Message::Subscribe(Subscribe::all()));
+
+    let relayed = alice.relayed(eve.id()).collect::<BTreeSet<_>>();
+
+    assert!(relayed.contains(&bob.refs_announcement(seeded)));
+    assert!(!relayed.contains(&bob.refs_announcement(blocked)));
+}
+
 #[test]
 fn test_announcement_relay() {
     let mut alice = Peer::new("alice", [7, 7, 7, 7]);
@@ -1282,6 +1318,78 @@ fn test_persistent_peer_reconnect_success() {
     alice.connected(bob.id(), bob.addr(), Link::Outbound);
 }
 
+#[test]
+fn test_persistent_peer_reconnect_rate_limited() {
+    use std::collections::HashSet;
+
+    let bob = Peer::with_storage("bob", [9, 9, 9, 9], MockStorage::empty());
+    let mut alice = Peer::config(
+        "alice",
+        [7, 7, 7, 7],
+        MockStorage::empty(),
+        peer::Config {
+            config: Config {
+                connect: HashSet::from_iter([(bob.id, bob.addr()).into()]),
+                ..Config::new(node::Alias::new("alice"))
+            },
+            ..peer::Config::default()
+        },
+    )
+    .initialized();
+    alice.connect_to(&bob);
+
+    // Bob asks Alice to back off for longer than she would otherwise wait.
+    let retry_after = LocalDuration::from_mins(5);
+    alice.disconnected(
+        bob.id(),
+        Link::Outbound,
+        &DisconnectReason::RateLimited { retry_after },
+    );
+    alice.elapse(service::MIN_RECONNECTION_DELTA);
+    assert!(!alice.outbox().any(|o| matches!(o, Io::Connect(_, _))));
+
+    alice.elapse(retry_after);
+    alice
+        .outbox()
+        .find(|o| matches!(o, Io::Connect(id, _) if id == &bob.id))
+        .expect("Alice attempts a re-connection");
+}
+
+#[test]
+fn test_persistent_peer_reconnect_rate_limited_clamped() {
+    use std::collections::HashSet;
+
+    let bob = Peer::with_storage("bob", [9, 9, 9, 9], MockStorage::empty());
+    let mut alice = Peer::config(
+        "alice",
+        [7, 7, 7, 7],
+        MockStorage::empty(),
+        peer::Config {
+            config: Config {
+                connect: HashSet::from_iter([(bob.id, bob.addr()).into()]),
+                ..Config::new(node::Alias::new("alice"))
+            },
+            ..peer::Config::default()
+        },
+    )
+    .initialized();
+    alice.connect_to(&bob);
+
+    // Bob asks Alice to back off for longer than her maximum reconnection delay.
+    alice.disconnected(
+        bob.id(),
+        Link::Outbound,
+        &DisconnectReason::RateLimited {
+            retry_after: LocalDuration::from_mins(60 * 24 * 365),
+        },
+    );
+    alice.elapse(service::MAX_RECONNECTION_DELTA);
+    alice
+        .outbox()
+        .find(|o| matches!(o, Io::Connect(id, _) if id == &bob.id))
+        .expect("Alice attempts a re-connection");
+}
+
 #[test]
 fn test_maintain_connections() {
     // Peers alice starts out connected to.
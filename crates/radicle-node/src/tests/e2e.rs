@@ -1,5 +1,6 @@
-use std::{collections::HashSet, thread, time};
+use std::{collections::HashSet, fs, thread, time};
 
+use crossbeam_channel as chan;
 use radicle::cob::Title;
 use test_log::test;
 
@@ -15,9 +16,11 @@ use radicle::storage::{
 use radicle::test::fixtures;
 use radicle::{assert_matches, rad};
 use radicle::{git, issue};
+use radicle_signals::Signal;
 
-use crate::node::config::Limits;
+use crate::node::config::{DefaultSeedingPolicy, Limits};
 use crate::node::{Config, ConnectOptions};
+use crate::runtime::{ControlMode, RuntimeOptions};
 use crate::service;
 use crate::storage::git::transport;
 use crate::test::node::{converge, Node};
@@ -645,6 +648,126 @@ fn test_fetch_unseeded() {
     assert_matches!(result, FetchResult::Failed { .. });
 }
 
+#[test]
+fn test_control_disabled() {
+    let tmp = tempfile::tempdir().unwrap();
+    let alice = Node::init(tmp.path(), config::relay("alice"));
+    let socket = alice.home.socket();
+    let alice = alice.spawn_with(RuntimeOptions {
+        control: ControlMode::Disabled,
+        config: None,
+        log_level: None,
+    });
+
+    // The node runs, and can be controlled through its runtime handle.
+    assert!(alice.handle.is_running());
+    assert!(alice.handle.uptime().is_ok());
+    assert!(!socket.exists());
+
+    alice.shutdown();
+    assert!(!socket.exists());
+}
+
+#[test]
+fn test_control_in_process() {
+    let tmp = tempfile::tempdir().unwrap();
+    let alice = Node::init(tmp.path(), config::relay("alice"));
+    let socket = alice.home.socket();
+    let (sender, receiver) = chan::bounded(1);
+    let alice = alice.spawn_with(RuntimeOptions {
+        control: ControlMode::InProcess(sender),
+        config: None,
+        log_level: None,
+    });
+    let control = receiver.recv_timeout(DEFAULT_TIMEOUT).unwrap();
+
+    assert_eq!(control.handle.nid().unwrap(), alice.id);
+    assert!(!socket.exists());
+}
+
+#[test]
+fn test_reload_config() {
+    let tmp = tempfile::tempdir().unwrap();
+    let alice = Node::init(tmp.path(), config::relay("alice"));
+    let path = tmp.path().join("config.json");
+    let mut profile = radicle::profile::Config::new(alice.config.alias.clone());
+    profile.preferred_seeds.clear();
+    profile.node = Config {
+        log: log::Level::Trace.into(),
+        seeding_policy: DefaultSeedingPolicy::permissive(),
+        listen: vec![([0, 0, 0, 0], 8776).into()],
+        limits: Limits {
+            max_frame_size: (1024 * 1024).into(),
+            ..alice.config.limits.clone()
+        },
+        ..alice.config.clone()
+    };
+    fs::write(&path, serde_json::to_vec(&profile).unwrap()).unwrap();
+
+    let level = log::max_level();
+    let alice = alice.spawn_with(RuntimeOptions {
+        control: ControlMode::Disabled,
+        config: Some(path),
+        log_level: None,
+    });
+    alice.signals.send(Signal::Hangup).unwrap();
+
+    // Signals are handled asynchronously.
+    let deadline = time::Instant::now() + DEFAULT_TIMEOUT;
+    let config = loop {
+        let config = alice.handle.config().unwrap();
+        if config.seeding_policy.is_allow() {
+            break config;
+        }
+        assert!(
+            time::Instant::now() < deadline,
+            "configuration was not reloaded"
+        );
+        thread::sleep(time::Duration::from_millis(10));
+    };
+    assert_eq!(log::max_level(), log::LevelFilter::Trace);
+    assert_eq!(usize::from(config.limits.max_frame_size), 1024 * 1024);
+    // Listen addresses only change on restart.
+    assert!(config.listen.is_empty());
+
+    log::set_max_level(level);
+}
+
+#[test]
+fn test_fetch_blocked() {
+    let tmp = tempfile::tempdir().unwrap();
+    let alice = Node::init(tmp.path(), config::relay("alice"));
+    let mut bob = Node::init(tmp.path(), config::relay("bob"));
+    let acme = bob.project("acme", "");
+
+    let mut alice = alice.spawn();
+    let mut bob = bob.spawn();
+
+    alice.connect(&bob);
+    converge([&alice, &bob]);
+
+    transport::local::register(alice.storage.clone());
+
+    let _ = alice.handle.seed(acme, Scope::All).unwrap();
+    let result = alice.handle.fetch(acme, bob.id, DEFAULT_TIMEOUT).unwrap();
+    assert!(result.is_success());
+
+    // Bob blocks the repository, without restarting.
+    assert!(bob.handle.block(acme).unwrap());
+    assert!(!bob.handle.block(acme).unwrap());
+
+    // Alice's fetch request is declined.
+    let result = alice.handle.fetch(acme, bob.id, DEFAULT_TIMEOUT).unwrap();
+    assert_matches!(result, FetchResult::Failed { .. });
+
+    // Once unblocked and seeded again, Bob serves the repository.
+    assert!(bob.handle.unblock(acme).unwrap());
+    assert!(bob.handle.seed(acme, Scope::All).unwrap());
+
+    let result = alice.handle.fetch(acme, bob.id, DEFAULT_TIMEOUT).unwrap();
+    assert!(result.is_success());
+}
+
 #[test]
 fn test_large_fetch() {
     let tmp = tempfile::tempdir().unwrap();
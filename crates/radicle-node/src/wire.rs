@@ -13,20 +13,30 @@ use cyphernet::addr::{HostName, InetHost, NetAddr};
 use cyphernet::encrypt::noise::{HandshakePattern, Keyset, NoiseState};
 use cyphernet::proxy::socks5;
 use cyphernet::{Digest, EcSk, Ecdh, Sha256};
+use localtime::{LocalDuration, LocalTime};
 use mio::net::TcpStream;
 use radicle::node::device::Device;
+use radicle::security::CompressionLayer;
 
 use radicle::collections::{RandomMap, RandomSet};
 use radicle::crypto;
+use radicle::node::address;
 use radicle::node::config::AddressConfig;
+use radicle::node::config::{FetchPackSizeLimit, InboxOverflow, KeepAlive, Limits, RateLimit};
+use radicle::node::Event;
+use radicle::node::Features;
 use radicle::node::Link;
 use radicle::node::NodeId;
+use radicle::prelude::RepoId;
+use radicle::storage::refs::RefsAt;
 use radicle::storage::WriteStorage;
+use radicle_protocol::bounded::{BoundedQueue, BoundedVec, Overflow};
 use radicle_protocol::deserializer::Deserializer;
 pub use radicle_protocol::wire::frame;
 pub use radicle_protocol::wire::frame::{Frame, FrameData, StreamId};
 pub use radicle_protocol::wire::*;
-use radicle_protocol::worker::{FetchRequest, FetchResult};
+use radicle_protocol::worker::{FetchError, FetchRequest, FetchResult};
+use serde::Serialize;
 
 use crate::reactor;
 use crate::reactor::{Listener, Transport};
@@ -34,8 +44,10 @@ use crate::reactor::{NoiseSession, ProtocolArtifact, SessionEvent, Socks5Session
 use crate::reactor::{Token, Tokens};
 use crate::service;
 use crate::service::io::Io;
-use crate::service::FETCH_TIMEOUT;
+use crate::service::limiter::RateLimiter;
+use crate::service::message::{Announcement, AnnouncementMessage, InventoryAnnouncement};
 use crate::service::{session, DisconnectReason, Metrics, Service};
+use crate::service::{FETCH_TIMEOUT, IDLE_INTERVAL};
 use crate::worker;
 use crate::worker::{ChannelEvent, ChannelsConfig};
 use crate::worker::{Task, TaskResult};
@@ -46,11 +58,18 @@ pub const NOISE_XK: HandshakePattern = HandshakePattern {
     responder: cyphernet::encrypt::noise::OneWayPattern::Known,
 };
 
-/// Default time to wait until a network connection is considered inactive.
-pub const DEFAULT_CONNECTION_TIMEOUT: time::Duration = time::Duration::from_secs(6);
+/// Maximum number of gossip messages held back when a peer inbox is full, unless
+/// the inbox overflow policy is to reject.
+pub const MAX_INBOX_BACKLOG: usize = 64;
 
-/// Maximum size of a peer inbox, in bytes.
-pub const MAX_INBOX_SIZE: usize = 1024 * 1024 * 2;
+/// Maximum number of frames decoded from a peer inbox per tick. Any remaining
+/// frames are decoded on the next tick, so that a single peer sending many small
+/// frames can't monopolize the reactor.
+pub const MAX_INBOX_FRAMES: usize = 256;
+
+/// How long a peer that is disconnected for sending data too fast is asked to
+/// wait before reconnecting.
+pub const RATE_LIMITED_RETRY_AFTER: LocalDuration = LocalDuration::from_mins(1);
 
 /// Control message used internally between workers, users, and the service.
 #[allow(clippy::large_enum_variant)]
@@ -62,6 +81,17 @@ pub enum Control {
     Worker(TaskResult),
     /// Flush data in the given stream to the remote.
     Flush { remote: NodeId, stream: StreamId },
+    /// Begin a graceful shutdown, see [`Wire::begin_shutdown`].
+    Shutdown {
+        grace: time::Duration,
+        done: chan::Sender<()>,
+    },
+    /// Get the time elapsed since the node started.
+    Uptime(chan::Sender<time::Duration>),
+    /// Get a snapshot of the connection state, for debugging.
+    Snapshot(chan::Sender<Snapshot>),
+    /// Reload the node configuration, see [`Wire::reload`].
+    Reload(Box<radicle::node::Config>, chan::Sender<Vec<&'static str>>),
 }
 
 /// Peer session type.
@@ -78,6 +108,10 @@ struct Stream {
     sent_bytes: usize,
     /// Data received.
     received_bytes: usize,
+    /// Whether git data we send is compressed.
+    compress: bool,
+    /// Whether git data we receive is compressed.
+    decompress: bool,
 }
 
 impl Stream {
@@ -86,6 +120,8 @@ impl Stream {
             channels,
             sent_bytes: 0,
             received_bytes: 0,
+            compress: false,
+            decompress: false,
         }
     }
 }
@@ -184,8 +220,14 @@ enum Peer {
         addr: NetAddr<HostName>,
         link: Link,
         nid: NodeId,
-        inbox: Deserializer<MAX_INBOX_SIZE, Frame>,
+        /// Data received from the peer, limited to the configured inbox size.
+        inbox: Deserializer<{ usize::MAX }, Frame>,
+        /// Gossip messages decoded to make room in the inbox, not yet handed to
+        /// the service.
+        backlog: BoundedQueue<service::Message, MAX_INBOX_BACKLOG>,
         streams: Streams,
+        /// Whether we advertised our supported versions to the peer.
+        advertised: bool,
     },
     /// The peer was scheduled for disconnection. Once the transport is handed over
     /// by the reactor, we can consider it disconnected.
@@ -222,21 +264,58 @@ impl Peer {
     }
 
     /// Connected peer.
-    fn connected(nid: NodeId, addr: NetAddr<HostName>, link: Link) -> Self {
+    fn connected(nid: NodeId, addr: NetAddr<HostName>, link: Link, limits: &Limits) -> Self {
+        let overflow = match limits.inbox_overflow {
+            InboxOverflow::Reject => Overflow::Reject,
+            InboxOverflow::DropOldest => Overflow::DropOldest,
+            InboxOverflow::DropNewest => Overflow::DropNewest,
+        };
         Self::Connected {
             link,
             addr,
             nid,
-            inbox: Deserializer::default(),
+            inbox: Deserializer::default()
+                .with_limit(limits.inbox_size.into())
+                .with_budget(MAX_INBOX_FRAMES),
+            backlog: BoundedQueue::new(overflow),
             streams: Streams::new(link),
+            advertised: false,
         }
     }
 }
 
 /// Holds connected peers.
+///
+/// Peers are iterated in arbitrary order, except under test, where they are
+/// iterated in token order, so that behavior depending on the order, eg.
+/// resolving conflicting sessions, is reproducible.
 struct Peers(RandomMap<Token, Peer>);
 
 impl Peers {
+    #[cfg(not(any(test, feature = "test")))]
+    fn entries(&self) -> impl Iterator<Item = (&Token, &Peer)> {
+        self.0.iter()
+    }
+
+    #[cfg(any(test, feature = "test"))]
+    fn entries(&self) -> impl Iterator<Item = (&Token, &Peer)> {
+        let mut entries = self.0.iter().collect::<Vec<_>>();
+        entries.sort_unstable_by_key(|(token, _)| **token);
+        entries.into_iter()
+    }
+
+    #[cfg(not(any(test, feature = "test")))]
+    fn entries_mut(&mut self) -> impl Iterator<Item = (&Token, &mut Peer)> {
+        self.0.iter_mut()
+    }
+
+    #[cfg(any(test, feature = "test"))]
+    fn entries_mut(&mut self) -> impl Iterator<Item = (&Token, &mut Peer)> {
+        let mut entries = self.0.iter_mut().collect::<Vec<_>>();
+        entries.sort_unstable_by_key(|(token, _)| **token);
+        entries.into_iter()
+    }
+
     fn get_mut(&mut self, token: &Token) -> Option<&mut Peer> {
         self.0.get_mut(token)
     }
@@ -256,28 +335,26 @@ impl Peers {
     }
 
     fn lookup(&self, id: &NodeId) -> Option<(Token, &Peer)> {
-        self.0
-            .iter()
+        self.entries()
             .find(|(_, peer)| peer.id() == Some(id))
             .map(|(token, peer)| (*token, peer))
     }
 
     fn lookup_mut(&mut self, id: &NodeId) -> Option<(Token, &mut Peer)> {
-        self.0
-            .iter_mut()
+        self.entries_mut()
             .find(|(_, peer)| peer.id() == Some(id))
             .map(|(fd, peer)| (*fd, peer))
     }
 
     fn active(&self) -> impl Iterator<Item = (Token, &NodeId, Link)> {
-        self.0.iter().filter_map(|(id, peer)| match peer {
+        self.entries().filter_map(|(id, peer)| match peer {
             Peer::Connected { nid, link, .. } => Some((*id, nid, *link)),
             Peer::Disconnecting { .. } => None,
         })
     }
 
     fn connected(&self) -> impl Iterator<Item = (Token, &NodeId)> {
-        self.0.iter().filter_map(|(id, peer)| {
+        self.entries().filter_map(|(id, peer)| {
             if let Peer::Connected { nid, .. } = peer {
                 Some((*id, nid))
             } else {
@@ -287,10 +364,65 @@ impl Peers {
     }
 
     fn iter(&self) -> impl Iterator<Item = &Peer> {
-        self.0.values()
+        self.entries().map(|(_, peer)| peer)
     }
 }
 
+/// A point-in-time view of the wire state, used for debugging.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Snapshot {
+    /// Peers with an established session, or being disconnected.
+    pub peers: Vec<PeerSnapshot>,
+    /// Outbound connections that haven't completed their handshake.
+    pub outbound: Vec<OutboundSnapshot>,
+    /// Tokens of inbound connections that haven't completed their handshake.
+    pub inbound: Vec<usize>,
+    /// Listeners that are not yet registered with the reactor.
+    pub listening: Vec<ListenerSnapshot>,
+}
+
+/// Snapshot of a peer session.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PeerSnapshot {
+    pub token: usize,
+    /// Remote Node ID. Only unknown for peers disconnected before the handshake completed.
+    pub nid: Option<NodeId>,
+    pub link: Link,
+    /// Set if the peer is scheduled for disconnection.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disconnecting: Option<String>,
+    /// Open worker streams. The gossip and control streams are implied.
+    pub streams: Vec<StreamSnapshot>,
+}
+
+/// Snapshot of an open worker stream.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StreamSnapshot {
+    pub id: u64,
+    pub sent_bytes: usize,
+    pub received_bytes: usize,
+}
+
+/// Snapshot of an outbound connection attempt.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutboundSnapshot {
+    pub token: usize,
+    pub nid: NodeId,
+    pub addr: String,
+}
+
+/// Snapshot of a pending listener.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListenerSnapshot {
+    pub token: usize,
+    pub addr: net::SocketAddr,
+}
+
 /// The epoch time of when the node started.
 struct Epoch {
     /// The system time when the node started.
@@ -318,6 +450,81 @@ impl Epoch {
     fn elapsed_time(&self, later: Instant) -> SystemTime {
         self.started_time + (later - self.started_at)
     }
+
+    /// Get the time elapsed since the node started, given the current [`Instant`].
+    /// Since instants are monotonic, this is unaffected by changes to the system clock.
+    fn uptime(&self, now: Instant) -> time::Duration {
+        now.saturating_duration_since(self.started_at)
+    }
+}
+
+/// Keeps the service manager informed that the reactor is not hung, if it
+/// expects keep-alive pings.
+struct Watchdog {
+    /// The time between two pings, i.e. half of the watchdog timeout.
+    interval: time::Duration,
+    /// When the last ping was sent.
+    pinged_at: Option<Instant>,
+}
+
+impl Watchdog {
+    /// Construct a [`Watchdog`] if the service manager expects pings.
+    fn from_env() -> Option<Self> {
+        #[cfg(all(feature = "systemd", target_os = "linux"))]
+        {
+            radicle_systemd::watchdog::enabled().map(|timeout| Self {
+                interval: timeout / 2,
+                pinged_at: None,
+            })
+        }
+
+        #[cfg(not(all(feature = "systemd", target_os = "linux")))]
+        {
+            None
+        }
+    }
+
+    /// Send a ping if one is due, returning the time until the next one is.
+    fn tick(&mut self, now: Instant) -> Option<time::Duration> {
+        if self
+            .pinged_at
+            .is_some_and(|pinged_at| now.duration_since(pinged_at) < self.interval)
+        {
+            return None;
+        }
+        self.pinged_at = Some(now);
+
+        #[cfg(all(feature = "systemd", target_os = "linux"))]
+        if let Err(err) = radicle_systemd::watchdog::ping() {
+            log::warn!(target: "wire", "Failed to ping systemd watchdog: {err}");
+        }
+
+        Some(self.interval)
+    }
+}
+
+/// A fetch that was deferred until the worker pool catches up.
+#[derive(Debug)]
+struct Deferred {
+    rid: RepoId,
+    remote: NodeId,
+    refs_at: Option<Vec<RefsAt>>,
+    timeout: time::Duration,
+    reader_limit: FetchPackSizeLimit,
+}
+
+/// Whether the worker pool has more queued tasks than the limit allows.
+fn saturated(worker: &chan::Sender<Task>, limits: &Limits) -> bool {
+    worker.len() >= usize::from(limits.max_queued_fetches)
+}
+
+/// State of a graceful shutdown.
+#[derive(Debug)]
+struct Drain {
+    /// When we stop waiting for active streams to finish.
+    deadline: Instant,
+    /// Notified once all peers are disconnected.
+    done: Option<chan::Sender<()>>,
 }
 
 /// Wire protocol implementation for a set of peers.
@@ -340,10 +547,24 @@ pub(crate) struct Wire<D, S, G: crypto::signature::Signer<crypto::Signature> + E
     listening: RandomMap<Token, net::SocketAddr>,
     /// Peer (established) sessions.
     peers: Peers,
+    /// Peers with frames left in their inbox, after exhausting their budget.
+    pending: RandomSet<Token>,
     /// A (practically) infinite source of tokens to identify transports and listeners.
     tokens: Tokens,
     /// Record of system time and instant when the node started.
     epoch: Epoch,
+    /// Keep-alive pings to the service manager, if enabled.
+    watchdog: Option<Watchdog>,
+    /// Set once a graceful shutdown has begun.
+    drain: Option<Drain>,
+    /// Limits the rate of inbound connections per IP address.
+    accepts: RateLimiter,
+    /// Last time the inbound connection rate limiter was pruned.
+    accepts_pruned_at: LocalTime,
+    /// Used to compress git data on streams with peers that support it.
+    compression: CompressionLayer,
+    /// Fetches waiting for the worker pool to catch up, in order.
+    deferred: VecDeque<Deferred>,
 }
 
 impl<D, S, G> Wire<D, S, G>
@@ -365,8 +586,16 @@ where
             outbound: RandomMap::default(),
             listening: RandomMap::default(),
             peers: Peers(RandomMap::default()),
+            pending: RandomSet::default(),
             tokens: Tokens::default(),
             epoch: Epoch::now(),
+            watchdog: Watchdog::from_env(),
+            drain: None,
+            accepts: RateLimiter::default(),
+            accepts_pruned_at: LocalTime::default(),
+            compression: CompressionLayer::default()
+                .with_limit(service.config().limits.inbox_size.into()),
+            deferred: VecDeque::new(),
         }
     }
 
@@ -381,6 +610,505 @@ where
             .push_back(Action::RegisterListener(token, socket));
     }
 
+    /// Take a snapshot of the current connection state. Entries are ordered by token.
+    pub fn snapshot(&self) -> Snapshot {
+        let mut peers = self
+            .peers
+            .0
+            .iter()
+            .map(|(token, peer)| {
+                let (disconnecting, mut streams) = match peer {
+                    Peer::Connected { streams, .. } => (
+                        None,
+                        streams
+                            .streams
+                            .iter()
+                            .map(|(id, stream)| StreamSnapshot {
+                                id: u64::from(*id),
+                                sent_bytes: stream.sent_bytes,
+                                received_bytes: stream.received_bytes,
+                            })
+                            .collect::<Vec<_>>(),
+                    ),
+                    Peer::Disconnecting { reason, .. } => (Some(reason.to_string()), Vec::new()),
+                };
+                streams.sort_by_key(|s| s.id);
+
+                PeerSnapshot {
+                    token: token.0,
+                    nid: peer.id().copied(),
+                    link: peer.link(),
+                    disconnecting,
+                    streams,
+                }
+            })
+            .collect::<Vec<_>>();
+        let mut outbound = self
+            .outbound
+            .values()
+            .map(|o| OutboundSnapshot {
+                token: o.token.0,
+                nid: o.nid,
+                addr: o.addr.to_string(),
+            })
+            .collect::<Vec<_>>();
+        let mut inbound = self.inbound.iter().map(|t| t.0).collect::<Vec<_>>();
+        let mut listening = self
+            .listening
+            .iter()
+            .map(|(token, addr)| ListenerSnapshot {
+                token: token.0,
+                addr: *addr,
+            })
+            .collect::<Vec<_>>();
+
+        peers.sort_by_key(|p| p.token);
+        outbound.sort_by_key(|o| o.token);
+        inbound.sort();
+        listening.sort_by_key(|l| l.token);
+
+        Snapshot {
+            peers,
+            outbound,
+            inbound,
+            listening,
+        }
+    }
+
+    /// Begin a graceful shutdown.
+    ///
+    /// New inbound connections and fetches are refused, while active streams are given up to
+    /// `grace` to finish. Once they have, or the grace period has elapsed, all peers are
+    /// disconnected and `done` is notified.
+    pub fn begin_shutdown(&mut self, grace: time::Duration, done: chan::Sender<()>) {
+        if self.drain.is_some() {
+            log::debug!(target: "wire", "Shutdown is already in progress");
+            return;
+        }
+        log::info!(
+            target: "wire",
+            "Shutting down; waiting up to {}s for {} active stream(s)..",
+            grace.as_secs(), self.active_streams()
+        );
+        let now = Instant::now();
+
+        self.drain = Some(Drain {
+            deadline: now + grace,
+            done: Some(done),
+        });
+        // Make sure we wake up once the grace period has elapsed.
+        self.actions.push_back(Action::SetTimer(grace));
+        self.drained(now);
+    }
+
+    /// Decode and process frames from a connected peer's inbox, until either the
+    /// inbox is empty, or its budget of frames for this tick is exhausted.
+    ///
+    /// When `hold_back` is set, the inbox is decoded regardless of the decoding
+    /// budget, to make room for more data, and gossip messages are held back
+    /// instead of being handed to the service. Otherwise, held back messages are
+    /// handed to the service first.
+    fn receive(&mut self, token: Token, hold_back: bool) {
+        let Some(Peer::Connected {
+            nid,
+            link,
+            inbox,
+            backlog,
+            streams,
+            advertised,
+            ..
+        }) = self.peers.get_mut(&token)
+        else {
+            return;
+        };
+        let metrics = self.metrics.peer(*nid);
+
+        if !hold_back {
+            while let Some(msg) = backlog.pop() {
+                metrics.received_gossip_messages += 1;
+                self.service.received_message(*nid, msg);
+            }
+        }
+
+        loop {
+            match inbox.deserialize_next() {
+                Ok(Some(Frame {
+                    data: FrameData::Control(frame::Control::Open { stream }),
+                    ..
+                })) => {
+                    log::debug!(target: "wire", "Received `open` command for stream {stream} from {nid}");
+                    metrics.received_fetch_requests += 1;
+
+                    if saturated(&self.worker, &self.service.config().limits) {
+                        log::debug!(
+                            target: "wire",
+                            "Declining fetch request on stream {stream} from {nid}: worker pool is busy ({} task(s) pending)",
+                            self.worker.len()
+                        );
+                        metrics.declined_fetch_requests += 1;
+
+                        self.actions.push_back(Action::Send(
+                            token,
+                            Frame::<service::Message>::control(
+                                *link,
+                                frame::Control::Close { stream },
+                            )
+                            .encode_to_vec(),
+                        ));
+                        continue;
+                    }
+                    metrics.streams_opened += 1;
+                    let reader_limit = self.service.config().limits.fetch_pack_receive;
+                    let Some(channels) = streams.register(
+                        stream,
+                        ChannelsConfig::new(FETCH_TIMEOUT).with_reader_limit(reader_limit),
+                    ) else {
+                        log::warn!(target: "wire", "Peer attempted to open already-open stream stream {stream}");
+                        continue;
+                    };
+                    if self.service.config().compression
+                        && supports(
+                            self.service.database().addresses(),
+                            nid,
+                            Features::COMPRESSION,
+                        )
+                    {
+                        if let Some(s) = streams.get_mut(&stream) {
+                            s.compress = true;
+                        }
+                        self.actions.push_back(Action::Send(
+                            token,
+                            Frame::<service::Message>::control(
+                                *link,
+                                frame::Control::Compress { stream },
+                            )
+                            .encode_to_vec(),
+                        ));
+                    }
+
+                    let task = Task {
+                        fetch: FetchRequest::Responder {
+                            remote: *nid,
+                            emitter: self.service.emitter(),
+                        },
+                        stream,
+                        channels,
+                    };
+                    if let Err(e) = self.worker.try_send(task) {
+                        log::error!(
+                            target: "wire",
+                            "Worker pool failed to accept incoming fetch request: {e}"
+                        );
+                    }
+                }
+                Ok(Some(Frame {
+                    data: FrameData::Control(frame::Control::Eof { stream }),
+                    ..
+                })) => {
+                    if let Some(s) = streams.get(&stream) {
+                        log::debug!(target: "wire", "Received `end-of-file` on stream {stream} from {nid}");
+
+                        if s.channels.send(ChannelEvent::Eof).is_err() {
+                            log::error!(target: "wire", "Worker is disconnected; cannot send `EOF`");
+                        }
+                    } else {
+                        log::debug!(target: "wire", "Ignoring frame on closed or unknown stream {stream}");
+                    }
+                }
+                Ok(Some(Frame {
+                    data: FrameData::Control(frame::Control::Close { stream }),
+                    ..
+                })) => {
+                    log::debug!(target: "wire", "Received `close` command for stream {stream} from {nid}");
+
+                    if let Some(s) = streams.unregister(&stream) {
+                        log::debug!(
+                            target: "wire",
+                            "Stream {stream} of {nid} closed with {} byte(s) sent and {} byte(s) received",
+                            s.sent_bytes, s.received_bytes
+                        );
+                        s.channels.close().ok();
+                    }
+                }
+                Ok(Some(Frame {
+                    data: FrameData::Control(frame::Control::Compress { stream }),
+                    ..
+                })) => {
+                    if let Some(s) = streams.get_mut(&stream) {
+                        log::debug!(target: "wire", "Received `compress` command for stream {stream} from {nid}");
+                        s.decompress = true;
+                    } else {
+                        log::debug!(target: "wire", "Ignoring frame on closed or unknown stream {stream}");
+                    }
+                }
+                Ok(Some(Frame {
+                    data: FrameData::Control(frame::Control::Versions { range }),
+                    ..
+                })) => {
+                    let ours = frame::VersionRange::SUPPORTED;
+                    let Some(negotiated) = ours.negotiate(&range) else {
+                        log::error!(target: "wire", "Peer {nid} supports protocol versions {range}, which are incompatible with ours ({ours})");
+                        self.disconnect(
+                            token,
+                            DisconnectReason::Session(session::Error::IncompatibleVersion {
+                                ours,
+                                theirs: range,
+                            }),
+                        );
+                        break;
+                    };
+                    if !*advertised {
+                        *advertised = true;
+                        self.actions.push_back(Action::Send(
+                            token,
+                            Frame::<service::Message>::control(
+                                *link,
+                                frame::Control::Versions { range: ours },
+                            )
+                            .encode_to_vec(),
+                        ));
+                    }
+                    log::debug!(target: "wire", "Negotiated protocol version {negotiated} with {nid} (theirs: {range})");
+                }
+                Ok(Some(Frame {
+                    data: FrameData::Control(frame::Control::RateLimited { retry_after }),
+                    ..
+                })) => {
+                    let retry_after = LocalDuration::from_secs(u64::from(retry_after));
+                    log::warn!(target: "wire", "Rate-limited by {nid}; not reconnecting for {retry_after}");
+
+                    self.disconnect(token, DisconnectReason::RateLimited { retry_after });
+                    break;
+                }
+                Ok(Some(Frame {
+                    data: FrameData::Gossip(msg),
+                    ..
+                })) => {
+                    if !hold_back {
+                        metrics.received_gossip_messages += 1;
+                        self.service.received_message(*nid, msg);
+                    } else if !matches!(backlog.push(msg), Ok(None)) {
+                        log::warn!(target: "wire", "Inbox backlog is full for peer {nid}; dropping a gossip message");
+                    }
+                }
+                Ok(Some(Frame {
+                    stream,
+                    data: FrameData::Git(data),
+                    ..
+                })) => {
+                    if let Some(s) = streams.get_mut(&stream) {
+                        metrics.received_git_bytes += data.len();
+
+                        let data = if s.decompress {
+                            match self.compression.decompress(&data) {
+                                Ok(data) => data,
+                                Err(e) => {
+                                    log::error!(target: "wire", "Invalid compressed data on stream {stream} from {nid}: {e}");
+
+                                    self.disconnect(
+                                        token,
+                                        DisconnectReason::Session(session::Error::Misbehavior),
+                                    );
+                                    break;
+                                }
+                            }
+                        } else {
+                            data
+                        };
+                        if s.channels.send(ChannelEvent::Data(data)).is_err() {
+                            log::error!(target: "wire", "Worker is disconnected; cannot send data");
+                        }
+                    } else {
+                        log::debug!(target: "wire", "Ignoring frame on closed or unknown stream {stream}");
+                    }
+                }
+                Ok(None) if inbox.is_exhausted() && hold_back => {
+                    inbox.refill();
+                }
+                Ok(None) if inbox.is_exhausted() => {
+                    // We've decoded as many frames as we're allowed to this tick.
+                    // Resume on the next tick, so that other peers get a chance.
+                    log::trace!(target: "wire", "Inbox budget exhausted for peer {nid}; yielding");
+
+                    self.pending.insert(token);
+                    self.actions
+                        .push_back(Action::SetTimer(time::Duration::ZERO));
+                    break;
+                }
+                Ok(None) => {
+                    // Buffer is empty, or message isn't complete.
+                    break;
+                }
+                Err(e) => {
+                    log::error!(target: "wire", "Invalid gossip message from {nid}: {e}");
+
+                    if !inbox.is_empty() {
+                        log::debug!(target: "wire", "Dropping read buffer for {nid} with {} bytes", inbox.len());
+                    }
+                    self.disconnect(
+                        token,
+                        DisconnectReason::Session(session::Error::Misbehavior),
+                    );
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Advertise our supported protocol versions to a connected peer, unless
+    /// we already did.
+    fn advertise_versions(&mut self, token: Token) {
+        let Some(Peer::Connected {
+            link, advertised, ..
+        }) = self.peers.get_mut(&token)
+        else {
+            return;
+        };
+        if !*advertised {
+            *advertised = true;
+            self.actions.push_back(Action::Send(
+                token,
+                Frame::<service::Message>::control(
+                    *link,
+                    frame::Control::Versions {
+                        range: frame::VersionRange::SUPPORTED,
+                    },
+                )
+                .encode_to_vec(),
+            ));
+        }
+    }
+
+    /// Number of active worker streams, across all connected peers.
+    fn active_streams(&self) -> usize {
+        self.peers
+            .iter()
+            .filter_map(|p| {
+                if let Peer::Connected { streams, .. } = p {
+                    Some(streams.streams.len())
+                } else {
+                    None
+                }
+            })
+            .sum()
+    }
+
+    /// If shutting down, disconnect all peers once there are no more active streams, or
+    /// the grace period has elapsed.
+    fn drained(&mut self, now: Instant) {
+        let Some(drain) = &self.drain else {
+            return;
+        };
+        if drain.done.is_none() {
+            return;
+        }
+        let active = self.active_streams();
+
+        if active > 0 {
+            if now < drain.deadline {
+                return;
+            }
+            log::warn!(target: "wire", "Shutdown grace period elapsed with {active} active stream(s)");
+        }
+        let connected = self
+            .peers
+            .connected()
+            .map(|(token, _)| token)
+            .collect::<Vec<_>>();
+
+        for token in connected {
+            if let Some((nid, _)) = self.disconnect(token, DisconnectReason::Command) {
+                self.metrics.peer(nid).disconnects += 1;
+            }
+        }
+        if let Some(done) = self.drain.as_mut().and_then(|d| d.done.take()) {
+            done.send(()).ok();
+        }
+    }
+
+    /// Disconnect a peer that sends data faster than we can process it. Unlike with
+    /// misbehaving peers, we let the peer know when to reconnect, if it supports it.
+    /// This notice is sent on a best-effort basis, just before the transport is closed.
+    fn rate_limit(&mut self, token: Token, retry_after: LocalDuration) {
+        if let Some(Peer::Connected { nid, link, .. }) = self.peers.get_mut(&token) {
+            if supports(
+                self.service.database().addresses(),
+                nid,
+                Features::RATE_LIMITS,
+            ) {
+                let retry_after = u32::try_from(retry_after.as_secs()).unwrap_or(u32::MAX);
+
+                self.actions.push_back(Action::Send(
+                    token,
+                    Frame::<service::Message>::control(
+                        *link,
+                        frame::Control::RateLimited { retry_after },
+                    )
+                    .encode_to_vec(),
+                ));
+            }
+        }
+        self.disconnect(token, DisconnectReason::RateLimited { retry_after });
+    }
+
+    /// Return the sessions to close, given a newly established session with `nid`.
+    ///
+    /// Conflicting connections are typical when users have mutually configured their nodes to
+    /// connect to each other on startup. We handle this by deterministically choosing one node
+    /// whose outbound connection is the one that is kept. The other connections are dropped.
+    fn conflicts(&self, token: Token, nid: NodeId, link: Link) -> Vec<Token> {
+        // Having precedence means that our outbound connection will win over
+        // the other node's outbound connection.
+        enum Precedence {
+            Ours,
+            Theirs,
+        }
+
+        use Link::*;
+        use Precedence::*;
+
+        // Whether we have precedence in case of conflicting connections.
+        let precedence = if *self.signer.public_key() > nid {
+            Ours
+        } else {
+            Theirs
+        };
+
+        // Active sessions with the same NID but a different token are conflicting.
+        let peers = self
+            .peers
+            .active()
+            .filter_map(|(c_id, d, link)| (*d == nid && c_id != token).then_some((c_id, link)));
+
+        // Outbound connection attempts with the same remote key but a different file
+        // descriptor are conflicting.
+        let outbound = self.outbound.iter().filter_map(|(c_id, other)| {
+            (other.nid == nid && *c_id != token).then_some((*c_id, Outbound))
+        });
+        let mut disconnect = Vec::new();
+
+        for (c_token, c_link) in peers.chain(outbound) {
+            // If we have precedence, the inbound connection is closed.
+            // In the case where both connections are inbound or outbound,
+            // we close the newer connection, ie. the one with the higher
+            // token.
+            let close = match (link, c_link, &precedence) {
+                (Inbound, Outbound, Ours) => token,
+                (Inbound, Outbound, Theirs) => c_token,
+                (Outbound, Inbound, Ours) => c_token,
+                (Outbound, Inbound, Theirs) => token,
+                (Inbound, Inbound, _) => token.max(c_token),
+                (Outbound, Outbound, _) => token.max(c_token),
+            };
+
+            log::warn!(
+                target: "wire", "Established session with token {} conflicts with existing session with token {} for {nid}. Disconnecting session with token {}.", token.0, c_token.0, close.0
+            );
+            disconnect.push(close);
+        }
+        disconnect
+    }
+
     fn disconnect(&mut self, token: Token, reason: DisconnectReason) -> Option<(NodeId, Link)> {
         match self.peers.entry(token) {
             Entry::Vacant(_) => {
@@ -422,6 +1150,148 @@ where
         }
     }
 
+    /// Open a stream for a fetch from a connected peer, and hand it to the
+    /// worker pool.
+    fn fetch(&mut self, fetch: Deferred) {
+        let Deferred {
+            rid,
+            remote,
+            refs_at,
+            timeout,
+            reader_limit,
+        } = fetch;
+
+        log::trace!(target: "wire", "Processing fetch for {rid} from {remote}..");
+
+        if self.drain.is_some() {
+            log::debug!(target: "wire", "Dropping fetch for {rid} from {remote}: shutting down..");
+
+            self.service.fetched(
+                rid,
+                remote,
+                Err(FetchError::Io(io::Error::new(
+                    io::ErrorKind::Interrupted,
+                    "node is shutting down",
+                ))),
+            );
+            return;
+        }
+
+        let Some((fd, Peer::Connected { link, streams, .. })) = self.peers.lookup_mut(&remote)
+        else {
+            // Nb. It's possible that a peer is disconnected while an `Io::Fetch`
+            // is in the service's i/o buffer. Since the service may not purge the
+            // buffer on disconnect, we should just ignore i/o actions that don't
+            // have a connected peer.
+            log::error!(target: "wire", "Peer {remote} is not connected: dropping fetch");
+            return;
+        };
+        let (stream, channels) =
+            streams.open(ChannelsConfig::new(timeout).with_reader_limit(reader_limit));
+        let compress = self.service.config().compression
+            && supports(
+                self.service.database().addresses(),
+                &remote,
+                Features::COMPRESSION,
+            );
+
+        if compress {
+            if let Some(s) = streams.get_mut(&stream) {
+                s.compress = true;
+            }
+        }
+
+        log::debug!(target: "wire", "Opened new stream with id {stream} for {rid} and remote {remote}");
+
+        let link = *link;
+        let task = Task {
+            fetch: FetchRequest::Initiator {
+                rid,
+                remote,
+                refs_at,
+            },
+            stream,
+            channels,
+        };
+
+        if !self.worker.is_empty() {
+            log::warn!(
+                target: "wire",
+                "Worker pool is busy: {} tasks pending, fetch requests may be delayed", self.worker.len()
+            );
+        }
+        if let Err(e) = self.worker.try_send(task) {
+            log::error!(
+                target: "wire",
+                "Worker pool failed to accept outgoing fetch request: {e}"
+            );
+        }
+        let metrics = self.metrics.peer(remote);
+        metrics.streams_opened += 1;
+        metrics.sent_fetch_requests += 1;
+
+        self.actions.push_back(Action::Send(
+            fd,
+            Frame::<service::Message>::control(link, frame::Control::Open { stream })
+                .encode_to_vec(),
+        ));
+        if compress {
+            self.actions.push_back(Action::Send(
+                fd,
+                Frame::<service::Message>::control(link, frame::Control::Compress { stream })
+                    .encode_to_vec(),
+            ));
+        }
+    }
+
+    /// Reload the node configuration, see [`Service::reload`]. Returns the
+    /// changed fields that require a restart.
+    fn reload(&mut self, config: radicle::node::Config) -> Vec<&'static str> {
+        let restart = self.service.reload(config);
+        let limit = self.service.config().limits.inbox_size.into();
+
+        if self.compression.limit() != Some(limit) {
+            self.compression = std::mem::take(&mut self.compression).with_limit(limit);
+        }
+        restart
+    }
+
+    /// Issue deferred fetches, for as long as the worker pool isn't saturated.
+    /// While shutting down, all of them are issued, to be failed.
+    fn undefer(&mut self) {
+        while self.drain.is_some() || !saturated(&self.worker, &self.service.config().limits) {
+            let Some(fetch) = self.deferred.pop_front() else {
+                break;
+            };
+            self.fetch(fetch);
+        }
+    }
+
+    /// Fail deferred fetches from the given peer, once it is no longer connected,
+    /// so that the service and those waiting on the fetches learn of it.
+    fn fail_deferred(&mut self, nid: &NodeId) {
+        if self.peers.active().any(|(_, id, _)| id == nid) {
+            return;
+        }
+        let (failed, deferred): (VecDeque<_>, _) = std::mem::take(&mut self.deferred)
+            .into_iter()
+            .partition(|fetch| fetch.remote == *nid);
+        self.deferred = deferred;
+
+        for Deferred { rid, remote, .. } in failed {
+            log::debug!(target: "wire", "Dropping deferred fetch for {rid} from {remote}: peer disconnected");
+
+            self.service.fetched(
+                rid,
+                remote,
+                Err(FetchError::Io(io::Error::new(
+                    io::ErrorKind::NotConnected,
+                    "peer disconnected",
+                ))),
+            );
+        }
+    }
+
     fn worker_result(&mut self, task: TaskResult) {
         log::debug!(
             target: "wire",
@@ -453,6 +1323,7 @@ where
                 self.actions
                     .push_back(Action::Send(fd, frame.encode_to_vec()));
             }
+            self.drained(Instant::now());
         } else {
             // If the peer disconnected, we'll get here, but we still want to let the service know
             // about the fetch result, so we don't return here.
@@ -495,6 +1366,17 @@ where
         for data in s.channels.try_iter() {
             let frame = match data {
                 ChannelEvent::Data(data) => {
+                    let data = if s.compress {
+                        match self.compression.compress(&data) {
+                            Ok(data) => data,
+                            Err(e) => {
+                                log::error!(target: "wire", "Failed to compress data on stream {stream}: {e}");
+                                continue;
+                            }
+                        }
+                    } else {
+                        data
+                    };
                     metrics.sent_git_bytes += data.len();
                     metrics.sent_bytes += data.len();
                     Frame::<service::Message>::git(stream, data)
@@ -512,11 +1394,15 @@ where
             log::debug!(target: "wire", token=token.0; "Cleaning up inbound peer state");
         } else if let Some(outbound) = self.outbound.remove(&token) {
             log::debug!(target: "wire", token=token.0; "Cleaning up outbound peer state");
-            self.service.disconnected(
-                outbound.nid,
-                Link::Outbound,
-                &DisconnectReason::connection(),
-            );
+            let reason = DisconnectReason::connection();
+
+            self.service.emitter().emit(Event::ConnectionFailed {
+                nid: outbound.nid,
+                addr: outbound.addr.into(),
+                reason: reason.to_string(),
+            });
+            self.service
+                .disconnected(outbound.nid, Link::Outbound, &reason);
         } else {
             log::debug!(target: "wire", token=token.0; "Tried to cleanup unknown peer");
         }
@@ -533,20 +1419,30 @@ where
     type Transport = Transport<WireSession<G>>;
 
     fn tick(&mut self, time: Instant) {
-        self.metrics.open_channels = self
-            .peers
-            .iter()
-            .filter_map(|p| {
-                if let Peer::Connected { streams, .. } = p {
-                    Some(streams.streams.len())
-                } else {
-                    None
-                }
-            })
-            .sum();
+        self.metrics.open_channels = self.active_streams();
         self.metrics.worker_queue_size = self.worker.len();
 
+        for peer in self.peers.0.values_mut() {
+            if let Peer::Connected { inbox, .. } = peer {
+                inbox.refill();
+            }
+        }
+        for token in std::mem::take(&mut self.pending) {
+            self.receive(token, false);
+        }
+
+        let now = LocalTime::from(self.time(time));
+        if now - self.accepts_pruned_at >= IDLE_INTERVAL {
+            self.accepts.prune(now);
+            self.accepts_pruned_at = now;
+        }
+
         self.service.tick(self.time(time).into(), &self.metrics);
+        self.drained(time);
+
+        if let Some(next) = self.watchdog.as_mut().and_then(|w| w.tick(time)) {
+            self.actions.push_back(Action::SetTimer(next));
+        }
     }
 
     fn timer_reacted(&mut self) {
@@ -557,7 +1453,7 @@ where
         &mut self,
         _: Token, // Note that this is the token of the listener socket.
         event: io::Result<(TcpStream, std::net::SocketAddr)>,
-        _: Instant,
+        time: Instant,
     ) {
         match event {
             Ok((connection, peer)) => {
@@ -570,6 +1466,36 @@ where
                 };
                 log::debug!(target: "wire", "Inbound connection from {remote}..");
 
+                if self.drain.is_some() {
+                    log::debug!(target: "wire", "Rejecting inbound connection from {ip}: shutting down..");
+                    drop(connection);
+
+                    return;
+                }
+
+                // Drop connections from addresses that connect too often, before any
+                // resources are spent on a handshake.
+                let limit = usize::from(
+                    self.service
+                        .config()
+                        .limits
+                        .connection
+                        .max_inbound_per_ip_per_min,
+                );
+                let tokens = RateLimit {
+                    fill_rate: limit as f64 / 60.,
+                    capacity: limit,
+                };
+                if self
+                    .accepts
+                    .limit(ip.into(), None, &tokens, self.time(time).into())
+                {
+                    log::debug!(target: "wire", "Rate limiting inbound connection from {ip}..");
+                    drop(connection);
+
+                    return;
+                }
+
                 // If the service doesn't want to accept this connection,
                 // we drop the connection here, which disconnects the socket.
                 if !self.service.accepted(ip) {
@@ -582,6 +1508,7 @@ where
                 let session = accept::<G>(
                     remote.clone().into(),
                     connection,
+                    self.service.config(),
                     self.signer.clone().into_inner(),
                 );
                 let transport = match Transport::with_session(session, Link::Inbound) {
@@ -650,68 +1577,31 @@ where
                 );
 
                 // Connections to close.
-                let mut disconnect = Vec::new();
-
-                // Handle conflicting connections.
-                // This is typical when users have mutually configured their nodes to connect to
-                // each other on startup. We handle this by deterministically choosing one node
-                // whose outbound connection is the one that is kept. The other connections are
-                // dropped.
-                {
-                    // Having precedence means that our outbound connection will win over
-                    // the other node's outbound connection.
-                    enum Precedence {
-                        Ours,
-                        Theirs,
-                    }
-
-                    use Link::*;
-                    use Precedence::*;
-
-                    // Whether we have precedence in case of conflicting connections.
-                    let precedence = if *self.signer.public_key() > nid {
-                        Ours
-                    } else {
-                        Theirs
-                    };
-
-                    // Active sessions with the same NID but a different token are conflicting.
-                    let peers = self.peers.active().filter_map(|(c_id, d, link)| {
-                        (*d == nid && c_id != token).then_some((c_id, link))
-                    });
-
-                    // Outbound connection attempts with the same remote key but a different file
-                    // descriptor are conflicting.
-                    let outbound = self.outbound.iter().filter_map(|(c_id, other)| {
-                        (other.nid == nid && *c_id != token).then_some((*c_id, Outbound))
-                    });
-
-                    for (c_token, c_link) in peers.chain(outbound) {
-                        // If we have precedence, the inbound connection is closed.
-                        // In the case where both connections are inbound or outbound,
-                        // we close the newer connection, ie. the one with the higher
-                        // token.
-                        let close = match (link, c_link, &precedence) {
-                            (Inbound, Outbound, Ours) => token,
-                            (Inbound, Outbound, Theirs) => c_token,
-                            (Outbound, Inbound, Ours) => c_token,
-                            (Outbound, Inbound, Theirs) => token,
-                            (Inbound, Inbound, _) => token.max(c_token),
-                            (Outbound, Outbound, _) => token.max(c_token),
-                        };
+                let disconnect = self.conflicts(token, nid, link);
 
-                        log::warn!(
-                            target: "wire", "Established session with token {} conflicts with existing session with token {} for {nid}. Disconnecting session with token {}.", token.0, c_token.0, close.0
-                        );
-                        disconnect.push(close);
-                    }
-                }
                 for id in &disconnect {
                     log::warn!(
                         target: "wire", token=token.0; "Closing conflicting session with {nid}.."
                     );
                     // Disconnect and return the associated NID of the peer, if available.
-                    if let Some((nid, link)) = self.disconnect(*id, DisconnectReason::Conflict) {
+                    let disconnected = self.disconnect(*id, DisconnectReason::Conflict);
+
+                    // Closing a connection we attempted, that isn't established yet, fails
+                    // the attempt. Pending attempts are forgotten here, so that their failure
+                    // isn't reported again when the transport is cleaned up.
+                    let attempt = if *id == token {
+                        link.is_outbound().then(|| addr.clone())
+                    } else {
+                        self.outbound.remove(id).map(|o| o.addr)
+                    };
+                    if let Some(addr) = attempt {
+                        self.service.emitter().emit(Event::ConnectionFailed {
+                            nid,
+                            addr: addr.into(),
+                            reason: DisconnectReason::Conflict.to_string(),
+                        });
+                    }
+                    if let Some((nid, link)) = disconnected {
                         // We disconnect the session eagerly because otherwise we will get the new
                         // `connected` event before the `disconnect`, resulting in a duplicate
                         // connection.
@@ -720,139 +1610,75 @@ where
                     }
                 }
                 if !disconnect.contains(&token) {
-                    self.peers
-                        .insert(token, Peer::connected(nid, addr.clone(), link));
+                    if link.is_outbound() {
+                        self.service.emitter().emit(Event::ConnectionSucceeded {
+                            nid,
+                            addr: addr.clone().into(),
+                        });
+                    }
+                    self.peers.insert(
+                        token,
+                        Peer::connected(nid, addr.clone(), link, &self.service.config().limits),
+                    );
+
+                    if supports(
+                        self.service.database().addresses(),
+                        &nid,
+                        Features::VERSIONS,
+                    ) {
+                        self.advertise_versions(token);
+                    }
                     self.service.connected(nid, addr.into(), link);
                 }
             }
             SessionEvent::Data(data) => {
-                if let Some(Peer::Connected {
-                    nid,
-                    inbox,
-                    streams,
-                    ..
-                }) = self.peers.get_mut(&token)
-                {
+                if let Some(Peer::Connected { nid, .. }) = self.peers.get(&token) {
                     let metrics = self.metrics.peer(*nid);
                     metrics.received_bytes += data.len();
-
-                    if inbox.input(&data).is_err() {
-                        log::error!(target: "wire", "Maximum inbox size ({MAX_INBOX_SIZE}) reached for peer {nid}");
+                } else {
+                    log::warn!(target: "wire", token=token.0; "Dropping message from unconnected peer");
+                    return;
+                }
+                // Stream data is never dropped, as that would break the framing.
+                // If the inbox is full, it is decoded to make room, holding back
+                // gossip messages, unless the overflow policy is to reject.
+                let mut data = data.as_slice();
+                let mut held_back = false;
+                loop {
+                    let Some(Peer::Connected {
+                        nid,
+                        inbox,
+                        backlog,
+                        ..
+                    }) = self.peers.get_mut(&token)
+                    else {
+                        return;
+                    };
+                    let room = inbox.limit().saturating_sub(inbox.len());
+                    let (chunk, rest) = data.split_at(room.min(data.len()));
+                    // Nothing more can be decoded if the inbox is still full after
+                    // holding back messages.
+                    let stalled = !rest.is_empty()
+                        && (backlog.overflow() == Overflow::Reject || (held_back && room == 0));
+
+                    if stalled || inbox.input(chunk).is_err() {
+                        log::error!(target: "wire", "Maximum inbox size ({}) reached for peer {nid}", inbox.limit());
                         log::error!(target: "wire", "Unable to process messages fast enough for peer {nid}; disconnecting..");
-                        self.disconnect(
-                            token,
-                            DisconnectReason::Session(session::Error::Misbehavior),
-                        );
+                        self.rate_limit(token, RATE_LIMITED_RETRY_AFTER);
 
                         return;
                     }
+                    data = rest;
 
-                    loop {
-                        match inbox.deserialize_next() {
-                            Ok(Some(Frame {
-                                data: FrameData::Control(frame::Control::Open { stream }),
-                                ..
-                            })) => {
-                                log::debug!(target: "wire", "Received `open` command for stream {stream} from {nid}");
-                                metrics.streams_opened += 1;
-                                metrics.received_fetch_requests += 1;
-                                let reader_limit = self.service.config().limits.fetch_pack_receive;
-                                let Some(channels) = streams.register(
-                                    stream,
-                                    ChannelsConfig::new(FETCH_TIMEOUT)
-                                        .with_reader_limit(reader_limit),
-                                ) else {
-                                    log::warn!(target: "wire", "Peer attempted to open already-open stream stream {stream}");
-                                    continue;
-                                };
-
-                                let task = Task {
-                                    fetch: FetchRequest::Responder {
-                                        remote: *nid,
-                                        emitter: self.service.emitter(),
-                                    },
-                                    stream,
-                                    channels,
-                                };
-                                if let Err(e) = self.worker.try_send(task) {
-                                    log::error!(
-                                        target: "wire",
-                                        "Worker pool failed to accept incoming fetch request: {e}"
-                                    );
-                                }
-                            }
-                            Ok(Some(Frame {
-                                data: FrameData::Control(frame::Control::Eof { stream }),
-                                ..
-                            })) => {
-                                if let Some(s) = streams.get(&stream) {
-                                    log::debug!(target: "wire", "Received `end-of-file` on stream {stream} from {nid}");
-
-                                    if s.channels.send(ChannelEvent::Eof).is_err() {
-                                        log::error!(target: "wire", "Worker is disconnected; cannot send `EOF`");
-                                    }
-                                } else {
-                                    log::debug!(target: "wire", "Ignoring frame on closed or unknown stream {stream}");
-                                }
-                            }
-                            Ok(Some(Frame {
-                                data: FrameData::Control(frame::Control::Close { stream }),
-                                ..
-                            })) => {
-                                log::debug!(target: "wire", "Received `close` command for stream {stream} from {nid}");
-
-                                if let Some(s) = streams.unregister(&stream) {
-                                    log::debug!(
-                                        target: "wire",
-                                        "Stream {stream} of {nid} closed with {} byte(s) sent and {} byte(s) received",
-                                        s.sent_bytes, s.received_bytes
-                                    );
-                                    s.channels.close().ok();
-                                }
-                            }
-                            Ok(Some(Frame {
-                                data: FrameData::Gossip(msg),
-                                ..
-                            })) => {
-                                metrics.received_gossip_messages += 1;
-                                self.service.received_message(*nid, msg);
-                            }
-                            Ok(Some(Frame {
-                                stream,
-                                data: FrameData::Git(data),
-                                ..
-                            })) => {
-                                if let Some(s) = streams.get_mut(&stream) {
-                                    metrics.received_git_bytes += data.len();
-
-                                    if s.channels.send(ChannelEvent::Data(data)).is_err() {
-                                        log::error!(target: "wire", "Worker is disconnected; cannot send data");
-                                    }
-                                } else {
-                                    log::debug!(target: "wire", "Ignoring frame on closed or unknown stream {stream}");
-                                }
-                            }
-                            Ok(None) => {
-                                // Buffer is empty, or message isn't complete.
-                                break;
-                            }
-                            Err(e) => {
-                                log::error!(target: "wire", "Invalid gossip message from {nid}: {e}");
-
-                                if !inbox.is_empty() {
-                                    log::debug!(target: "wire", "Dropping read buffer for {nid} with {} bytes", inbox.len());
-                                }
-                                self.disconnect(
-                                    token,
-                                    DisconnectReason::Session(session::Error::Misbehavior),
-                                );
-                                break;
-                            }
-                        }
+                    if data.is_empty() {
+                        break;
                     }
-                } else {
-                    log::warn!(target: "wire", token=token.0; "Dropping message from unconnected peer");
+                    log::debug!(target: "wire", "Maximum inbox size ({}) reached for peer {nid}; holding back gossip messages", inbox.limit());
+
+                    held_back = true;
+                    self.receive(token, true);
                 }
+                self.receive(token, false);
             }
             SessionEvent::Terminated(err) => {
                 self.disconnect(token, DisconnectReason::Connection(Arc::new(err)));
@@ -865,6 +1691,16 @@ where
             Control::User(cmd) => self.service.command(cmd),
             Control::Worker(result) => self.worker_result(result),
             Control::Flush { remote, stream } => self.flush(remote, stream),
+            Control::Shutdown { grace, done } => self.begin_shutdown(grace, done),
+            Control::Uptime(resp) => {
+                resp.send(self.epoch.uptime(Instant::now())).ok();
+            }
+            Control::Snapshot(resp) => {
+                resp.send(self.snapshot()).ok();
+            }
+            Control::Reload(config, resp) => {
+                resp.send(self.reload(*config)).ok();
+            }
         }
     }
 
@@ -894,6 +1730,7 @@ where
                         }
 
                         if let Some(id) = peer.id() {
+                            self.fail_deferred(id);
                             self.service.disconnected(
                                 *id,
                                 peer.link(),
@@ -916,7 +1753,7 @@ where
     fn handover_transport(&mut self, token: Token, transport: Self::Transport) {
         match self.peers.entry(token) {
             Entry::Occupied(e) => {
-                match e.get() {
+                match e.remove() {
                     Peer::Disconnecting {
                         nid, reason, link, ..
                     } => {
@@ -933,9 +1770,9 @@ where
                             //
                             // Therefore, we specify which of the connections we're closing by
                             // passing the `link`.
-                            self.service.disconnected(*nid, *link, reason);
+                            self.fail_deferred(&nid);
+                            self.service.disconnected(nid, link, &reason);
                         }
-                        e.remove();
                     }
                     Peer::Connected { nid, .. } => {
                         panic!("Wire::handover_transport: Unexpected handover of connected peer {nid} with token {}", token.0);
@@ -956,6 +1793,8 @@ where
     type Item = Action<G>;
 
     fn next(&mut self) -> Option<Self::Item> {
+        self.undefer();
+
         while let Some(ev) = self.service.next() {
             match ev {
                 Io::Write(node_id, msgs) => {
@@ -975,12 +1814,56 @@ where
                     log::trace!(
                         target: "wire", "Writing {} message(s) to {}", msgs.len(), node_id
                     );
-                    let mut data = Vec::new();
+                    let max = usize::from(self.service.config().limits.max_frame_size);
                     let metrics = self.metrics.peer(node_id);
-                    metrics.sent_gossip_messages += msgs.len();
+                    let mut msgs = VecDeque::from(msgs);
+                    let mut data = Vec::new();
+
+                    while let Some(msg) = msgs.pop_front() {
+                        let frame = Frame::gossip(link, msg);
+                        let start = data.len();
+                        frame.encode(&mut data);
+
+                        let size = data.len() - start;
+                        if size > max {
+                            data.truncate(start);
+
+                            let FrameData::Gossip(msg) = frame.data else {
+                                unreachable!("Wire::next: gossip frames only carry messages");
+                            };
+                            if let Some(parts) = split_inventory(&msg, link, max, &self.signer) {
+                                log::debug!(
+                                    target: "wire",
+                                    "Splitting inventory announcement to {node_id} of {size} byte(s) into {} part(s)",
+                                    parts.len()
+                                );
+                                metrics.split_gossip_messages += 1;
 
-                    for msg in msgs {
-                        Frame::gossip(link, msg).encode(&mut data);
+                                for part in parts.into_iter().rev() {
+                                    msgs.push_front(part);
+                                }
+                                continue;
+                            }
+                            log::warn!(
+                                target: "wire",
+                                "Dropping message to {node_id}: frame size of {size} byte(s) exceeds the maximum of {max} byte(s)"
+                            );
+                            metrics.dropped_gossip_messages += 1;
+                            continue;
+                        }
+                        metrics.sent_gossip_messages += 1;
+
+                        // Keep each write within the maximum frame size, by sending the data
+                        // before this frame separately.
+                        if start > 0 && data.len() > max {
+                            let frame = data.split_off(start);
+                            metrics.sent_bytes += data.len();
+                            self.actions.push_back(reactor::Action::Send(fd, data));
+                            data = frame;
+                        }
+                    }
+                    if data.is_empty() {
+                        continue;
                     }
                     metrics.sent_bytes += data.len();
 
@@ -997,6 +1880,10 @@ where
                         continue;
                     }
                     self.service.attempted(node_id, addr.clone());
+                    self.service.emitter().emit(Event::ConnectionAttempted {
+                        nid: node_id,
+                        addr: addr.clone(),
+                    });
                     self.metrics.peer(node_id).outbound_connection_attempts += 1;
 
                     match dial::<G>(
@@ -1004,6 +1891,7 @@ where
                         node_id,
                         self.signer.clone().into_inner(),
                         self.service.config(),
+                        &SystemResolver,
                     )
                     .and_then(|session| {
                         Transport::<WireSession<G>>::with_session(session, Link::Outbound)
@@ -1028,11 +1916,13 @@ where
                         Err(err) => {
                             log::error!(target: "wire", "Error establishing connection to {addr}: {err}");
 
-                            self.service.disconnected(
-                                node_id,
-                                Link::Outbound,
-                                &DisconnectReason::Dial(Arc::new(err)),
-                            );
+                            let reason = DisconnectReason::Dial(Arc::new(err));
+                            self.service.emitter().emit(Event::ConnectionFailed {
+                                nid: node_id,
+                                addr,
+                                reason: reason.to_string(),
+                            });
+                            self.service.disconnected(node_id, Link::Outbound, &reason);
                         }
                     }
                 }
@@ -1055,55 +1945,28 @@ where
                     reader_limit,
                     refs_at,
                 } => {
-                    log::trace!(target: "wire", "Processing fetch for {rid} from {remote}..");
-
-                    let Some((fd, Peer::Connected { link, streams, .. })) =
-                        self.peers.lookup_mut(&remote)
-                    else {
-                        // Nb. It's possible that a peer is disconnected while an `Io::Fetch`
-                        // is in the service's i/o buffer. Since the service may not purge the
-                        // buffer on disconnect, we should just ignore i/o actions that don't
-                        // have a connected peer.
-                        log::error!(target: "wire", "Peer {remote} is not connected: dropping fetch");
-                        continue;
-                    };
-                    let (stream, channels) =
-                        streams.open(ChannelsConfig::new(timeout).with_reader_limit(reader_limit));
-
-                    log::debug!(target: "wire", "Opened new stream with id {stream} for {rid} and remote {remote}");
-
-                    let link = *link;
-                    let task = Task {
-                        fetch: FetchRequest::Initiator {
-                            rid,
-                            remote,
-                            refs_at,
-                        },
-                        stream,
-                        channels,
+                    let fetch = Deferred {
+                        rid,
+                        remote,
+                        refs_at,
+                        timeout,
+                        reader_limit,
                     };
 
-                    if !self.worker.is_empty() {
-                        log::warn!(
+                    // Keep fetches in order, by deferring behind those already deferred.
+                    if self.drain.is_none()
+                        && (!self.deferred.is_empty()
+                            || saturated(&self.worker, &self.service.config().limits))
+                    {
+                        log::debug!(
                             target: "wire",
-                            "Worker pool is busy: {} tasks pending, fetch requests may be delayed", self.worker.len()
-                        );
-                    }
-                    if let Err(e) = self.worker.try_send(task) {
-                        log::error!(
-                            target: "wire",
-                            "Worker pool failed to accept outgoing fetch request: {e}"
+                            "Deferring fetch for {rid} from {remote}: worker pool is busy ({} task(s) pending)",
+                            self.worker.len()
                         );
+                        self.deferred.push_back(fetch);
+                    } else {
+                        self.fetch(fetch);
                     }
-                    let metrics = self.metrics.peer(remote);
-                    metrics.streams_opened += 1;
-                    metrics.sent_fetch_requests += 1;
-
-                    self.actions.push_back(Action::Send(
-                        fd,
-                        Frame::<service::Message>::control(link, frame::Control::Open { stream })
-                            .encode_to_vec(),
-                    ));
                 }
             }
         }
@@ -1111,33 +1974,139 @@ where
     }
 }
 
-/// Establish a new outgoing connection.
+/// Check whether a node has advertised support for the given feature,
+/// eg. compressed git streams.
+fn supports(db: &impl address::Store, nid: &NodeId, feature: Features) -> bool {
+    match db.get(nid) {
+        Ok(node) => node.is_some_and(|n| n.features.has(feature)),
+        Err(e) => {
+            log::warn!(target: "wire", "Failed to get features of {nid} from address book: {e}");
+            false
+        }
+    }
+}
+
+/// Resolves host names to socket addresses, when dialing peers.
+pub trait Resolver {
+    /// Resolve a host name and port to a socket address.
+    fn resolve(&self, host: &HostName, port: u16) -> io::Result<net::SocketAddr>;
+}
+
+/// Resolves host names using the operating system, via [`net::ToSocketAddrs`].
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemResolver;
+
+impl Resolver for SystemResolver {
+    fn resolve(&self, host: &HostName, port: u16) -> io::Result<net::SocketAddr> {
+        use std::net::ToSocketAddrs as _;
+
+        match host {
+            HostName::Ip(ip) => Ok(net::SocketAddr::new(*ip, port)),
+            HostName::Dns(dns) => (dns.as_str(), port)
+                .to_socket_addrs()?
+                .next()
+                .ok_or(io::ErrorKind::AddrNotAvailable.into()),
+            _ => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!("cannot resolve host name {host}"),
+            )),
+        }
+    }
+}
+
+/// Split an inventory announcement of ours into announcements of the same
+/// inventory, whose frames are each at most `max` bytes.
+///
+/// The parts share the timestamp of the announcement, so that peers add them
+/// up instead of replacing one with the other. Returns `None` if the message
+/// isn't our own inventory announcement, or can't be split small enough.
+fn split_inventory<G>(
+    msg: &service::Message,
+    link: Link,
+    max: usize,
+    signer: &Device<G>,
+) -> Option<Vec<service::Message>>
+where
+    G: crypto::signature::Signer<crypto::Signature>,
+{
+    let service::Message::Announcement(Announcement {
+        node,
+        message: AnnouncementMessage::Inventory(announcement),
+        ..
+    }) = msg
+    else {
+        return None;
+    };
+    if node != signer.public_key() || announcement.inventory.len() < 2 {
+        return None;
+    }
+    let part = |inventory: &[RepoId]| {
+        let part = InventoryAnnouncement {
+            inventory: BoundedVec::truncate(inventory.to_vec()),
+            timestamp: announcement.timestamp,
+        };
+        service::Message::from(AnnouncementMessage::from(part).signed(signer))
+    };
+    let size = |inventory: &[RepoId]| Frame::gossip(link, part(inventory)).encode_to_vec().len();
+
+    // Inventory entries are of a fixed size.
+    let inventory = announcement.inventory.as_slice();
+    let overhead = size(&[]);
+    let entry = (size(inventory) - overhead).div_ceil(inventory.len());
+    let capacity = max.checked_sub(overhead)? / entry;
+    if capacity == 0 {
+        return None;
+    }
+    Some(inventory.chunks(capacity).map(part).collect())
+}
+
+/// Establish a new outgoing connection, using the given resolver to look up
+/// the address to connect to.
+///
+/// Connecting to one of our own addresses, ie. one of our external addresses,
+/// or a loopback address we listen on, is refused before connecting. Self-connections
+/// that get past this, eg. via NAT hairpinning, are detected once the handshake completes.
 pub fn dial<G: Ecdh<Pk = NodeId>>(
     remote_addr: NetAddr<HostName>,
     remote_id: <G as EcSk>::Pk,
     signer: G,
     config: &radicle::node::Config,
+    resolver: &impl Resolver,
 ) -> io::Result<WireSession<G>> {
+    let self_connection = || {
+        log::warn!(
+            target: "wire",
+            "Refusing to connect to {remote_addr}: it is one of our own addresses, check the node's `connect` configuration"
+        );
+        io::Error::new(
+            io::ErrorKind::ConnectionRefused,
+            format!("{remote_addr} is one of our own addresses"),
+        )
+    };
+    if config.external_addresses.iter().any(|a| **a == remote_addr) {
+        return Err(self_connection());
+    }
+    let proxy = |addr: net::SocketAddr| NetAddr::new(HostName::Ip(addr.ip()), addr.port());
     // Determine what address to establish a TCP connection with, given the remote peer
     // address and our node configuration.
-    let inet_addr: NetAddr<InetHost> = match (&remote_addr.host, config.proxy) {
+    let target: NetAddr<HostName> = match (&remote_addr.host, config.proxy) {
         // For IP and DNS addresses, use the global proxy if set, otherwise use the address as-is.
-        (HostName::Ip(_), Some(proxy)) => proxy.into(),
-        (HostName::Ip(ip), None) => NetAddr::new(InetHost::Ip(*ip), remote_addr.port),
-        (HostName::Dns(_), Some(proxy)) => proxy.into(),
-        (HostName::Dns(dns), None) => NetAddr::new(InetHost::Dns(dns.clone()), remote_addr.port),
+        (HostName::Ip(_), Some(addr)) => proxy(addr),
+        (HostName::Ip(ip), None) => NetAddr::new(HostName::Ip(*ip), remote_addr.port),
+        (HostName::Dns(_), Some(addr)) => proxy(addr),
+        (HostName::Dns(dns), None) => NetAddr::new(HostName::Dns(dns.clone()), remote_addr.port),
         // For onion addresses, handle with care.
-        (HostName::Tor(onion), proxy) => match config.onion {
+        (HostName::Tor(onion), global) => match config.onion {
             // In onion proxy mode, simply use the configured proxy address.
             // This takes precedence over any global proxy.
-            Some(AddressConfig::Proxy { address }) => address.into(),
+            Some(AddressConfig::Proxy { address }) => proxy(address),
             // In "forward" mode, if a global proxy is set, we use that, otherwise
             // we treat `.onion` addresses as regular DNS names.
             Some(AddressConfig::Forward) => {
-                if let Some(proxy) = proxy {
-                    proxy.into()
+                if let Some(addr) = global {
+                    proxy(addr)
                 } else {
-                    NetAddr::new(InetHost::Dns(onion.to_string()), remote_addr.port)
+                    NetAddr::new(HostName::Dns(onion.to_string()), remote_addr.port)
                 }
             }
             // If onion address support isn't configured, refuse to connect.
@@ -1156,14 +2125,12 @@ pub fn dial<G: Ecdh<Pk = NodeId>>(
         }
     };
 
-    let addr = {
-        use std::net::ToSocketAddrs as _;
+    let addr = resolver.resolve(&target.host, target.port)?;
 
-        inet_addr
-            .to_socket_addrs()?
-            .next()
-            .ok_or(io::ErrorKind::AddrNotAvailable)?
-    };
+    // When not going through a proxy, the resolved address may turn out to be our own.
+    if target == remote_addr && is_listening(config, addr) {
+        return Err(self_connection());
+    }
 
     // NOTE: Previously, here was a note about setting the timeout for connecting
     // to DEFAULT_DIAL_TIMEOUT, for which we have not figured out a way yet.
@@ -1180,17 +2147,38 @@ pub fn dial<G: Ecdh<Pk = NodeId>>(
         Some(remote_id),
         connection,
         force_proxy,
+        config,
         signer,
     ))
 }
 
+/// Whether we listen on the given address, ie. connecting to it would connect to ourselves.
+fn is_listening(config: &radicle::node::Config, addr: net::SocketAddr) -> bool {
+    config.listen.iter().any(|listen| {
+        listen.port() == addr.port()
+            && (listen.ip() == addr.ip()
+                || (addr.ip().is_loopback()
+                    && (listen.ip().is_unspecified() || listen.ip().is_loopback())))
+    })
+}
+
 /// Accept a new connection.
 pub fn accept<G: Ecdh<Pk = NodeId>>(
     remote_addr: NetAddr<HostName>,
     connection: TcpStream,
+    config: &radicle::node::Config,
     signer: G,
 ) -> WireSession<G> {
-    session::<G>(remote_addr, None, connection, false, signer)
+    session::<G>(remote_addr, None, connection, false, config, signer)
+}
+
+/// TCP keepalive idle time, probe interval and probe count to use, given the configured
+/// settings, if any.
+#[cfg_attr(not(feature = "socket2"), allow(dead_code))]
+fn keepalive(config: Option<&KeepAlive>) -> (time::Duration, time::Duration, u32) {
+    let ka = config.copied().unwrap_or_default();
+
+    (ka.time.into(), ka.interval.into(), ka.retries)
 }
 
 /// Create a new [`WireSession`].
@@ -1199,32 +2187,38 @@ fn session<G: Ecdh<Pk = NodeId>>(
     remote_id: Option<NodeId>,
     connection: TcpStream,
     force_proxy: bool,
+    config: &radicle::node::Config,
     signer: G,
 ) -> WireSession<G> {
+    let timeout = time::Duration::from(LocalDuration::from(config.limits.connection.timeout));
+
     if let Err(e) = connection.set_nodelay(true) {
         log::warn!(target: "wire", "Unable to set TCP_NODELAY on socket {connection:?}: {e}");
     }
 
     let connection = std::net::TcpStream::from(connection);
 
-    if let Err(e) = connection.set_read_timeout(Some(DEFAULT_CONNECTION_TIMEOUT)) {
+    if let Err(e) = connection.set_read_timeout(Some(timeout)) {
         log::warn!(target: "wire", "Unable to set TCP read timeout on socket {connection:?}: {e}");
     }
 
-    if let Err(e) = connection.set_write_timeout(Some(DEFAULT_CONNECTION_TIMEOUT)) {
+    if let Err(e) = connection.set_write_timeout(Some(timeout)) {
         log::warn!(target: "wire", "Unable to set TCP write timeout on socket {connection:?}: {e}");
     }
 
     #[cfg(feature = "socket2")]
     {
         let connection = socket2::SockRef::from(&connection);
+        let (idle, interval, retries) = self::keepalive(config.keepalive.as_ref());
 
         let ka = socket2::TcpKeepalive::new()
-            .with_time(time::Duration::from_secs(30))
-            .with_interval(time::Duration::from_secs(10));
+            .with_time(idle)
+            .with_interval(interval);
 
         #[cfg(not(windows))]
-        let ka = ka.with_retries(3);
+        let ka = ka.with_retries(retries);
+        #[cfg(windows)]
+        let _ = retries;
 
         if let Err(e) = connection.set_tcp_keepalive(&ka) {
             log::warn!(target: "wire", "Failed to set TCP_KEEPALIVE on socket {connection:?}: {e}");
@@ -1259,11 +2253,1119 @@ fn session<G: Ecdh<Pk = NodeId>>(
 
 #[cfg(test)]
 mod test {
+    use std::cell::RefCell;
+    use std::collections::BTreeSet;
+
     use super::*;
+    use crate::crypto::test::signer::MockSigner;
     use crate::service::{Message, ZeroBytes};
+    use crate::test::peer;
+    use crate::test::storage::MockStorage;
     use crate::wire;
     use crate::wire::varint;
 
+    /// Create a wire instance backed by a test service, along with the worker task queue.
+    fn wire() -> (
+        Wire<radicle::node::Database, MockStorage, MockSigner>,
+        chan::Receiver<Task>,
+    ) {
+        wire_with(peer::Config::default())
+    }
+
+    /// Like [`wire`], with the given service configuration.
+    fn wire_with(
+        config: peer::Config<MockSigner>,
+    ) -> (
+        Wire<radicle::node::Database, MockStorage, MockSigner>,
+        chan::Receiver<Task>,
+    ) {
+        let alice =
+            peer::Peer::config("alice", [7, 7, 7, 7], MockStorage::empty(), config).initialized();
+        let signer = alice.service.signer().clone();
+        let (worker, tasks) = chan::unbounded();
+
+        (Wire::new(alice.service, worker, signer), tasks)
+    }
+
+    /// Resolves every host name to a fixed address, and records lookups.
+    struct FixedResolver {
+        addr: net::SocketAddr,
+        lookups: RefCell<Vec<(HostName, u16)>>,
+    }
+
+    impl FixedResolver {
+        fn new(addr: net::SocketAddr) -> Self {
+            Self {
+                addr,
+                lookups: RefCell::default(),
+            }
+        }
+    }
+
+    impl Resolver for FixedResolver {
+        fn resolve(&self, host: &HostName, port: u16) -> io::Result<net::SocketAddr> {
+            self.lookups.borrow_mut().push((host.clone(), port));
+            Ok(self.addr)
+        }
+    }
+
+    #[test]
+    fn test_epoch_uptime() {
+        let started_at = Instant::now();
+        let started_time = SystemTime::UNIX_EPOCH;
+        let epoch = Epoch::new(started_time, started_at);
+
+        assert_eq!(epoch.uptime(started_at), time::Duration::ZERO);
+
+        // Only the instant advances, the system time stays fixed.
+        let now = started_at + time::Duration::from_secs(90);
+        assert_eq!(epoch.uptime(now), time::Duration::from_secs(90));
+        assert_eq!(epoch.started_time, started_time);
+
+        // Instants before the node started can't yield a negative uptime.
+        let before = started_at.checked_sub(time::Duration::from_secs(1));
+        if let Some(before) = before {
+            assert_eq!(epoch.uptime(before), time::Duration::ZERO);
+        }
+    }
+
+    #[test]
+    fn test_inbox_size() {
+        use crate::reactor::ReactionHandler as _;
+        use radicle::test::arbitrary;
+
+        let data = Frame::gossip(
+            Link::Outbound,
+            Message::Pong {
+                zeroes: ZeroBytes::new(64),
+            },
+        )
+        .encode_to_vec();
+        let receive = |inbox_size: usize| {
+            let mut config = peer::Config::default();
+            config.config.limits.inbox_size = inbox_size.into();
+
+            let (mut wire, _) = wire_with(config);
+            let token = wire.tokens.advance();
+            let addr = NetAddr::new(HostName::Ip(net::Ipv4Addr::new(8, 8, 8, 8).into()), 8776);
+
+            wire.peers.insert(
+                token,
+                Peer::connected(
+                    arbitrary::gen::<NodeId>(1),
+                    addr,
+                    Link::Inbound,
+                    &wire.service.config().limits,
+                ),
+            );
+            wire.transport_reacted(token, SessionEvent::Data(data.clone()), Instant::now());
+            wire.peers.0.remove(&token).unwrap()
+        };
+
+        // The peer exceeds a tiny inbox, and is disconnected.
+        radicle::assert_matches!(
+            receive(16),
+            Peer::Disconnecting {
+                reason: DisconnectReason::RateLimited { .. },
+                ..
+            }
+        );
+        // With a large enough inbox, the same data is received.
+        radicle::assert_matches!(
+            receive(1024),
+            Peer::Connected { inbox, .. } if inbox.is_empty()
+        );
+    }
+
+    #[test]
+    fn test_inbox_overflow() {
+        use crate::reactor::ReactionHandler as _;
+        use radicle::test::arbitrary;
+
+        let frame = Frame::gossip(
+            Link::Outbound,
+            Message::Pong {
+                zeroes: ZeroBytes::new(64),
+            },
+        )
+        .encode_to_vec();
+        let count = 2 * MAX_INBOX_BACKLOG + 8;
+        let data = frame.repeat(count);
+        let receive = |overflow: InboxOverflow| {
+            let mut config = peer::Config::default();
+            config.config.limits.inbox_size = (frame.len() * 2).into();
+            config.config.limits.inbox_overflow = overflow;
+
+            let (mut wire, _) = wire_with(config);
+            let token = wire.tokens.advance();
+            let nid = arbitrary::gen::<NodeId>(1);
+            let addr = NetAddr::new(HostName::Ip(net::Ipv4Addr::new(8, 8, 8, 8).into()), 8776);
+
+            wire.peers.insert(
+                token,
+                Peer::connected(nid, addr, Link::Inbound, &wire.service.config().limits),
+            );
+            // Send the data in two parts, splitting a frame, to check that the
+            // framing survives holding back messages.
+            let (a, b) = data.split_at(data.len() / 2 + 1);
+            wire.transport_reacted(token, SessionEvent::Data(a.to_vec()), Instant::now());
+            wire.transport_reacted(token, SessionEvent::Data(b.to_vec()), Instant::now());
+
+            let received = wire.metrics.peer(nid).received_gossip_messages;
+            (wire.peers.0.remove(&token).unwrap(), received)
+        };
+
+        // The peer is disconnected as soon as its inbox is full.
+        let (peer, _) = receive(InboxOverflow::Reject);
+        radicle::assert_matches!(
+            peer,
+            Peer::Disconnecting {
+                reason: DisconnectReason::RateLimited { .. },
+                ..
+            }
+        );
+        // Whole messages are held back and dropped, but all data is decoded.
+        for overflow in [InboxOverflow::DropOldest, InboxOverflow::DropNewest] {
+            let (peer, received) = receive(overflow);
+            radicle::assert_matches!(
+                peer,
+                Peer::Connected { inbox, backlog, .. } if inbox.is_empty() && backlog.is_empty()
+            );
+            assert!(
+                received >= MAX_INBOX_BACKLOG,
+                "{received} message(s) received"
+            );
+            assert!(received < count, "{received} message(s) received");
+        }
+    }
+
+    #[test]
+    fn test_rate_limited() {
+        use crate::reactor::ReactionHandler as _;
+        use radicle::node::address::Store as _;
+        use radicle::test::arbitrary;
+
+        let mut config = peer::Config::default();
+        config.config.limits.inbox_size = 16.into();
+
+        let (mut wire, _) = wire_with(config);
+        let bob = arbitrary::gen::<NodeId>(1);
+        let carol = arbitrary::gen::<NodeId>(1);
+        let dave = arbitrary::gen::<NodeId>(1);
+        let addr = NetAddr::new(HostName::Ip(net::Ipv4Addr::new(8, 8, 8, 8).into()), 8776);
+        let bob_token = wire.tokens.advance();
+        let carol_token = wire.tokens.advance();
+        let dave_token = wire.tokens.advance();
+
+        for (nid, token) in [(bob, bob_token), (carol, carol_token), (dave, dave_token)] {
+            wire.peers.insert(
+                token,
+                Peer::connected(
+                    nid,
+                    addr.clone(),
+                    Link::Inbound,
+                    &wire.service.config().limits,
+                ),
+            );
+        }
+        // Only Bob understands rate-limit notices.
+        wire.service
+            .database_mut()
+            .addresses_mut()
+            .insert(
+                &bob,
+                crate::PROTOCOL_VERSION,
+                Features::SEED.with(Features::RATE_LIMITS),
+                &radicle::node::Alias::new("bob"),
+                0,
+                &crate::UserAgent::default(),
+                crate::Timestamp::MAX,
+                None,
+            )
+            .unwrap();
+
+        let data = Frame::gossip(
+            Link::Outbound,
+            Message::Pong {
+                zeroes: ZeroBytes::new(64),
+            },
+        )
+        .encode_to_vec();
+
+        // Bob overflows his inbox: the notice is queued before his transport is unregistered.
+        wire.transport_reacted(bob_token, SessionEvent::Data(data.clone()), Instant::now());
+
+        radicle::assert_matches!(
+            wire.actions.pop_front(),
+            Some(Action::Send(t, data)) if t == bob_token && matches!(
+                Frame::<Message>::decode(&mut data.as_slice()),
+                Ok(Frame {
+                    data: FrameData::Control(frame::Control::RateLimited { retry_after: 60 }),
+                    ..
+                })
+            )
+        );
+        radicle::assert_matches!(
+            wire.actions.pop_front(),
+            Some(Action::UnregisterTransport(t)) if t == bob_token
+        );
+        radicle::assert_matches!(
+            wire.peers.get_mut(&bob_token),
+            Some(Peer::Disconnecting {
+                reason: DisconnectReason::RateLimited { retry_after },
+                ..
+            }) if *retry_after == RATE_LIMITED_RETRY_AFTER
+        );
+
+        // Carol doesn't support the notice, and is simply disconnected.
+        wire.transport_reacted(carol_token, SessionEvent::Data(data), Instant::now());
+
+        radicle::assert_matches!(
+            wire.actions.pop_front(),
+            Some(Action::UnregisterTransport(t)) if t == carol_token
+        );
+
+        // Dave tells us to back off: we disconnect, and keep the hint for the service.
+        wire.transport_reacted(
+            dave_token,
+            SessionEvent::Data(
+                Frame::<Message>::control(
+                    Link::Outbound,
+                    frame::Control::RateLimited { retry_after: 90 },
+                )
+                .encode_to_vec(),
+            ),
+            Instant::now(),
+        );
+
+        radicle::assert_matches!(
+            wire.peers.get_mut(&dave_token),
+            Some(Peer::Disconnecting {
+                reason: DisconnectReason::RateLimited { retry_after },
+                ..
+            }) if *retry_after == LocalDuration::from_secs(90)
+        );
+    }
+
+    #[test]
+    fn test_snapshot() {
+        use radicle::test::arbitrary;
+
+        let (mut wire, _) = wire();
+        let addr = NetAddr::new(HostName::Ip(net::Ipv4Addr::new(8, 8, 8, 8).into()), 8776);
+        let bob = arbitrary::gen::<NodeId>(1);
+        let carol = arbitrary::gen::<NodeId>(1);
+        let dave = arbitrary::gen::<NodeId>(1);
+
+        let b = wire.tokens.advance();
+        let c = wire.tokens.advance();
+        let d = wire.tokens.advance();
+        let e = wire.tokens.advance();
+        for (token, nid, link) in [(b, bob, Link::Outbound), (c, carol, Link::Inbound)] {
+            wire.peers.insert(
+                token,
+                Peer::connected(nid, addr.clone(), link, &Limits::default()),
+            );
+        }
+        wire.outbound.insert(
+            d,
+            Outbound {
+                token: d,
+                addr: addr.clone(),
+                nid: dave,
+            },
+        );
+        wire.inbound.insert(e);
+
+        let Some((_, Peer::Connected { streams, .. })) = wire.peers.lookup_mut(&bob) else {
+            panic!("Bob is not connected");
+        };
+        let (stream, _worker) = streams.open(ChannelsConfig::new(FETCH_TIMEOUT));
+        let s = streams.get_mut(&stream).unwrap();
+        s.sent_bytes = 42;
+        s.received_bytes = 7;
+
+        let snapshot = wire.snapshot();
+        assert_eq!(snapshot.peers.len(), 2);
+
+        let peer = &snapshot.peers[0];
+        assert_eq!(peer.token, b.0);
+        assert_eq!(peer.nid, Some(bob));
+        assert_eq!(peer.link, Link::Outbound);
+        assert_eq!(peer.disconnecting, None);
+        assert_eq!(peer.streams.len(), 1);
+        assert_eq!(peer.streams[0].id, u64::from(stream));
+        assert_eq!(peer.streams[0].sent_bytes, 42);
+        assert_eq!(peer.streams[0].received_bytes, 7);
+
+        let peer = &snapshot.peers[1];
+        assert_eq!(peer.token, c.0);
+        assert_eq!(peer.nid, Some(carol));
+        assert_eq!(peer.link, Link::Inbound);
+        assert!(peer.streams.is_empty());
+
+        assert_eq!(snapshot.outbound.len(), 1);
+        assert_eq!(snapshot.outbound[0].token, d.0);
+        assert_eq!(snapshot.outbound[0].nid, dave);
+        assert_eq!(snapshot.inbound, vec![e.0]);
+        assert!(snapshot.listening.is_empty());
+
+        // Peers being disconnected are still listed, along with the reason.
+        wire.disconnect(c, DisconnectReason::Command);
+
+        let snapshot = wire.snapshot();
+        let peer = &snapshot.peers[1];
+        assert_eq!(peer.nid, Some(carol));
+        assert!(peer.disconnecting.is_some());
+
+        let json = serde_json::to_value(&snapshot).unwrap();
+        assert_eq!(json["peers"][0]["streams"][0]["sentBytes"], 42);
+    }
+
+    #[test]
+    fn test_conflicting_inbound_sessions() {
+        use radicle::test::arbitrary;
+
+        let addr = NetAddr::new(HostName::Ip(net::Ipv4Addr::new(8, 8, 8, 8).into()), 8776);
+        let bob = arbitrary::gen::<NodeId>(1);
+
+        for _ in 0..8 {
+            let (mut wire, _) = wire();
+            let tokens = (0..3).map(|_| wire.tokens.advance()).collect::<Vec<_>>();
+
+            // Bob connected to us twice already, and a third session was just established.
+            for token in &tokens[..2] {
+                wire.peers.insert(
+                    *token,
+                    Peer::connected(bob, addr.clone(), Link::Inbound, &Limits::default()),
+                );
+            }
+            assert_eq!(
+                wire.peers.active().map(|(t, _, _)| t).collect::<Vec<_>>(),
+                tokens[..2]
+            );
+            // The newest session, with the highest token, loses against either.
+            assert_eq!(
+                wire.conflicts(tokens[2], bob, Link::Inbound),
+                vec![tokens[2], tokens[2]]
+            );
+            // Of the two existing sessions, the one with the higher token is closed.
+            assert_eq!(
+                wire.conflicts(tokens[0], bob, Link::Inbound),
+                vec![tokens[1]]
+            );
+
+            wire.disconnect(tokens[1], DisconnectReason::Conflict);
+            assert_eq!(wire.peers.lookup(&bob).map(|(t, _)| t), Some(tokens[0]));
+        }
+    }
+
+    #[test]
+    fn test_max_frame_size() {
+        use radicle::test::arbitrary;
+
+        let mut config = peer::Config::default();
+        config.config.limits.max_frame_size = 1024.into();
+
+        let (mut wire, _) = wire_with(config);
+        let bob = arbitrary::gen::<NodeId>(1);
+        let token = wire.tokens.advance();
+        wire.peers.insert(
+            token,
+            Peer::connected(
+                bob,
+                NetAddr::new(HostName::Ip(net::Ipv4Addr::new(8, 8, 8, 8).into()), 8776),
+                Link::Outbound,
+                &Limits::default(),
+            ),
+        );
+        let inventory = Message::announcement(
+            bob,
+            service::gossip::inventory(
+                radicle::node::Timestamp::MAX,
+                (0..128).map(|_| arbitrary::gen(1)),
+            ),
+            radicle::crypto::Signature::from(arbitrary::gen::<[u8; 64]>(1)),
+        );
+        let pong = Message::Pong {
+            zeroes: ZeroBytes::new(400),
+        };
+        assert!(
+            Frame::gossip(Link::Outbound, inventory.clone())
+                .encode_to_vec()
+                .len()
+                > 1024
+        );
+
+        wire.service.outbox().queue().push_back(Io::Write(
+            bob,
+            vec![pong.clone(), inventory, pong.clone(), pong.clone()],
+        ));
+        let sent = wire
+            .by_ref()
+            .filter_map(|action| match action {
+                Action::Send(fd, data) if fd == token => Some(data),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        // The oversized inventory announcement of another node is dropped, and the pongs are
+        // split across writes, each within the maximum frame size.
+        assert_eq!(sent.len(), 2);
+        assert!(sent.iter().all(|data| data.len() <= 1024));
+
+        let mut frames = Deserializer::<{ usize::MAX }, Frame>::default();
+        for data in &sent {
+            frames.input(data).unwrap();
+        }
+        for _ in 0..3 {
+            radicle::assert_matches!(
+                frames.deserialize_next().unwrap(),
+                Some(Frame { data: FrameData::Gossip(msg), .. }) if msg == pong
+            );
+        }
+        assert!(frames.deserialize_next().unwrap().is_none());
+
+        let metrics = wire.metrics.peer(bob);
+        assert_eq!(metrics.sent_gossip_messages, 3);
+        assert_eq!(metrics.dropped_gossip_messages, 1);
+    }
+
+    #[test]
+    fn test_max_frame_size_split() {
+        use radicle::test::arbitrary;
+
+        let mut config = peer::Config::default();
+        config.config.limits.max_frame_size = 1024.into();
+
+        let (mut wire, _) = wire_with(config);
+        let bob = arbitrary::gen::<NodeId>(1);
+        let token = wire.tokens.advance();
+        wire.peers.insert(
+            token,
+            Peer::connected(
+                bob,
+                NetAddr::new(HostName::Ip(net::Ipv4Addr::new(8, 8, 8, 8).into()), 8776),
+                Link::Outbound,
+                &Limits::default(),
+            ),
+        );
+        let timestamp = radicle::node::Timestamp::MAX;
+        let repos = (0..128)
+            .map(|_| arbitrary::gen::<RepoId>(1))
+            .collect::<BTreeSet<_>>();
+        let inventory = Message::from(
+            AnnouncementMessage::from(service::gossip::inventory(timestamp, repos.clone()))
+                .signed(&wire.signer),
+        );
+        assert!(
+            Frame::gossip(Link::Outbound, inventory.clone())
+                .encode_to_vec()
+                .len()
+                > 1024
+        );
+
+        wire.service
+            .outbox()
+            .queue()
+            .push_back(Io::Write(bob, vec![inventory]));
+        let sent = wire
+            .by_ref()
+            .filter_map(|action| match action {
+                Action::Send(fd, data) if fd == token => Some(data),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        // Our own oversized inventory announcement is split into announcements
+        // with the same timestamp, each within the maximum frame size.
+        assert!(sent.len() > 1);
+        assert!(sent.iter().all(|data| data.len() <= 1024));
+
+        let mut frames = Deserializer::<{ usize::MAX }, Frame>::default();
+        for data in &sent {
+            frames.input(data).unwrap();
+        }
+        let mut parts = 0;
+        let mut inventory = BTreeSet::new();
+        while let Some(frame) = frames.deserialize_next().unwrap() {
+            let FrameData::Gossip(Message::Announcement(ann)) = frame.data else {
+                panic!("unexpected frame {frame:?}");
+            };
+            let AnnouncementMessage::Inventory(part) = &ann.message else {
+                panic!("unexpected announcement {ann:?}");
+            };
+            assert!(ann.verify());
+            assert_eq!(ann.node, *wire.signer.public_key());
+            assert_eq!(part.timestamp, timestamp);
+
+            inventory.extend(part.inventory.iter().copied());
+            parts += 1;
+        }
+        assert_eq!(inventory, repos);
+
+        let metrics = wire.metrics.peer(bob);
+        assert_eq!(metrics.sent_gossip_messages, parts);
+        assert_eq!(metrics.split_gossip_messages, 1);
+        assert_eq!(metrics.dropped_gossip_messages, 0);
+    }
+
+    #[test]
+    fn test_keepalive() {
+        use crate::LocalDuration;
+
+        assert_eq!(
+            keepalive(None),
+            (
+                time::Duration::from_secs(30),
+                time::Duration::from_secs(10),
+                3
+            )
+        );
+        assert_eq!(
+            keepalive(Some(&KeepAlive {
+                time: LocalDuration::from_secs(60),
+                interval: LocalDuration::from_secs(5),
+                retries: 9,
+            })),
+            (
+                time::Duration::from_secs(60),
+                time::Duration::from_secs(5),
+                9
+            )
+        );
+    }
+
+    #[test]
+    fn test_inbound_rate_limit() {
+        use crate::reactor::ReactionHandler as _;
+
+        let (mut wire, _) = wire();
+        let listener = net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let remote = net::SocketAddr::from(([8, 8, 8, 8], 8776));
+        let limit = usize::from(
+            wire.service
+                .config()
+                .limits
+                .connection
+                .max_inbound_per_ip_per_min,
+        );
+        let now = Instant::now();
+        let mut clients = Vec::new();
+
+        for _ in 0..limit * 2 {
+            clients.push(net::TcpStream::connect(listener.local_addr().unwrap()).unwrap());
+
+            let (connection, _) = listener.accept().unwrap();
+            connection.set_nonblocking(true).unwrap();
+
+            wire.listener_reacted(Token(0), Ok((TcpStream::from_std(connection), remote)), now);
+        }
+        let registered = wire
+            .actions
+            .iter()
+            .filter(|a| matches!(a, Action::RegisterTransport(..)))
+            .count();
+
+        assert_eq!(registered, limit);
+        assert_eq!(wire.inbound.len(), limit);
+    }
+
+    #[test]
+    fn test_shutdown_drain() {
+        use crate::reactor::ReactionHandler as _;
+        use radicle::node::config::FetchPackSizeLimit;
+        use radicle::test::arbitrary;
+
+        let (mut wire, tasks) = wire();
+
+        let bob = arbitrary::gen::<NodeId>(1);
+        let token = wire.tokens.advance();
+        wire.peers.insert(
+            token,
+            Peer::connected(
+                bob,
+                NetAddr::new(HostName::Ip(net::Ipv4Addr::new(8, 8, 8, 8).into()), 8776),
+                Link::Outbound,
+                &Limits::default(),
+            ),
+        );
+        let fetch = |rid| Io::Fetch {
+            rid,
+            remote: bob,
+            timeout: FETCH_TIMEOUT,
+            reader_limit: FetchPackSizeLimit::default(),
+            refs_at: None,
+        };
+        let opens = |actions: &[Action<_>]| {
+            actions
+                .iter()
+                .filter(|a| {
+                    matches!(
+                        a,
+                        Action::Send(_, data) if matches!(
+                            Frame::<Message>::decode(&mut data.as_slice()),
+                            Ok(Frame { data: FrameData::Control(frame::Control::Open { .. }), .. })
+                        )
+                    )
+                })
+                .count()
+        };
+
+        // Before shutting down, fetches open new streams.
+        wire.service
+            .outbox()
+            .queue()
+            .push_back(fetch(arbitrary::gen(1)));
+        let actions = wire.by_ref().collect::<Vec<_>>();
+        let task = tasks.try_recv().unwrap();
+        assert_eq!(opens(&actions), 1);
+
+        // While draining, we wait for the active stream, and no new streams are opened.
+        let (done, drained) = chan::bounded(1);
+        wire.begin_shutdown(time::Duration::from_secs(60), done);
+        wire.service
+            .outbox()
+            .queue()
+            .push_back(fetch(arbitrary::gen(1)));
+
+        let actions = wire.by_ref().collect::<Vec<_>>();
+        assert_eq!(opens(&actions), 0);
+        assert!(tasks.is_empty());
+        assert!(drained.is_empty());
+        assert!(!actions
+            .iter()
+            .any(|a| matches!(a, Action::UnregisterTransport(_))));
+
+        // Once the stream completes, the peer is disconnected.
+        wire.handle_command(Control::Worker(TaskResult {
+            remote: bob,
+            stream: task.stream,
+            result: FetchResult::Responder {
+                rid: None,
+                result: Ok(()),
+            },
+        }));
+        drained.try_recv().unwrap();
+
+        let actions = wire.by_ref().collect::<Vec<_>>();
+        assert_eq!(opens(&actions), 0);
+        assert!(actions
+            .iter()
+            .any(|a| matches!(a, Action::UnregisterTransport(t) if *t == token)));
+    }
+
+    #[test]
+    fn test_deferred_fetches() {
+        use crate::reactor::ReactionHandler as _;
+        use radicle::test::arbitrary;
+
+        let mut config = peer::Config::default();
+        config.config.limits.max_queued_fetches = 1.into();
+
+        let (mut wire, tasks) = wire_with(config);
+        let bob = arbitrary::gen::<NodeId>(1);
+        let token = wire.tokens.advance();
+        wire.peers.insert(
+            token,
+            Peer::connected(
+                bob,
+                NetAddr::new(HostName::Ip(net::Ipv4Addr::new(8, 8, 8, 8).into()), 8776),
+                Link::Outbound,
+                &Limits::default(),
+            ),
+        );
+        let fetch = |rid| Io::Fetch {
+            rid,
+            remote: bob,
+            timeout: FETCH_TIMEOUT,
+            reader_limit: FetchPackSizeLimit::default(),
+            refs_at: None,
+        };
+        let controls = |actions: &[Action<_>]| {
+            actions
+                .iter()
+                .filter_map(|a| match a {
+                    Action::Send(_, data) => match Frame::<Message>::decode(&mut data.as_slice()) {
+                        Ok(Frame {
+                            data: FrameData::Control(control),
+                            ..
+                        }) => Some(control),
+                        _ => None,
+                    },
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+        let first = arbitrary::gen(1);
+        let second = arbitrary::gen(1);
+        let third = arbitrary::gen(1);
+
+        // The first fetch is handed to the worker pool, which is then saturated.
+        wire.service.outbox().queue().push_back(fetch(first));
+        let actions = wire.by_ref().collect::<Vec<_>>();
+        assert!(matches!(
+            controls(&actions)[..],
+            [frame::Control::Open { .. }]
+        ));
+        assert_eq!(tasks.len(), 1);
+
+        // Further fetches are deferred, rather than dropped.
+        wire.service.outbox().queue().push_back(fetch(second));
+        wire.service.outbox().queue().push_back(fetch(third));
+        let actions = wire.by_ref().collect::<Vec<_>>();
+        assert!(controls(&actions).is_empty());
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(wire.deferred.len(), 2);
+
+        // Fetch requests from the peer are declined.
+        let stream = StreamId::git(Link::Inbound).nth(1).unwrap();
+        let open = Frame::<Message>::control(Link::Inbound, frame::Control::Open { stream });
+        wire.transport_reacted(
+            token,
+            SessionEvent::Data(open.encode_to_vec()),
+            Instant::now(),
+        );
+        let actions = wire.by_ref().collect::<Vec<_>>();
+        assert!(matches!(
+            controls(&actions)[..],
+            [frame::Control::Close { stream: s }] if s == stream
+        ));
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(wire.metrics.peer(bob).declined_fetch_requests, 1);
+
+        // Once the worker pool catches up, deferred fetches are issued in order.
+        let task = tasks.try_recv().unwrap();
+        assert!(matches!(task.fetch, FetchRequest::Initiator { rid, .. } if rid == first));
+
+        let actions = wire.by_ref().collect::<Vec<_>>();
+        assert!(matches!(
+            controls(&actions)[..],
+            [frame::Control::Open { .. }]
+        ));
+        assert_eq!(wire.deferred.len(), 1);
+
+        let task = tasks.try_recv().unwrap();
+        assert!(matches!(task.fetch, FetchRequest::Initiator { rid, .. } if rid == second));
+
+        wire.by_ref().for_each(drop);
+        let task = tasks.try_recv().unwrap();
+        assert!(matches!(task.fetch, FetchRequest::Initiator { rid, .. } if rid == third));
+        assert!(wire.deferred.is_empty());
+    }
+
+    #[test]
+    fn test_reload_limits() {
+        use crate::reactor::ReactionHandler as _;
+        use radicle::node::config::LimitInboxSize;
+
+        let (mut wire, _) = wire();
+        let mut config = wire.service.config().clone();
+        config.limits.inbox_size = (LimitInboxSize::MIN * 2).into();
+        config.limits.max_frame_size = 1024.into();
+
+        let (sender, receiver) = chan::bounded(1);
+        wire.handle_command(Control::Reload(Box::new(config), sender));
+
+        // Limits need no restart, and are in effect right away.
+        assert!(receiver.try_recv().unwrap().is_empty());
+        assert_eq!(
+            usize::from(wire.service.config().limits.max_frame_size),
+            1024
+        );
+        assert_eq!(wire.compression.limit(), Some(LimitInboxSize::MIN * 2));
+    }
+
+    #[test]
+    fn test_deferred_fetches_disconnect() {
+        use radicle::test::arbitrary;
+
+        let mut config = peer::Config::default();
+        config.config.limits.max_queued_fetches = 0.into();
+
+        let (mut wire, tasks) = wire_with(config);
+        let bob = arbitrary::gen::<NodeId>(1);
+        let token = wire.tokens.advance();
+        wire.peers.insert(
+            token,
+            Peer::connected(
+                bob,
+                NetAddr::new(HostName::Ip(net::Ipv4Addr::new(8, 8, 8, 8).into()), 8776),
+                Link::Outbound,
+                &Limits::default(),
+            ),
+        );
+        wire.service.outbox().queue().push_back(Io::Fetch {
+            rid: arbitrary::gen(1),
+            remote: bob,
+            timeout: FETCH_TIMEOUT,
+            reader_limit: FetchPackSizeLimit::default(),
+            refs_at: None,
+        });
+        wire.by_ref().for_each(drop);
+        assert_eq!(wire.deferred.len(), 1);
+
+        // Deferred fetches are kept for as long as the peer is connected.
+        wire.fail_deferred(&bob);
+        assert_eq!(wire.deferred.len(), 1);
+
+        // And failed once it disconnects, instead of lingering.
+        wire.peers.remove(&token);
+        wire.fail_deferred(&bob);
+        assert!(wire.deferred.is_empty());
+        assert!(tasks.is_empty());
+    }
+
+    #[test]
+    fn test_git_compression_roundtrip() {
+        use crate::reactor::ReactionHandler as _;
+        use radicle::test::arbitrary;
+
+        let (mut wire, _) = wire();
+        let bob = arbitrary::gen::<NodeId>(1);
+        let carol = arbitrary::gen::<NodeId>(1);
+        let addr = NetAddr::new(HostName::Ip(net::Ipv4Addr::new(8, 8, 8, 8).into()), 8776);
+        let carol_token = wire.tokens.advance();
+
+        for (nid, link, token) in [
+            (bob, Link::Outbound, wire.tokens.advance()),
+            (carol, Link::Inbound, carol_token),
+        ] {
+            wire.peers.insert(
+                token,
+                Peer::connected(nid, addr.clone(), link, &Limits::default()),
+            );
+        }
+        // A fake packfile: a header followed by repetitive object data.
+        let mut pack = b"PACK\x00\x00\x00\x02\x00\x00\x00\x40".to_vec();
+        pack.extend(b"tree 4b825dc642cb6eb9a060e54bf8d69288fbee4904\n".repeat(64));
+
+        // Send the pack to Bob, compressed.
+        let Some((_, Peer::Connected { streams, .. })) = wire.peers.lookup_mut(&bob) else {
+            panic!("Bob is not connected");
+        };
+        let (stream, worker) = streams.open(ChannelsConfig::new(FETCH_TIMEOUT));
+        streams.get_mut(&stream).unwrap().compress = true;
+        worker.send(ChannelEvent::Data(pack.clone())).unwrap();
+        wire.flush(bob, stream);
+
+        let Some(Action::Send(_, bytes)) = wire.actions.pop_front() else {
+            panic!("No frame was sent to Bob");
+        };
+        let Ok(Frame {
+            data: FrameData::Git(compressed),
+            ..
+        }) = Frame::<Message>::decode(&mut bytes.as_slice())
+        else {
+            panic!("Expected a git frame");
+        };
+        assert!(compressed.len() < pack.len());
+        assert_eq!(wire.metrics.peer(bob).sent_git_bytes, compressed.len());
+
+        // Receive the same data from Carol, and decompress it.
+        let Some((_, Peer::Connected { streams, .. })) = wire.peers.lookup_mut(&carol) else {
+            panic!("Carol is not connected");
+        };
+        let worker = streams
+            .register(stream, ChannelsConfig::new(FETCH_TIMEOUT))
+            .unwrap();
+        streams.get_mut(&stream).unwrap().decompress = true;
+        wire.transport_reacted(carol_token, SessionEvent::Data(bytes), Instant::now());
+
+        radicle::assert_matches!(
+            worker.try_iter().next(),
+            Some(ChannelEvent::Data(data)) if data == pack
+        );
+        assert_eq!(
+            wire.metrics.peer(carol).received_git_bytes,
+            compressed.len()
+        );
+    }
+
+    #[test]
+    fn test_version_negotiation() {
+        use crate::reactor::ReactionHandler as _;
+        use radicle::test::arbitrary;
+
+        let (mut wire, _) = wire();
+        let bob = arbitrary::gen::<NodeId>(1);
+        let carol = arbitrary::gen::<NodeId>(1);
+        let addr = NetAddr::new(HostName::Ip(net::Ipv4Addr::new(8, 8, 8, 8).into()), 8776);
+        let bob_token = wire.tokens.advance();
+        let carol_token = wire.tokens.advance();
+
+        for (nid, token) in [(bob, bob_token), (carol, carol_token)] {
+            wire.peers.insert(
+                token,
+                Peer::connected(nid, addr.clone(), Link::Inbound, &Limits::default()),
+            );
+        }
+        let versions = |min, max| {
+            Frame::<Message>::control(
+                Link::Outbound,
+                frame::Control::Versions {
+                    range: frame::VersionRange { min, max },
+                },
+            )
+            .encode_to_vec()
+        };
+
+        // Bob supports a range that overlaps with ours: we reply with our own range.
+        wire.transport_reacted(
+            bob_token,
+            SessionEvent::Data(versions(1, 9)),
+            Instant::now(),
+        );
+
+        radicle::assert_matches!(
+            wire.peers.get_mut(&bob_token),
+            Some(Peer::Connected {
+                advertised: true,
+                ..
+            })
+        );
+        radicle::assert_matches!(
+            wire.actions.pop_front(),
+            Some(Action::Send(t, data)) if t == bob_token && matches!(
+                Frame::<Message>::decode(&mut data.as_slice()),
+                Ok(Frame {
+                    data: FrameData::Control(frame::Control::Versions {
+                        range: frame::VersionRange::SUPPORTED
+                    }),
+                    ..
+                })
+            )
+        );
+
+        // Carol only supports versions newer than ours: she is disconnected.
+        let min = frame::VersionRange::SUPPORTED.max + 1;
+        wire.transport_reacted(
+            carol_token,
+            SessionEvent::Data(versions(min, min)),
+            Instant::now(),
+        );
+
+        radicle::assert_matches!(
+            wire.peers.get_mut(&carol_token),
+            Some(Peer::Disconnecting {
+                reason: DisconnectReason::Session(session::Error::IncompatibleVersion { .. }),
+                ..
+            })
+        );
+        assert!(wire
+            .actions
+            .iter()
+            .any(|a| matches!(a, Action::UnregisterTransport(t) if *t == carol_token)));
+    }
+
+    #[test]
+    fn test_dial_resolver() {
+        use radicle::test::arbitrary;
+
+        let (wire, _) = wire();
+        let listener = net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let resolver = FixedResolver::new(listener.local_addr().unwrap());
+        let remote = NetAddr::new(HostName::Dns(String::from("seed.radicle.example")), 8776);
+
+        dial(
+            remote,
+            arbitrary::gen::<NodeId>(1),
+            wire.signer.clone().into_inner(),
+            wire.service.config(),
+            &resolver,
+        )
+        .unwrap();
+        listener.accept().unwrap();
+
+        assert_eq!(
+            resolver.lookups.into_inner(),
+            vec![(HostName::Dns(String::from("seed.radicle.example")), 8776)]
+        );
+    }
+
+    #[test]
+    fn test_dial_self() {
+        use radicle::node::Address;
+        use radicle::test::arbitrary;
+
+        let listener = net::TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.set_nonblocking(true).unwrap();
+        let local = listener.local_addr().unwrap();
+        let external = NetAddr::new(HostName::Dns(String::from("seed.radicle.example")), 8776);
+
+        let mut config = peer::Config::default();
+        config.config.external_addresses = vec![Address::from(external.clone())];
+        config.config.listen = vec![net::SocketAddr::from(([0, 0, 0, 0], local.port()))];
+
+        let (wire, _) = wire_with(config);
+        let resolver = FixedResolver::new(local);
+        let connect = |addr: NetAddr<HostName>| {
+            dial(
+                addr,
+                arbitrary::gen::<NodeId>(1),
+                wire.signer.clone().into_inner(),
+                wire.service.config(),
+                &resolver,
+            )
+        };
+
+        // Our external address is refused before it is even resolved.
+        let err = connect(external).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::ConnectionRefused);
+        assert!(resolver.lookups.borrow().is_empty());
+
+        // An address resolving to a port we listen on is refused before connecting.
+        let err = connect(NetAddr::new(
+            HostName::Dns(String::from("localhost")),
+            local.port(),
+        ))
+        .err()
+        .unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::ConnectionRefused);
+        assert_eq!(resolver.lookups.borrow().len(), 1);
+
+        assert_eq!(
+            listener.accept().unwrap_err().kind(),
+            io::ErrorKind::WouldBlock
+        );
+    }
+
+    #[test]
+    fn test_dial_failure_event() {
+        use radicle::node::{Address, ConnectOptions};
+        use radicle::test::arbitrary;
+
+        use crate::service::Command;
+
+        let (mut wire, _) = wire();
+        let events = wire.service.events();
+        let bob = arbitrary::gen::<NodeId>(1);
+        // Onion addresses can't be dialed without an onion configuration.
+        let onion = cyphernet::addr::tor::OnionAddrV3::from(
+            cyphernet::ed25519::PublicKey::from_pk_compressed(**bob).unwrap(),
+        );
+        let addr = Address::from(NetAddr::new(HostName::Tor(onion), 8776));
+
+        wire.service.command(Command::Connect(
+            bob,
+            addr.clone(),
+            ConnectOptions::default(),
+        ));
+        let actions = wire.by_ref().collect::<Vec<_>>();
+
+        assert!(!actions
+            .iter()
+            .any(|a| matches!(a, Action::RegisterTransport(..))));
+        radicle::assert_matches!(
+            events.try_recv(),
+            Ok(Event::ConnectionAttempted { nid, addr: a }) if nid == bob && a == addr
+        );
+        radicle::assert_matches!(
+            events.try_recv(),
+            Ok(Event::ConnectionFailed { nid, addr: a, .. }) if nid == bob && a == addr
+        );
+    }
+
     #[test]
     fn test_pong_message_with_extension() {
         use radicle_protocol::deserializer;
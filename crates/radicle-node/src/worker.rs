@@ -6,6 +6,7 @@ pub mod fetch;
 pub mod garbage;
 
 use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
 
 use crossbeam_channel as chan;
 
@@ -37,7 +38,8 @@ pub struct Config {
     /// Configuration for performing fetched.
     pub fetch: FetchConfig,
     /// Default policy, if a policy for a specific node or repository was not found.
-    pub policy: SeedingPolicy,
+    /// Shared with the runtime, which updates it when the configuration is reloaded.
+    pub policy: Arc<RwLock<SeedingPolicy>>,
     /// Path to the policies database.
     pub policies_db: PathBuf,
 }
@@ -77,6 +79,7 @@ struct Worker {
     tasks: chan::Receiver<Task>,
     handle: Handle,
     policies: policy::Config<policy::store::Read>,
+    policy: Arc<RwLock<SeedingPolicy>>,
     notifications: notifications::StoreWriter,
     cache: cob::cache::StoreWriter,
     db: radicle::node::Database,
@@ -101,6 +104,10 @@ impl Worker {
         }: Task,
     ) {
         let remote = fetch.remote();
+        // Pick up changes to the default policy.
+        if let Ok(policy) = self.policy.read() {
+            self.policies.set_default_policy(*policy);
+        }
         let channels = channels::ChannelsFlush::new(self.handle.clone(), channels, remote, stream);
         let result = self._process(fetch, stream, channels, self.notifications.clone());
 
@@ -264,8 +271,11 @@ impl Pool {
     ) -> Result<Self, policy::Error> {
         let mut pool = Vec::with_capacity(config.capacity);
         for i in 0..config.capacity {
-            let policies =
-                policy::Config::new(config.policy, policy::Store::reader(&config.policies_db)?);
+            let policy = *config
+                .policy
+                .read()
+                .expect("Pool::with: policy lock is poisoned");
+            let policies = policy::Config::new(policy, policy::Store::reader(&config.policies_db)?);
             let worker = Worker {
                 nid,
                 tasks: tasks.clone(),
@@ -273,6 +283,7 @@ impl Pool {
                 storage: config.storage.clone(),
                 fetch_config: config.fetch.clone(),
                 policies,
+                policy: config.policy.clone(),
                 notifications: notifications.clone(),
                 cache: cache.clone(),
                 db: db.clone(),
@@ -27,7 +27,8 @@
 //!
 //! [`git2::Oid`]: ::git2::Oid
 //!
-//! Provides conversions to/from [`git2::Oid`].
+//! Provides conversions to/from [`git2::Oid`], as well as the module `compat`
+//! for hashing that agrees with [`git2::Oid`] regardless of the `std` feature.
 //!
 //! Note that as of version 0.19.0,
 //!
@@ -35,7 +36,8 @@
 //!
 //! [`ObjectId`]: ::gix_hash::ObjectId
 //!
-//! Provides conversions to/from [`ObjectId`].
+//! Provides conversions to/from [`ObjectId`], as well as the module `compat`
+//! for hashing converted [`ObjectId`]s like [`git2::Oid`].
 //!
 //! ## `schemars`
 //!
@@ -48,7 +50,9 @@
 //! [`Serialize`]: ::serde::ser::Serialize
 //! [`Deserialize`]: ::serde::de::Deserialize
 //!
-//! Provides implementations of [`Serialize`] and [`Deserialize`].
+//! Provides implementations of [`Serialize`] and [`Deserialize`], as well as
+//! the module `serde::bytes` for a more compact representation with
+//! serializers that are not human readable.
 //!
 //! ## `qcheck`
 //!
@@ -76,6 +80,12 @@ compile_error!("The `sha1` feature is required.");
 
 const SHA1_DIGEST_LEN: usize = 20;
 
+/// A Git object identifier.
+///
+/// The derived [`Ord`] compares the kind of hash first, and only then the
+/// digests. Once other hashes are supported, all SHA-1 object identifiers
+/// will thus be ordered before all others, regardless of their digests. Use
+/// [`Oid::cmp_bytes`] for an ordering that only depends on the digests.
 #[derive(PartialEq, Eq, Ord, PartialOrd, Clone, Copy)]
 #[non_exhaustive]
 pub enum Oid {
@@ -113,6 +123,20 @@ impl Oid {
     }
 }
 
+/// Access to the raw digest.
+impl Oid {
+    /// The raw bytes of the digest, exactly as Git stores them, without any
+    /// indication of the kind of hash.
+    ///
+    /// Note that `git2::Oid` and `gix_hash::ObjectId` both hash these bytes,
+    /// but differently, see the module `compat`.
+    pub fn as_git_bytes(&self) -> &[u8] {
+        match self {
+            Oid::Sha1(ref array) => array,
+        }
+    }
+}
+
 impl AsRef<[u8]> for Oid {
     fn as_ref(&self) -> &[u8] {
         match self {
@@ -129,6 +153,52 @@ impl From<Oid> for alloc::boxed::Box<[u8]> {
     }
 }
 
+mod cmp {
+    use core::cmp::Ordering;
+
+    use super::Oid;
+
+    impl Oid {
+        /// Compare the digests of two object identifiers byte by byte,
+        /// regardless of the kind of hash.
+        ///
+        /// This agrees with comparing the hexadecimal representations, where
+        /// a shorter digest that is a prefix of a longer one is ordered first.
+        pub fn cmp_bytes(&self, other: &Self) -> Ordering {
+            AsRef::<[u8]>::as_ref(self).cmp(other.as_ref())
+        }
+    }
+
+    #[cfg(test)]
+    mod test {
+        use super::*;
+        use alloc::string::ToString;
+        use qcheck_macros::quickcheck;
+
+        #[test]
+        fn fixture() {
+            let a = Oid::from_sha1([0x0f; 20]);
+            let b = Oid::from_sha1([0xf0; 20]);
+
+            assert_eq!(a.cmp_bytes(&b), Ordering::Less);
+            assert_eq!(b.cmp_bytes(&a), Ordering::Greater);
+            assert_eq!(a.cmp_bytes(&a), Ordering::Equal);
+        }
+
+        #[quickcheck]
+        fn agrees_with_hex(a: Oid, b: Oid) {
+            assert_eq!(a.cmp_bytes(&b), a.to_string().cmp(&b.to_string()));
+            assert_eq!(a.cmp_bytes(&a), Ordering::Equal);
+        }
+
+        #[quickcheck]
+        fn agrees_with_ord(a: Oid, b: Oid) {
+            // As long as there is only one kind of hash.
+            assert_eq!(a.cmp_bytes(&b), a.cmp(&b));
+        }
+    }
+}
+
 pub mod str {
     use super::{Oid, SHA1_DIGEST_LEN};
     use core::str;
@@ -136,27 +206,61 @@ pub mod str {
     /// Length of the string representation of a SHA-1 digest in hexadecimal notation.
     pub(super) const SHA1_DIGEST_STR_LEN: usize = SHA1_DIGEST_LEN * 2;
 
+    /// Values of hexadecimal digits, indexed by character. Characters that
+    /// are not hexadecimal digits map to `0xff`.
+    const HEX_VALUES: [u8; 256] = {
+        let mut table = [0xff; 256];
+        let mut i = 0;
+        while i < 16 {
+            table[b"0123456789abcdef"[i] as usize] = i as u8;
+            table[b"0123456789ABCDEF"[i] as usize] = i as u8;
+            i += 1;
+        }
+        table
+    };
+
     impl str::FromStr for Oid {
         type Err = error::ParseOidError;
 
         fn from_str(s: &str) -> Result<Self, Self::Err> {
-            use error::ParseOidError::*;
-
             let len = s.len();
             if len != SHA1_DIGEST_STR_LEN {
-                return Err(Len(len));
+                return Err(error::ParseOidError::Len(len));
             }
 
             let mut bytes = [0u8; SHA1_DIGEST_LEN];
-            for i in 0..SHA1_DIGEST_LEN {
-                bytes[i] = u8::from_str_radix(&s[i * 2..=i * 2 + 1], 16)
-                    .map_err(|source| At { index: i, source })?;
+            for (i, (byte, digits)) in bytes
+                .iter_mut()
+                .zip(s.as_bytes().chunks_exact(2))
+                .enumerate()
+            {
+                let hi = HEX_VALUES[digits[0] as usize];
+                let lo = HEX_VALUES[digits[1] as usize];
+
+                // Only invalid digits have any of the high bits set.
+                *byte = if (hi | lo) & 0xf0 == 0 {
+                    (hi << 4) | lo
+                } else {
+                    parse_byte(s, i)?
+                };
             }
 
             Ok(Self::Sha1(bytes))
         }
     }
 
+    /// Parse the byte at `index` with [`u8::from_str_radix`], which rejects
+    /// the digits, to obtain the error. Note that it also accepts a leading
+    /// `+`, eg. `+f`, which is thus accepted here as well.
+    #[cold]
+    fn parse_byte(s: &str, index: usize) -> Result<u8, error::ParseOidError> {
+        // If the digits are not on character boundaries, at least one of
+        // them is not ASCII, and thus invalid.
+        let digits = s.get(index * 2..index * 2 + 2).unwrap_or("x");
+
+        u8::from_str_radix(digits, 16).map_err(|source| error::ParseOidError::At { index, source })
+    }
+
     pub mod error {
         use core::{fmt, num};
 
@@ -234,6 +338,108 @@ pub mod str {
             );
         }
 
+        /// Parse like [`Oid::from_str`] did before using a lookup table.
+        fn from_str_radix(s: &str) -> Result<Oid, ParseOidError> {
+            use ParseOidError::*;
+
+            let len = s.len();
+            if len != SHA1_DIGEST_STR_LEN {
+                return Err(Len(len));
+            }
+
+            let mut bytes = [0u8; SHA1_DIGEST_LEN];
+            for i in 0..SHA1_DIGEST_LEN {
+                bytes[i] = u8::from_str_radix(&s[i * 2..=i * 2 + 1], 16)
+                    .map_err(|source| At { index: i, source })?;
+            }
+
+            Ok(Oid::Sha1(bytes))
+        }
+
+        fn assert_agrees(s: &str) {
+            use ParseOidError::*;
+
+            match (s.parse::<Oid>(), from_str_radix(s)) {
+                (Ok(a), Ok(b)) => assert_eq!(a, b),
+                (Err(Len(a)), Err(Len(b))) => assert_eq!(a, b),
+                (
+                    Err(At {
+                        index: a,
+                        source: e,
+                    }),
+                    Err(At {
+                        index: b,
+                        source: f,
+                    }),
+                ) => {
+                    assert_eq!((a, e), (b, f))
+                }
+                (a, b) => panic!("{s:?}: {a:?} != {b:?}"),
+            }
+        }
+
+        #[test]
+        fn invalid() {
+            let s = "123456789abcdef0123456789abcdef01234567g";
+            assert_agrees(s);
+            assert!(matches!(
+                s.parse::<Oid>(),
+                Err(ParseOidError::At { index: 19, .. })
+            ));
+            // Upper case digits and a leading `+` are accepted by `u8::from_str_radix`.
+            assert_agrees("123456789ABCDEF0123456789ABCDEF012345678");
+            assert_agrees("+f3456789abcdef0123456789abcdef012345678");
+            assert_agrees("12345678 abcdef0123456789abcdef012345678");
+            assert_agrees("123456789abcdef0123456789abcdef0123456ä");
+        }
+
+        #[test]
+        fn not_ascii() {
+            // The old implementation panics on this input, since `ä` is not
+            // on a character boundary.
+            let s = "123456789abcdef0123456789abcdef012345ä6";
+            assert_eq!(s.len(), SHA1_DIGEST_STR_LEN);
+            assert!(matches!(
+                s.parse::<Oid>(),
+                Err(ParseOidError::At { index: 18, .. })
+            ));
+        }
+
+        #[test]
+        fn agrees_on_many() {
+            for n in 0..=u16::MAX {
+                let mut digest = [0u8; SHA1_DIGEST_LEN];
+                for (i, byte) in digest.iter_mut().enumerate() {
+                    *byte = (n as usize).wrapping_mul(i + 1) as u8;
+                }
+                assert_agrees(&Oid::from_sha1(digest).to_string());
+            }
+        }
+
+        #[quickcheck]
+        fn agrees_on_oids(oid: Oid, upper: bool) {
+            let s = oid.to_string();
+            assert_agrees(&if upper { s.to_uppercase() } else { s });
+        }
+
+        #[quickcheck]
+        fn agrees_on_corrupted_oids(oid: Oid, index: usize, c: char) {
+            let mut s = oid.to_string();
+            let index = index % SHA1_DIGEST_STR_LEN;
+            s.replace_range(index..=index, c.encode_utf8(&mut [0u8; 4]));
+            assert_agrees(&s);
+        }
+
+        #[quickcheck]
+        fn agrees_on_strings(s: alloc::string::String) {
+            // The old implementation panics if digits are not on character boundaries.
+            if (0..=s.len()).step_by(2).all(|i| s.is_char_boundary(i)) {
+                assert_agrees(&s);
+            } else {
+                assert!(s.parse::<Oid>().is_err());
+            }
+        }
+
         #[quickcheck]
         fn git2_roundtrip(oid: Oid) {
             let other = git2::Oid::from(oid);
@@ -252,42 +458,319 @@ pub mod str {
     }
 }
 
+pub mod prefix {
+    //! Abbreviated object identifiers, such as `1234567`.
+
+    use core::{fmt, str};
+
+    use super::str::{ParseOidError, SHA1_DIGEST_STR_LEN};
+    use super::{Oid, SHA1_DIGEST_LEN};
+
+    /// A prefix of an [`Oid`], as parsed from an abbreviated hexadecimal
+    /// representation, e.g. `1234567`.
+    ///
+    /// Since every hexadecimal digit encodes four bits, the length of a
+    /// prefix is always a multiple of four bits. The empty prefix matches
+    /// every object identifier.
+    #[derive(Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct OidPrefix {
+        /// The parsed nibbles, packed into bytes. Bits beyond
+        /// [`OidPrefix::bits`] are zero.
+        bytes: [u8; SHA1_DIGEST_LEN],
+        /// The number of significant bits in `bytes`.
+        bits: usize,
+    }
+
+    impl OidPrefix {
+        /// The length of this prefix in bits.
+        pub fn bits(&self) -> usize {
+            self.bits
+        }
+
+        /// The length of this prefix in hexadecimal digits.
+        pub fn len(&self) -> usize {
+            self.bits / 4
+        }
+
+        /// Test whether this prefix is empty, in which case it matches every
+        /// object identifier.
+        pub fn is_empty(&self) -> bool {
+            self.bits == 0
+        }
+
+        /// Test whether `oid` starts with this prefix.
+        pub fn matches(&self, oid: &Oid) -> bool {
+            match oid {
+                Oid::Sha1(digest) => {
+                    let full = self.bits / 8;
+                    if digest[..full] != self.bytes[..full] {
+                        return false;
+                    }
+                    if self.bits % 8 == 0 {
+                        return true;
+                    }
+                    digest[full] & 0xf0 == self.bytes[full]
+                }
+            }
+        }
+    }
+
+    impl Oid {
+        /// Parse an abbreviated object identifier of at most
+        /// 40 hexadecimal digits.
+        ///
+        /// Use [`OidPrefix::matches`] to test candidate object identifiers
+        /// against the result.
+        pub fn from_str_prefix(s: &str) -> Result<OidPrefix, ParseOidError> {
+            s.parse()
+        }
+
+        /// Test whether the hexadecimal representation of this object
+        /// identifier starts with `prefix`, of at most 40 hexadecimal digits,
+        /// in either case.
+        ///
+        /// This is equivalent to matching the result of
+        /// [`Oid::from_str_prefix`] against `self`, without constructing an
+        /// [`OidPrefix`]. The whole prefix is validated, even if it doesn't
+        /// match.
+        pub fn starts_with_hex(&self, prefix: &str) -> Result<bool, ParseOidError> {
+            let len = prefix.len();
+            if len > SHA1_DIGEST_STR_LEN {
+                return Err(ParseOidError::Len(len));
+            }
+
+            let digest = self.as_git_bytes();
+            let mut matches = true;
+            for (i, c) in prefix.char_indices() {
+                let byte = digest[i / 2];
+                // Even digits are the high nibble of a byte.
+                let expected = if i % 2 == 0 { byte >> 4 } else { byte & 0x0f };
+
+                matches &= nibble(i, c)? == expected;
+            }
+            Ok(matches)
+        }
+    }
+
+    /// Parse the hexadecimal digit `c` at index `i` of an abbreviated object
+    /// identifier. All characters before `c` must be ASCII, such that `i` is
+    /// also the index of the nibble.
+    fn nibble(i: usize, c: char) -> Result<u8, ParseOidError> {
+        u8::from_str_radix(c.encode_utf8(&mut [0u8; 4]), 16).map_err(|source| ParseOidError::At {
+            index: i / 2,
+            source,
+        })
+    }
+
+    impl str::FromStr for OidPrefix {
+        type Err = ParseOidError;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            use ParseOidError::*;
+
+            let len = s.len();
+            if len > SHA1_DIGEST_STR_LEN {
+                return Err(Len(len));
+            }
+
+            let mut bytes = [0u8; SHA1_DIGEST_LEN];
+            for (i, c) in s.char_indices() {
+                let nibble = nibble(i, c)?;
+                bytes[i / 2] |= if i % 2 == 0 { nibble << 4 } else { nibble };
+            }
+
+            Ok(Self {
+                bytes,
+                bits: len * 4,
+            })
+        }
+    }
+
+    impl fmt::Display for OidPrefix {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            for i in 0..self.len() {
+                let byte = self.bytes[i / 2];
+                let nibble = if i % 2 == 0 { byte >> 4 } else { byte & 0x0f };
+                write!(f, "{nibble:x}")?;
+            }
+            Ok(())
+        }
+    }
+
+    impl fmt::Debug for OidPrefix {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            fmt::Display::fmt(self, f)
+        }
+    }
+
+    #[cfg(test)]
+    mod test {
+        use super::*;
+        use alloc::string::ToString;
+        use qcheck_macros::quickcheck;
+
+        const FIXTURE: &str = "123456789abcdef0123456789abcdef012345678";
+
+        #[test]
+        fn fixture() {
+            let oid = FIXTURE.parse::<Oid>().unwrap();
+            for len in 0..=SHA1_DIGEST_STR_LEN {
+                let prefix = Oid::from_str_prefix(&FIXTURE[..len]).unwrap();
+                assert_eq!(prefix.len(), len);
+                assert_eq!(prefix.bits(), len * 4);
+                assert_eq!(prefix.to_string(), FIXTURE[..len]);
+                assert!(prefix.matches(&oid));
+            }
+        }
+
+        #[test]
+        fn mismatch() {
+            let oid = FIXTURE.parse::<Oid>().unwrap();
+            assert!(!Oid::from_str_prefix("1235").unwrap().matches(&oid));
+            assert!(!Oid::from_str_prefix("12345")
+                .unwrap()
+                .matches(&Oid::sha1_zero()));
+            assert!(Oid::from_str_prefix("00000")
+                .unwrap()
+                .matches(&Oid::sha1_zero()));
+        }
+
+        #[test]
+        fn too_long() {
+            assert!(matches!(
+                Oid::from_str_prefix("123456789abcdef0123456789abcdef0123456789"),
+                Err(ParseOidError::Len(41))
+            ));
+        }
+
+        #[test]
+        fn not_hex() {
+            assert!(matches!(
+                Oid::from_str_prefix("12345g"),
+                Err(ParseOidError::At { index: 2, .. })
+            ));
+            assert!(matches!(
+                Oid::from_str_prefix("123ä"),
+                Err(ParseOidError::At { index: 1, .. })
+            ));
+        }
+
+        #[test]
+        fn starts_with_hex() {
+            let oid = FIXTURE.parse::<Oid>().unwrap();
+            for len in 0..=SHA1_DIGEST_STR_LEN {
+                assert!(oid.starts_with_hex(&FIXTURE[..len]).unwrap());
+            }
+            assert!(oid.starts_with_hex("123456789ABCDEF").unwrap());
+            // Even length, mismatching in the last byte.
+            assert!(!oid.starts_with_hex("1235").unwrap());
+            // Odd length, mismatching in the high nibble of the last byte.
+            assert!(!oid.starts_with_hex("12346").unwrap());
+            assert!(!oid.starts_with_hex("2").unwrap());
+            assert!(Oid::sha1_zero().starts_with_hex("00000").unwrap());
+            assert!(!Oid::sha1_zero().starts_with_hex("00001").unwrap());
+        }
+
+        #[test]
+        fn starts_with_hex_invalid() {
+            let oid = FIXTURE.parse::<Oid>().unwrap();
+            assert!(matches!(
+                oid.starts_with_hex("123456789abcdef0123456789abcdef0123456789"),
+                Err(ParseOidError::Len(41))
+            ));
+            // The prefix is validated past the first mismatch.
+            assert!(matches!(
+                oid.starts_with_hex("fffffg"),
+                Err(ParseOidError::At { index: 2, .. })
+            ));
+            assert!(matches!(
+                oid.starts_with_hex("123ä"),
+                Err(ParseOidError::At { index: 1, .. })
+            ));
+        }
+
+        #[quickcheck]
+        fn prefix_of_display(oid: Oid, len: usize) {
+            let len = len % (SHA1_DIGEST_STR_LEN + 1);
+            let s = oid.to_string();
+            let prefix = Oid::from_str_prefix(&s[..len]).unwrap();
+            assert!(prefix.matches(&oid));
+            assert!(oid.starts_with_hex(&s[..len]).unwrap());
+        }
+
+        #[quickcheck]
+        fn starts_with_hex_matches(oid: Oid, other: Oid, len: usize) {
+            let len = len % (SHA1_DIGEST_STR_LEN + 1);
+            let s = other.to_string();
+            let prefix = Oid::from_str_prefix(&s[..len]).unwrap();
+            assert_eq!(
+                oid.starts_with_hex(&s[..len]).unwrap(),
+                prefix.matches(&oid)
+            );
+        }
+    }
+}
+
+pub use prefix::OidPrefix;
+
 mod fmt {
-    use alloc::format;
     use core::fmt;
 
-    use super::Oid;
+    use super::{Oid, SHA1_DIGEST_LEN};
+
+    pub(super) const HEX_DIGITS: &[u8; 16] = b"0123456789abcdef";
+    const HEX_DIGITS_UPPER: &[u8; 16] = b"0123456789ABCDEF";
+
+    /// Encode `digest` as hexadecimal, using `digits`.
+    pub(super) fn hex(
+        digest: &[u8; SHA1_DIGEST_LEN],
+        digits: &[u8; 16],
+    ) -> [u8; SHA1_DIGEST_LEN * 2] {
+        let mut buf = [0u8; SHA1_DIGEST_LEN * 2];
+        for (byte, chunk) in digest.iter().zip(buf.chunks_exact_mut(2)) {
+            chunk[0] = digits[(byte >> 4) as usize];
+            chunk[1] = digits[(byte & 0x0f) as usize];
+        }
+        buf
+    }
 
     impl fmt::Display for Oid {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             match self {
-                Oid::Sha1(digest) =>
-                // SAFETY (for all 20 blocks below): The length of `digest` is
-                // known to be `SHA1_DIGEST_LEN`, which is 20.
-                // The indices below are manually verified to not be out of bounds.
-                format!(
-                    "{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}",
-                    unsafe { digest.get_unchecked(0) },
-                    unsafe { digest.get_unchecked(1) },
-                    unsafe { digest.get_unchecked(2) },
-                    unsafe { digest.get_unchecked(3) },
-                    unsafe { digest.get_unchecked(4) },
-                    unsafe { digest.get_unchecked(5) },
-                    unsafe { digest.get_unchecked(6) },
-                    unsafe { digest.get_unchecked(7) },
-                    unsafe { digest.get_unchecked(8) },
-                    unsafe { digest.get_unchecked(9) },
-                    unsafe { digest.get_unchecked(10) },
-                    unsafe { digest.get_unchecked(11) },
-                    unsafe { digest.get_unchecked(12) },
-                    unsafe { digest.get_unchecked(13) },
-                    unsafe { digest.get_unchecked(14) },
-                    unsafe { digest.get_unchecked(15) },
-                    unsafe { digest.get_unchecked(16) },
-                    unsafe { digest.get_unchecked(17) },
-                    unsafe { digest.get_unchecked(18) },
-                    unsafe { digest.get_unchecked(19) },
-                ).fmt(f)
+                Oid::Sha1(digest) => {
+                    let buf = hex(digest, HEX_DIGITS);
+                    // `buf` only contains ASCII hexadecimal digits, so this never fails.
+                    let s = core::str::from_utf8(&buf).map_err(|_| fmt::Error)?;
+                    // Use `pad` rather than `write_str` so that width, fill
+                    // and precision flags are honored, like for `String`.
+                    f.pad(s)
+                }
+            }
+        }
+    }
+
+    impl fmt::LowerHex for Oid {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Oid::Sha1(digest) => {
+                    let buf = hex(digest, HEX_DIGITS);
+                    let s = core::str::from_utf8(&buf).map_err(|_| fmt::Error)?;
+                    // Like for integers, the `#` flag adds a `0x` prefix.
+                    f.pad_integral(true, "0x", s)
+                }
+            }
+        }
+    }
+
+    impl fmt::UpperHex for Oid {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Oid::Sha1(digest) => {
+                    let buf = hex(digest, HEX_DIGITS_UPPER);
+                    let s = core::str::from_utf8(&buf).map_err(|_| fmt::Error)?;
+                    // Like for integers, the `#` flag adds a `0x` prefix.
+                    f.pad_integral(true, "0x", s)
+                }
             }
         }
     }
@@ -324,6 +807,49 @@ mod fmt {
             );
         }
 
+        #[test]
+        fn padding() {
+            use alloc::format;
+
+            let oid = Oid::sha1_zero();
+            assert_eq!(format!("{oid:>42}"), format!("{:>42}", oid.to_string()));
+            assert_eq!(format!("{oid:.7}"), "0000000");
+        }
+
+        #[test]
+        fn hex() {
+            use alloc::format;
+
+            let oid = Oid::from_sha1([
+                0x12, 0x34, 0x56, 0x78, 0x9a, 0xbc, 0xde, 0xf0, 0x12, 0x34, 0x56, 0x78, 0x9a, 0xbc,
+                0xde, 0xf0, 0x12, 0x34, 0x56, 0x78,
+            ]);
+            assert_eq!(
+                format!("{oid:x}"),
+                "123456789abcdef0123456789abcdef012345678"
+            );
+            assert_eq!(
+                format!("{oid:X}"),
+                "123456789ABCDEF0123456789ABCDEF012345678"
+            );
+            assert_eq!(
+                format!("{oid:#x}"),
+                "0x123456789abcdef0123456789abcdef012345678"
+            );
+            assert_eq!(
+                format!("{oid:#X}"),
+                "0x123456789ABCDEF0123456789ABCDEF012345678"
+            );
+            assert_eq!(oid.to_string(), format!("{oid:x}"));
+        }
+
+        #[quickcheck]
+        fn upper_hex(oid: Oid) {
+            use alloc::format;
+
+            assert_eq!(format!("{oid:X}"), oid.to_string().to_uppercase());
+        }
+
         #[quickcheck]
         fn git2(oid: Oid) {
             assert_eq!(oid.to_string(), git2::Oid::from(oid).to_string());
@@ -336,6 +862,144 @@ mod fmt {
     }
 }
 
+pub mod abbrev {
+    //! Abbreviated hexadecimal representations of object identifiers, for
+    //! human-facing output.
+
+    use core::{fmt, ops, str};
+
+    use super::str::SHA1_DIGEST_STR_LEN;
+    use super::Oid;
+
+    /// Default length of abbreviated object identifiers, as used by Git.
+    pub const DEFAULT_LEN: usize = 7;
+
+    /// The first hexadecimal digits of an [`Oid`], stored on the stack.
+    ///
+    /// Dereferences to [`str`].
+    #[derive(Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct Abbreviated {
+        buf: [u8; SHA1_DIGEST_STR_LEN],
+        len: usize,
+    }
+
+    impl Abbreviated {
+        /// The abbreviation as a string slice.
+        pub fn as_str(&self) -> &str {
+            // `buf` only contains ASCII hexadecimal digits.
+            str::from_utf8(&self.buf[..self.len]).expect("abbreviation is valid UTF-8")
+        }
+    }
+
+    impl Oid {
+        /// Abbreviate this object identifier to its first `len` hexadecimal
+        /// digits, without allocating.
+        ///
+        /// `len` is clamped to the length of the full hexadecimal representation.
+        pub fn abbreviate(&self, len: usize) -> Abbreviated {
+            match self {
+                Oid::Sha1(digest) => Abbreviated {
+                    buf: super::fmt::hex(digest, super::fmt::HEX_DIGITS),
+                    len: len.min(SHA1_DIGEST_STR_LEN),
+                },
+            }
+        }
+
+        /// Abbreviate this object identifier to its first [`DEFAULT_LEN`]
+        /// hexadecimal digits.
+        pub fn abbreviated(&self) -> Abbreviated {
+            self.abbreviate(DEFAULT_LEN)
+        }
+    }
+
+    impl ops::Deref for Abbreviated {
+        type Target = str;
+
+        fn deref(&self) -> &str {
+            self.as_str()
+        }
+    }
+
+    impl AsRef<str> for Abbreviated {
+        fn as_ref(&self) -> &str {
+            self.as_str()
+        }
+    }
+
+    impl PartialEq<str> for Abbreviated {
+        fn eq(&self, other: &str) -> bool {
+            self.as_str() == other
+        }
+    }
+
+    impl PartialEq<&str> for Abbreviated {
+        fn eq(&self, other: &&str) -> bool {
+            self.as_str() == *other
+        }
+    }
+
+    impl fmt::Display for Abbreviated {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.pad(self.as_str())
+        }
+    }
+
+    impl fmt::Debug for Abbreviated {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            fmt::Display::fmt(self, f)
+        }
+    }
+
+    #[cfg(test)]
+    mod test {
+        use super::*;
+        use alloc::string::ToString;
+        use qcheck_macros::quickcheck;
+
+        const FIXTURE: &str = "123456789abcdef0123456789abcdef012345678";
+
+        #[test]
+        fn empty() {
+            let oid = FIXTURE.parse::<Oid>().unwrap();
+            assert_eq!(oid.abbreviate(0), "");
+            assert!(oid.abbreviate(0).is_empty());
+        }
+
+        #[test]
+        fn default_len() {
+            let oid = FIXTURE.parse::<Oid>().unwrap();
+            assert_eq!(oid.abbreviate(7), "1234567");
+            assert_eq!(oid.abbreviated(), oid.abbreviate(7));
+            assert_eq!(oid.abbreviated().to_string(), "1234567");
+        }
+
+        #[test]
+        fn clamped() {
+            let oid = FIXTURE.parse::<Oid>().unwrap();
+            assert_eq!(oid.abbreviate(SHA1_DIGEST_STR_LEN), FIXTURE);
+            assert_eq!(oid.abbreviate(41), FIXTURE);
+            assert_eq!(oid.abbreviate(usize::MAX), FIXTURE);
+        }
+
+        #[test]
+        fn prefix() {
+            let oid = FIXTURE.parse::<Oid>().unwrap();
+            let abbrev = oid.abbreviate(9);
+            assert!(Oid::from_str_prefix(&abbrev).unwrap().matches(&oid));
+        }
+
+        #[quickcheck]
+        fn prefix_of_display(oid: Oid, len: usize) {
+            let s = oid.to_string();
+            let abbrev = oid.abbreviate(len);
+            assert_eq!(abbrev.len(), len.min(SHA1_DIGEST_STR_LEN));
+            assert!(s.starts_with(abbrev.as_str()));
+        }
+    }
+}
+
+pub use abbrev::Abbreviated;
+
 #[cfg(feature = "std")]
 mod std {
     extern crate std;
@@ -347,6 +1011,10 @@ mod std {
 
         use super::*;
 
+        /// Hashes the digest as a slice, ie. its length, followed by its
+        /// bytes. This agrees with `git2::Oid`, but not `gix_hash::ObjectId`,
+        /// which only hashes the bytes. Wrap in `compat::GitHashCompat` to
+        /// rely on the former.
         #[allow(clippy::derived_hash_with_manual_eq)]
         impl hash::Hash for Oid {
             fn hash<H: hash::Hasher>(&self, state: &mut H) {
@@ -454,25 +1122,211 @@ mod git2 {
     }
 }
 
+#[cfg(any(feature = "git2", feature = "gix", test))]
+pub mod compat {
+    //! Hashing that agrees with `git2::Oid`.
+    //!
+    //! `git2::Oid` hashes its digest as an array, and thus like a slice, ie.
+    //! its length followed by its bytes, as returned by
+    //! [`Oid::as_git_bytes`]. In contrast, `gix_hash::ObjectId` only writes
+    //! the bytes to the [`Hasher`], so it never agrees with `git2::Oid`.
+    //!
+    //! Keys of type [`GitHashCompat`] hash identically to the equal keys of
+    //! `git2`, no matter whether they were converted from `git2::Oid`,
+    //! `gix_hash::ObjectId` or [`Oid`], so that they can be used in maps that
+    //! mix object identifiers from different crates.
+
+    use core::hash::{Hash, Hasher};
+
+    use super::Oid;
+
+    /// An [`Oid`], hashed like `git2::Oid`.
+    #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+    pub struct GitHashCompat(pub Oid);
+
+    impl Hash for GitHashCompat {
+        fn hash<H: Hasher>(&self, state: &mut H) {
+            self.0.as_git_bytes().hash(state)
+        }
+    }
+
+    impl From<Oid> for GitHashCompat {
+        fn from(oid: Oid) -> Self {
+            Self(oid)
+        }
+    }
+
+    impl From<GitHashCompat> for Oid {
+        fn from(compat: GitHashCompat) -> Self {
+            compat.0
+        }
+    }
+
+    #[cfg(any(feature = "git2", test))]
+    impl From<::git2::Oid> for GitHashCompat {
+        fn from(other: ::git2::Oid) -> Self {
+            Self(other.into())
+        }
+    }
+
+    #[cfg(any(feature = "gix", test))]
+    impl From<::gix_hash::ObjectId> for GitHashCompat {
+        fn from(other: ::gix_hash::ObjectId) -> Self {
+            Self(other.into())
+        }
+    }
+
+    #[cfg(test)]
+    mod test {
+        extern crate std;
+
+        use std::collections::HashMap;
+        use std::hash::{BuildHasher, RandomState};
+
+        use qcheck_macros::quickcheck;
+
+        use super::*;
+
+        #[quickcheck]
+        fn agrees_with_git2(oid: Oid) {
+            let state = RandomState::new();
+            let compat = state.hash_one(GitHashCompat(oid));
+
+            assert_eq!(compat, state.hash_one(::git2::Oid::from(oid)));
+            assert_eq!(
+                compat,
+                state.hash_one(GitHashCompat::from(::gix_hash::ObjectId::from(oid)))
+            );
+        }
+
+        #[test]
+        fn fixture() {
+            let oid = "123456789abcdef0123456789abcdef012345678"
+                .parse::<Oid>()
+                .unwrap();
+            let git2 = ::git2::Oid::from_str("123456789abcdef0123456789abcdef012345678").unwrap();
+            let gix = ::gix_hash::ObjectId::from_hex(b"123456789abcdef0123456789abcdef012345678")
+                .unwrap();
+            let state = RandomState::new();
+            let compat = state.hash_one(GitHashCompat(oid));
+
+            assert_eq!(compat, state.hash_one(GitHashCompat::from(git2)));
+            assert_eq!(compat, state.hash_one(GitHashCompat::from(gix)));
+            assert_eq!(compat, state.hash_one(git2));
+
+            let map = HashMap::from([(GitHashCompat(oid), ())]);
+            assert!(map.contains_key(&GitHashCompat::from(git2)));
+            assert!(map.contains_key(&GitHashCompat::from(gix)));
+        }
+    }
+}
+
+#[cfg(any(feature = "git2", feature = "gix", test))]
+pub use compat::GitHashCompat;
+
 #[cfg(any(test, feature = "qcheck"))]
 mod test {
     mod qcheck {
+        use alloc::boxed::Box;
+
         use ::qcheck::{Arbitrary, Gen};
 
         use crate::*;
 
         impl Arbitrary for Oid {
             fn arbitrary(g: &mut Gen) -> Self {
-                let slice = [0u8; SHA1_DIGEST_LEN];
-                g.fill(slice);
+                let mut slice = [0u8; SHA1_DIGEST_LEN];
+                g.fill(&mut slice);
                 Self::Sha1(slice)
             }
+
+            /// Shrinks towards [`Oid::sha1_zero`], by zeroing trailing bytes
+            /// of the digest, and then by halving its last non-zero byte.
+            /// Every candidate is smaller than `self`, per [`Oid::cmp_bytes`].
+            fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+                let Self::Sha1(digest) = *self;
+                let Some(last) = digest.iter().rposition(|b| *b != 0) else {
+                    return ::qcheck::empty_shrinker();
+                };
+                let zeroed = (0..=last).map(move |keep| {
+                    let mut digest = digest;
+                    digest[keep..].fill(0);
+                    Self::Sha1(digest)
+                });
+                let halved = (digest[last] > 1).then(|| {
+                    let mut digest = digest;
+                    digest[last] /= 2;
+                    Self::Sha1(digest)
+                });
+
+                Box::new(zeroed.chain(halved))
+            }
+        }
+
+        #[cfg(test)]
+        mod test {
+            use core::cmp::Ordering;
+
+            use qcheck_macros::quickcheck;
+
+            use super::*;
+
+            /// Properties over arbitrary [`Oid`]s hold vacuously if the
+            /// generated digests are all the same, eg. all zero.
+            #[test]
+            fn arbitrary_not_constant() {
+                let mut g = Gen::new(16);
+                let oids = (0..8)
+                    .map(|_| Oid::arbitrary(&mut g))
+                    .collect::<alloc::collections::BTreeSet<_>>();
+
+                assert!(oids.len() > 1);
+                assert!(!oids.contains(&Oid::sha1_zero()));
+            }
+
+            #[test]
+            fn shrink_zero() {
+                assert_eq!(Oid::sha1_zero().shrink().count(), 0);
+            }
+
+            #[test]
+            fn shrink_fixture() {
+                let sha1 = |prefix: &[u8]| {
+                    let mut digest = [0u8; SHA1_DIGEST_LEN];
+                    digest[..prefix.len()].copy_from_slice(prefix);
+                    Oid::from_sha1(digest)
+                };
+
+                assert_eq!(
+                    sha1(&[0xab, 0xcd, 0x03])
+                        .shrink()
+                        .collect::<alloc::vec::Vec<_>>(),
+                    [
+                        Oid::sha1_zero(),
+                        sha1(&[0xab]),
+                        sha1(&[0xab, 0xcd]),
+                        sha1(&[0xab, 0xcd, 0x01]),
+                    ]
+                );
+            }
+
+            #[quickcheck]
+            fn shrink_simpler(oid: Oid) {
+                let mut candidates = oid.shrink().peekable();
+
+                if oid.is_zero() {
+                    assert!(candidates.peek().is_none());
+                    return;
+                }
+                assert_eq!(candidates.peek(), Some(&Oid::sha1_zero()));
+                assert!(candidates.all(|c| c.cmp_bytes(&oid) == Ordering::Less));
+            }
         }
     }
 }
 
 #[cfg(feature = "serde")]
-mod serde {
+pub mod serde {
     mod ser {
         use ::serde::ser;
 
@@ -522,6 +1376,141 @@ mod serde {
             }
         }
     }
+
+    pub mod bytes {
+        //! Compact (de)serialization of [`Oid`], for use with
+        //! `#[serde(with = "radicle_oid::serde::bytes")]`.
+        //!
+        //! For serializers that are not human readable (see
+        //! [`Serializer::is_human_readable`]), the raw digest is serialized as
+        //! bytes. Human readable serializers fall back to the hexadecimal
+        //! notation that is also used by the [`Serialize`] implementation of
+        //! [`Oid`].
+        //!
+        //! [`Serializer::is_human_readable`]: ::serde::ser::Serializer::is_human_readable
+        //! [`Serialize`]: ::serde::ser::Serialize
+
+        use core::fmt;
+
+        use ::serde::{de, ser};
+
+        use crate::*;
+
+        pub fn serialize<S>(oid: &Oid, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: ser::Serializer,
+        {
+            if serializer.is_human_readable() {
+                serializer.collect_str(oid)
+            } else {
+                serializer.serialize_bytes(oid.as_ref())
+            }
+        }
+
+        pub fn deserialize<'de, D>(deserializer: D) -> Result<Oid, D::Error>
+        where
+            D: de::Deserializer<'de>,
+        {
+            struct BytesVisitor;
+
+            impl<'de> de::Visitor<'de> for BytesVisitor {
+                type Value = Oid;
+
+                fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                    use crate::str::SHA1_DIGEST_STR_LEN;
+                    write!(f, "a Git object identifier (SHA-1 digest as {SHA1_DIGEST_LEN} bytes, or in hexadecimal notation; {SHA1_DIGEST_STR_LEN} characters)")
+                }
+
+                fn visit_str<E>(self, s: &str) -> Result<Self::Value, E>
+                where
+                    E: de::Error,
+                {
+                    s.parse().map_err(de::Error::custom)
+                }
+
+                fn visit_bytes<E>(self, bytes: &[u8]) -> Result<Self::Value, E>
+                where
+                    E: de::Error,
+                {
+                    let digest = bytes
+                        .try_into()
+                        .map_err(|_| de::Error::invalid_length(bytes.len(), &self))?;
+                    Ok(Oid::Sha1(digest))
+                }
+
+                fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+                where
+                    A: de::SeqAccess<'de>,
+                {
+                    let mut digest = [0u8; SHA1_DIGEST_LEN];
+                    for (i, byte) in digest.iter_mut().enumerate() {
+                        *byte = seq
+                            .next_element()?
+                            .ok_or_else(|| de::Error::invalid_length(i, &self))?;
+                    }
+                    if seq.next_element::<de::IgnoredAny>()?.is_some() {
+                        return Err(de::Error::invalid_length(SHA1_DIGEST_LEN + 1, &self));
+                    }
+                    Ok(Oid::Sha1(digest))
+                }
+            }
+
+            if deserializer.is_human_readable() {
+                deserializer.deserialize_str(BytesVisitor)
+            } else {
+                deserializer.deserialize_bytes(BytesVisitor)
+            }
+        }
+
+        #[cfg(test)]
+        mod test {
+            use ::serde::{Deserialize, Serialize};
+            use serde_test::{assert_de_tokens_error, assert_tokens, Configure, Token};
+
+            use crate::*;
+
+            #[derive(Debug, PartialEq, Serialize, Deserialize)]
+            struct Wrapper(#[serde(with = "crate::serde::bytes")] Oid);
+
+            const DIGEST: [u8; SHA1_DIGEST_LEN] = [
+                0x12, 0x34, 0x56, 0x78, 0x9a, 0xbc, 0xde, 0xf0, 0x12, 0x34, 0x56, 0x78, 0x9a, 0xbc,
+                0xde, 0xf0, 0x12, 0x34, 0x56, 0x78,
+            ];
+
+            #[test]
+            fn compact() {
+                assert_tokens(
+                    &Wrapper(Oid::from_sha1(DIGEST)).compact(),
+                    &[
+                        Token::NewtypeStruct { name: "Wrapper" },
+                        Token::Bytes(&DIGEST),
+                    ],
+                );
+            }
+
+            #[test]
+            fn readable() {
+                assert_tokens(
+                    &Wrapper(Oid::from_sha1(DIGEST)).readable(),
+                    &[
+                        Token::NewtypeStruct { name: "Wrapper" },
+                        Token::Str("123456789abcdef0123456789abcdef012345678"),
+                    ],
+                );
+            }
+
+            #[test]
+            fn wrong_length() {
+                assert_de_tokens_error::<serde_test::Compact<Wrapper>>(
+                    &[
+                        Token::NewtypeStruct { name: "Wrapper" },
+                        Token::Bytes(&DIGEST[..19]),
+                    ],
+                    "invalid length 19, expected a Git object identifier (SHA-1 digest as 20 bytes, or in hexadecimal notation; 40 characters)",
+                );
+            }
+        }
+    }
 }
 
 #[cfg(feature = "radicle-git-ref-format")]
@@ -539,8 +1528,31 @@ mod radicle_git_ref_format {
 
     impl From<&Oid> for RefString {
         fn from(id: &Oid) -> Self {
-            RefString::try_from(alloc::format!("{id}"))
-                .expect("Git object identifiers are valid reference strings")
+            match id {
+                Oid::Sha1(digest) => {
+                    // Encode on the stack, to avoid formatting into an
+                    // intermediate `String`.
+                    let buf = fmt::hex(digest, fmt::HEX_DIGITS);
+                    let s = core::str::from_utf8(&buf).expect("hexadecimal digits are valid UTF-8");
+
+                    RefString::try_from(s)
+                        .expect("Git object identifiers are valid reference strings")
+                }
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod test {
+        use super::*;
+        use qcheck_macros::quickcheck;
+
+        #[quickcheck]
+        fn agrees_with_display(oid: Oid) {
+            let expected = RefString::try_from(alloc::format!("{oid}")).unwrap();
+
+            assert_eq!(RefString::from(&oid), expected);
+            assert_eq!(Component::from(&oid).as_str(), expected.as_str());
         }
     }
 }
@@ -553,6 +1565,16 @@ mod schemars {
 
     use super::Oid;
 
+    impl Oid {
+        /// Value of the `format` keyword in the schema of [`Oid`], so that
+        /// clients generated from it can tell object identifiers apart from
+        /// other strings.
+        const SCHEMA_FORMAT: &'static str = "git-oid";
+
+        /// Length of the hexadecimal notation constrained by the schema of [`Oid`].
+        const SCHEMA_STR_LEN: usize = crate::str::SHA1_DIGEST_STR_LEN;
+    }
+
     impl JsonSchema for Oid {
         fn schema_name() -> Cow<'static, str> {
             "Oid".into()
@@ -567,10 +1589,32 @@ mod schemars {
             json_schema!({
                 "description": format!("A Git object identifier (SHA-1 digest in hexadecimal notation; {SHA1_DIGEST_STR_LEN} characters; {SHA1_DIGEST_LEN} bytes)"),
                 "type": "string",
-                "maxLength": SHA1_DIGEST_STR_LEN,
-                "minLength": SHA1_DIGEST_STR_LEN,
-                "pattern":  format!("^[0-9a-fA-F]{{{SHA1_DIGEST_STR_LEN}}}$"),
+                "format": Self::SCHEMA_FORMAT,
+                "maxLength": Self::SCHEMA_STR_LEN,
+                "minLength": Self::SCHEMA_STR_LEN,
+                "pattern":  format!("^[0-9a-fA-F]{{{}}}$", Self::SCHEMA_STR_LEN),
             })
         }
     }
+
+    #[cfg(test)]
+    mod test {
+        use super::Oid;
+
+        #[test]
+        fn schema() {
+            let schema = ::schemars::schema_for!(Oid);
+
+            assert_eq!(schema.get("type").unwrap(), "string");
+            assert_eq!(schema.get("format").unwrap(), "git-oid");
+            assert_eq!(schema.get("minLength").unwrap(), 40);
+            assert_eq!(schema.get("maxLength").unwrap(), 40);
+            assert_eq!(schema.get("pattern").unwrap(), "^[0-9a-fA-F]{40}$");
+            assert!(schema
+                .get("description")
+                .and_then(|d| d.as_str())
+                .unwrap()
+                .starts_with("A Git object identifier"));
+        }
+    }
 }
@@ -1,5 +1,5 @@
 use std::{
-    collections::BTreeSet,
+    collections::{BTreeSet, VecDeque},
     ops::{self, RangeBounds},
 };
 
@@ -285,6 +285,143 @@ unsafe impl<const N: usize> bytes::BufMut for BoundedVec<u8, N> {
     }
 }
 
+/// What to do when an item is pushed onto a full [`BoundedQueue`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Overflow {
+    /// Reject the new item, returning an error.
+    #[default]
+    Reject,
+    /// Drop the oldest item in the queue, to make room for the new item.
+    DropOldest,
+    /// Drop the new item.
+    DropNewest,
+}
+
+/// A first-in, first-out queue with an upper limit on its size using type level
+/// constants, and a policy for when that limit is reached.
+#[derive(Clone, PartialEq, Eq)]
+pub struct BoundedQueue<T, const N: usize> {
+    q: VecDeque<T>,
+    overflow: Overflow,
+}
+
+impl<T, const N: usize> Default for BoundedQueue<T, N> {
+    fn default() -> Self {
+        Self::new(Overflow::default())
+    }
+}
+
+impl<T, const N: usize> BoundedQueue<T, N> {
+    /// Create a new empty `BoundedQueue<T,N>`, which handles pushing onto a
+    /// full queue according to `overflow`.
+    pub fn new(overflow: Overflow) -> Self {
+        Self {
+            q: VecDeque::new(),
+            overflow,
+        }
+    }
+
+    /// Return the maximum number of elements the queue can contain.
+    #[inline]
+    pub fn max() -> usize {
+        N
+    }
+
+    /// Return the policy for pushing onto a full queue.
+    pub fn overflow(&self) -> Overflow {
+        self.overflow
+    }
+
+    /// Push an item onto the back of the queue.
+    ///
+    /// If the queue is full, the [`Overflow`] policy of the queue decides what
+    /// happens: either an error is returned, or the item that was dropped is
+    /// returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use radicle_protocol::bounded::{BoundedQueue, Overflow};
+    ///
+    /// let mut queue = BoundedQueue::<_, 2>::new(Overflow::DropOldest);
+    /// assert_eq!(queue.push(1).unwrap(), None);
+    /// assert_eq!(queue.push(2).unwrap(), None);
+    ///
+    /// // The queue is full, so the oldest item is dropped.
+    /// assert_eq!(queue.push(3).unwrap(), Some(1));
+    /// assert_eq!(queue.pop(), Some(2));
+    /// ```
+    pub fn push(&mut self, item: T) -> Result<Option<T>, Error> {
+        if self.q.len() < N {
+            self.q.push_back(item);
+            return Ok(None);
+        }
+        match self.overflow {
+            Overflow::Reject => Err(Error::InvalidSize {
+                expected: N,
+                actual: N + 1,
+            }),
+            Overflow::DropOldest => {
+                let oldest = self.q.pop_front();
+                self.q.push_back(item);
+                Ok(oldest)
+            }
+            Overflow::DropNewest => Ok(Some(item)),
+        }
+    }
+
+    /// Remove the item at the front of the queue.
+    pub fn pop(&mut self) -> Option<T> {
+        self.q.pop_front()
+    }
+
+    /// Put an item back at the front of the queue, e.g. after it was popped and
+    /// could not be processed.
+    ///
+    /// Returns the item as an error if the queue is full.
+    pub fn push_front(&mut self, item: T) -> Result<(), T> {
+        if self.q.len() >= N {
+            return Err(item);
+        }
+        self.q.push_front(item);
+        Ok(())
+    }
+
+    /// Return the item at the front of the queue.
+    pub fn front(&self) -> Option<&T> {
+        self.q.front()
+    }
+
+    /// Return the number of items in the queue.
+    pub fn len(&self) -> usize {
+        self.q.len()
+    }
+
+    /// Return whether the queue is empty.
+    pub fn is_empty(&self) -> bool {
+        self.q.is_empty()
+    }
+
+    /// Return whether the queue is full.
+    pub fn is_full(&self) -> bool {
+        self.q.len() >= N
+    }
+
+    /// Iterate over the items in the queue, from front to back.
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = &T> + '_ {
+        self.q.iter()
+    }
+}
+
+impl<T: std::fmt::Debug, const N: usize> std::fmt::Debug for BoundedQueue<T, N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BoundedQueue")
+            .field("q", &self.q)
+            .field("overflow", &self.overflow)
+            .finish()
+    }
+}
+
 #[cfg(any(test, feature = "test"))]
 impl<T, const N: usize> qcheck::Arbitrary for BoundedVec<T, N>
 where
@@ -296,3 +433,57 @@ where
         v.try_into().expect("size within bounds")
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn full(overflow: Overflow) -> BoundedQueue<u8, 3> {
+        let mut queue = BoundedQueue::new(overflow);
+        for i in 0..3 {
+            assert_eq!(queue.push(i).unwrap(), None);
+        }
+        assert!(queue.is_full());
+        queue
+    }
+
+    #[test]
+    fn queue_reject() {
+        let mut queue = full(Overflow::Reject);
+
+        assert!(queue.push(3).is_err());
+        assert_eq!(queue.iter().copied().collect::<Vec<_>>(), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn queue_drop_oldest() {
+        let mut queue = full(Overflow::DropOldest);
+
+        assert_eq!(queue.push(3).unwrap(), Some(0));
+        assert_eq!(queue.push(4).unwrap(), Some(1));
+        assert_eq!(queue.iter().copied().collect::<Vec<_>>(), vec![2, 3, 4]);
+    }
+
+    #[test]
+    fn queue_drop_newest() {
+        let mut queue = full(Overflow::DropNewest);
+
+        assert_eq!(queue.push(3).unwrap(), Some(3));
+        assert_eq!(queue.iter().copied().collect::<Vec<_>>(), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn queue_fifo() {
+        let mut queue = full(Overflow::Reject);
+
+        assert_eq!(queue.push_front(9), Err(9));
+        assert_eq!(queue.pop(), Some(0));
+        assert_eq!(queue.push_front(9), Ok(()));
+        assert_eq!(queue.front(), Some(&9));
+        assert_eq!(queue.pop(), Some(9));
+        assert_eq!(queue.pop(), Some(1));
+        assert_eq!(queue.pop(), Some(2));
+        assert_eq!(queue.pop(), None);
+        assert!(queue.is_empty());
+    }
+}
@@ -9,12 +9,34 @@ use crate::wire;
 /// Message stream deserializer.
 ///
 /// Used to for example turn a byte stream into network messages.
+///
+/// Optionally, the number of messages decoded can be limited with a budget, see
+/// [`Deserializer::with_budget`]. This allows callers to stop decoding after a
+/// number of messages and resume later, so that a stream of many small messages
+/// cannot monopolize the caller.
+///
+/// The unparsed input is bounded by `B`, or by a smaller limit chosen at runtime,
+/// see [`Deserializer::with_limit`].
+///
+/// With the `crc` feature, items can be required to be followed by a checksum,
+/// see [`Deserializer::with_crc`].
 #[derive(Debug)]
 pub struct Deserializer<const B: usize, D = Message> {
     unparsed: BoundedVec<u8, B>,
+    limit: Option<usize>,
+    budget: Option<Budget>,
+    #[cfg(any(test, feature = "crc"))]
+    crc: bool,
     item: PhantomData<D>,
 }
 
+/// Number of messages that can be decoded before the budget is refilled.
+#[derive(Debug, Clone, Copy)]
+struct Budget {
+    limit: usize,
+    remaining: usize,
+}
+
 impl<const B: usize, D: wire::Decode> Default for Deserializer<B, D> {
     fn default() -> Self {
         Self::new(wire::Size::MAX as usize + 1)
@@ -27,6 +49,10 @@ impl<const B: usize, D> TryFrom<Vec<u8>> for Deserializer<B, D> {
     fn try_from(unparsed: Vec<u8>) -> Result<Self, Self::Error> {
         BoundedVec::try_from(unparsed).map(|unparsed| Self {
             unparsed,
+            limit: None,
+            budget: None,
+            #[cfg(any(test, feature = "crc"))]
+            crc: false,
             item: PhantomData,
         })
     }
@@ -38,23 +64,90 @@ impl<const B: usize, D: wire::Decode> Deserializer<B, D> {
         Self {
             unparsed: BoundedVec::with_capacity(capacity)
                 .expect("Deserializer::new: capacity exceeds maximum"),
+            limit: None,
+            budget: None,
+            #[cfg(any(test, feature = "crc"))]
+            crc: false,
             item: PhantomData,
         }
     }
 
+    /// Limit the size of the unparsed input to `limit` bytes. Limits greater
+    /// than `B` have no effect.
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit.min(B));
+        self
+    }
+
+    /// Return the maximum size of the unparsed input, in bytes.
+    pub fn limit(&self) -> usize {
+        self.limit.unwrap_or(B)
+    }
+
+    /// Limit the number of messages decoded to `limit`, until the budget is
+    /// refilled with [`Deserializer::refill`].
+    pub fn with_budget(mut self, limit: usize) -> Self {
+        self.budget = Some(Budget {
+            limit,
+            remaining: limit,
+        });
+        self
+    }
+
+    /// Require every item to be preceded by its length and followed by the
+    /// CRC32 checksum of its encoding, as written by [`wire::encode_with_crc`].
+    /// Items are checked before they are decoded, and those whose checksum
+    /// doesn't match are rejected with [`wire::Invalid::Integrity`].
+    ///
+    /// This isn't compatible with the node's transport, which relies on Noise
+    /// for integrity, and is meant for plaintext transports and tooling.
+    #[cfg(any(test, feature = "crc"))]
+    pub fn with_crc(mut self) -> Self {
+        self.crc = true;
+        self
+    }
+
+    /// Refill the decoding budget, if any.
+    pub fn refill(&mut self) {
+        if let Some(budget) = &mut self.budget {
+            budget.remaining = budget.limit;
+        }
+    }
+
+    /// Return whether the decoding budget is exhausted. If so, no more messages
+    /// are decoded until the budget is refilled.
+    pub fn is_exhausted(&self) -> bool {
+        self.budget.is_some_and(|b| b.remaining == 0)
+    }
+
     /// Input bytes into the decoder.
     pub fn input(&mut self, bytes: &[u8]) -> Result<(), bounded::Error> {
+        let size = self.unparsed.len() + bytes.len();
+        if size > self.limit() {
+            return Err(bounded::Error::InvalidSize {
+                expected: self.limit(),
+                actual: size,
+            });
+        }
         self.unparsed.extend_from_slice(bytes)
     }
 
-    /// Decode and return the next message. Returns [`None`] if nothing was decoded.
+    /// Decode and return the next message. Returns [`None`] if nothing was decoded,
+    /// which is also the case if the decoding budget is exhausted.
     pub fn deserialize_next(&mut self) -> Result<Option<D>, wire::Invalid> {
+        if self.is_exhausted() {
+            return Ok(None);
+        }
+        #[cfg(any(test, feature = "crc"))]
+        if self.crc {
+            return self.deserialize_next_checked();
+        }
         let mut reader = io::Cursor::new(self.unparsed.as_slice());
 
         match D::decode(&mut reader) {
             Ok(msg) => {
                 let pos = reader.position() as usize;
-                self.unparsed.drain(..pos);
+                self.consumed(pos);
 
                 Ok(Some(msg))
             }
@@ -63,6 +156,61 @@ impl<const B: usize, D: wire::Decode> Deserializer<B, D> {
         }
     }
 
+    /// Check the next item against its checksum, and decode it if it matches.
+    /// See [`Deserializer::with_crc`].
+    #[cfg(any(test, feature = "crc"))]
+    fn deserialize_next_checked(&mut self) -> Result<Option<D>, wire::Invalid> {
+        let Some(len) = self.unparsed.get(..wire::CRC_LENGTH_SIZE) else {
+            return Ok(None);
+        };
+        let len = u32::from_be_bytes(
+            len.try_into()
+                .expect("Deserializer::deserialize_next_checked: length is 4 bytes"),
+        ) as usize;
+        let start = wire::CRC_LENGTH_SIZE;
+        let end = start + len;
+
+        let Some(checksum) = self.unparsed.get(end..end + wire::CRC_SIZE) else {
+            // Wait for the rest of the item and its checksum to arrive.
+            return Ok(None);
+        };
+        let expected = u32::from_be_bytes(
+            checksum
+                .try_into()
+                .expect("Deserializer::deserialize_next_checked: checksum is 4 bytes"),
+        );
+        let item = &self.unparsed[start..end];
+        let actual = crc32fast::hash(item);
+
+        if actual != expected {
+            return Err(wire::Invalid::Integrity { expected, actual });
+        }
+        let mut reader = io::Cursor::new(item);
+        let msg = match D::decode(&mut reader) {
+            Ok(msg) if reader.position() as usize == len => msg,
+            Ok(_) | Err(wire::Error::UnexpectedEnd { .. }) => {
+                return Err(wire::Invalid::ItemSize {
+                    expected: len,
+                    actual: reader.position() as usize,
+                });
+            }
+            Err(wire::Error::Invalid(err)) => return Err(err),
+        };
+        self.consumed(end + wire::CRC_SIZE);
+
+        Ok(Some(msg))
+    }
+
+    /// Drop the first `len` bytes of unparsed input, once an item was decoded
+    /// from them.
+    fn consumed(&mut self, len: usize) {
+        self.unparsed.drain(..len);
+
+        if let Some(budget) = &mut self.budget {
+            budget.remaining -= 1;
+        }
+    }
+
     /// Drain the unparsed buffer.
     pub fn unparsed(&mut self) -> impl ExactSizeIterator<Item = u8> + '_ {
         self.unparsed.drain(..)
@@ -81,7 +229,7 @@ impl<const B: usize, D: wire::Decode> Deserializer<B, D> {
 
 unsafe impl<const B: usize, D: wire::Decode> bytes::BufMut for Deserializer<B, D> {
     fn remaining_mut(&self) -> usize {
-        self.unparsed.remaining_mut()
+        self.limit().saturating_sub(self.unparsed.len())
     }
 
     unsafe fn advance_mut(&mut self, cnt: usize) {
@@ -150,6 +298,100 @@ mod test {
         assert!(decoder.is_empty());
     }
 
+    #[test]
+    fn test_budget() {
+        let mut decoder = Deserializer::<1024, String>::new(8).with_budget(3);
+
+        for _ in 0..10 {
+            decoder.input(MSG_BYE).unwrap();
+        }
+        assert_eq!(decoder.by_ref().count(), 3);
+        assert!(decoder.is_exhausted());
+        assert_matches!(decoder.deserialize_next(), Ok(None));
+        assert_eq!(decoder.len(), MSG_BYE.len() * 7);
+
+        decoder.refill();
+        assert!(!decoder.is_exhausted());
+        assert_eq!(decoder.by_ref().count(), 3);
+
+        decoder.refill();
+        assert_eq!(decoder.by_ref().count(), 3);
+
+        decoder.refill();
+        assert_eq!(decoder.by_ref().count(), 1);
+        assert!(!decoder.is_exhausted());
+        assert!(decoder.is_empty());
+    }
+
+    #[test]
+    fn test_limit() {
+        let mut decoder = Deserializer::<1024, String>::new(8).with_limit(MSG_BYE.len() * 2);
+
+        assert_eq!(decoder.limit(), MSG_BYE.len() * 2);
+        decoder.input(MSG_BYE).unwrap();
+        decoder.input(MSG_BYE).unwrap();
+        assert_matches!(
+            decoder.input(MSG_BYE),
+            Err(bounded::Error::InvalidSize { .. })
+        );
+        assert_eq!(decoder.by_ref().count(), 2);
+        decoder.input(MSG_BYE).unwrap();
+
+        // Limits can't exceed the type-level bound.
+        let decoder = Deserializer::<1024, String>::new(8).with_limit(4096);
+        assert_eq!(decoder.limit(), 1024);
+    }
+
+    #[test]
+    fn test_no_budget() {
+        let mut decoder = Deserializer::<1024, String>::new(8);
+
+        for _ in 0..100 {
+            decoder.input(MSG_BYE).unwrap();
+        }
+        assert_eq!(decoder.by_ref().count(), 100);
+        assert!(!decoder.is_exhausted());
+    }
+
+    #[test]
+    fn test_crc() {
+        use crate::wire::frame::{Frame, StreamId};
+        use radicle::node::Link;
+
+        let frame = Frame::<Message>::git(StreamId::git(Link::Outbound), b"hello".to_vec());
+        let mut bytes = Vec::new();
+        wire::encode_with_crc(&frame, &mut bytes);
+
+        let mut decoder = Deserializer::<1024, Frame<Message>>::new(8).with_crc();
+        decoder.input(&bytes[..bytes.len() - 1]).unwrap();
+        assert_matches!(decoder.deserialize_next(), Ok(None));
+        decoder.input(&bytes[bytes.len() - 1..]).unwrap();
+        assert_matches!(decoder.deserialize_next(), Ok(Some(f)) if f == frame);
+        assert!(decoder.is_empty());
+
+        // Flip a bit of the payload. The frame would still decode, but doesn't
+        // match its checksum.
+        let mut corrupted = bytes.clone();
+        corrupted[bytes.len() - wire::CRC_SIZE - 1] ^= 1;
+        let mut decoder = Deserializer::<1024, Frame<Message>>::new(8).with_crc();
+        decoder.input(&corrupted).unwrap();
+        assert_matches!(
+            decoder.deserialize_next(),
+            Err(wire::Invalid::Integrity { .. })
+        );
+
+        // Flip a bit of the protocol version. The checksum is verified before
+        // the frame is decoded.
+        let mut corrupted = bytes.clone();
+        corrupted[wire::CRC_LENGTH_SIZE] ^= 1;
+        let mut decoder = Deserializer::<1024, Frame<Message>>::new(8).with_crc();
+        decoder.input(&corrupted).unwrap();
+        assert_matches!(
+            decoder.deserialize_next(),
+            Err(wire::Invalid::Integrity { .. })
+        );
+    }
+
     #[quickcheck]
     fn prop_decode_next(chunk_size: usize) {
         let mut bytes = vec![];
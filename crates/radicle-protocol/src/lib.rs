@@ -6,3 +6,6 @@ pub mod worker;
 
 /// Peer-to-peer protocol version.
 pub const PROTOCOL_VERSION: u8 = 1;
+
+/// Oldest peer-to-peer protocol version still supported.
+pub const MIN_PROTOCOL_VERSION: u8 = 1;
@@ -10,7 +10,7 @@ pub mod message;
 pub mod session;
 
 use std::collections::hash_map::Entry;
-use std::collections::{BTreeSet, HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::net::IpAddr;
 use std::ops::{Deref, DerefMut};
 use std::sync::Arc;
@@ -27,7 +27,7 @@ use radicle::node;
 use radicle::node::address;
 use radicle::node::address::Store as _;
 use radicle::node::address::{AddressBook, AddressType, KnownAddress};
-use radicle::node::config::{PeerConfig, RateLimit};
+use radicle::node::config::{PeerConfig, RateLimit, Reconnect};
 use radicle::node::device::Device;
 use radicle::node::refs::Store as _;
 use radicle::node::routing::Store as _;
@@ -60,7 +60,7 @@ use radicle::node::Link;
 use radicle::node::PROTOCOL_VERSION;
 
 use crate::bounded::BoundedVec;
-use crate::service::filter::Filter;
+use crate::service::filter::{Composite, Filter};
 pub use crate::service::message::{Message, ZeroBytes};
 pub use crate::service::session::{QueuedFetch, Session};
 use crate::worker::FetchError;
@@ -100,10 +100,10 @@ pub const INITIAL_SUBSCRIBE_BACKLOG_DELTA: LocalDuration = LocalDuration::from_m
 /// When subscribing, what margin of error do we give ourselves. A igher delta means we ask for
 /// messages further back than strictly necessary, to account for missed messages.
 pub const SUBSCRIBE_BACKLOG_DELTA: LocalDuration = LocalDuration::from_mins(3);
-/// Minimum amount of time to wait before reconnecting to a peer.
-pub const MIN_RECONNECTION_DELTA: LocalDuration = LocalDuration::from_secs(3);
-/// Maximum amount of time to wait before reconnecting to a peer.
-pub const MAX_RECONNECTION_DELTA: LocalDuration = LocalDuration::from_mins(60);
+/// Minimum amount of time to wait before reconnecting to a peer, by default.
+pub const MIN_RECONNECTION_DELTA: LocalDuration = Reconnect::MIN_DELAY;
+/// Maximum amount of time to wait before reconnecting to a peer, by default.
+pub const MAX_RECONNECTION_DELTA: LocalDuration = Reconnect::MAX_DELAY;
 /// Connection retry delta used for ephemeral peers that failed to connect previously.
 pub const CONNECTION_RETRY_DELTA: LocalDuration = LocalDuration::from_mins(10);
 /// How long to wait for a fetch to stall before aborting, default is 3s.
@@ -135,26 +135,85 @@ impl Metrics {
     pub fn peer(&mut self, nid: NodeId) -> &mut PeerMetrics {
         self.peers.entry(nid).or_default()
     }
+
+    /// Take a snapshot of the metrics, including the totals across all peers.
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        let peers = self
+            .peers
+            .iter()
+            .map(|(nid, metrics)| (*nid, metrics.clone()))
+            .collect::<BTreeMap<_, _>>();
+        let total = peers
+            .values()
+            .fold(PeerMetrics::default(), |mut total, metrics| {
+                total += metrics;
+                total
+            });
+
+        MetricsSnapshot {
+            peers,
+            total,
+            worker_queue_size: self.worker_queue_size,
+            open_channels: self.open_channels,
+        }
+    }
+}
+
+/// A point-in-time copy of the [`Metrics`], for reporting.
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetricsSnapshot {
+    /// Metrics for each peer, ordered by node ID.
+    pub peers: BTreeMap<NodeId, PeerMetrics>,
+    /// Sum of the metrics of all peers.
+    pub total: PeerMetrics,
+    /// Tasks queued in worker queue.
+    pub worker_queue_size: usize,
+    /// Current open channel count.
+    pub open_channels: usize,
 }
 
 /// Per-peer metrics we track.
-#[derive(Clone, Debug, Default, serde::Serialize)]
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PeerMetrics {
     pub received_git_bytes: usize,
     pub received_fetch_requests: usize,
+    pub declined_fetch_requests: usize,
     pub received_bytes: usize,
     pub received_gossip_messages: usize,
     pub sent_bytes: usize,
     pub sent_fetch_requests: usize,
     pub sent_git_bytes: usize,
     pub sent_gossip_messages: usize,
+    pub dropped_gossip_messages: usize,
+    pub split_gossip_messages: usize,
     pub streams_opened: usize,
     pub inbound_connection_attempts: usize,
     pub outbound_connection_attempts: usize,
     pub disconnects: usize,
 }
 
+impl std::ops::AddAssign<&PeerMetrics> for PeerMetrics {
+    fn add_assign(&mut self, other: &PeerMetrics) {
+        self.received_git_bytes += other.received_git_bytes;
+        self.received_fetch_requests += other.received_fetch_requests;
+        self.declined_fetch_requests += other.declined_fetch_requests;
+        self.received_bytes += other.received_bytes;
+        self.received_gossip_messages += other.received_gossip_messages;
+        self.sent_bytes += other.sent_bytes;
+        self.sent_fetch_requests += other.sent_fetch_requests;
+        self.sent_git_bytes += other.sent_git_bytes;
+        self.sent_gossip_messages += other.sent_gossip_messages;
+        self.dropped_gossip_messages += other.dropped_gossip_messages;
+        self.split_gossip_messages += other.split_gossip_messages;
+        self.streams_opened += other.streams_opened;
+        self.inbound_connection_attempts += other.inbound_connection_attempts;
+        self.outbound_connection_attempts += other.outbound_connection_attempts;
+        self.disconnects += other.disconnects;
+    }
+}
+
 /// Result of syncing our routing table with a node's inventory.
 #[derive(Default)]
 struct SyncedRouting {
@@ -259,10 +318,16 @@ pub enum Command {
     Seed(RepoId, Scope, chan::Sender<bool>),
     /// Unseed the given repository.
     Unseed(RepoId, chan::Sender<bool>),
+    /// Block the given repository.
+    Block(RepoId, chan::Sender<bool>),
+    /// Unblock the given repository.
+    Unblock(RepoId, chan::Sender<bool>),
     /// Follow the given node.
     Follow(NodeId, Option<Alias>, chan::Sender<bool>),
     /// Unfollow the given node.
     Unfollow(NodeId, chan::Sender<bool>),
+    /// Set the maximum log level.
+    SetLogLevel(log::Level),
     /// Query the internal service state.
     QueryState(Arc<QueryState>, chan::Sender<Result<(), CommandError>>),
 }
@@ -281,8 +346,11 @@ impl fmt::Debug for Command {
             Self::Fetch(id, node, _, _) => write!(f, "Fetch({id}, {node})"),
             Self::Seed(id, scope, _) => write!(f, "Seed({id}, {scope})"),
             Self::Unseed(id, _) => write!(f, "Unseed({id})"),
+            Self::Block(id, _) => write!(f, "Block({id})"),
+            Self::Unblock(id, _) => write!(f, "Unblock({id})"),
             Self::Follow(id, _, _) => write!(f, "Follow({id})"),
             Self::Unfollow(id, _) => write!(f, "Unfollow({id})"),
+            Self::SetLogLevel(level) => write!(f, "SetLogLevel({level})"),
             Self::QueryState { .. } => write!(f, "QueryState(..)"),
         }
     }
@@ -443,6 +511,8 @@ pub struct Service<D, S, G> {
     limiter: RateLimiter,
     /// Current seeded repositories bloom filter.
     filter: Filter,
+    /// Repositories blocked by our seeding policy.
+    blocked: HashSet<RepoId>,
     /// Last time the service was idle.
     last_idle: LocalTime,
     /// Last time the gossip messages were relayed.
@@ -522,6 +592,7 @@ where
             sessions,
             fetching: HashMap::new(),
             filter: Filter::empty(),
+            blocked: HashSet::new(),
             relayed_by: HashMap::default(),
             last_idle: LocalTime::default(),
             last_gossip: LocalTime::default(),
@@ -554,6 +625,7 @@ where
     pub fn seed(&mut self, id: &RepoId, scope: Scope) -> Result<bool, policy::Error> {
         let updated = self.policies.seed(id, scope)?;
         self.filter.insert(id);
+        self.blocked.remove(id);
 
         Ok(updated)
     }
@@ -578,6 +650,115 @@ where
         Ok(updated)
     }
 
+    /// Block a repository.
+    /// Returns whether or not the repo policy was updated.
+    /// The policy is persisted, and fetches of the repository, to or from us, are
+    /// refused until it is unblocked.
+    pub fn block(&mut self, id: &RepoId) -> Result<bool, policy::Error> {
+        let updated = self.policies.set_seed_policy(id, policy::Policy::Block)?;
+
+        if updated {
+            self.filter = Filter::allowed_by(self.policies.seed_policies()?);
+            self.blocked.insert(*id);
+
+            if let Err(e) = self.remove_inventory(id) {
+                error!(target: "service", "Error updating inventory after block: {e}");
+            }
+        }
+        Ok(updated)
+    }
+
+    /// Unblock a repository.
+    /// Returns whether or not the repo policy was updated.
+    /// The repository is then subject to the default seeding policy.
+    pub fn unblock(&mut self, id: &RepoId) -> Result<bool, policy::Error> {
+        let updated = self.policies.unblock_rid(id)?;
+
+        if updated {
+            self.filter = Filter::allowed_by(self.policies.seed_policies()?);
+            self.blocked.remove(id);
+        }
+        Ok(updated)
+    }
+
+    /// Reload the node configuration.
+    ///
+    /// Changes to the log level, the default seeding policy, the persistent
+    /// peers and the limits are applied immediately, though the inbox size
+    /// limit only applies to new sessions. Other changes only take effect once the node restarts;
+    /// the names of the changed fields are returned.
+    pub fn reload(&mut self, config: Config) -> Vec<&'static str> {
+        // Not all configuration types implement `PartialEq`, so compare them
+        // in their serialized form.
+        fn changed<T: serde::Serialize>(a: &T, b: &T) -> bool {
+            serde_json::to_value(a).ok() != serde_json::to_value(b).ok()
+        }
+        let current = &self.config;
+        let restart = [
+            ("alias", changed(&current.alias, &config.alias)),
+            ("listen", current.listen != config.listen),
+            ("peers", changed(&current.peers, &config.peers)),
+            (
+                "externalAddresses",
+                changed(&current.external_addresses, &config.external_addresses),
+            ),
+            ("proxy", current.proxy != config.proxy),
+            ("onion", changed(&current.onion, &config.onion)),
+            ("network", current.network != config.network),
+            ("relay", changed(&current.relay, &config.relay)),
+            ("workers", changed(&current.workers, &config.workers)),
+            ("secret", current.secret != config.secret),
+            (
+                "passphraseCommand",
+                current.passphrase_command != config.passphrase_command,
+            ),
+            ("keepalive", current.keepalive != config.keepalive),
+            ("reconnect", current.reconnect != config.reconnect),
+        ]
+        .into_iter()
+        .filter_map(|(field, changed)| changed.then_some(field))
+        .collect();
+
+        let level = log::Level::from(config.log);
+        if level != log::Level::from(current.log) {
+            log::set_max_level(level.to_level_filter());
+            info!(target: "service", "Log level set to {level}");
+        }
+        if config.seeding_policy != current.seeding_policy {
+            let policy = config.seeding_policy.into();
+
+            self.policies.set_default_policy(policy);
+            info!(target: "service", "Default seeding policy set to '{policy}'");
+        }
+        if changed(&current.limits, &config.limits) {
+            info!(target: "service", "Limits updated; the inbox size applies to new sessions");
+        }
+        let added = config
+            .connect
+            .difference(&current.connect)
+            .cloned()
+            .collect::<Vec<_>>();
+        let removed = current.connect.difference(&config.connect).count();
+        if removed > 0 {
+            info!(target: "service", "No longer maintaining connections to {removed} peer(s)");
+        }
+        self.config = Config {
+            log: config.log,
+            seeding_policy: config.seeding_policy,
+            connect: config.connect,
+            limits: config.limits,
+            ..self.config.clone()
+        };
+
+        // Connect to new persistent peers, now that they are configured as such.
+        for (nid, addr) in added.into_iter().map(|ca| ca.into()) {
+            if let Err(e) = self.connect(nid, addr) {
+                error!(target: "service", "Error connecting to persistent peer after reload: {e}");
+            }
+        }
+        restart
+    }
+
     /// Find the closest `n` peers by proximity in seeding graphs.
     /// Returns a sorted list from the closest peer to the furthest.
     /// Peers with more seedings in common score score higher.
@@ -744,6 +925,14 @@ where
 
         // Setup subscription filter for seeded repos.
         self.filter = Filter::allowed_by(self.policies.seed_policies()?);
+        // Keep track of blocked repos, which we don't gossip about.
+        self.blocked = self
+            .policies
+            .seed_policies()?
+            .filter_map(|seed| seed.ok())
+            .filter(|seed| seed.policy.is_block())
+            .map(|seed| seed.rid)
+            .collect();
         // Connect to configured peers.
         let addrs = self.config.connect.clone();
         for (id, addr) in addrs.into_iter().map(|ca| ca.into()) {
@@ -915,6 +1104,18 @@ where
                     .expect("Service::command: error unseeding repository");
                 resp.send(updated).ok();
             }
+            Command::Block(id, resp) => {
+                let updated = self
+                    .block(&id)
+                    .expect("Service::command: error blocking repository");
+                resp.send(updated).ok();
+            }
+            Command::Unblock(id, resp) => {
+                let updated = self
+                    .unblock(&id)
+                    .expect("Service::command: error unblocking repository");
+                resp.send(updated).ok();
+            }
             Command::Follow(id, alias, resp) => {
                 let seeded = self
                     .policies
@@ -929,6 +1130,10 @@ where
                     .expect("Service::command: error unfollowing node");
                 resp.send(updated).ok();
             }
+            Command::SetLogLevel(level) => {
+                log::set_max_level(level.to_level_filter());
+                info!(target: "service", "Log level set to {level}");
+            }
             Command::AnnounceRefs(id, namespaces, resp) => {
                 let doc = match self.storage.get(id) {
                     Ok(Some(doc)) => doc,
@@ -1436,8 +1641,14 @@ where
 
         // Attempt to re-connect to persistent peers.
         if self.config.peer(&remote).is_some() {
-            let delay = LocalDuration::from_secs(2u64.saturating_pow(session.attempts() as u32))
-                .clamp(MIN_RECONNECTION_DELTA, MAX_RECONNECTION_DELTA);
+            let reconnect = self.config.reconnect.unwrap_or_default();
+            let mut delay = session.backoff(reconnect.min_delay, reconnect.max_delay);
+
+            // Honor the peer's request to back off, if any, but never wait for longer than
+            // our own maximum delay, since the request is under the peer's control.
+            if let DisconnectReason::RateLimited { retry_after } = reason {
+                delay = delay.max((*retry_after).min(reconnect.max_delay));
+            }
 
             // Nb. We always try to reconnect to persistent peers, even when the error appears
             // to not be transient.
@@ -1465,7 +1676,8 @@ where
                 DisconnectReason::Session(e) => e.severity(),
                 DisconnectReason::Command
                 | DisconnectReason::Conflict
-                | DisconnectReason::SelfConnection => Severity::Low,
+                | DisconnectReason::SelfConnection
+                | DisconnectReason::RateLimited { .. } => Severity::Low,
             };
 
             if let Err(e) = self
@@ -1882,10 +2094,12 @@ where
             }
             Message::Subscribe(subscribe) => {
                 // Filter announcements by interest.
+                let filter = Composite::from(subscribe.filter.clone());
+
                 match self
                     .db
                     .gossip()
-                    .filtered(&subscribe.filter, subscribe.since, subscribe.until)
+                    .filtered(&filter, subscribe.since, subscribe.until)
                 {
                     Ok(anns) => {
                         for ann in anns {
@@ -1900,6 +2114,14 @@ where
                             if ann.node == *remote {
                                 continue;
                             }
+                            // Don't send announcements about repositories we block.
+                            if let AnnouncementMessage::Refs(RefsAnnouncement { rid, .. }) =
+                                &ann.message
+                            {
+                                if self.blocked.contains(rid) {
+                                    continue;
+                                }
+                            }
                             // Only send messages if we're a relay, or it's our own messages.
                             if relay || ann.node == local {
                                 self.outbox.write(peer, ann.into());
@@ -2123,7 +2345,10 @@ where
             }
         }
         for rid in self.db.routing().get_inventory(&from)?.into_iter() {
-            if !included.contains(&rid) {
+            // Entries with the timestamp of this announcement were added by another part of the
+            // same, split announcement.
+            if !included.contains(&rid) && self.db.routing().entry(&rid, &from)? != Some(timestamp)
+            {
                 if self.db.routing_mut().remove_inventory(&rid, &from)? {
                     synced.removed.push(rid);
                     self.emitter.emit(Event::SeedDropped { rid, nid: from });
@@ -2765,6 +2990,9 @@ pub enum DisconnectReason {
     SelfConnection,
     /// User requested disconnect
     Command,
+    /// The remote is sending data faster than it can be processed. It should not
+    /// reconnect before `retry_after` has elapsed.
+    RateLimited { retry_after: LocalDuration },
 }
 
 impl DisconnectReason {
@@ -2793,6 +3021,9 @@ impl fmt::Display for DisconnectReason {
             Self::Conflict => write!(f, "conflict"),
             Self::Session(err) => write!(f, "{err}"),
             Self::Fetch(err) => write!(f, "fetch: {err}"),
+            Self::RateLimited { retry_after } => {
+                write!(f, "rate-limited (retry after {retry_after})")
+            }
         }
     }
 }
@@ -2877,3 +3108,45 @@ impl DerefMut for Sessions {
         &mut self.0
     }
 }
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod test {
+    use super::*;
+    use radicle::test::arbitrary;
+
+    #[test]
+    fn metrics_snapshot() {
+        let alice = arbitrary::gen::<NodeId>(1);
+        let bob = arbitrary::gen::<NodeId>(1);
+        let mut metrics = Metrics::default();
+
+        assert_eq!(metrics.snapshot(), MetricsSnapshot::default());
+
+        metrics.peer(alice).sent_bytes += 10;
+        metrics.peer(alice).streams_opened += 1;
+        metrics.peer(bob).sent_bytes += 5;
+        metrics.peer(bob).received_git_bytes += 42;
+        metrics.worker_queue_size = 2;
+        metrics.open_channels = 1;
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.peers.len(), 2);
+        assert_eq!(snapshot.peers[&alice].sent_bytes, 10);
+        assert_eq!(snapshot.peers[&bob].received_git_bytes, 42);
+        assert_eq!(snapshot.total.sent_bytes, 15);
+        assert_eq!(snapshot.total.streams_opened, 1);
+        assert_eq!(snapshot.total.received_git_bytes, 42);
+        assert_eq!(snapshot.worker_queue_size, 2);
+        assert_eq!(snapshot.open_channels, 1);
+
+        // The snapshot doesn't change with the metrics.
+        metrics.peer(bob).disconnects += 1;
+        assert_eq!(snapshot.total.disconnects, 0);
+        assert_eq!(metrics.snapshot().total.disconnects, 1);
+
+        let json = serde_json::to_value(&snapshot).unwrap();
+        assert_eq!(json["total"]["sentBytes"], 15);
+        assert_eq!(json["workerQueueSize"], 2);
+    }
+}
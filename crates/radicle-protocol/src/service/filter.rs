@@ -1,5 +1,5 @@
 #![allow(clippy::identity_op)]
-use std::ops::{Deref, DerefMut};
+use std::ops::{BitAnd, BitOr, Deref, DerefMut, Not};
 
 pub use bloomy::BloomFilter;
 
@@ -92,6 +92,124 @@ impl Filter {
     pub fn size(&self) -> usize {
         self.0.bits() / 8
     }
+
+    /// Composite filter matching what both this filter and `other` match.
+    pub fn and(self, other: impl Into<Composite>) -> Composite {
+        self & other
+    }
+
+    /// Composite filter matching what either this filter or `other` matches.
+    pub fn or(self, other: impl Into<Composite>) -> Composite {
+        self | other
+    }
+
+    /// Composite filter matching what this filter doesn't match.
+    #[allow(clippy::should_implement_trait)]
+    pub fn not(self) -> Composite {
+        !self
+    }
+}
+
+/// Composition of inventory filters, built with [`Filter::and`], [`Filter::or`]
+/// and [`Filter::not`], or the equivalent `&`, `|` and `!` operators.
+///
+/// Composite filters are evaluated locally, and can't be sent to peers. Note that
+/// since filters have false positives, negated filters have false negatives.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Composite {
+    /// Matches what the filter matches.
+    Filter(Filter),
+    /// Matches what both filters match.
+    And(Box<Composite>, Box<Composite>),
+    /// Matches what either filter matches.
+    Or(Box<Composite>, Box<Composite>),
+    /// Matches what the filter doesn't match.
+    Not(Box<Composite>),
+}
+
+impl Composite {
+    /// Composite filter matching what both this filter and `other` match.
+    pub fn and(self, other: impl Into<Composite>) -> Self {
+        self & other
+    }
+
+    /// Composite filter matching what either this filter or `other` matches.
+    pub fn or(self, other: impl Into<Composite>) -> Self {
+        self | other
+    }
+
+    /// Composite filter matching what this filter doesn't match.
+    #[allow(clippy::should_implement_trait)]
+    pub fn not(self) -> Self {
+        !self
+    }
+
+    /// Check whether the repository is matched by the filter.
+    ///
+    /// Operands are evaluated from left to right, and only until the result is
+    /// known, so cheaper or more selective filters should come first.
+    pub fn contains(&self, rid: &RepoId) -> bool {
+        match self {
+            Self::Filter(filter) => filter.contains(rid),
+            Self::And(left, right) => left.contains(rid) && right.contains(rid),
+            Self::Or(left, right) => left.contains(rid) || right.contains(rid),
+            Self::Not(filter) => !filter.contains(rid),
+        }
+    }
+}
+
+impl From<Filter> for Composite {
+    fn from(filter: Filter) -> Self {
+        Self::Filter(filter)
+    }
+}
+
+impl<T: Into<Composite>> BitAnd<T> for Composite {
+    type Output = Composite;
+
+    fn bitand(self, other: T) -> Self::Output {
+        Self::And(Box::new(self), Box::new(other.into()))
+    }
+}
+
+impl<T: Into<Composite>> BitOr<T> for Composite {
+    type Output = Composite;
+
+    fn bitor(self, other: T) -> Self::Output {
+        Self::Or(Box::new(self), Box::new(other.into()))
+    }
+}
+
+impl Not for Composite {
+    type Output = Composite;
+
+    fn not(self) -> Self::Output {
+        Self::Not(Box::new(self))
+    }
+}
+
+impl<T: Into<Composite>> BitAnd<T> for Filter {
+    type Output = Composite;
+
+    fn bitand(self, other: T) -> Self::Output {
+        Composite::from(self) & other
+    }
+}
+
+impl<T: Into<Composite>> BitOr<T> for Filter {
+    type Output = Composite;
+
+    fn bitor(self, other: T) -> Self::Output {
+        Composite::from(self) | other
+    }
+}
+
+impl Not for Filter {
+    type Output = Composite;
+
+    fn not(self) -> Self::Output {
+        !Composite::from(self)
+    }
 }
 
 impl Deref for Filter {
@@ -192,6 +310,34 @@ mod test {
         assert_eq!(hs.iter().size_hint(), (42, Some(42)));
     }
 
+    #[test]
+    fn test_composite() {
+        let rids = arbitrary::vec::<RepoId>(4);
+        let (a, b, c, d) = (rids[0], rids[1], rids[2], rids[3]);
+
+        // Seed what matches `x`, but not `y`, as well as anything in `z`.
+        let x = Filter::new([a, b, c]);
+        let y = Filter::new([b]);
+        let z = Filter::new([d]);
+        let composite = x.clone() & !y.clone() | z.clone();
+
+        assert!(composite.contains(&a));
+        assert!(!composite.contains(&b));
+        assert!(composite.contains(&c));
+        assert!(composite.contains(&d));
+
+        let composite = x.clone() & y.clone() & !z.clone();
+        assert!(!composite.contains(&a));
+        assert!(composite.contains(&b));
+        assert!(!composite.contains(&d));
+        assert_eq!(x.and(y).and(z.not()), composite);
+
+        assert!((!Filter::empty()).contains(&a));
+        assert!(!(!Filter::default()).contains(&a));
+        assert!(!(Filter::empty() | Filter::empty()).contains(&a));
+        assert!((Filter::default() & Filter::default()).contains(&a));
+    }
+
     /// Checks that a particular filter extracted from a live deployment of
     /// `radicle-node` at `release/1.5.0`, which is known to contain
     /// "heartwood", actually also evaluates to contain "heartwood".
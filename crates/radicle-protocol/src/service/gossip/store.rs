@@ -5,7 +5,7 @@ use radicle::crypto::Signature;
 use sqlite as sql;
 use thiserror::Error;
 
-use crate::service::filter::Filter;
+use crate::service::filter::Composite;
 use crate::service::message::{
     Announcement, AnnouncementMessage, InventoryAnnouncement, NodeAnnouncement, RefsAnnouncement,
 };
@@ -61,7 +61,7 @@ pub trait Store {
     ///
     fn filtered<'a>(
         &'a self,
-        filter: &'a Filter,
+        filter: &'a Composite,
         from: Timestamp,
         to: Timestamp,
     ) -> Result<Box<dyn Iterator<Item = Result<Announcement, Error>> + 'a>, Error>;
@@ -103,12 +103,14 @@ impl Store for Database {
             Timestamp::MIN,
             "Timestamp of {ann:?} must not be zero"
         );
+        // Announcements with the same timestamp but different contents are the parts of a
+        // split announcement, and are also new.
         let mut stmt = self.db.prepare(
             "INSERT INTO `announcements` (node, repo, type, message, signature, timestamp)
              VALUES (?1, ?2, ?3, ?4, ?5, ?6)
              ON CONFLICT DO UPDATE
              SET message = ?4, signature = ?5, timestamp = ?6
-             WHERE timestamp < ?6
+             WHERE timestamp < ?6 OR (timestamp = ?6 AND message != ?4)
              RETURNING rowid",
         )?;
         stmt.bind((1, nid))?;
@@ -183,7 +185,7 @@ impl Store for Database {
 
     fn filtered<'a>(
         &'a self,
-        filter: &'a Filter,
+        filter: &'a Composite,
         from: Timestamp,
         to: Timestamp,
     ) -> Result<Box<dyn Iterator<Item = Result<Announcement, Error>> + 'a>, Error> {
@@ -412,6 +414,15 @@ mod test {
         let id2 = db.announced(&nid, &inv).unwrap().unwrap();
         assert!(db.announced(&nid, &inv).unwrap().is_none());
 
+        // Unless another part of a split announcement comes in, with the same timestamp.
+        let part = AnnouncementMessage::Inventory(InventoryAnnouncement {
+            inventory: BoundedVec::collect_from([rid]),
+            timestamp,
+        })
+        .signed(&signer);
+        assert_eq!(db.announced(&nid, &part).unwrap(), Some(id2));
+        assert!(db.announced(&nid, &part).unwrap().is_none());
+
         // Nothing was set to be relayed.
         assert_eq!(db.relays(LocalTime::now().into()).unwrap().len(), 0);
 
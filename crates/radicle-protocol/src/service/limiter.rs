@@ -53,6 +53,13 @@ impl RateLimiter {
             .or_insert_with(|| TokenBucket::new(tokens.capacity(), tokens.rate(), now))
             .take(now)
     }
+
+    /// Remove the buckets that are full at `now`. A full bucket behaves like
+    /// a new one, so this doesn't change which actions are rate-limited, while
+    /// keeping the number of buckets from growing with every address seen.
+    pub fn prune(&mut self, now: LocalTime) {
+        self.buckets.retain(|_, bucket| !bucket.is_full(now));
+    }
 }
 
 /// Any type that can be assigned a number of rate-limit tokens.
@@ -105,6 +112,13 @@ impl TokenBucket {
         self.refilled_at = now;
     }
 
+    fn is_full(&self, now: LocalTime) -> bool {
+        let elapsed = now.duration_since(self.refilled_at);
+        let tokens = elapsed.as_secs() as f64 * self.rate;
+
+        self.tokens + tokens >= self.capacity
+    }
+
     fn take(&mut self, now: LocalTime) -> bool {
         self.refill(now);
 
@@ -165,6 +179,31 @@ mod test {
         assert_eq!(r.limit(a.clone(), n, &t, LocalTime::from_secs(60)), true); // Limited
     }
 
+    #[test]
+    fn test_limitter_prune() {
+        let mut r = RateLimiter::default();
+        let t = (2, 1.0); // Two tokens burst. One token per second.
+        let a = HostName::Dns(String::from("seed.radicle.example.com"));
+        let b = HostName::Dns(String::from("seed.radicle.example.net"));
+
+        assert_eq!(r.limit(a.clone(), None, &t, LocalTime::from_secs(0)), false);
+        assert_eq!(r.limit(b.clone(), None, &t, LocalTime::from_secs(0)), false);
+        assert_eq!(r.limit(b.clone(), None, &t, LocalTime::from_secs(0)), false);
+
+        // Neither bucket has refilled yet.
+        r.prune(LocalTime::from_secs(0));
+        assert_eq!(r.buckets.len(), 2);
+
+        // Only the first bucket has refilled.
+        r.prune(LocalTime::from_secs(1));
+        assert!(!r.buckets.contains_key(&a));
+        assert!(r.buckets.contains_key(&b));
+
+        // Both buckets have refilled.
+        r.prune(LocalTime::from_secs(2));
+        assert!(r.buckets.is_empty());
+    }
+
     #[test]
     #[rustfmt::skip]
     fn test_limitter_multi() {
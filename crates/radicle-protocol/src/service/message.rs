@@ -13,7 +13,7 @@ use radicle::storage;
 use radicle::storage::refs::RefsAt;
 
 use crate::bounded::BoundedVec;
-use crate::service::filter::Filter;
+use crate::service::filter::{Composite, Filter};
 use crate::service::{Link, NodeId, Timestamp};
 use crate::wire;
 use crate::wire::Encode as _;
@@ -372,7 +372,7 @@ impl Announcement {
         self.node.verify(msg, &self.signature).is_ok()
     }
 
-    pub fn matches(&self, filter: &Filter) -> bool {
+    pub fn matches(&self, filter: &Composite) -> bool {
         match &self.message {
             AnnouncementMessage::Inventory(_) => true,
             AnnouncementMessage::Node(_) => true,
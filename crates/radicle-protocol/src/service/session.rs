@@ -11,6 +11,7 @@ use radicle::storage::refs::RefsAt;
 use crate::service::message;
 use crate::service::message::Message;
 use crate::service::{Address, LocalDuration, LocalTime, NodeId, Outbox, RepoId, Rng};
+use crate::wire::frame::VersionRange;
 
 /// Time after which a connection is considered stable.
 pub const CONNECTION_STABLE_THRESHOLD: LocalDuration = LocalDuration::from_mins(1);
@@ -33,6 +34,14 @@ pub enum Error {
     /// The remote peer timed out.
     #[error("peer timed out")]
     Timeout,
+    /// The remote peer doesn't support any of our protocol versions.
+    #[error("incompatible protocol versions: we support {ours}, peer supports {theirs}")]
+    IncompatibleVersion {
+        /// Versions supported by us.
+        ours: VersionRange,
+        /// Versions supported by the peer.
+        theirs: VersionRange,
+    },
 }
 
 impl Error {
@@ -43,6 +52,7 @@ impl Error {
             Self::ProtocolMismatch => Severity::High,
             Self::Misbehavior => Severity::High,
             Self::Timeout => Severity::Low,
+            Self::IncompatibleVersion { .. } => Severity::Low,
         }
     }
 }
@@ -116,7 +126,7 @@ pub struct Session {
 
     /// Connection attempts. For persistent peers, Tracks
     /// how many times we've attempted to connect. We reset this to zero
-    /// upon successful connection, once the connection is stable.
+    /// upon successful connection, once the handshake is completed.
     attempts: usize,
     /// Source of entropy.
     rng: Rng,
@@ -263,6 +273,11 @@ impl Session {
         self.attempts
     }
 
+    /// Delay before the next connection attempt, see [`backoff`].
+    pub fn backoff(&mut self, min: LocalDuration, max: LocalDuration) -> LocalDuration {
+        backoff(self.attempts, min, max, &mut self.rng)
+    }
+
     /// Run 'idle' task for session.
     pub fn idle(&mut self, now: LocalTime) {
         if let State::Connected {
@@ -273,8 +288,6 @@ impl Session {
         {
             if now >= since && now.duration_since(since) >= CONNECTION_STABLE_THRESHOLD {
                 *stable = true;
-                // Reset number of attempts for stable connections.
-                self.attempts = 0;
             }
         }
     }
@@ -317,6 +330,7 @@ impl Session {
 
     pub fn to_connected(&mut self, since: LocalTime) {
         self.last_active = since;
+        self.attempts = 0;
 
         if let State::Connected { .. } = &self.state {
             log::error!(target: "service", "Session {} is already in 'connected' state, resetting..", self.id);
@@ -358,3 +372,96 @@ impl Session {
         Ok(())
     }
 }
+
+/// Compute the delay before a connection attempt, given the number of attempts
+/// made since the last successful handshake.
+///
+/// The delay starts at `min` and doubles with every attempt, up to `max`. To
+/// avoid many nodes reconnecting in lockstep, up to half of it is taken off at
+/// random. The result is always within `min..=max`.
+pub fn backoff(
+    attempts: usize,
+    min: LocalDuration,
+    max: LocalDuration,
+    rng: &mut Rng,
+) -> LocalDuration {
+    let max = max.max(min);
+    let factor = u32::try_from(attempts)
+        .ok()
+        .and_then(|n| 1u128.checked_shl(n))
+        .unwrap_or(u128::MAX);
+    let delay = min.as_millis().saturating_mul(factor).min(max.as_millis());
+    let jittered = rng.u128(delay / 2..=delay);
+
+    LocalDuration::from_millis(jittered).clamp(min, max)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_backoff_growth() {
+        let min = LocalDuration::from_secs(3);
+        let max = LocalDuration::from_mins(60);
+        let mut rng = Rng::with_seed(42);
+
+        for attempts in 0..32 {
+            let ceiling = (min.as_millis() << attempts.min(20)).min(max.as_millis());
+            let floor = (ceiling / 2).max(min.as_millis());
+
+            for _ in 0..64 {
+                let delay = backoff(attempts, min, max, &mut rng).as_millis();
+                assert!(
+                    (floor..=ceiling).contains(&delay),
+                    "delay {delay}ms for attempt {attempts} is out of bounds ({floor}..={ceiling})"
+                );
+            }
+        }
+        // The first attempt isn't jittered below the minimum, and the delay is
+        // capped once it reaches the maximum.
+        assert_eq!(backoff(0, min, max, &mut rng), min);
+        let capped = backoff(usize::MAX, min, max, &mut rng);
+        assert!(capped.as_millis() >= max.as_millis() / 2);
+        assert!(capped <= max);
+    }
+
+    #[test]
+    fn test_backoff_jitter() {
+        let min = LocalDuration::from_secs(1);
+        let max = LocalDuration::from_mins(60);
+        let mut rng = Rng::with_seed(7);
+        let delays = (0..64)
+            .map(|_| backoff(8, min, max, &mut rng).as_millis())
+            .collect::<HashSet<_>>();
+
+        assert!(delays.len() > 1, "delays should be jittered");
+    }
+
+    #[test]
+    fn test_backoff_reset() {
+        let min = LocalDuration::from_secs(3);
+        let max = LocalDuration::from_mins(60);
+        let addr = Address::from(std::net::SocketAddr::from(([8, 8, 8, 8], 8776)));
+        let mut session = Session::outbound(
+            radicle::test::arbitrary::gen(1),
+            addr,
+            true,
+            Rng::with_seed(1),
+            Limits::default(),
+        );
+
+        for _ in 0..5 {
+            session.to_attempted();
+            session.to_disconnected(LocalTime::default(), LocalTime::default());
+            session.to_initial();
+        }
+        assert!(session.backoff(min, max).as_millis() >= min.as_millis() * 16);
+
+        // A successful handshake resets the schedule.
+        session.to_attempted();
+        session.to_connected(LocalTime::default());
+        assert_eq!(session.attempts(), 0);
+        assert_eq!(session.backoff(min, max), min);
+    }
+}
@@ -1,8 +1,10 @@
 pub mod frame;
+pub mod framed;
 pub mod message;
 pub mod varint;
 
 pub use frame::StreamId;
+pub use framed::{FramedReader, FramedWriter};
 pub use message::{AddressType, MessageType};
 
 use std::collections::BTreeMap;
@@ -80,6 +82,12 @@ pub enum Invalid {
     ProtocolVersion { actual: [u8; 4] },
     #[error("unsupported protocol version: {actual}")]
     ProtocolVersionUnsupported { actual: u8 },
+
+    // Integrity
+    #[error("checksum mismatch: expected {expected:08x}, got {actual:08x}")]
+    Integrity { expected: u32, actual: u32 },
+    #[error("item size mismatch: expected {expected} byte(s), decoded {actual}")]
+    ItemSize { expected: usize, actual: usize },
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -119,6 +127,30 @@ pub trait Encode {
     }
 }
 
+/// Size of the CRC32 checksum that optionally follows encoded items.
+#[cfg(any(test, feature = "crc"))]
+pub const CRC_SIZE: usize = 4;
+
+/// Size of the length that precedes encoded items followed by a checksum.
+#[cfg(any(test, feature = "crc"))]
+pub const CRC_LENGTH_SIZE: usize = 4;
+
+/// Encode an item, preceded by the length of its encoding, and followed by the
+/// CRC32 checksum of its encoding.
+///
+/// Transports without integrity protection of their own can use this to
+/// detect corruption, see [`crate::deserializer::Deserializer::with_crc`].
+/// The length allows checking an item before decoding it.
+#[cfg(any(test, feature = "crc"))]
+pub fn encode_with_crc(item: &impl Encode, buf: &mut impl BufMut) {
+    let bytes = item.encode_to_vec();
+    let len = u32::try_from(bytes.len()).expect("encode_with_crc: item exceeds 4 GiB");
+
+    buf.put_u32(len);
+    buf.put_slice(&bytes);
+    buf.put_u32(crc32fast::hash(&bytes));
+}
+
 /// Things that can be decoded from binary.
 pub trait Decode: Sized {
     fn decode(buffer: &mut impl Buf) -> Result<Self, Error>;
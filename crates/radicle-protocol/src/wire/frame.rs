@@ -6,7 +6,7 @@ use bytes::{Buf, BufMut};
 use radicle::node::Link;
 
 use crate::service::Message;
-use crate::{wire, wire::varint, wire::varint::VarInt, PROTOCOL_VERSION};
+use crate::{wire, wire::varint, wire::varint::VarInt, MIN_PROTOCOL_VERSION, PROTOCOL_VERSION};
 
 /// Protocol version strings all start with the magic sequence `rad`, followed
 /// by a version number.
@@ -42,6 +42,63 @@ impl wire::Decode for Version {
     }
 }
 
+/// Range of protocol versions supported by a node, inclusive.
+///
+/// Peers advertise their range when connecting, and use the highest version
+/// supported by both. Peers that don't advertise a range only support
+/// version `1`, see [`VersionRange::V1`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VersionRange {
+    /// Oldest supported version.
+    pub min: u8,
+    /// Newest supported version.
+    pub max: u8,
+}
+
+impl VersionRange {
+    /// The versions supported by this node.
+    pub const SUPPORTED: Self = Self {
+        min: MIN_PROTOCOL_VERSION,
+        max: PROTOCOL_VERSION,
+    };
+
+    /// The versions supported by peers that don't advertise a range.
+    pub const V1: Self = Self { min: 1, max: 1 };
+
+    /// Return the highest version supported by both ranges, if any.
+    pub fn negotiate(&self, other: &Self) -> Option<u8> {
+        let version = self.max.min(other.max);
+
+        (version >= self.min.max(other.min)).then_some(version)
+    }
+}
+
+impl fmt::Display for VersionRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.min == self.max {
+            write!(f, "{{{}}}", self.min)
+        } else {
+            write!(f, "{{{}..{}}}", self.min, self.max)
+        }
+    }
+}
+
+impl wire::Encode for VersionRange {
+    fn encode(&self, buf: &mut impl BufMut) {
+        self.min.encode(buf);
+        self.max.encode(buf);
+    }
+}
+
+impl wire::Decode for VersionRange {
+    fn decode(buf: &mut impl Buf) -> Result<Self, wire::Error> {
+        let min = u8::decode(buf)?;
+        let max = u8::decode(buf)?;
+
+        Ok(Self { min, max })
+    }
+}
+
 /// Identifies a (multiplexed) stream.
 ///
 /// Stream IDs are variable-length integers with the least significant 3 bits
@@ -264,6 +321,25 @@ pub enum Control {
         /// The stream to send an EOF on.
         stream: StreamId,
     },
+    /// Signal that all further git data sent by us on this stream is compressed.
+    /// Only sent to peers that advertise support for compression.
+    Compress {
+        /// The stream on which data is compressed.
+        stream: StreamId,
+    },
+    /// Advertise the range of protocol versions supported. Only sent to peers
+    /// that advertise support for version negotiation.
+    Versions {
+        /// The supported versions.
+        range: VersionRange,
+    },
+    /// Signal that we are about to disconnect because the peer is sending data
+    /// faster than we can process it. Only sent to peers that advertise support
+    /// for rate-limit notices.
+    RateLimited {
+        /// Number of seconds the peer should wait before reconnecting.
+        retry_after: u32,
+    },
 }
 
 /// Type of control message.
@@ -276,6 +352,12 @@ pub enum ControlType {
     Close = 1,
     /// Control EOF byte.
     Eof = 2,
+    /// Control compress byte.
+    Compress = 3,
+    /// Control versions byte.
+    Versions = 4,
+    /// Control rate-limited byte.
+    RateLimited = 5,
 }
 
 impl TryFrom<u8> for ControlType {
@@ -286,6 +368,9 @@ impl TryFrom<u8> for ControlType {
             0b00 => Ok(ControlType::Open),
             0b01 => Ok(ControlType::Close),
             0b10 => Ok(ControlType::Eof),
+            0b11 => Ok(ControlType::Compress),
+            0b100 => Ok(ControlType::Versions),
+            0b101 => Ok(ControlType::RateLimited),
             n => Err(n),
         }
     }
@@ -309,6 +394,15 @@ impl wire::Decode for Control {
             Ok(ControlType::Eof) => Ok(Control::Eof {
                 stream: StreamId::decode(buf)?,
             }),
+            Ok(ControlType::Compress) => Ok(Control::Compress {
+                stream: StreamId::decode(buf)?,
+            }),
+            Ok(ControlType::Versions) => Ok(Control::Versions {
+                range: VersionRange::decode(buf)?,
+            }),
+            Ok(ControlType::RateLimited) => Ok(Control::RateLimited {
+                retry_after: u32::decode(buf)?,
+            }),
             Err(other) => Err(wire::Invalid::ControlType { actual: other }.into()),
         }
     }
@@ -329,6 +423,18 @@ impl wire::Encode for Control {
                 u8::from(ControlType::Close).encode(buf);
                 id.encode(buf);
             }
+            Self::Compress { stream: id } => {
+                u8::from(ControlType::Compress).encode(buf);
+                id.encode(buf);
+            }
+            Self::Versions { range } => {
+                u8::from(ControlType::Versions).encode(buf);
+                range.encode(buf);
+            }
+            Self::RateLimited { retry_after } => {
+                u8::from(ControlType::RateLimited).encode(buf);
+                retry_after.encode(buf);
+            }
         }
     }
 }
@@ -413,6 +519,21 @@ mod test {
         assert_eq!(StreamId::gossip(Link::Inbound), StreamId(VarInt(0b011)));
     }
 
+    #[test]
+    fn test_control_compress() {
+        use wire::{Decode as _, Encode as _};
+
+        let frame: Frame<Message> = Frame::control(
+            Link::Outbound,
+            Control::Compress {
+                stream: StreamId::git(Link::Outbound),
+            },
+        );
+        let bytes = frame.encode_to_vec();
+
+        assert_eq!(Frame::decode(&mut bytes.as_slice()).unwrap(), frame);
+    }
+
     #[test]
     fn test_encode_git_large() {
         use wire::Encode as _;
@@ -435,4 +556,71 @@ mod test {
             "just making sure that whatever was encoded is still quite large"
         );
     }
+
+    #[test]
+    fn test_max_gossip_frame_fits_inbox() {
+        use crate::service::message::{Ping, ZeroBytes};
+        use radicle::node::config::LimitInboxSize;
+        use wire::Encode as _;
+
+        let frame = Frame::gossip(
+            Link::Outbound,
+            Message::Ping(Ping {
+                ponglen: 0,
+                zeroes: ZeroBytes::new(Ping::MAX_PING_ZEROES),
+            }),
+        );
+        assert!(frame.encode_to_vec().len() <= LimitInboxSize::MIN);
+    }
+
+    #[test]
+    fn test_version_negotiate() {
+        let range = |min, max| VersionRange { min, max };
+
+        assert_eq!(VersionRange::V1.negotiate(&VersionRange::V1), Some(1));
+        assert_eq!(range(1, 2).negotiate(&VersionRange::V1), Some(1));
+        assert_eq!(VersionRange::V1.negotiate(&range(1, 3)), Some(1));
+        assert_eq!(range(1, 3).negotiate(&range(2, 4)), Some(3));
+        assert_eq!(range(2, 4).negotiate(&range(1, 3)), Some(3));
+        assert_eq!(range(1, 4).negotiate(&range(2, 3)), Some(3));
+        assert_eq!(range(3, 3).negotiate(&range(1, 5)), Some(3));
+        assert_eq!(
+            VersionRange::SUPPORTED.negotiate(&VersionRange::V1),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn test_version_negotiate_no_overlap() {
+        let range = |min, max| VersionRange { min, max };
+
+        assert_eq!(range(2, 3).negotiate(&VersionRange::V1), None);
+        assert_eq!(VersionRange::V1.negotiate(&range(2, 2)), None);
+        assert_eq!(range(1, 2).negotiate(&range(3, 4)), None);
+        // Invalid ranges never overlap.
+        assert_eq!(range(2, 1).negotiate(&range(1, 2)), None);
+    }
+
+    #[test]
+    fn test_versions_control_roundtrip() {
+        use wire::{Decode as _, Encode as _};
+
+        let ctrl = Control::Versions {
+            range: VersionRange { min: 1, max: 2 },
+        };
+        let bytes = ctrl.encode_to_vec();
+
+        assert_eq!(Control::decode(&mut bytes.as_slice()).unwrap(), ctrl);
+    }
+
+    #[test]
+    fn test_rate_limited_control_roundtrip() {
+        use wire::{Decode as _, Encode as _};
+
+        let frame: Frame<Message> =
+            Frame::control(Link::Inbound, Control::RateLimited { retry_after: 90 });
+        let bytes = frame.encode_to_vec();
+
+        assert_eq!(Frame::decode(&mut bytes.as_slice()).unwrap(), frame);
+    }
 }
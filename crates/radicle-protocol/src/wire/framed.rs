@@ -0,0 +1,285 @@
+//! Blocking, framed I/O over synchronous streams.
+//!
+//! [`FramedWriter`] and [`FramedReader`] send and receive protocol [`Frame`]s
+//! over any [`io::Write`] and [`io::Read`], eg. a [`std::net::TcpStream`],
+//! using the same encoding as the node.
+use std::io;
+use std::marker::PhantomData;
+
+use radicle::node::Link;
+
+use crate::deserializer::Deserializer;
+use crate::service::Message;
+use crate::wire;
+use crate::wire::frame::Frame;
+use crate::wire::Encode as _;
+
+/// Maximum number of bytes buffered by a [`FramedReader`] while decoding a frame.
+pub const MAX_BUFFER_SIZE: usize = 1024 * 1024 * 2;
+
+/// Size of the chunks read from the underlying reader.
+const READ_CHUNK_SIZE: usize = 8 * 1024;
+
+/// Writes protocol frames to an underlying [`io::Write`].
+#[derive(Debug)]
+pub struct FramedWriter<W, T = Message> {
+    writer: W,
+    link: Link,
+    #[cfg(any(test, feature = "crc"))]
+    crc: bool,
+    item: PhantomData<T>,
+}
+
+impl<W: io::Write, T: wire::Encode> FramedWriter<W, T> {
+    /// Create a new writer. Messages are written on the gossip stream of the
+    /// given `link`, ie. the direction of the connection from our point of view.
+    pub fn new(writer: W, link: Link) -> Self {
+        Self {
+            writer,
+            link,
+            #[cfg(any(test, feature = "crc"))]
+            crc: false,
+            item: PhantomData,
+        }
+    }
+
+    /// Precede every frame with its length and follow it with its CRC32
+    /// checksum, to be verified by a [`FramedReader::with_crc`].
+    #[cfg(any(test, feature = "crc"))]
+    pub fn with_crc(mut self) -> Self {
+        self.crc = true;
+        self
+    }
+
+    /// Write a message as a gossip frame.
+    pub fn write(&mut self, msg: T) -> io::Result<()> {
+        self.write_frame(&Frame::gossip(self.link, msg))
+    }
+
+    /// Write a frame.
+    pub fn write_frame(&mut self, frame: &Frame<T>) -> io::Result<()> {
+        #[cfg(any(test, feature = "crc"))]
+        if self.crc {
+            let mut buf = Vec::new();
+            wire::encode_with_crc(frame, &mut buf);
+
+            return self.writer.write_all(&buf);
+        }
+        self.writer.write_all(&frame.encode_to_vec())
+    }
+
+    /// Flush the underlying writer.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// Get a reference to the underlying writer.
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    /// Return the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// Reads protocol frames from an underlying [`io::Read`].
+///
+/// Frames are decoded as they are read, and returned by iterating over the
+/// reader. The iterator ends once the underlying reader reaches end-of-file
+/// on a frame boundary.
+#[derive(Debug)]
+pub struct FramedReader<R, T = Message> {
+    reader: R,
+    inbox: Deserializer<MAX_BUFFER_SIZE, Frame<T>>,
+}
+
+impl<R: io::Read, T: wire::Decode> FramedReader<R, T> {
+    /// Create a new reader.
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            inbox: Deserializer::default(),
+        }
+    }
+
+    /// Verify the CRC32 checksum of every frame before decoding it, as written by a
+    /// [`FramedWriter::with_crc`]. Frames that don't match their checksum fail
+    /// with [`io::ErrorKind::InvalidData`], wrapping [`wire::Invalid::Integrity`].
+    #[cfg(any(test, feature = "crc"))]
+    pub fn with_crc(mut self) -> Self {
+        self.inbox = self.inbox.with_crc();
+        self
+    }
+
+    /// Read the next frame, blocking until a full frame is read.
+    /// Returns [`None`] on end-of-file.
+    pub fn read_frame(&mut self) -> io::Result<Option<Frame<T>>> {
+        let mut chunk = [0; READ_CHUNK_SIZE];
+
+        loop {
+            if let Some(frame) = self
+                .inbox
+                .deserialize_next()
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?
+            {
+                return Ok(Some(frame));
+            }
+            let n = match self.reader.read(&mut chunk) {
+                Ok(n) => n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+            if n == 0 {
+                if self.inbox.is_empty() {
+                    return Ok(None);
+                }
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    format!(
+                        "stream ended with {} byte(s) of partial frame",
+                        self.inbox.len()
+                    ),
+                ));
+            }
+            self.inbox.input(&chunk[..n]).map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("frame exceeds maximum size of {MAX_BUFFER_SIZE} bytes"),
+                )
+            })?;
+        }
+    }
+
+    /// Get a reference to the underlying reader.
+    pub fn get_ref(&self) -> &R {
+        &self.reader
+    }
+
+    /// Return the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<R: io::Read, T: wire::Decode> Iterator for FramedReader<R, T> {
+    type Item = io::Result<Frame<T>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_frame().transpose()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use radicle::assert_matches;
+
+    use super::*;
+    use crate::service::message::{Ping, ZeroBytes};
+    use crate::wire::frame::{Control, FrameData, StreamId};
+
+    #[test]
+    fn test_roundtrip() {
+        let pong = Message::Pong {
+            zeroes: ZeroBytes::new(42),
+        };
+        let mut writer = FramedWriter::new(Vec::new(), Link::Outbound);
+        writer.write(pong.clone()).unwrap();
+        writer
+            .write_frame(&Frame::control(
+                Link::Outbound,
+                Control::Close {
+                    stream: StreamId::git(Link::Outbound),
+                },
+            ))
+            .unwrap();
+        writer
+            .write(Message::Ping(Ping {
+                ponglen: 1,
+                zeroes: ZeroBytes::new(0),
+            }))
+            .unwrap();
+
+        let bytes = writer.into_inner();
+        // Split the input, so that the first read returns a partial frame.
+        let mut reader = FramedReader::<_, Message>::new(io::Read::chain(&bytes[..1], &bytes[1..]));
+        let frame = reader.next().unwrap().unwrap();
+
+        assert_eq!(frame.stream, StreamId::gossip(Link::Outbound));
+        assert_eq!(frame.data, FrameData::Gossip(pong));
+        assert_matches!(
+            reader.next(),
+            Some(Ok(Frame {
+                data: FrameData::Control(Control::Close { .. }),
+                ..
+            }))
+        );
+        assert_matches!(
+            reader.next(),
+            Some(Ok(Frame {
+                data: FrameData::Gossip(Message::Ping(_)),
+                ..
+            }))
+        );
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn test_unexpected_eof() {
+        let mut writer = FramedWriter::new(Vec::new(), Link::Inbound);
+        writer
+            .write(Message::Pong {
+                zeroes: ZeroBytes::new(8),
+            })
+            .unwrap();
+
+        let bytes = writer.into_inner();
+        let mut reader = FramedReader::<_, Message>::new(&bytes[..bytes.len() - 1]);
+        let err = reader.next().unwrap().unwrap_err();
+
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn test_crc() {
+        let data = b"corruptible".to_vec();
+        let mut writer = FramedWriter::<_, Message>::new(Vec::new(), Link::Outbound).with_crc();
+        writer
+            .write_frame(&Frame::git(StreamId::git(Link::Outbound), data.clone()))
+            .unwrap();
+
+        let mut bytes = writer.into_inner();
+        let mut reader = FramedReader::<_, Message>::new(&bytes[..]).with_crc();
+        assert_matches!(
+            reader.next(),
+            Some(Ok(Frame {
+                data: FrameData::Git(d),
+                ..
+            })) if d == data
+        );
+        assert!(reader.next().is_none());
+
+        // Corrupt a byte of the payload.
+        let i = bytes.len() - wire::CRC_SIZE - 1;
+        bytes[i] = !bytes[i];
+
+        let mut reader = FramedReader::<_, Message>::new(&bytes[..]).with_crc();
+        let err = reader.next().unwrap().unwrap_err();
+
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_matches!(
+            err.get_ref()
+                .and_then(|e| e.downcast_ref::<wire::Invalid>()),
+            Some(wire::Invalid::Integrity { .. })
+        );
+    }
+
+    #[test]
+    fn test_invalid_version() {
+        let mut reader = FramedReader::<_, Message>::new(&b"git\x01\x00"[..]);
+        let err = reader.next().unwrap().unwrap_err();
+
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -1,3 +1,4 @@
+use std::io::{BufRead, Write};
 use std::str::FromStr;
 use std::{io, process::ExitStatus};
 
@@ -23,6 +24,14 @@ pub enum Error {
     #[error("invalid oid: {0}")]
     InvalidOid(#[from] radicle::git::ParseOidError),
 
+    /// Only some of the refs could be fetched.
+    #[error("fetched {} of {} ref(s): {source}", .fetched.len(), .fetched.len() + .missing.len())]
+    Partial {
+        fetched: Vec<Ref>,
+        missing: Vec<Ref>,
+        source: Box<Error>,
+    },
+
     /// Error fetching pack from storage to working copy.
     #[error("`git fetch-pack` failed with exit status {status}, stderr and stdout follow:\n{stderr}\n{stdout}")]
     FetchPackFailed {
@@ -32,17 +41,14 @@ pub enum Error {
     },
 }
 
-/// Run a git fetch command.
-pub fn run<R: ReadRepository>(
-    mut refs: Vec<(git::Oid, git::fmt::RefString)>,
-    stored: R,
-    stdin: &io::Stdin,
-    verbosity: Verbosity,
-) -> Result<(), Error> {
-    // Read all the `fetch` lines.
+/// A ref to fetch, and the object it points to.
+pub type Ref = (git::Oid, git::fmt::RefString);
+
+/// Read the remaining `fetch` commands, following the given refs.
+pub fn read(mut refs: Vec<Ref>, input: &mut impl BufRead) -> Result<Vec<Ref>, Error> {
     let mut line = String::new();
     loop {
-        let tokens = read_line(stdin, &mut line)?;
+        let tokens = read_line(input, &mut line)?;
         match tokens.as_slice() {
             ["fetch", oid, refstr] => {
                 let oid = git::Oid::from_str(oid)?;
@@ -56,34 +62,261 @@ pub fn run<R: ReadRepository>(
             _ => return Err(Error::InvalidCommand(line.trim().to_owned())),
         }
     }
+    Ok(refs)
+}
 
-    // Verify them and prepare the final refspecs.
-    let oids = refs.into_iter().map(|(oid, _)| oid);
-
-    // Rely on the environment variable `GIT_DIR` pointing at the repository.
-    let working = None;
-
-    // N.b. we shell out to `git`, avoiding using `git2`. This is to
-    // avoid an issue where somewhere within the fetch there is an
-    // attempt to lookup a `rad/sigrefs` object, which says that the
-    // object is missing. We suspect that this is due to the object
-    // being localised in the same packfile as other objects we are
-    // fetching. Since the `rad/sigrefs` object is never needed nor
-    // used in the working copy, this will always result in the object
-    // missing. This seems to only be an issue with `libgit2`/`git2`
-    // and not `git` itself.
-    let output = git::process::fetch_pack(working, &stored, oids, verbosity.into())?;
-
-    if !output.status.success() {
-        return Err(Error::FetchPackFailed {
-            stderr: String::from_utf8_lossy(&output.stderr).to_string(),
-            stdout: String::from_utf8_lossy(&output.stdout).to_string(),
-            status: output.status,
-        });
-    }
+/// Run a git fetch command.
+///
+/// If only some of the refs could be fetched, [`Error::Partial`] is returned,
+/// and the missing refs can be retried.
+pub fn run<R: ReadRepository>(
+    refs: Vec<Ref>,
+    stored: &R,
+    verbosity: Verbosity,
+    output: &mut impl Write,
+) -> Result<(), Error> {
+    fetch(refs, |oids| {
+        // Rely on the environment variable `GIT_DIR` pointing at the repository.
+        let working = None;
+
+        // N.b. we shell out to `git`, avoiding using `git2`. This is to
+        // avoid an issue where somewhere within the fetch there is an
+        // attempt to lookup a `rad/sigrefs` object, which says that the
+        // object is missing. We suspect that this is due to the object
+        // being localised in the same packfile as other objects we are
+        // fetching. Since the `rad/sigrefs` object is never needed nor
+        // used in the working copy, this will always result in the object
+        // missing. This seems to only be an issue with `libgit2`/`git2`
+        // and not `git` itself.
+        let output = git::process::fetch_pack(working, stored, oids, verbosity.into())?;
+
+        if !output.status.success() {
+            return Err(Error::FetchPackFailed {
+                stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+                stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+                status: output.status,
+            });
+        }
+        Ok(())
+    })?;
 
     // Nb. An empty line means we're done.
-    println!();
+    writeln!(output)?;
 
     Ok(())
 }
+
+/// Fetch the objects of the given refs using `fetch_pack`.
+///
+/// All objects are fetched at once. If that fails, the refs are split in
+/// halves which are fetched separately, until the refs that can't be fetched
+/// are isolated. A range is not bisected further if both of its halves fail,
+/// since the failure is then unlikely to be caused by a few refs, and if no
+/// refs could be fetched at all, the original error is returned.
+fn fetch<F>(refs: Vec<Ref>, mut fetch_pack: F) -> Result<(), Error>
+where
+    F: FnMut(Vec<git::Oid>) -> Result<(), Error>,
+{
+    if refs.is_empty() {
+        return Ok(());
+    }
+    let mut fetched = Vec::new();
+    let mut missing = Vec::new();
+    let mut last = None;
+
+    if let Err(err) = fetch_pack(oids(&refs)) {
+        bisect(
+            refs,
+            err,
+            &mut fetch_pack,
+            &mut fetched,
+            &mut missing,
+            &mut last,
+        );
+    }
+
+    match last {
+        None => Ok(()),
+        Some(err) if fetched.is_empty() => Err(err),
+        Some(err) => Err(Error::Partial {
+            fetched,
+            missing,
+            source: Box::new(err),
+        }),
+    }
+}
+
+/// Bisect the given refs, which failed to be fetched with `err`. Refs are added
+/// to `fetched` or `missing`, and `last` is set to the last error.
+fn bisect<F>(
+    mut refs: Vec<Ref>,
+    err: Error,
+    fetch_pack: &mut F,
+    fetched: &mut Vec<Ref>,
+    missing: &mut Vec<Ref>,
+    last: &mut Option<Error>,
+) where
+    F: FnMut(Vec<git::Oid>) -> Result<(), Error>,
+{
+    if refs.len() <= 1 {
+        missing.extend(refs);
+        *last = Some(err);
+        return;
+    }
+    let rest = refs.split_off(refs.len() / 2);
+
+    match (fetch_pack(oids(&refs)), fetch_pack(oids(&rest))) {
+        (Ok(()), Ok(())) => {
+            fetched.extend(refs);
+            fetched.extend(rest);
+        }
+        (Ok(()), Err(e)) => {
+            fetched.extend(refs);
+            bisect(rest, e, fetch_pack, fetched, missing, last);
+        }
+        (Err(e), Ok(())) => {
+            fetched.extend(rest);
+            bisect(refs, e, fetch_pack, fetched, missing, last);
+        }
+        (Err(_), Err(_)) => {
+            missing.extend(refs);
+            missing.extend(rest);
+            *last = Some(err);
+        }
+    }
+}
+
+/// The objects pointed to by `refs`.
+fn oids(refs: &[Ref]) -> Vec<git::Oid> {
+    refs.iter().map(|(oid, _)| *oid).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn oid(n: u8) -> git::Oid {
+        git::Oid::from_str(&format!("{n:040x}")).unwrap()
+    }
+
+    fn failed() -> Error {
+        Error::Io(io::Error::other("connection reset"))
+    }
+
+    #[test]
+    fn test_fetch_partial() {
+        let refs = vec![
+            (
+                oid(1),
+                git::fmt::RefString::try_from("refs/heads/master").unwrap(),
+            ),
+            (
+                oid(2),
+                git::fmt::RefString::try_from("refs/heads/dev").unwrap(),
+            ),
+        ];
+        let err = fetch(refs.clone(), |oids| {
+            if oids.contains(&oid(2)) {
+                Err(failed())
+            } else {
+                Ok(())
+            }
+        })
+        .unwrap_err();
+
+        let Error::Partial {
+            fetched, missing, ..
+        } = err
+        else {
+            panic!("expected partial fetch, got {err}");
+        };
+        assert_eq!(fetched, refs[..1]);
+        assert_eq!(missing, refs[1..]);
+    }
+
+    #[test]
+    fn test_fetch_retry() {
+        let refs = vec![
+            (
+                oid(1),
+                git::fmt::RefString::try_from("refs/heads/master").unwrap(),
+            ),
+            (
+                oid(2),
+                git::fmt::RefString::try_from("refs/heads/dev").unwrap(),
+            ),
+        ];
+        let mut attempts = 0;
+
+        // The batch fails, but each ref can be fetched on its own.
+        fetch(refs, |oids| {
+            attempts += 1;
+            if oids.len() > 1 {
+                Err(failed())
+            } else {
+                Ok(())
+            }
+        })
+        .unwrap();
+        assert_eq!(attempts, 3);
+    }
+
+    #[test]
+    fn test_fetch_bisect() {
+        let refs = (1..=16)
+            .map(|n| {
+                let name = git::fmt::RefString::try_from(format!("refs/heads/{n}")).unwrap();
+                (oid(n), name)
+            })
+            .collect::<Vec<_>>();
+        let mut attempts = 0;
+
+        // Only one ref can't be fetched: it is isolated without fetching
+        // every ref on its own.
+        let err = fetch(refs.clone(), |oids| {
+            attempts += 1;
+            if oids.contains(&oid(11)) {
+                Err(failed())
+            } else {
+                Ok(())
+            }
+        })
+        .unwrap_err();
+
+        let Error::Partial {
+            fetched, missing, ..
+        } = err
+        else {
+            panic!("expected partial fetch, got {err}");
+        };
+        assert_eq!(missing, refs[10..11]);
+        assert_eq!(fetched.len(), 15);
+        assert_eq!(attempts, 9);
+    }
+
+    #[test]
+    fn test_fetch_failed() {
+        let refs = (1..=16)
+            .map(|n| {
+                let name = git::fmt::RefString::try_from(format!("refs/heads/{n}")).unwrap();
+                (oid(n), name)
+            })
+            .collect::<Vec<_>>();
+        let mut attempts = 0;
+
+        // Nothing can be fetched: both halves fail, so bisection stops, and
+        // the original error is returned.
+        let err = fetch(refs, |_| {
+            attempts += 1;
+            if attempts == 1 {
+                Err(failed())
+            } else {
+                Err(Error::InvalidCommand(String::from("halves")))
+            }
+        })
+        .unwrap_err();
+
+        assert!(matches!(err, Error::Io(_)), "unexpected error {err}");
+        assert_eq!(attempts, 3);
+    }
+}
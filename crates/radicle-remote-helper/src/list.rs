@@ -1,9 +1,17 @@
+use std::io;
+use std::io::Write;
+
 use radicle::patch::cache::Patches as _;
 use radicle::profile;
 use thiserror::Error;
 
 use radicle::cob;
 use radicle::git;
+use radicle::git::canonical::rules::Rules;
+use radicle::git::fmt::Qualified;
+use radicle::identity::crefs::GetCanonicalRefs as _;
+use radicle::identity::doc::CanonicalRefsError;
+use radicle::identity::CanonicalRefs;
 use radicle::prelude::NodeId;
 use radicle::storage::git::transport::local::Url;
 use radicle::storage::ReadRepository;
@@ -32,24 +40,34 @@ pub enum Error {
     /// General repository error.
     #[error(transparent)]
     Repository(#[from] radicle::storage::RepositoryError),
+    /// Canonical references error.
+    #[error(transparent)]
+    CanonicalRefs(#[from] CanonicalRefsError),
+    /// I/O error.
+    #[error("i/o error: {0}")]
+    Io(#[from] io::Error),
 }
 
 /// List refs for fetching (`git fetch` and `git ls-remote`).
+///
+/// Patch refs are only listed if a profile is given, since they are read from
+/// its COB cache.
 pub fn for_fetch<R: ReadRepository + cob::Store<Namespace = NodeId> + 'static>(
     url: &Url,
-    profile: &Profile,
+    profile: Option<&Profile>,
     stored: &R,
+    output: &mut impl Write,
 ) -> Result<(), Error> {
     if let Some(namespace) = url.namespace {
         // Listing namespaced refs.
         for (name, oid) in stored.references_of(&namespace)? {
-            println!("{oid} {name}");
+            writeln!(output, "{oid} {name}")?;
         }
     } else {
         // List the symbolic reference `HEAD`, which is interpreted by
         // Git clients to determine the default branch.
         match stored.head() {
-            Ok((target, _)) => println!("@{target} HEAD"),
+            Ok((target, _)) => writeln!(output, "@{target} HEAD")?,
             Err(err) => eprintln!("remote: error resolving HEAD: {err}"),
         }
 
@@ -60,42 +78,74 @@ pub fn for_fetch<R: ReadRepository + cob::Store<Namespace = NodeId> + 'static>(
             git::fmt::pattern!("refs/tags/*"),
         ] {
             for (name, oid) in stored.references_glob(&glob)? {
-                println!("{oid} {name}");
+                writeln!(output, "{oid} {name}")?;
             }
         }
 
         // List the patch refs, but do not abort if there is an error,
         // as this would break all fetch behavior.
         // Instead, just output an error to the user.
-        if let Err(e) = patch_refs(profile, stored) {
-            eprintln!("remote: error listing patch refs: {e}");
+        if let Some(profile) = profile {
+            if let Err(e) = patch_refs(profile, stored, output) {
+                eprintln!("remote: error listing patch refs: {e}");
+            }
         }
     }
-    println!();
+    writeln!(output)?;
 
     Ok(())
 }
 
 /// List refs for pushing (`git push`).
-pub fn for_push<R: ReadRepository>(profile: &Profile, stored: &R) -> Result<(), Error> {
+///
+/// If canonical `rules` are given, the refs they govern are listed with the
+/// `canonical` attribute, since pushing to those doesn't update the canonical
+/// ref on its own. Git ignores attributes it doesn't know.
+pub fn for_push<R: ReadRepository>(
+    nid: &NodeId,
+    stored: &R,
+    rules: Option<&Rules>,
+    output: &mut impl Write,
+) -> Result<(), Error> {
     // Only our own refs can be pushed to.
-    for (name, oid) in stored.references_of(profile.id())? {
+    for (name, oid) in stored.references_of(nid)? {
         // Only branches and tags can be pushed to.
         if name.starts_with(git::fmt::refname!("refs/heads").as_str())
             || name.starts_with(git::fmt::refname!("refs/tags").as_str())
         {
-            println!("{oid} {name}");
+            let canonical = rules
+                .zip(Qualified::from_refstr(&name))
+                .is_some_and(|(rules, refname)| rules.governs(&refname));
+
+            if canonical {
+                writeln!(output, "{oid} {name} canonical")?;
+            } else {
+                writeln!(output, "{oid} {name}")?;
+            }
         }
     }
-    println!();
+    writeln!(output)?;
 
     Ok(())
 }
 
+/// Load the canonical reference rules of a repository, defaulting to the rule
+/// for its default branch.
+pub fn canonical_refs<R: ReadRepository + cob::Store>(stored: &R) -> Result<CanonicalRefs, Error> {
+    let identity = stored.identity()?;
+    let crefs = identity.canonical_refs_or_default(|| {
+        let rule = identity.doc().default_branch_rule()?;
+        Ok::<_, CanonicalRefsError>(CanonicalRefs::from_iter([rule]))
+    })?;
+
+    Ok(crefs)
+}
+
 /// List canonical patch references. These are magic refs that can be used to pull patch updates.
 fn patch_refs<R: ReadRepository + cob::Store<Namespace = NodeId> + 'static>(
     profile: &Profile,
     stored: &R,
+    output: &mut impl Write,
 ) -> Result<(), Error> {
     let patches = crate::patches(profile, stored)?;
     for patch in patches.list()? {
@@ -104,10 +154,82 @@ fn patch_refs<R: ReadRepository + cob::Store<Namespace = NodeId> + 'static>(
             continue;
         };
         let head = patch.head();
+        let refname = git::refs::patch(&id);
 
         if patch.is_open() && stored.commit(*head).is_ok() {
-            println!("{} {}", patch.head(), git::refs::patch(&id));
+            writeln!(output, "{} {refname}", patch.head())?;
         }
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use radicle::node::device::Device;
+    use radicle::storage::git::Storage;
+    use radicle::storage::WriteStorage as _;
+    use radicle::test::fixtures;
+
+    use super::*;
+
+    #[test]
+    fn test_for_fetch() {
+        let tmp = tempfile::tempdir().unwrap();
+        let signer = Device::mock();
+        let storage = Storage::open(tmp.path().join("storage"), fixtures::user()).unwrap();
+        let (rid, _, _, head) =
+            fixtures::project(tmp.path().join("project"), &storage, &signer).unwrap();
+        let stored = storage.repository_mut(rid).unwrap();
+        let url = Url {
+            repo: rid,
+            namespace: None,
+        };
+        stored
+            .raw()
+            .reference("refs/tags/v1.0", head, false, "test")
+            .unwrap();
+
+        let mut output = Vec::new();
+        for_fetch(&url, None, &stored, &mut output).unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            format!("@refs/heads/master HEAD\n{head} refs/heads/master\n{head} refs/tags/v1.0\n\n")
+        );
+    }
+
+    #[test]
+    fn test_for_push_canonical() {
+        let tmp = tempfile::tempdir().unwrap();
+        let signer = Device::mock();
+        let storage = Storage::open(tmp.path().join("storage"), fixtures::user()).unwrap();
+        let (rid, _, _, head) =
+            fixtures::project(tmp.path().join("project"), &storage, &signer).unwrap();
+        let stored = storage.repository_mut(rid).unwrap();
+        let nid = signer.public_key();
+        stored
+            .raw()
+            .reference(
+                &format!("refs/namespaces/{nid}/refs/tags/v1.0"),
+                head,
+                false,
+                "test",
+            )
+            .unwrap();
+        let crefs = canonical_refs(&stored).unwrap();
+
+        let mut output = Vec::new();
+        for_push(nid, &stored, Some(crefs.rules()), &mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            format!("{head} refs/heads/master canonical\n{head} refs/tags/v1.0\n\n")
+        );
+
+        let mut output = Vec::new();
+        for_push(nid, &stored, None, &mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            format!("{head} refs/heads/master\n{head} refs/tags/v1.0\n\n")
+        );
+    }
+}
@@ -20,6 +20,8 @@ mod fetch;
 mod list;
 mod push;
 
+use std::collections::BTreeSet;
+use std::io::Write as _;
 use std::path::PathBuf;
 use std::process;
 use std::str::FromStr;
@@ -27,7 +29,9 @@ use std::{env, fmt, io};
 
 use thiserror::Error;
 
-use radicle::prelude::NodeId;
+use radicle::cob::{Label, LabelError};
+use radicle::identity::did::DidError;
+use radicle::prelude::{Did, NodeId};
 use radicle::storage::git::transport::local::{Url, UrlError};
 use radicle::storage::{ReadRepository, WriteStorage};
 use radicle::version::Version;
@@ -90,9 +94,18 @@ pub enum Error {
     /// Invalid arguments received.
     #[error("invalid arguments: {0:?}")]
     InvalidArguments(Vec<String>),
+    /// A command that requires a profile was received without one.
+    #[error("no profile was loaded")]
+    NoProfile,
     /// Unknown push option received.
     #[error("unknown push option {0:?}")]
     UnsupportedPushOption(String),
+    /// Invalid patch label.
+    #[error("invalid patch label: {0}")]
+    InvalidLabel(#[source] LabelError),
+    /// Invalid patch assignee.
+    #[error("invalid patch assignee {0:?}: expected a DID, eg. `did:key:z6Mk..`")]
+    InvalidAssignee(String, #[source] DidError),
     /// Error with the remote url.
     #[error("invalid remote url: {0}")]
     RemoteUrl(#[from] UrlError),
@@ -120,6 +133,82 @@ pub enum Error {
     /// Invalid object ID.
     #[error("invalid oid: {0}")]
     InvalidOid(#[from] radicle::git::ParseOidError),
+    /// Object format error.
+    #[error(transparent)]
+    ObjectFormat(#[from] ObjectFormatError),
+}
+
+#[derive(Debug, Error)]
+pub enum ObjectFormatError {
+    /// The repository uses an object format that Git doesn't know.
+    #[error("unknown object format {0:?}")]
+    Unknown(String),
+    /// An object ID doesn't match the repository's object format.
+    #[error("object id {oid} does not match the {format} object format of the repository")]
+    Mismatch { oid: git::Oid, format: ObjectFormat },
+    /// Git error.
+    #[error("git: {0}")]
+    Git(#[from] git::raw::Error),
+}
+
+/// The hash algorithm used by a Git repository to identify objects, see
+/// <https://git-scm.com/docs/hash-function-transition>.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ObjectFormat {
+    #[default]
+    Sha1,
+    Sha256,
+}
+
+impl ObjectFormat {
+    /// Detect the object format of a repository from its `extensions.objectFormat`
+    /// configuration. Repositories without it use SHA-1.
+    pub fn detect(repo: &git::raw::Repository) -> Result<Self, ObjectFormatError> {
+        match repo.config()?.get_string("extensions.objectformat") {
+            Ok(name) => name.parse(),
+            Err(e) if e.code() == git::raw::ErrorCode::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Check that an object ID is of this format.
+    pub fn check(&self, oid: &git::Oid) -> Result<(), ObjectFormatError> {
+        let matches = match self {
+            Self::Sha1 => oid.into_sha1().is_some(),
+            // Nb. [`git::Oid`] can't represent SHA-256 digests yet.
+            Self::Sha256 => false,
+        };
+
+        if matches {
+            Ok(())
+        } else {
+            Err(ObjectFormatError::Mismatch {
+                oid: *oid,
+                format: *self,
+            })
+        }
+    }
+}
+
+impl fmt::Display for ObjectFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Sha1 => f.write_str("sha1"),
+            Self::Sha256 => f.write_str("sha256"),
+        }
+    }
+}
+
+impl FromStr for ObjectFormat {
+    type Err = ObjectFormatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "sha1" => Ok(Self::Sha1),
+            "sha256" => Ok(Self::Sha256),
+            _ => Err(ObjectFormatError::Unknown(s.to_owned())),
+        }
+    }
 }
 
 /// Models values for the `verbosity` option, see
@@ -188,6 +277,15 @@ impl Branch {
     }
 }
 
+/// Whether to abort a push when secrets are found in the pushed commits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SecretScan {
+    /// Report secrets and abort the push.
+    Enforce,
+    /// Report secrets, but push anyway.
+    Warn,
+}
+
 #[derive(Debug, Default, Clone)]
 pub struct Options {
     /// Don't sync after push.
@@ -204,6 +302,14 @@ pub struct Options {
     message: cli::patch::Message,
     /// Create a branch and set its upstream when opening a patch.
     branch: Branch,
+    /// Scan the pushed commits for secrets.
+    secret_scan: Option<SecretScan>,
+    /// Declare the object format when listing refs.
+    object_format: bool,
+    /// Labels to add when opening a patch.
+    labels: Vec<Label>,
+    /// Assignees to add when opening a patch.
+    assignees: BTreeSet<Did>,
     verbosity: Verbosity,
 }
 
@@ -237,86 +343,213 @@ pub fn run(profile: radicle::Profile) -> Result<(), Error> {
     // Whether we should output debug logs.
     let debug = radicle::profile::env::debug();
 
-    let stdin = io::stdin();
-    let mut line = String::new();
-    let mut opts = Options::default();
-
     if let Err(e) = radicle::io::set_file_limit(4096) {
         if debug {
             eprintln!("{}: unable to set open file limit: {e}", VERSION.name);
         }
     }
 
-    loop {
-        let tokens = read_line(&stdin, &mut line)?;
+    Helper::new(io::stdin().lock(), io::stdout().lock(), &stored, url)
+        .with_remote(remote)
+        .with_profile(&profile)
+        .with_debug(debug)
+        .run()
+}
 
-        if debug {
-            eprintln!("{}: {}", VERSION.name, &tokens.join(" "));
+/// The remote helper command loop.
+///
+/// Reads commands sent by Git tooling from the input, and writes responses to
+/// the output. Commands that need a [`Profile`], ie. `push` and `list for-push`,
+/// fail unless one is given, and patch refs are only listed with a profile.
+pub struct Helper<'a, I, O> {
+    /// Commands from Git.
+    input: I,
+    /// Responses to Git.
+    output: O,
+    /// The stored repository being fetched from or pushed to.
+    stored: &'a storage::git::Repository,
+    /// The remote URL.
+    url: Url,
+    /// The remote name, if known.
+    remote: Option<git::fmt::RefString>,
+    /// The user profile.
+    profile: Option<&'a Profile>,
+    /// Options set by Git.
+    opts: Options,
+    /// Whether to echo received commands to `stderr`.
+    debug: bool,
+}
+
+impl<'a, I: io::BufRead, O: io::Write> Helper<'a, I, O> {
+    /// Create a new helper for the given stored repository and remote URL.
+    pub fn new(input: I, output: O, stored: &'a storage::git::Repository, url: Url) -> Self {
+        Self {
+            input,
+            output,
+            stored,
+            url,
+            remote: None,
+            profile: None,
+            opts: Options::default(),
+            debug: false,
         }
+    }
+
+    /// Set the remote name.
+    pub fn with_remote(mut self, remote: Option<git::fmt::RefString>) -> Self {
+        self.remote = remote;
+        self
+    }
+
+    /// Set the user profile.
+    pub fn with_profile(mut self, profile: &'a Profile) -> Self {
+        self.profile = Some(profile);
+        self
+    }
 
-        match tokens.as_slice() {
-            ["capabilities"] => {
-                println!("option");
-                println!("push"); // Implies `list` command.
-                println!("fetch");
-                println!();
+    /// Echo received commands to `stderr`.
+    pub fn with_debug(mut self, debug: bool) -> Self {
+        self.debug = debug;
+        self
+    }
+
+    /// Process commands until the input is exhausted, or a command that ends
+    /// the session, ie. `fetch` or `push`, is complete.
+    pub fn run(mut self) -> Result<(), Error> {
+        let format = ObjectFormat::detect(self.stored.raw())?;
+        let mut line = String::new();
+
+        loop {
+            let tokens = read_line(&mut self.input, &mut line)?;
+
+            if self.debug {
+                eprintln!("{}: {}", VERSION.name, &tokens.join(" "));
             }
-            ["option", "verbosity", verbosity] => match verbosity.parse::<Verbosity>() {
-                Ok(verbosity) => {
-                    opts.verbosity = verbosity;
-                    println!("ok");
+
+            match tokens.as_slice() {
+                ["capabilities"] => {
+                    writeln!(self.output, "option")?;
+                    writeln!(self.output, "push")?; // Implies `list` command.
+                    writeln!(self.output, "fetch")?;
+                    writeln!(self.output, "object-format")?;
+                    writeln!(self.output)?;
                 }
-                Err(err) => {
-                    println!("error {err}");
+                ["option", "verbosity", verbosity] => match verbosity.parse::<Verbosity>() {
+                    Ok(verbosity) => {
+                        self.opts.verbosity = verbosity;
+                        writeln!(self.output, "ok")?;
+                    }
+                    Err(err) => {
+                        writeln!(self.output, "error {err}")?;
+                    }
+                },
+                ["option", "push-option", args @ ..] => {
+                    // Nb. Git documentation says that we can print `error <msg>` or `unsupported`
+                    // for options that are not supported, but this results in Git saying that
+                    // "push-option" itself is an unsupported option, which is not helpful or correct.
+                    // Hence, we just exit with an error in this case.
+                    push_option(args, &mut self.opts)?;
+                    writeln!(self.output, "ok")?;
+                }
+                ["option", "object-format", "true"] => {
+                    self.opts.object_format = true;
+                    writeln!(self.output, "ok")?;
+                }
+                ["option", "progress", ..] | ["option", ..] => {
+                    writeln!(self.output, "unsupported")?;
+                }
+                ["fetch", oid, refstr] => {
+                    let oid = git::Oid::from_str(oid)?;
+                    let refstr = git::fmt::RefString::try_from(*refstr)?;
+
+                    let mut refs = fetch::read(vec![(oid, refstr)], &mut self.input)?;
+                    for (oid, _) in &refs {
+                        format.check(oid)?;
+                    }
+
+                    loop {
+                        match fetch::run(refs, self.stored, self.opts.verbosity, &mut self.output) {
+                            // Retry the missing refs, as long as we're making progress.
+                            Err(fetch::Error::Partial {
+                                fetched,
+                                missing,
+                                source,
+                            }) if !fetched.is_empty() => {
+                                warn(format!(
+                                    "fetched {} ref(s), retrying {} ref(s) after error: {source}",
+                                    fetched.len(),
+                                    missing.len()
+                                ));
+                                refs = missing;
+                            }
+                            result => return Ok(result?),
+                        }
+                    }
+                }
+                ["push", refspec] => {
+                    let profile = self.profile()?;
+
+                    return Ok(push::run(
+                        vec![refspec.to_string()],
+                        self.remote,
+                        self.url,
+                        self.stored,
+                        profile,
+                        &mut self.input,
+                        &mut self.output,
+                        self.opts,
+                    )?);
+                }
+                ["list", args @ ..] => {
+                    if self.opts.object_format {
+                        writeln!(self.output, ":object-format {format}")?;
+                    }
+
+                    match args {
+                        ["for-push"] => {
+                            let profile = self.profile()?;
+                            // Canonical rules are only needed to annotate the listed refs,
+                            // and failing to load them shouldn't prevent pushing.
+                            let crefs = list::canonical_refs(self.stored)
+                                .inspect_err(|e| {
+                                    eprintln!("remote: error loading canonical rules: {e}")
+                                })
+                                .ok();
+
+                            list::for_push(
+                                profile.id(),
+                                self.stored,
+                                crefs.as_ref().map(|crefs| crefs.rules()),
+                                &mut self.output,
+                            )?;
+                        }
+                        // Git only sends the `for-push` attribute, other attributes are
+                        // ignored, as recommended for remote helpers.
+                        _ => {
+                            list::for_fetch(
+                                &self.url,
+                                self.profile,
+                                self.stored,
+                                &mut self.output,
+                            )?;
+                        }
+                    }
+                }
+                [] => {
+                    return Ok(());
+                }
+                _ => {
+                    return Err(Error::InvalidCommand(line.trim().to_owned()));
                 }
-            },
-            ["option", "push-option", args @ ..] => {
-                // Nb. Git documentation says that we can print `error <msg>` or `unsupported`
-                // for options that are not supported, but this results in Git saying that
-                // "push-option" itself is an unsupported option, which is not helpful or correct.
-                // Hence, we just exit with an error in this case.
-                push_option(args, &mut opts)?;
-                println!("ok");
-            }
-            ["option", "progress", ..] | ["option", ..] => {
-                println!("unsupported");
-            }
-            ["fetch", oid, refstr] => {
-                let oid = git::Oid::from_str(oid)?;
-                let refstr = git::fmt::RefString::try_from(*refstr)?;
-
-                return Ok(fetch::run(
-                    vec![(oid, refstr)],
-                    stored,
-                    &stdin,
-                    opts.verbosity,
-                )?);
-            }
-            ["push", refspec] => {
-                return Ok(push::run(
-                    vec![refspec.to_string()],
-                    remote,
-                    url,
-                    &stored,
-                    &profile,
-                    &stdin,
-                    opts,
-                )?);
-            }
-            ["list"] => {
-                list::for_fetch(&url, &profile, &stored)?;
-            }
-            ["list", "for-push"] => {
-                list::for_push(&profile, &stored)?;
-            }
-            [] => {
-                return Ok(());
-            }
-            _ => {
-                return Err(Error::InvalidCommand(line.trim().to_owned()));
             }
+            self.output.flush()?;
         }
     }
+
+    /// Get the user profile, which is required by some commands.
+    fn profile(&self) -> Result<&'a Profile, Error> {
+        self.profile.ok_or(Error::NoProfile)
+    }
 }
 
 /// Parse a single push option. Returns `Ok` if it was successful.
@@ -330,6 +563,7 @@ fn push_option(args: &[&str], opts: &mut Options) -> Result<(), Error> {
         ["no-sync"] => opts.no_sync = true,
         ["patch.draft"] => opts.draft = true,
         ["patch.branch"] => opts.branch = Branch::MirrorUpstream,
+        ["secret-scan"] => opts.secret_scan = Some(SecretScan::Enforce),
         _ => {
             let args = args.join(" ");
 
@@ -359,6 +593,24 @@ fn push_option(args: &[&str], opts: &mut Options) -> Result<(), Error> {
                 "patch.branch" => {
                     opts.branch = Branch::Provided(git::fmt::RefString::try_from(val)?)
                 }
+                "patch.label" => {
+                    let label = Label::new(val).map_err(Error::InvalidLabel)?;
+                    if !opts.labels.contains(&label) {
+                        opts.labels.push(label);
+                    }
+                }
+                "patch.assignee" => {
+                    let did = Did::from_str(val)
+                        .map_err(|e| Error::InvalidAssignee(val.to_owned(), e))?;
+                    opts.assignees.insert(did);
+                }
+                "secret-scan" => {
+                    opts.secret_scan = Some(match val {
+                        "enforce" => SecretScan::Enforce,
+                        "warn" => SecretScan::Warn,
+                        _ => return Err(Error::UnsupportedPushOption(args.to_owned())),
+                    });
+                }
                 other => {
                     return Err(Error::UnsupportedPushOption(other.to_owned()));
                 }
@@ -368,11 +620,14 @@ fn push_option(args: &[&str], opts: &mut Options) -> Result<(), Error> {
     Ok(())
 }
 
-/// Read one line from stdin, and split it into tokens.
-pub(crate) fn read_line<'a>(stdin: &io::Stdin, line: &'a mut String) -> io::Result<Vec<&'a str>> {
+/// Read one line from the input, and split it into tokens.
+pub(crate) fn read_line<'a>(
+    input: &mut impl io::BufRead,
+    line: &'a mut String,
+) -> io::Result<Vec<&'a str>> {
     line.clear();
 
-    let read = stdin.read_line(line)?;
+    let read = input.read_line(line)?;
     if read == 0 {
         return Ok(vec![]);
     }
@@ -424,3 +679,167 @@ pub(crate) fn patches_mut<'a>(
         Err(err) => Err(err.into()),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use radicle::node::device::Device;
+    use radicle::storage::git::transport;
+    use radicle::storage::git::Storage;
+    use radicle::test::fixtures;
+
+    #[test]
+    fn test_helper() {
+        let tmp = tempfile::tempdir().unwrap();
+        let signer = Device::mock();
+        let storage = Storage::open(tmp.path().join("storage"), fixtures::user()).unwrap();
+
+        transport::local::register(storage.clone());
+
+        let (rid, _, _, head) =
+            fixtures::project(tmp.path().join("project"), &storage, &signer).unwrap();
+        let stored = storage.repository_mut(rid).unwrap();
+        let url = Url {
+            repo: rid,
+            namespace: None,
+        };
+        let input = "capabilities\noption verbosity 2\noption progress true\nlist\n\n";
+        let mut output = Vec::new();
+
+        Helper::new(input.as_bytes(), &mut output, &stored, url.clone())
+            .run()
+            .unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            format!(
+                "option\npush\nfetch\nobject-format\n\n\
+                 ok\n\
+                 unsupported\n\
+                 @refs/heads/master HEAD\n{head} refs/heads/master\n\n"
+            )
+        );
+
+        // Unknown `list` attributes are ignored.
+        let mut output = Vec::new();
+        Helper::new(
+            "list unknown\n\n".as_bytes(),
+            &mut output,
+            &stored,
+            url.clone(),
+        )
+        .run()
+        .unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            format!("@refs/heads/master HEAD\n{head} refs/heads/master\n\n")
+        );
+
+        // Without a profile, we can't push.
+        for input in [
+            "list for-push\n",
+            "push refs/heads/master:refs/heads/master\n\n",
+        ] {
+            let result = Helper::new(input.as_bytes(), io::sink(), &stored, url.clone()).run();
+            assert!(matches!(result, Err(Error::NoProfile)));
+        }
+
+        // Unknown commands are rejected.
+        let result = Helper::new("connect\n".as_bytes(), io::sink(), &stored, url).run();
+        assert!(matches!(result, Err(Error::InvalidCommand(cmd)) if cmd == "connect"));
+    }
+
+    #[test]
+    fn test_object_format() {
+        let tmp = tempfile::tempdir().unwrap();
+        let signer = Device::mock();
+        let storage = Storage::open(tmp.path().join("storage"), fixtures::user()).unwrap();
+
+        transport::local::register(storage.clone());
+
+        let (rid, _, _, head) =
+            fixtures::project(tmp.path().join("project"), &storage, &signer).unwrap();
+        let stored = storage.repository_mut(rid).unwrap();
+        let url = Url {
+            repo: rid,
+            namespace: None,
+        };
+        assert_eq!(
+            ObjectFormat::detect(stored.raw()).unwrap(),
+            ObjectFormat::Sha1
+        );
+
+        let input = "capabilities\noption object-format true\nlist\n\n";
+        let mut output = Vec::new();
+
+        Helper::new(input.as_bytes(), &mut output, &stored, url)
+            .run()
+            .unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            format!(
+                "option\npush\nfetch\nobject-format\n\n\
+                 ok\n\
+                 :object-format sha1\n\
+                 @refs/heads/master HEAD\n{head} refs/heads/master\n\n"
+            )
+        );
+        assert!(matches!(
+            ObjectFormat::Sha256.check(&head.into()),
+            Err(ObjectFormatError::Mismatch {
+                format: ObjectFormat::Sha256,
+                ..
+            })
+        ));
+        assert_eq!(
+            "SHA256".parse::<ObjectFormat>().unwrap(),
+            ObjectFormat::Sha256
+        );
+        assert!("md5".parse::<ObjectFormat>().is_err());
+    }
+
+    #[test]
+    fn test_push_option_labels_assignees() {
+        let alice = "did:key:z6MknSLrJoTcukLrE435hVNQT4JUhbvWLX4kUzqkEStBU8Vi";
+        let bob = "did:key:z6Mkt67GdsW7715MEfRuP4pSZxJRJh6kj6Y48WRqVv4N1tRk";
+        let mut opts = Options::default();
+
+        for arg in [
+            "patch.label=bug",
+            "patch.label=ui",
+            "patch.label=bug",
+            &format!("patch.assignee={alice}"),
+            &format!("patch.assignee={bob}"),
+        ] {
+            push_option(&[arg], &mut opts).unwrap();
+        }
+        assert_eq!(
+            opts.labels,
+            vec![Label::new("bug").unwrap(), Label::new("ui").unwrap()]
+        );
+        assert_eq!(
+            opts.assignees,
+            BTreeSet::from([alice.parse().unwrap(), bob.parse().unwrap()])
+        );
+
+        assert!(matches!(
+            push_option(
+                &["patch.assignee=z6MknSLrJoTcukLrE435hVNQT4JUhbvWLX4kUzqkEStBU8Vi"],
+                &mut opts
+            ),
+            Err(Error::InvalidAssignee(..))
+        ));
+        assert!(matches!(
+            push_option(&["patch.assignee=did:key:alice"], &mut opts),
+            Err(Error::InvalidAssignee(..))
+        ));
+        assert!(matches!(
+            push_option(&["patch.label="], &mut opts),
+            Err(Error::InvalidLabel(..))
+        ));
+        assert_eq!(opts.labels.len(), 2);
+        assert_eq!(opts.assignees.len(), 2);
+    }
+}
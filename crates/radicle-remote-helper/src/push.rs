@@ -2,9 +2,10 @@
 
 mod canonical;
 mod error;
+mod secrets;
 
 use std::collections::HashMap;
-use std::io::IsTerminal;
+use std::io::{BufRead, IsTerminal, Write};
 use std::process::ExitStatus;
 use std::str::FromStr;
 use std::{assert_eq, io};
@@ -31,7 +32,7 @@ use radicle::{git, rad};
 use radicle_cli as cli;
 use radicle_cli::terminal as term;
 
-use crate::{hint, read_line, Options, Verbosity};
+use crate::{hint, read_line, ObjectFormat, Options, Verbosity};
 
 #[derive(Debug, Error)]
 pub enum Error {
@@ -77,6 +78,9 @@ pub enum Error {
     /// Parse error for object IDs.
     #[error(transparent)]
     ParseObjectId(#[from] ParseObjectId),
+    /// Object format error.
+    #[error(transparent)]
+    ObjectFormat(#[from] crate::ObjectFormatError),
     /// Patch COB error.
     #[error(transparent)]
     Patch(#[from] radicle::cob::patch::Error),
@@ -98,6 +102,9 @@ pub enum Error {
     /// Patch is empty.
     #[error("patch commits are already included in the base branch")]
     EmptyPatch,
+    /// Base is not an ancestor of the patch head.
+    #[error("base {base} is not an ancestor of {head}")]
+    BaseNotAncestor { base: git::Oid, head: git::Oid },
     /// Missing canonical head.
     #[error("the canonical head is missing from your working copy; please pull before pushing")]
     MissingCanonicalHead(git::Oid),
@@ -121,6 +128,12 @@ pub enum Error {
     #[error(transparent)]
     FindObjects(#[from] git::canonical::error::FindObjectsError),
 
+    /// Secrets were found in the pushed commits.
+    #[error(
+        "refusing to push {count} potential secret(s); use `-o secret-scan=warn` to push anyway"
+    )]
+    SecretsFound { count: usize },
+
     /// Error sending pack from the working copy to storage.
     #[error("`git send-pack` failed with exit status {status}, stderr and stdout follow:\n{stderr}\n{stdout}")]
     SendPackFailed {
@@ -249,7 +262,8 @@ pub fn run(
     url: Url,
     stored: &storage::git::Repository,
     profile: &Profile,
-    stdin: &io::Stdin,
+    input: &mut impl BufRead,
+    output: &mut impl Write,
     opts: Options,
 ) -> Result<(), Error> {
     // Don't allow push if either of these conditions is true:
@@ -272,7 +286,7 @@ pub fn run(
 
     // Read all the `push` lines.
     loop {
-        let tokens = read_line(stdin, &mut line)?;
+        let tokens = read_line(input, &mut line)?;
         match tokens.as_slice() {
             ["push", spec] => {
                 specs.push(spec.to_string());
@@ -292,12 +306,22 @@ pub fn run(
 
     // Rely on the environment variable `GIT_DIR`.
     let working = git::raw::Repository::open_from_env()?;
+    let format = ObjectFormat::detect(stored.raw())?;
+
+    // Nothing is written to storage if secrets are found, since pushed
+    // objects cannot be retracted from the network.
+    if let Some(mode) = opts.secret_scan {
+        secrets::check(mode, &specs, &nid, &working, stored)?;
+    }
 
     // For each refspec, push a ref or delete a ref.
     for spec in specs {
         let Ok(cmd) = Command::parse(&spec, &working) else {
             return Err(Error::InvalidCommand(format!("push {spec}")));
         };
+        if let Command::Push(refspec) = &cmd {
+            format.check(&refspec.src)?;
+        }
         let result = match &cmd {
             Command::Delete(dst) => {
                 // Delete refs.
@@ -380,6 +404,9 @@ pub fn run(
                                 Err(e) => canonical::io::handle_error(e)?,
                             }
                         }
+                        if let Some(msg) = canonical::hint(rules, &dst).filter(|_| hints) {
+                            hint(msg);
+                        }
                         Ok(explorer)
                     }
                 }
@@ -389,11 +416,11 @@ pub fn run(
         match result {
             // Let Git tooling know that this ref has been pushed.
             Ok(resource) => {
-                println!("ok {}", cmd.dst());
+                writeln!(output, "ok {}", cmd.dst())?;
                 ok.insert(spec, resource);
             }
             // Let Git tooling know that there was an error pushing the ref.
-            Err(e) => println!("error {} {e}", cmd.dst()),
+            Err(e) => writeln!(output, "error {} {e}", cmd.dst())?,
         }
     }
 
@@ -468,7 +495,7 @@ pub fn run(
     }
 
     // Done.
-    println!();
+    writeln!(output)?;
 
     Ok(())
 }
@@ -476,9 +503,21 @@ pub fn run(
 fn patch_base(
     head: &git::Oid,
     opts: &Options,
+    working: &git::raw::Repository,
     stored: &storage::git::Repository,
 ) -> Result<git::Oid, Error> {
     Ok(if let Some(base) = opts.base {
+        // Otherwise, the patch would contain the commits of the base that
+        // aren't part of the head, eg. if the base is on a divergent branch.
+        if base != *head && !working.graph_descendant_of(head.into(), base.into())? {
+            hint(format!(
+                "the patch base {} is not an ancestor of the pushed commit {}",
+                term::format::oid(base),
+                term::format::oid(*head),
+            ));
+            hint("omit `patch.base` to use the merge base with the default branch instead");
+            return Err(Error::BaseNotAncestor { base, head: *head });
+        }
         base
     } else {
         // Computation of the canonical head is required only if the user
@@ -553,7 +592,7 @@ where
 {
     let temp = TempPatchRef::new(stored, head, nid);
     temp.push(head, opts.verbosity)?;
-    let base = patch_base(head, &opts, stored)?;
+    let base = patch_base(head, &opts, working, stored)?;
 
     if base == *head {
         return Err(Error::EmptyPatch);
@@ -562,14 +601,14 @@ where
     let (title, description) =
         term::patch::get_create_message(opts.message, &stored.backend, &base.into(), &head.into())?;
 
-    let patch = if opts.draft {
+    let mut patch = if opts.draft {
         patches.draft(
             title,
             &description,
             patch::MergeTarget::default(),
             base,
             *head,
-            &[],
+            &opts.labels,
             signer,
         )
     } else {
@@ -579,10 +618,13 @@ where
             patch::MergeTarget::default(),
             base,
             *head,
-            &[],
+            &opts.labels,
             signer,
         )
     }?;
+    if !opts.assignees.is_empty() {
+        patch.assign(opts.assignees, signer)?;
+    }
 
     let action = if patch.is_draft() {
         "drafted"
@@ -692,7 +734,7 @@ where
     let temp = TempPatchRef::new(stored, head, nid);
     temp.push(head, opts.verbosity)?;
 
-    let base = patch_base(head, &opts, stored)?;
+    let base = patch_base(head, &opts, working, stored)?;
 
     // Don't update patch if it already has a matching revision.
     if patch
@@ -2,6 +2,7 @@ use radicle::git;
 use radicle::git::canonical;
 use radicle::git::canonical::effects;
 use radicle::git::canonical::error::QuorumError;
+use radicle::git::canonical::rules::Rules;
 use radicle::git::canonical::QuorumWithConvergence;
 use radicle::prelude::Did;
 
@@ -47,6 +48,17 @@ where
     }
 }
 
+/// Hint about a pushed ref governed by one of the canonical `rules`, if any,
+/// since pushing to it doesn't update the canonical ref on its own.
+pub(crate) fn hint(rules: &Rules, refname: &git::fmt::Qualified) -> Option<String> {
+    let threshold = rules.explain(refname).threshold()?;
+
+    Some(format!(
+        "`{refname}` is canonical: pushing updates your copy, and the canonical \
+        ref follows once {threshold} delegate(s) agree"
+    ))
+}
+
 pub(crate) mod io {
     use radicle::git::canonical::error::QuorumError;
 
@@ -85,3 +97,28 @@ pub(crate) mod io {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use radicle::git::canonical::rules::ValidRule;
+
+    use super::*;
+
+    #[test]
+    fn test_hint() {
+        let did = "did:key:z6MknLWe8A7UJxvTfY36JcB8XrP1KTLb5HFTX38hEmdY3b56"
+            .parse()
+            .unwrap();
+        let rules =
+            [ValidRule::default_branch(did, git::fmt::refname!("master").as_ref()).unwrap()]
+                .into_iter()
+                .collect::<Rules>();
+
+        let msg = hint(&rules, &git::fmt::qualified!("refs/heads/master")).unwrap();
+        assert!(msg.contains("refs/heads/master"));
+        assert!(msg.contains("once 1 delegate(s) agree"));
+
+        assert!(hint(&rules, &git::fmt::qualified!("refs/heads/feature")).is_none());
+        assert!(hint(&rules, &git::fmt::qualified!("refs/tags/v1.0")).is_none());
+    }
+}
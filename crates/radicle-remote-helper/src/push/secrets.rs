@@ -0,0 +1,134 @@
+//! Scanning of pushed commits for secrets, see the `secret-scan` push option.
+use std::collections::HashSet;
+
+use radicle::git;
+use radicle::prelude::NodeId;
+use radicle::security::secrets::SecretMatch;
+use radicle::security::SecretScanner;
+use radicle::storage;
+use radicle::storage::ReadRepository as _;
+use radicle_cli::terminal as term;
+
+use super::{Command, Error};
+use crate::SecretScan;
+
+/// A potential secret found in a pushed commit.
+#[derive(Debug)]
+pub struct Finding {
+    /// Commit adding the secret.
+    pub commit: git::Oid,
+    /// The secret.
+    pub secret: SecretMatch,
+}
+
+/// Scan the commits pushed by the given refspecs for secrets.
+///
+/// Findings are reported to the user. If any are found and the mode is
+/// [`SecretScan::Enforce`], the push is aborted.
+pub(super) fn check(
+    mode: SecretScan,
+    specs: &[String],
+    nid: &NodeId,
+    working: &git::raw::Repository,
+    stored: &storage::git::Repository,
+) -> Result<(), Error> {
+    let mut heads = Vec::new();
+    // Commits reachable from the canonical head are already published.
+    let mut known = stored
+        .canonical_head()
+        .map(|(_, oid)| oid)
+        .into_iter()
+        .collect::<Vec<_>>();
+
+    for spec in specs {
+        let Ok(Command::Push(git::fmt::refspec::Refspec { src, dst, .. })) =
+            Command::parse(spec, working)
+        else {
+            continue;
+        };
+        heads.push(src);
+        known.extend(
+            stored
+                .backend
+                .refname_to_id(nid.to_namespace().join(&dst).as_str())
+                .ok()
+                .map(git::Oid::from),
+        );
+    }
+    let findings = scan(working, heads, known)?;
+    if findings.is_empty() {
+        return Ok(());
+    }
+    let prefix = match mode {
+        SecretScan::Enforce => term::PREFIX_ERROR,
+        SecretScan::Warn => term::PREFIX_WARNING,
+    };
+    for Finding { commit, secret } in &findings {
+        eprintln!(
+            "{prefix} {} found in {}:{} of commit {}: {}",
+            secret.kind,
+            secret.file_path.as_deref().unwrap_or("<unknown>"),
+            secret.line,
+            term::format::oid(*commit),
+            secret.match_text,
+        );
+    }
+    match mode {
+        SecretScan::Enforce => Err(Error::SecretsFound {
+            count: findings.len(),
+        }),
+        SecretScan::Warn => Ok(()),
+    }
+}
+
+/// Scan the blobs added or modified by the commits reachable from any of the
+/// `heads`, but not from any of the `known` commits.
+pub fn scan(
+    working: &git::raw::Repository,
+    heads: impl IntoIterator<Item = git::Oid>,
+    known: impl IntoIterator<Item = git::Oid>,
+) -> Result<Vec<Finding>, Error> {
+    let scanner = SecretScanner::new();
+    let mut walk = working.revwalk()?;
+    let mut blobs = HashSet::new();
+    let mut findings = Vec::new();
+
+    for oid in heads {
+        walk.push(oid.into())?;
+    }
+    for oid in known {
+        // Commits missing from the working copy can't be ancestors of the heads.
+        walk.hide(oid.into()).ok();
+    }
+    for oid in walk {
+        let commit = working.find_commit(oid?)?;
+        let tree = commit.tree()?;
+        let parent = commit.parents().next().map(|p| p.tree()).transpose()?;
+        let diff = working.diff_tree_to_tree(parent.as_ref(), Some(&tree), None)?;
+
+        for delta in diff.deltas() {
+            if delta.status() == git::raw::Delta::Deleted {
+                continue;
+            }
+            let file = delta.new_file();
+            if !blobs.insert(file.id()) {
+                continue;
+            }
+            // Submodules are not blobs.
+            let Ok(blob) = working.find_blob(file.id()) else {
+                continue;
+            };
+            if blob.is_binary() {
+                continue;
+            }
+            let path = file.path().map(|p| p.to_string_lossy().into_owned());
+            let secrets = scanner.scan_reader(blob.content(), path.as_deref())?;
+
+            findings.extend(secrets.into_iter().map(|secret| Finding {
+                commit: git::Oid::from(commit.id()),
+                secret,
+            }));
+        }
+    }
+    Ok(findings)
+}
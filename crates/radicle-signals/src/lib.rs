@@ -4,6 +4,12 @@ mod unix;
 #[cfg(unix)]
 pub use unix::*;
 
+#[cfg(all(unix, any(feature = "tokio", test)))]
+mod stream;
+
+#[cfg(all(unix, any(feature = "tokio", test)))]
+pub use stream::{stream, SignalStream};
+
 /// Operating system signal.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum Signal {
@@ -15,4 +21,8 @@ pub enum Signal {
     Hangup,
     /// `SIGWINCH`.
     WindowChanged,
+    /// `SIGUSR1`.
+    User1,
+    /// `SIGUSR2`.
+    User2,
 }
@@ -0,0 +1,78 @@
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_core::Stream;
+use tokio::signal::unix::{self, SignalKind};
+
+use crate::Signal;
+
+/// Asynchronous stream of signals, see [`stream()`].
+#[derive(Debug)]
+pub struct SignalStream {
+    listeners: Vec<(Signal, unix::Signal)>,
+}
+
+impl Stream for SignalStream {
+    type Item = Signal;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut closed = 0;
+
+        for (signal, listener) in self.listeners.iter_mut() {
+            match listener.poll_recv(cx) {
+                Poll::Ready(Some(())) => return Poll::Ready(Some(*signal)),
+                Poll::Ready(None) => closed += 1,
+                Poll::Pending => {}
+            }
+        }
+
+        if closed == self.listeners.len() {
+            Poll::Ready(None)
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+/// Listen for signals using the signal driver of the current `tokio` runtime,
+/// and deliver them as a [`Stream`].
+///
+/// This is an alternative to [`crate::install`] for consumers that use `tokio`,
+/// and must be called from within a runtime. Unlike [`crate::install`], it
+/// can be called multiple times, and every stream receives every signal.
+pub fn stream() -> io::Result<SignalStream> {
+    let kinds = [
+        (Signal::Interrupt, SignalKind::interrupt()),
+        (Signal::Terminate, SignalKind::terminate()),
+        (Signal::Hangup, SignalKind::hangup()),
+        (Signal::WindowChanged, SignalKind::window_change()),
+        (Signal::User1, SignalKind::user_defined1()),
+        (Signal::User2, SignalKind::user_defined2()),
+    ];
+    let listeners = kinds
+        .into_iter()
+        .map(|(signal, kind)| Ok((signal, unix::signal(kind)?)))
+        .collect::<io::Result<_>>()?;
+
+    Ok(SignalStream { listeners })
+}
+
+#[cfg(test)]
+mod test {
+    use std::future;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn hangup() {
+        let mut signals = stream().unwrap();
+
+        // SAFETY: `raise` is safe to call, and the signal is handled by
+        // the listeners installed above.
+        assert_eq!(unsafe { libc::raise(libc::SIGHUP) }, 0);
+
+        let signal = future::poll_fn(|cx| Pin::new(&mut signals).poll_next(cx)).await;
+        assert_eq!(signal, Some(Signal::Hangup));
+    }
+}
@@ -21,6 +21,8 @@ impl TryFrom<SignalNumber> for Signal {
             libc::SIGINT => Ok(Self::Interrupt),
             libc::SIGWINCH => Ok(Self::WindowChanged),
             libc::SIGHUP => Ok(Self::Hangup),
+            libc::SIGUSR1 => Ok(Self::User1),
+            libc::SIGUSR2 => Ok(Self::User2),
             _ => Err(value),
         }
     }
@@ -32,6 +34,8 @@ signals_receipts::channel_notify_facility! {
     SIGTERM
     SIGHUP
     SIGWINCH
+    SIGUSR1
+    SIGUSR2
 }
 
 /// Install global signal handlers, with notifications sent to the given
@@ -1,7 +1,7 @@
-use std::env::{var, VarError::*};
+use std::env::{var, var_os, VarError, VarError::*};
 use std::ffi::OsString;
-use std::fmt;
-use std::path::{is_separator, PathBuf};
+use std::path::{is_separator, Path, PathBuf};
+use std::{fmt, fs, io};
 
 const CREDENTIALS_DIRECTORY: &str = "CREDENTIALS_DIRECTORY";
 
@@ -13,13 +13,18 @@ const CREDENTIALS_DIRECTORY: &str = "CREDENTIALS_DIRECTORY";
 /// Absence of the environment variable and inexistence of the file are handled
 /// gracefully returning `Ok(None)`.
 pub fn path(id: &str) -> Result<Option<PathBuf>, PathError> {
+    path_in(id, var(CREDENTIALS_DIRECTORY))
+}
+
+/// Like [`path`], taking the value of `CREDENTIALS_DIRECTORY` as `env`.
+fn path_in(id: &str, env: Result<String, VarError>) -> Result<Option<PathBuf>, PathError> {
     use PathError::*;
 
     if id.contains(is_separator) {
         return Err(InvalidCredentialId { id: id.to_owned() });
     }
 
-    let credential = match var(CREDENTIALS_DIRECTORY) {
+    let credential = match env {
         Err(NotUnicode(os)) => return Err(EnvVarNotUnicode { os }),
         Err(NotPresent) => return Ok(None),
         Ok(env) => PathBuf::from(env).join(id),
@@ -28,6 +33,113 @@ pub fn path(id: &str) -> Result<Option<PathBuf>, PathError> {
     Ok(credential.exists().then_some(credential))
 }
 
+/// Takes a systemd credential ID, and returns the path of the file
+/// corresponding to the credential, looking in the directory given by the
+/// environment variable `CREDENTIALS_DIRECTORY` first, and then in each of
+/// the `fallbacks` directories, in order.
+///
+/// Fallbacks allow for credentials to be provided without systemd, eg. during
+/// local development.
+pub fn resolve(id: &str, fallbacks: &[PathBuf]) -> Result<PathBuf, CredentialError> {
+    resolve_in(
+        id,
+        var_os(CREDENTIALS_DIRECTORY).map(PathBuf::from),
+        fallbacks,
+    )
+}
+
+/// Like [`resolve`], taking the systemd credentials directory as `systemd`.
+fn resolve_in(
+    id: &str,
+    systemd: Option<PathBuf>,
+    fallbacks: &[PathBuf],
+) -> Result<PathBuf, CredentialError> {
+    use CredentialError::*;
+
+    if id.contains(is_separator) {
+        return Err(InvalidCredentialId { id: id.to_owned() });
+    }
+
+    let dirs = systemd
+        .into_iter()
+        .chain(fallbacks.iter().cloned())
+        .collect::<Vec<_>>();
+    if dirs.is_empty() {
+        return Err(DirectoryUnset);
+    }
+
+    for dir in &dirs {
+        let credential = dir.join(id);
+        match fs::metadata(&credential) {
+            Ok(_) => return Ok(credential),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+            Err(source) => {
+                return Err(Io {
+                    path: credential,
+                    source,
+                })
+            }
+        }
+    }
+
+    Err(NotFound {
+        id: id.to_owned(),
+        searched: dirs,
+    })
+}
+
+/// Returns the IDs of all credentials in the directory given by the environment
+/// variable `CREDENTIALS_DIRECTORY`, in lexicographic order.
+///
+/// Absence of the environment variable is handled gracefully returning an
+/// empty list.
+pub fn list() -> io::Result<Vec<String>> {
+    match var_os(CREDENTIALS_DIRECTORY) {
+        None => Ok(Vec::new()),
+        Some(dir) => ids(Path::new(&dir)),
+    }
+}
+
+/// Takes a systemd credential ID, and reads the contents of the file
+/// corresponding to the credential, see [`path`].
+///
+/// Absence of the environment variable and inexistence of the file are handled
+/// gracefully returning `Ok(None)`.
+pub fn read(id: &str) -> io::Result<Option<Vec<u8>>> {
+    read_in(id, var(CREDENTIALS_DIRECTORY))
+}
+
+/// Like [`read`], taking the value of `CREDENTIALS_DIRECTORY` as `env`.
+fn read_in(id: &str, env: Result<String, VarError>) -> io::Result<Option<Vec<u8>>> {
+    let Some(path) = path_in(id, env).map_err(io::Error::other)? else {
+        return Ok(None);
+    };
+
+    match fs::read(path) {
+        Ok(bytes) => Ok(Some(bytes)),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+/// Returns the names of all files in `dir` that are valid Unicode.
+fn ids(dir: &Path) -> io::Result<Vec<String>> {
+    let mut ids = Vec::new();
+
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        if !entry.file_type()?.is_file() {
+            continue;
+        }
+        if let Ok(id) = entry.file_name().into_string() {
+            ids.push(id);
+        }
+    }
+    ids.sort();
+
+    Ok(ids)
+}
+
 /// The error returned by [`path`].
 #[derive(Debug)]
 pub enum PathError {
@@ -44,3 +156,136 @@ impl fmt::Display for PathError {
 	}
     }
 }
+
+impl std::error::Error for PathError {}
+
+/// The error returned by [`resolve`].
+#[derive(Debug)]
+pub enum CredentialError {
+    /// The credential ID is not a valid file name.
+    InvalidCredentialId { id: String },
+    /// Neither `CREDENTIALS_DIRECTORY` nor any fallback directory is set.
+    DirectoryUnset,
+    /// The credential is in none of the directories searched.
+    NotFound { id: String, searched: Vec<PathBuf> },
+    /// The file corresponding to the credential could not be accessed.
+    Io { path: PathBuf, source: io::Error },
+}
+
+impl fmt::Display for CredentialError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use CredentialError::*;
+        match self {
+            InvalidCredentialId { id } => write!(f, "The systemd credential ID '{id}' is invalid."),
+            DirectoryUnset => write!(f, "The environment variable '{CREDENTIALS_DIRECTORY}' is not set, and there are no fallback directories."),
+            NotFound { id, searched } => {
+                write!(f, "The credential '{id}' was not found in any of")?;
+                for (i, dir) in searched.iter().enumerate() {
+                    let sep = if i == 0 { " " } else { ", " };
+                    write!(f, "{sep}'{}'", dir.display())?;
+                }
+                write!(f, ".")
+            }
+            Io { path, source } => write!(f, "The credential file '{}' could not be accessed: {source}", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for CredentialError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CredentialError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn list_and_read() {
+        let tmp = tempfile::tempdir().unwrap();
+        fs::write(tmp.path().join("xyz.radicle.passphrase"), b"secret").unwrap();
+        fs::write(tmp.path().join("xyz.radicle.key"), b"key").unwrap();
+        fs::create_dir(tmp.path().join("dir")).unwrap();
+
+        // The environment is not modified, since tests run in parallel.
+        let env = || Ok(tmp.path().to_str().unwrap().to_owned());
+
+        assert_eq!(read_in("xyz.radicle.key", Err(NotPresent)).unwrap(), None);
+        assert_eq!(
+            ids(tmp.path()).unwrap(),
+            vec![
+                "xyz.radicle.key".to_owned(),
+                "xyz.radicle.passphrase".to_owned()
+            ]
+        );
+        assert_eq!(
+            read_in("xyz.radicle.passphrase", env()).unwrap(),
+            Some(b"secret".to_vec())
+        );
+        assert_eq!(read_in("xyz.radicle.missing", env()).unwrap(), None);
+        assert_eq!(
+            read_in("../xyz.radicle.key", env()).unwrap_err().kind(),
+            io::ErrorKind::Other
+        );
+        assert!(matches!(
+            path_in("xyz.radicle.key", Err(NotUnicode(OsString::from("x")))),
+            Err(PathError::EnvVarNotUnicode { .. })
+        ));
+    }
+
+    #[test]
+    fn resolve() {
+        let systemd = tempfile::tempdir().unwrap();
+        let fallback = tempfile::tempdir().unwrap();
+        let fallbacks = [fallback.path().to_path_buf()];
+        let dir = Some(systemd.path().to_path_buf());
+        fs::write(systemd.path().join("xyz.radicle.key"), b"key").unwrap();
+        fs::write(fallback.path().join("xyz.radicle.key"), b"key").unwrap();
+        fs::write(fallback.path().join("xyz.radicle.passphrase"), b"secret").unwrap();
+
+        assert!(matches!(
+            resolve_in("xyz.radicle.key", None, &[]),
+            Err(CredentialError::DirectoryUnset)
+        ));
+        assert!(matches!(
+            resolve_in("../xyz.radicle.key", dir.clone(), &fallbacks),
+            Err(CredentialError::InvalidCredentialId { .. })
+        ));
+        // Without systemd, only the fallbacks are searched.
+        assert_eq!(
+            resolve_in("xyz.radicle.key", None, &fallbacks).unwrap(),
+            fallback.path().join("xyz.radicle.key")
+        );
+        // The systemd directory takes precedence.
+        assert_eq!(
+            resolve_in("xyz.radicle.key", dir.clone(), &fallbacks).unwrap(),
+            systemd.path().join("xyz.radicle.key")
+        );
+        assert_eq!(
+            resolve_in("xyz.radicle.passphrase", dir.clone(), &fallbacks).unwrap(),
+            fallback.path().join("xyz.radicle.passphrase")
+        );
+        match resolve_in("xyz.radicle.missing", dir, &fallbacks) {
+            Err(CredentialError::NotFound { id, searched }) => {
+                assert_eq!(id, "xyz.radicle.missing");
+                assert_eq!(
+                    searched,
+                    vec![systemd.path().to_path_buf(), fallback.path().to_path_buf()]
+                );
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+
+        // A directory that is not a directory can't be searched, which is an
+        // error rather than being skipped.
+        let file = systemd.path().join("xyz.radicle.key");
+        assert!(matches!(
+            resolve_in("xyz.radicle.key", Some(file), &fallbacks),
+            Err(CredentialError::Io { .. })
+        ));
+    }
+}
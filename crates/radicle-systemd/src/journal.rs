@@ -1,20 +1,665 @@
-use systemd_journal_logger::{connected_to_journal, JournalLog};
+use std::env::var_os;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Write as _};
+use std::mem::size_of;
+use std::os::fd::{AsFd as _, AsRawFd as _, FromRawFd as _, RawFd};
+use std::os::unix::fs::MetadataExt as _;
+use std::os::unix::net::UnixDatagram;
+use std::path::PathBuf;
+
+use log::kv;
+
+/// Path of the socket that the journal listens on for its native protocol.
+/// See <https://systemd.io/JOURNAL_NATIVE_PROTOCOL/>.
+const JOURNAL_SOCKET: &str = "/run/systemd/journal/socket";
+
+/// Maximum length of a journal field name.
+const FIELD_NAME_MAX_LEN: usize = 64;
+
+/// Builds a logger that writes to the systemd journal.
+///
+/// Every entry carries the fields `PRIORITY`, `MESSAGE`, `SYSLOG_IDENTIFIER`,
+/// `TARGET`, and, if known, `CODE_MODULE`, `CODE_FILE` and `CODE_LINE`,
+/// followed by the static fields given via [`Builder::field`], and the
+/// structured fields of the [`log::Record`] (see [`Builder::record_fields`]).
+///
+/// Field names are uppercased, and characters that are not allowed in field
+/// names are replaced with `_`, such that e.g. the key `rid` can be queried via
+/// `journalctl RID=...`.
+pub struct Builder {
+    identifier: String,
+    fields: Vec<(String, Vec<u8>)>,
+    record_fields: bool,
+    priority: fn(log::Level) -> Priority,
+    socket: PathBuf,
+}
+
+impl Builder {
+    /// Start building a logger that uses `identifier` as `SYSLOG_IDENTIFIER`.
+    pub fn new(identifier: impl Into<String>) -> Self {
+        Self {
+            identifier: identifier.into(),
+            fields: Vec::new(),
+            record_fields: true,
+            priority: Priority::from,
+            socket: PathBuf::from(JOURNAL_SOCKET),
+        }
+    }
+
+    /// Add a field that is attached to every entry.
+    pub fn field(mut self, key: impl AsRef<str>, value: impl AsRef<[u8]>) -> Self {
+        self.fields
+            .push((field_name(key.as_ref()), value.as_ref().to_vec()));
+        self
+    }
+
+    /// Add fields that are attached to every entry.
+    pub fn fields<K, V, I>(self, fields: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: AsRef<[u8]>,
+    {
+        fields
+            .into_iter()
+            .fold(self, |builder, (key, value)| builder.field(key, value))
+    }
+
+    /// Whether to attach the structured fields of each record, i.e.
+    /// [`log::Record::key_values`], to its entry. Enabled by default.
+    pub fn record_fields(mut self, enabled: bool) -> Self {
+        self.record_fields = enabled;
+        self
+    }
+
+    /// Map log levels to the `PRIORITY` of entries with `map`, instead of
+    /// [`Priority::from`], eg. to log debug messages as notices.
+    pub fn priority(mut self, map: fn(log::Level) -> Priority) -> Self {
+        self.priority = map;
+        self
+    }
+
+    /// Use the socket at `path` instead of the default socket of the journal.
+    pub fn socket(mut self, path: impl Into<PathBuf>) -> Self {
+        self.socket = path.into();
+        self
+    }
+
+    pub fn build(self) -> io::Result<Logger> {
+        Ok(Logger {
+            socket: UnixDatagram::unbound()?,
+            path: self.socket,
+            identifier: self.identifier,
+            fields: self.fields,
+            record_fields: self.record_fields,
+            priority: self.priority,
+        })
+    }
+}
+
+/// A logger that writes to the systemd journal, see [`Builder`].
+pub struct Logger {
+    socket: UnixDatagram,
+    path: PathBuf,
+    identifier: String,
+    fields: Vec<(String, Vec<u8>)>,
+    record_fields: bool,
+    priority: fn(log::Level) -> Priority,
+}
+
+impl Logger {
+    /// Serialize `record` according to the native protocol of the journal.
+    fn serialize(&self, record: &log::Record) -> Vec<u8> {
+        let mut buf = Vec::new();
+
+        let priority = (self.priority)(record.level());
+
+        put(&mut buf, "PRIORITY", priority.as_str().as_bytes());
+        put(&mut buf, "MESSAGE", record.args().to_string().as_bytes());
+        put(&mut buf, "SYSLOG_IDENTIFIER", self.identifier.as_bytes());
+        put(&mut buf, "TARGET", record.target().as_bytes());
+        if let Some(module) = record.module_path() {
+            put(&mut buf, "CODE_MODULE", module.as_bytes());
+        }
+        if let Some(file) = record.file() {
+            put(&mut buf, "CODE_FILE", file.as_bytes());
+        }
+        if let Some(line) = record.line() {
+            put(&mut buf, "CODE_LINE", line.to_string().as_bytes());
+        }
+        for (name, value) in &self.fields {
+            put(&mut buf, name, value);
+        }
+        if self.record_fields {
+            struct Visitor<'a>(&'a mut Vec<u8>);
+
+            impl<'kvs> kv::VisitSource<'kvs> for Visitor<'_> {
+                fn visit_pair(
+                    &mut self,
+                    key: kv::Key<'kvs>,
+                    value: kv::Value<'kvs>,
+                ) -> Result<(), kv::Error> {
+                    put(
+                        self.0,
+                        &field_name(key.as_str()),
+                        value.to_string().as_bytes(),
+                    );
+                    Ok(())
+                }
+            }
+
+            record.key_values().visit(&mut Visitor(&mut buf)).ok();
+        }
+
+        buf
+    }
+
+    fn send(&self, payload: &[u8]) -> io::Result<()> {
+        match self.socket.send_to(payload, &self.path) {
+            Err(err)
+                if err.raw_os_error() == Some(libc::EMSGSIZE)
+                    || err.raw_os_error() == Some(libc::ENOBUFS) =>
+            {
+                self.send_memfd(payload)
+            }
+            result => result.map(|_| ()),
+        }
+    }
+
+    /// Send a payload that is too large for a single datagram, by writing it
+    /// to a sealed memory file and passing its file descriptor instead.
+    fn send_memfd(&self, payload: &[u8]) -> io::Result<()> {
+        // SAFETY: The name is a valid C string.
+        let fd = unsafe {
+            libc::memfd_create(
+                c"radicle-journal".as_ptr(),
+                libc::MFD_CLOEXEC | libc::MFD_ALLOW_SEALING,
+            )
+        };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // SAFETY: `fd` was just created, and is owned by `file` from now on.
+        let mut file = unsafe { File::from_raw_fd(fd) };
+        file.write_all(payload)?;
+
+        let seals =
+            libc::F_SEAL_SHRINK | libc::F_SEAL_GROW | libc::F_SEAL_WRITE | libc::F_SEAL_SEAL;
+        // SAFETY: `fd` is a valid memory file descriptor.
+        if unsafe { libc::fcntl(fd, libc::F_ADD_SEALS, seals) } < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let socket = UnixDatagram::unbound()?;
+        socket.connect(&self.path)?;
+
+        send_fd(socket.as_raw_fd(), file.as_raw_fd())
+    }
+}
+
+impl log::Log for Logger {
+    fn enabled(&self, _metadata: &log::Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &log::Record) {
+        // There is nowhere to report failure to.
+        self.send(&self.serialize(record)).ok();
+    }
+
+    fn flush(&self) {}
+}
 
 /// If the current process is directly connected to the systemd journal,
 /// return a logger that will write to it.
-pub fn logger<K, V, I>(identifier: String, extra_fields: I) -> std::io::Result<Box<dyn log::Log>>
+pub fn logger<K, V, I>(identifier: String, extra_fields: I) -> io::Result<Box<dyn log::Log>>
 where
     I: IntoIterator<Item = (K, V)>,
     K: AsRef<str>,
     V: AsRef<[u8]>,
 {
     Ok(Box::new(
-        JournalLog::new()?
-            .with_syslog_identifier(identifier)
-            .with_extra_fields(extra_fields),
+        Builder::new(identifier).fields(extra_fields).build()?,
     ))
 }
 
+/// Checks whether the standard error of the current process is connected to
+/// the journal, ie. the process was started by systemd with its output going to
+/// the journal. Use [`status`] to also check whether the socket is reachable.
 pub fn connected() -> bool {
-    connected_to_journal()
+    stream().unwrap_or(false)
+}
+
+/// Diagnose the connection of the current process to the journal, using its
+/// default socket.
+pub fn status() -> JournalStatus {
+    status_at(JOURNAL_SOCKET)
+}
+
+/// Diagnose the connection of the current process to the journal, using the
+/// socket at `path`.
+pub fn status_at(path: impl Into<PathBuf>) -> JournalStatus {
+    let mut status = JournalStatus {
+        socket: path.into(),
+        exists: false,
+        reachable: false,
+        stream: false,
+        error: None,
+    };
+
+    match status.socket.metadata() {
+        Ok(_) => status.exists = true,
+        Err(err) => status.error = Some(err),
+    }
+    if status.exists {
+        match UnixDatagram::unbound().and_then(|socket| socket.connect(&status.socket)) {
+            Ok(()) => status.reachable = true,
+            Err(err) => status.error = Some(err),
+        }
+    }
+    match stream() {
+        Ok(stream) => status.stream = stream,
+        Err(err) => {
+            status.error.get_or_insert(err);
+        }
+    }
+    status
+}
+
+/// The connection of the current process to the journal, see [`status`].
+#[derive(Debug)]
+pub struct JournalStatus {
+    /// Path of the socket of the journal.
+    pub socket: PathBuf,
+    /// Whether the socket exists.
+    pub exists: bool,
+    /// Whether the socket could be connected to.
+    pub reachable: bool,
+    /// Whether standard error is connected to the journal, according to the
+    /// environment variable `JOURNAL_STREAM`.
+    pub stream: bool,
+    /// The first error encountered, if any.
+    pub error: Option<io::Error>,
+}
+
+impl JournalStatus {
+    /// Whether entries can be written to the socket, and the standard error of
+    /// the current process is connected to the journal, ie. the process was
+    /// started by systemd with its output going to the journal.
+    pub fn is_connected(&self) -> bool {
+        self.reachable && self.stream
+    }
+}
+
+impl fmt::Display for JournalStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let socket = self.socket.display();
+
+        if !self.exists {
+            write!(f, "journal socket {socket} not found")?;
+        } else if !self.reachable {
+            write!(f, "journal socket {socket} is not reachable")?;
+        } else if !self.stream {
+            write!(
+                f,
+                "standard error is not connected to the journal (see `JOURNAL_STREAM`)"
+            )?;
+        } else {
+            write!(f, "connected to journal socket {socket}")?;
+        }
+        if let Some(err) = &self.error {
+            write!(f, ": {err}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Checks whether the standard error of the current process is connected to
+/// the journal, by comparing it to the environment variable `JOURNAL_STREAM`.
+/// See <https://www.freedesktop.org/software/systemd/man/254/systemd.exec.html#%24JOURNAL_STREAM>.
+fn stream() -> io::Result<bool> {
+    let Some(stream) = var_os("JOURNAL_STREAM") else {
+        return Ok(false);
+    };
+    let Some((dev, ino)) = stream.to_str().and_then(|s| s.split_once(':')) else {
+        return Ok(false);
+    };
+    let metadata = io::stderr()
+        .as_fd()
+        .try_clone_to_owned()
+        .and_then(|fd| File::from(fd).metadata())?;
+
+    Ok(dev.parse() == Ok(metadata.dev()) && ino.parse() == Ok(metadata.ino()))
+}
+
+/// Syslog priorities, see `syslog(3)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Priority {
+    Emergency = 0,
+    Alert = 1,
+    Critical = 2,
+    Error = 3,
+    Warning = 4,
+    Notice = 5,
+    Info = 6,
+    Debug = 7,
+}
+
+impl Priority {
+    /// The value of the `PRIORITY` field.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Emergency => "0",
+            Self::Alert => "1",
+            Self::Critical => "2",
+            Self::Error => "3",
+            Self::Warning => "4",
+            Self::Notice => "5",
+            Self::Info => "6",
+            Self::Debug => "7",
+        }
+    }
+}
+
+/// The default mapping of log levels to priorities, which shifts all but
+/// errors and warnings down by one, eg. info messages are logged as notices.
+impl From<log::Level> for Priority {
+    fn from(level: log::Level) -> Self {
+        match level {
+            log::Level::Error => Self::Error,
+            log::Level::Warn => Self::Warning,
+            log::Level::Info => Self::Notice,
+            log::Level::Debug => Self::Info,
+            log::Level::Trace => Self::Debug,
+        }
+    }
+}
+
+/// Turn `key` into a valid journal field name, i.e. at most 64 uppercase
+/// letters, digits and underscores, not starting with a digit or an underscore.
+fn field_name(key: &str) -> String {
+    let mut name = key
+        .chars()
+        .map(|c| match c.to_ascii_uppercase() {
+            c @ ('A'..='Z' | '0'..='9') => c,
+            _ => '_',
+        })
+        .collect::<String>();
+
+    if !name.starts_with(|c: char| c.is_ascii_uppercase()) {
+        name.insert_str(0, "ESCAPED_");
+    }
+    name.truncate(FIELD_NAME_MAX_LEN);
+    name
+}
+
+/// Append a field to `buf`. Values that contain newlines are framed with their
+/// length, as a little-endian 64-bit integer.
+fn put(buf: &mut Vec<u8>, name: &str, value: &[u8]) {
+    buf.extend_from_slice(name.as_bytes());
+    if value.contains(&b'\n') {
+        buf.push(b'\n');
+        buf.extend_from_slice(&(value.len() as u64).to_le_bytes());
+    } else {
+        buf.push(b'=');
+    }
+    buf.extend_from_slice(value);
+    buf.push(b'\n');
+}
+
+/// Send `fd` over the connected socket `socket`, without any payload.
+fn send_fd(socket: RawFd, fd: RawFd) -> io::Result<()> {
+    // SAFETY: `CMSG_SPACE` has no preconditions.
+    let space = unsafe { libc::CMSG_SPACE(size_of::<RawFd>() as u32) } as usize;
+    // Use `u64` for alignment of the control message header.
+    let mut control = vec![0u64; space.div_ceil(size_of::<u64>())];
+
+    // SAFETY: All-zero is a valid `msghdr`.
+    let mut msg: libc::msghdr = unsafe { std::mem::zeroed() };
+    msg.msg_control = control.as_mut_ptr().cast();
+    msg.msg_controllen = space as _;
+
+    // SAFETY: `msg_control` points to a buffer of `msg_controllen` bytes,
+    // which is large enough for one control message carrying one file
+    // descriptor.
+    unsafe {
+        let cmsg = libc::CMSG_FIRSTHDR(&msg);
+        (*cmsg).cmsg_level = libc::SOL_SOCKET;
+        (*cmsg).cmsg_type = libc::SCM_RIGHTS;
+        (*cmsg).cmsg_len = libc::CMSG_LEN(size_of::<RawFd>() as u32) as _;
+        std::ptr::write_unaligned(libc::CMSG_DATA(cmsg).cast::<RawFd>(), fd);
+    }
+
+    // SAFETY: `socket` is a valid file descriptor and `msg` is initialized.
+    if unsafe { libc::sendmsg(socket, &msg, 0) } < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use log::Log as _;
+
+    use super::*;
+
+    fn receive(logger: &Logger, receiver: &UnixDatagram, record: &log::Record) -> Vec<u8> {
+        logger.log(record);
+
+        let mut buf = vec![0u8; 4096];
+        let n = receiver.recv(&mut buf).unwrap();
+        buf.truncate(n);
+        buf
+    }
+
+    fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+        haystack.windows(needle.len()).any(|w| w == needle)
+    }
+
+    #[test]
+    fn record_fields() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("socket");
+        let receiver = UnixDatagram::bind(&path).unwrap();
+        let logger = Builder::new("radicle-node")
+            .field("nid", "z6Mk")
+            .socket(&path)
+            .build()
+            .unwrap();
+
+        let kvs = [("rid", "rad:z3gqcJUoA1n9HaHKufZs5FCSGazv5")];
+        let datagram = receive(
+            &logger,
+            &receiver,
+            &log::Record::builder()
+                .args(format_args!("Fetched"))
+                .level(log::Level::Info)
+                .target("node")
+                .key_values(&kvs)
+                .build(),
+        );
+
+        assert!(contains(&datagram, b"PRIORITY=5\n"));
+        assert!(contains(&datagram, b"MESSAGE=Fetched\n"));
+        assert!(contains(&datagram, b"SYSLOG_IDENTIFIER=radicle-node\n"));
+        assert!(contains(&datagram, b"NID=z6Mk\n"));
+        assert!(contains(
+            &datagram,
+            b"RID=rad:z3gqcJUoA1n9HaHKufZs5FCSGazv5\n"
+        ));
+    }
+
+    #[test]
+    fn record_fields_disabled() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("socket");
+        let receiver = UnixDatagram::bind(&path).unwrap();
+        let logger = Builder::new("radicle-node")
+            .record_fields(false)
+            .socket(&path)
+            .build()
+            .unwrap();
+
+        let kvs = [("rid", "rad:z3gqcJUoA1n9HaHKufZs5FCSGazv5")];
+        let datagram = receive(
+            &logger,
+            &receiver,
+            &log::Record::builder()
+                .args(format_args!("Fetched"))
+                .key_values(&kvs)
+                .build(),
+        );
+
+        assert!(!contains(&datagram, b"RID="));
+    }
+
+    #[test]
+    fn priority() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("socket");
+        let receiver = UnixDatagram::bind(&path).unwrap();
+        let record = |level: log::Level| {
+            let logger = Builder::new("radicle-node")
+                .priority(|level| match level {
+                    log::Level::Debug | log::Level::Trace => Priority::Notice,
+                    level => Priority::from(level),
+                })
+                .socket(&path)
+                .build()
+                .unwrap();
+
+            receive(
+                &logger,
+                &receiver,
+                &log::Record::builder()
+                    .args(format_args!("Fetching"))
+                    .level(level)
+                    .build(),
+            )
+        };
+
+        assert!(contains(&record(log::Level::Debug), b"PRIORITY=5\n"));
+        assert!(contains(&record(log::Level::Trace), b"PRIORITY=5\n"));
+        assert!(contains(&record(log::Level::Error), b"PRIORITY=3\n"));
+    }
+
+    #[test]
+    fn status_not_present() {
+        let tmp = tempfile::tempdir().unwrap();
+        let status = status_at(tmp.path().join("socket"));
+
+        assert!(!status.exists);
+        assert!(!status.reachable);
+        assert!(!status.is_connected());
+        assert_eq!(
+            status.error.as_ref().map(|err| err.kind()),
+            Some(io::ErrorKind::NotFound)
+        );
+        assert!(status.to_string().starts_with("journal socket "));
+    }
+
+    #[test]
+    fn status_present() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("socket");
+        let _receiver = UnixDatagram::bind(&path).unwrap();
+        let status = status_at(&path);
+
+        assert!(status.exists);
+        assert!(status.reachable);
+        assert_eq!(status.socket, path);
+        // Whether `JOURNAL_STREAM` matches depends on how the tests are run.
+        assert_eq!(status.is_connected(), status.stream);
+    }
+
+    #[test]
+    fn status_not_a_socket() {
+        let tmp = tempfile::tempdir().unwrap();
+        let status = status_at(tmp.path());
+
+        assert!(status.exists);
+        assert!(!status.reachable);
+        assert!(status.error.is_some());
+    }
+
+    #[test]
+    fn newline() {
+        let mut buf = Vec::new();
+        put(&mut buf, "MESSAGE", b"a\nb");
+
+        let mut expected = b"MESSAGE\n".to_vec();
+        expected.extend_from_slice(&3u64.to_le_bytes());
+        expected.extend_from_slice(b"a\nb\n");
+        assert_eq!(buf, expected);
+    }
+
+    /// Receive a file descriptor sent with [`send_fd`] over `socket`.
+    fn receive_fd(socket: &UnixDatagram) -> File {
+        // SAFETY: `CMSG_SPACE` has no preconditions.
+        let space = unsafe { libc::CMSG_SPACE(size_of::<RawFd>() as u32) } as usize;
+        let mut control = vec![0u64; space.div_ceil(size_of::<u64>())];
+        // SAFETY: All-zero is a valid `msghdr`.
+        let mut msg: libc::msghdr = unsafe { std::mem::zeroed() };
+        msg.msg_control = control.as_mut_ptr().cast();
+        msg.msg_controllen = space as _;
+
+        // SAFETY: `msg_control` points to a buffer of `msg_controllen` bytes.
+        let n = unsafe { libc::recvmsg(socket.as_raw_fd(), &mut msg, 0) };
+        assert!(n >= 0, "{}", io::Error::last_os_error());
+
+        // SAFETY: A control message carrying one file descriptor was received.
+        unsafe {
+            let cmsg = libc::CMSG_FIRSTHDR(&msg);
+            assert!(!cmsg.is_null());
+            assert_eq!((*cmsg).cmsg_type, libc::SCM_RIGHTS);
+
+            File::from_raw_fd(std::ptr::read_unaligned(
+                libc::CMSG_DATA(cmsg).cast::<RawFd>(),
+            ))
+        }
+    }
+
+    #[test]
+    fn too_large() {
+        use std::io::{Read as _, Seek as _};
+
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("socket");
+        let receiver = UnixDatagram::bind(&path).unwrap();
+        let logger = Builder::new("radicle-node").socket(&path).build().unwrap();
+        // Larger than the default maximum size of datagrams.
+        let message = "a".repeat(8 * 1024 * 1024);
+
+        logger.log(
+            &log::Record::builder()
+                .args(format_args!("{message}"))
+                .level(log::Level::Info)
+                .build(),
+        );
+
+        // The file offset is shared with the sender, so read from the start.
+        let mut entry = Vec::new();
+        let mut file = receive_fd(&receiver);
+        file.seek(io::SeekFrom::Start(0)).unwrap();
+        file.read_to_end(&mut entry).unwrap();
+
+        let expected = format!("MESSAGE={message}\n");
+        let start = entry.windows(8).position(|w| w == b"MESSAGE=").unwrap();
+        assert_eq!(
+            entry.get(start..start + expected.len()),
+            Some(expected.as_bytes())
+        );
+        assert!(contains(&entry, b"SYSLOG_IDENTIFIER=radicle-node\n"));
+    }
+
+    #[test]
+    fn field_names() {
+        assert_eq!(field_name("rid"), "RID");
+        assert_eq!(field_name("peer.addr"), "PEER_ADDR");
+        assert_eq!(field_name("_hidden"), "ESCAPED__HIDDEN");
+        assert_eq!(field_name("0day"), "ESCAPED_0DAY");
+        assert_eq!(field_name(&"a".repeat(100)).len(), FIELD_NAME_MAX_LEN);
+    }
 }
@@ -6,4 +6,10 @@ pub mod journal;
 #[cfg(all(feature = "listen", unix))]
 pub mod listen;
 
+#[cfg(all(feature = "notify", target_os = "linux"))]
+pub mod notify;
+
+#[cfg(all(feature = "watchdog", target_os = "linux"))]
+pub mod watchdog;
+
 pub mod credential;
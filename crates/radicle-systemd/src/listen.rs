@@ -1,4 +1,5 @@
 use std::env::{remove_var, var, VarError};
+use std::io;
 use std::os::fd::RawFd;
 use std::process::id;
 
@@ -37,3 +38,153 @@ pub fn fd(name: &str) -> Result<Option<RawFd>, VarError> {
 
     fd
 }
+
+/// Returns all file descriptors passed by systemd, in order, i.e. starting at
+/// [`SD_LISTEN_FDS_START`]. This is useful if more than one socket is
+/// configured in the socket unit, e.g. one for IPv4 and one for IPv6.
+/// If `LISTEN_PID` is not present, or does not match the current process, no
+/// file descriptors are returned. Unlike [`fd`], this does not modify the
+/// environment.
+/// This is a specialization of [`sd_listen_fds(3)`](man:sd_listen_fds(3)).
+/// See:
+///  - <https://www.freedesktop.org/software/systemd/man/254/sd_listen_fds.html>
+pub fn fds() -> io::Result<Vec<RawFd>> {
+    let pid = match var(LISTEN_PID) {
+        Err(VarError::NotPresent) => return Ok(Vec::new()),
+        Err(err) => return Err(invalid(LISTEN_PID, err)),
+        Ok(pid) => pid,
+    };
+    let fds = var(LISTEN_FDS).map_err(|err| invalid(LISTEN_FDS, err))?;
+
+    parse(&pid, id(), &fds)
+}
+
+/// Like [`fds`], but also returns the name of each file descriptor, as set via
+/// `FileDescriptorName=` in the socket unit and passed in `LISTEN_FDNAMES`.
+/// If `LISTEN_FDNAMES` is not present, or does not contain one name per file
+/// descriptor, all names are [`Option::None`].
+/// This is a specialization of [`sd_listen_fds_with_names(3)`](man:sd_listen_fds_with_names(3)).
+pub fn named_fds() -> io::Result<Vec<(Option<String>, RawFd)>> {
+    let fds = fds()?;
+    let names = match var(LISTEN_FDNAMES) {
+        Err(VarError::NotPresent) => None,
+        Err(err) => return Err(invalid(LISTEN_FDNAMES, err)),
+        Ok(names) => Some(names),
+    };
+
+    Ok(with_names(fds, names.as_deref()))
+}
+
+/// Like [`named_fds`], but removes `LISTEN_PID`, `LISTEN_FDS` and
+/// `LISTEN_FDNAMES` from the environment, like [`fd`], such that child
+/// processes don't inherit them. Thus, this should only be called once.
+pub fn take_named_fds() -> io::Result<Vec<(Option<String>, RawFd)>> {
+    let fds = named_fds();
+
+    remove_var(LISTEN_PID);
+    remove_var(LISTEN_FDS);
+    remove_var(LISTEN_FDNAMES);
+
+    fds
+}
+
+fn parse(pid: &str, own: u32, fds: &str) -> io::Result<Vec<RawFd>> {
+    let pid = pid.parse::<u32>().map_err(|err| invalid(LISTEN_PID, err))?;
+    if pid != own {
+        return Ok(Vec::new());
+    }
+
+    let n = fds
+        .parse::<RawFd>()
+        .map_err(|err| invalid(LISTEN_FDS, err))?;
+
+    Ok((SD_LISTEN_FDS_START..SD_LISTEN_FDS_START.saturating_add(n)).collect())
+}
+
+fn with_names(fds: Vec<RawFd>, names: Option<&str>) -> Vec<(Option<String>, RawFd)> {
+    match names.map(|names| names.split(':').collect::<Vec<_>>()) {
+        Some(names) if names.len() == fds.len() => names
+            .into_iter()
+            .map(|name| Some(name.to_owned()))
+            .zip(fds)
+            .collect(),
+        _ => fds.into_iter().map(|fd| (None, fd)).collect(),
+    }
+}
+
+fn invalid(name: &str, err: impl std::fmt::Display) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("The value of environment variable '{name}' is invalid: {err}"),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use std::env::set_var;
+
+    use super::*;
+
+    #[test]
+    fn parse_fds() {
+        assert_eq!(parse("42", 42, "2").unwrap(), vec![3, 4]);
+        assert_eq!(parse("42", 42, "0").unwrap(), vec![]);
+        assert_eq!(parse("42", 43, "2").unwrap(), vec![]);
+        assert!(parse("x", 42, "2").is_err());
+        assert!(parse("42", 42, "two").is_err());
+    }
+
+    #[test]
+    fn names() {
+        assert_eq!(
+            with_names(vec![3, 4], Some("control:peer")),
+            vec![
+                (Some("control".to_owned()), 3),
+                (Some("peer".to_owned()), 4)
+            ]
+        );
+        assert_eq!(
+            with_names(vec![3, 4], Some("control")),
+            vec![(None, 3), (None, 4)]
+        );
+        assert_eq!(with_names(vec![3], None), vec![(None, 3)]);
+    }
+
+    // All cases are checked in a single test, as they all modify the
+    // environment of the process.
+    #[test]
+    fn env() {
+        remove_var(LISTEN_PID);
+        remove_var(LISTEN_FDS);
+        remove_var(LISTEN_FDNAMES);
+        assert_eq!(fds().unwrap(), vec![]);
+
+        set_var(LISTEN_PID, id().to_string());
+        set_var(LISTEN_FDS, "2");
+        assert_eq!(fds().unwrap(), vec![3, 4]);
+        assert_eq!(named_fds().unwrap(), vec![(None, 3), (None, 4)]);
+
+        set_var(LISTEN_FDNAMES, "ipv4:ipv6");
+        assert_eq!(
+            named_fds().unwrap(),
+            vec![(Some("ipv4".to_owned()), 3), (Some("ipv6".to_owned()), 4)]
+        );
+
+        set_var(LISTEN_PID, (id() + 1).to_string());
+        assert_eq!(named_fds().unwrap(), vec![]);
+
+        set_var(LISTEN_PID, id().to_string());
+        assert_eq!(
+            take_named_fds().unwrap(),
+            vec![(Some("ipv4".to_owned()), 3), (Some("ipv6".to_owned()), 4)]
+        );
+        assert_eq!(var(LISTEN_PID), Err(VarError::NotPresent));
+        assert_eq!(var(LISTEN_FDS), Err(VarError::NotPresent));
+        assert_eq!(var(LISTEN_FDNAMES), Err(VarError::NotPresent));
+        assert_eq!(take_named_fds().unwrap(), vec![]);
+
+        remove_var(LISTEN_PID);
+        remove_var(LISTEN_FDS);
+        remove_var(LISTEN_FDNAMES);
+    }
+}
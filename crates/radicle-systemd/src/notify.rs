@@ -0,0 +1,107 @@
+use std::env::var_os;
+use std::ffi::OsStr;
+use std::io;
+use std::os::linux::net::SocketAddrExt;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::net::{SocketAddr, UnixDatagram};
+
+const NOTIFY_SOCKET: &str = "NOTIFY_SOCKET";
+
+/// Tell the service manager that start-up is finished, by sending `READY=1`.
+/// This is required for services with `Type=notify`.
+pub fn ready() -> io::Result<()> {
+    send("READY=1")
+}
+
+/// Tell the service manager that the service is reloading its configuration,
+/// by sending `RELOADING=1`, along with the current time as required for
+/// services with `Type=notify-reload`. Send [`ready`] once reloading is
+/// finished.
+pub fn reloading() -> io::Result<()> {
+    let mut now = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    // SAFETY: `now` is a valid pointer to a `timespec`.
+    if unsafe { libc::clock_gettime(libc::CLOCK_MONOTONIC, &mut now) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let usec = now.tv_sec as u64 * 1_000_000 + now.tv_nsec as u64 / 1_000;
+
+    send(&format!("RELOADING=1\nMONOTONIC_USEC={usec}"))
+}
+
+/// Describe the state of the service in free form, by sending `STATUS=...`.
+/// The status is shown by `systemctl status`.
+pub fn status(status: &str) -> io::Result<()> {
+    if status.contains('\n') {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "The status must not contain newlines.",
+        ));
+    }
+    send(&format!("STATUS={status}"))
+}
+
+/// Send `state` to the service manager, if the environment variable
+/// `NOTIFY_SOCKET` is set. Otherwise, this does nothing, so that it is safe to
+/// call unconditionally.
+/// This is a specialization of [`sd_notify(3)`](man:sd_notify(3)).
+/// See:
+///  - <https://www.freedesktop.org/software/systemd/man/254/sd_notify.html>
+pub(crate) fn send(state: &str) -> io::Result<()> {
+    match var_os(NOTIFY_SOCKET) {
+        Some(socket) => send_to(&socket, state),
+        None => Ok(()),
+    }
+}
+
+/// Send `state` to the socket at `socket`, which is either a path, or
+/// an abstract socket name prefixed with `@`.
+pub(crate) fn send_to(socket: &OsStr, state: &str) -> io::Result<()> {
+    let addr = match socket.as_bytes() {
+        [b'@', name @ ..] => SocketAddr::from_abstract_name(name)?,
+        [b'/', ..] => SocketAddr::from_pathname(socket)?,
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "The value of environment variable '{NOTIFY_SOCKET}' is neither an absolute path nor an abstract socket name (it lossily translates to '{}').",
+                    socket.to_string_lossy()
+                ),
+            ))
+        }
+    };
+
+    UnixDatagram::unbound()?.send_to_addr(state.as_bytes(), &addr)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn send_to_path() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("notify");
+        let receiver = UnixDatagram::bind(&path).unwrap();
+
+        send_to(path.as_os_str(), "WATCHDOG=1").unwrap();
+
+        let mut buf = [0u8; 64];
+        let n = receiver.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"WATCHDOG=1");
+    }
+
+    #[test]
+    fn status_newline() {
+        assert!(status("a\nb").is_err());
+    }
+
+    #[test]
+    fn send_to_relative() {
+        assert!(send_to(OsStr::new("notify"), "WATCHDOG=1").is_err());
+    }
+}
@@ -0,0 +1,73 @@
+use std::env::var;
+use std::io;
+use std::process::id;
+use std::time::Duration;
+
+use crate::notify;
+
+const WATCHDOG_USEC: &str = "WATCHDOG_USEC";
+const WATCHDOG_PID: &str = "WATCHDOG_PID";
+
+/// Checks whether the service manager expects keep-alive pings from this
+/// process, returning the watchdog timeout. Pings should be sent via [`ping`]
+/// at least every half of this duration.
+/// Missing or invalid environment variables, and a `WATCHDOG_PID` that does
+/// not match the current process, all result in [`Option::None`].
+/// This is a specialization of [`sd_watchdog_enabled(3)`](man:sd_watchdog_enabled(3)).
+/// See:
+///  - <https://www.freedesktop.org/software/systemd/man/254/sd_watchdog_enabled.html>
+pub fn enabled() -> Option<Duration> {
+    let usec = var(WATCHDOG_USEC).ok()?;
+
+    if let Ok(pid) = var(WATCHDOG_PID) {
+        if pid != id().to_string() {
+            return None;
+        }
+    }
+
+    match usec.parse::<u64>() {
+        Ok(0) | Err(_) => None,
+        Ok(usec) => Some(Duration::from_micros(usec)),
+    }
+}
+
+/// Tell the service manager that this process is still alive, by sending
+/// `WATCHDOG=1`. This does nothing if the environment variable `NOTIFY_SOCKET`
+/// is not set.
+pub fn ping() -> io::Result<()> {
+    notify::send("WATCHDOG=1")
+}
+
+#[cfg(test)]
+mod test {
+    use std::env::{remove_var, set_var};
+
+    use super::*;
+
+    // All cases are checked in a single test, as they all modify the
+    // environment of the process.
+    #[test]
+    fn enabled() {
+        remove_var(WATCHDOG_USEC);
+        remove_var(WATCHDOG_PID);
+        assert_eq!(super::enabled(), None);
+
+        set_var(WATCHDOG_USEC, "30000000");
+        assert_eq!(super::enabled(), Some(Duration::from_secs(30)));
+
+        set_var(WATCHDOG_PID, id().to_string());
+        assert_eq!(super::enabled(), Some(Duration::from_secs(30)));
+
+        set_var(WATCHDOG_PID, (id() + 1).to_string());
+        assert_eq!(super::enabled(), None);
+
+        remove_var(WATCHDOG_PID);
+        set_var(WATCHDOG_USEC, "0");
+        assert_eq!(super::enabled(), None);
+
+        set_var(WATCHDOG_USEC, "thirty seconds");
+        assert_eq!(super::enabled(), None);
+
+        remove_var(WATCHDOG_USEC);
+    }
+}
@@ -154,30 +154,13 @@ where
 
     /// Find the [`Quorum`] for the canonical computation.
     pub fn quorum(self) -> Result<Quorum<'a>, QuorumError> {
-        let mut finder = QuorumFinder::new(self.refname, self.rule, self.objects.values());
-        while let ControlFlow::Continue(pairs) = finder.find_merge_bases() {
-            let mut bases = Vec::with_capacity(pairs.size_hint().0);
-            for (a, b) in pairs {
-                bases.push(self.repo.merge_base(a, b)?);
-            }
-            finder.found_merge_bases(bases.into_iter());
-        }
-        let refname = finder.refname.clone();
-        let threshold = (*finder.rule.threshold()).into();
-        let results = finder.find_quorum();
-        match results {
-            (Ok(commit), Err(_)) => Ok(commit),
-            (Err(_), Ok(tag)) => Ok(tag),
-            (Ok(_), Ok(_)) => Err(QuorumError::DifferentTypes {
-                refname: refname.to_string(),
-            }),
-            (Err(ec), Err(eq)) => Err(Self::convert_failures(
-                ec,
-                eq,
-                refname.to_string(),
-                threshold,
-            )),
-        }
+        let object =
+            QuorumFinder::new(self.rule, &self.objects).run(self.repo, self.refname.as_str())?;
+
+        Ok(Quorum {
+            refname: self.refname,
+            object,
+        })
     }
 
     /// If there are [`Missing`] objects, these may be reported by the caller,
@@ -196,47 +179,6 @@ where
             self.objects.insert(did, object);
         }
     }
-
-    fn convert_failures(
-        commit: CommitQuorumFailure,
-        tag: TagQuorumFailure,
-        refname: String,
-        threshold: usize,
-    ) -> QuorumError {
-        match (commit, tag) {
-            (CommitQuorumFailure::NoCandidates, TagQuorumFailure::NoCandidates) => {
-                QuorumError::NoCandidates { refname, threshold }
-            }
-            (CommitQuorumFailure::NoCandidates, TagQuorumFailure::DivergingTags { candidates }) => {
-                QuorumError::DivergingTags {
-                    refname,
-                    threshold,
-                    candidates,
-                }
-            }
-            (
-                CommitQuorumFailure::DivergingCommits {
-                    base,
-                    longest,
-                    candidate,
-                },
-                _,
-            ) => QuorumError::DivergingCommits {
-                refname,
-                threshold,
-                base,
-                longest,
-                head: candidate,
-            },
-            (CommitQuorumFailure::NoMergeBase { a, b }, _) => {
-                #[derive(thiserror::Error, Debug)]
-                #[error("no existing merge base found for commit quorum")]
-                struct NoMergeBase;
-
-                effects::MergeBaseError::new(a, b, NoMergeBase).into()
-            }
-        }
-    }
 }
 
 impl<'a, 'b, 'r, R> CanonicalWithConvergence<'a, 'b, 'r, R>
@@ -261,6 +203,40 @@ where
     }
 }
 
+/// Preview the object that a canonical reference would point to, if the
+/// delegates' references pointed to the given `objects`, under the given `rule`.
+///
+/// Unlike [`Canonical::quorum`], no repository is needed: the [`MergeBase`]s of
+/// the commits are supplied by the caller. If a merge base that is needed for
+/// the calculation is not supplied, a [`QuorumError::MergeBase`] is returned.
+/// Since there is no reference, errors refer to it as `(preview)`.
+pub fn preview_quorum(
+    objects: &BTreeMap<Did, Object>,
+    rule: &ValidRule,
+    merge_bases: &[MergeBase],
+) -> Result<Object, QuorumError> {
+    QuorumFinder::new(rule, objects).run(&SuppliedMergeBases(merge_bases), "(preview)")
+}
+
+/// Merge bases supplied to [`preview_quorum`], in place of a repository.
+struct SuppliedMergeBases<'a>(&'a [MergeBase]);
+
+impl effects::FindMergeBase for SuppliedMergeBases<'_> {
+    fn merge_base(&self, a: Oid, b: Oid) -> Result<MergeBase, effects::MergeBaseError> {
+        self.0
+            .iter()
+            .find(|base| (base.a, base.b) == (a, b) || (base.a, base.b) == (b, a))
+            .map(|MergeBase { base, .. }| MergeBase { a, b, base: *base })
+            .ok_or_else(|| {
+                #[derive(thiserror::Error, Debug)]
+                #[error("merge base was not supplied")]
+                struct NotSupplied;
+
+                effects::MergeBaseError::new(a, b, NotSupplied)
+            })
+    }
+}
+
 /// The result of finding a quorum.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Quorum<'a> {
@@ -281,29 +257,84 @@ pub struct QuorumWithConvergence<'a> {
 /// Helper to perform the quorum check for both a [`TagQuorum`] and
 /// [`CommitQuorum`].
 #[derive(Debug)]
-struct QuorumFinder<'a, 'b> {
-    refname: Qualified<'a>,
+struct QuorumFinder<'b> {
     rule: &'b ValidRule,
     tag_quorum: TagQuorum,
     commit_quorum: CommitQuorum,
+    /// Merge bases that were already computed, keyed by the pair of commits,
+    /// where the smaller commit comes first.
+    merge_bases: BTreeMap<(Oid, Oid), Oid>,
+    /// The voters for each commit, used for reporting diverging commits.
+    voters: BTreeMap<Oid, BTreeSet<Did>>,
 }
 
-impl<'a, 'b> QuorumFinder<'a, 'b> {
-    fn new<'c, I>(refname: Qualified<'a>, rule: &'b ValidRule, objects: I) -> Self
-    where
-        I: Iterator<Item = &'c Object> + Clone,
-    {
-        let threshold = *rule.threshold();
-        let tag_quorum = TagQuorum::new(objects.clone(), threshold.into());
-        let commit_quorum = CommitQuorum::new(objects, threshold.into());
+impl<'b> QuorumFinder<'b> {
+    fn new(rule: &'b ValidRule, objects: &BTreeMap<Did, Object>) -> Self {
+        let threshold = **rule.threshold();
+        let tag_quorum = TagQuorum::new(objects.values(), threshold.into());
+        let commit_quorum = CommitQuorum::new(objects.values(), threshold.into());
+        let mut voters = BTreeMap::<_, BTreeSet<_>>::new();
+        for (did, object) in objects {
+            if let Object::Commit { id } = object {
+                voters.entry(*id).or_default().insert(*did);
+            }
+        }
         Self {
-            refname,
             rule,
             tag_quorum,
             commit_quorum,
+            merge_bases: BTreeMap::new(),
+            voters,
         }
     }
 
+    /// Find the [`MergeBase`] of each of the `pairs`, only using the `repo`
+    /// for the merge bases that are not trivial and were not found before.
+    fn merge_bases<R>(
+        &mut self,
+        repo: &R,
+        pairs: &[(Oid, Oid)],
+    ) -> Result<Vec<MergeBase>, effects::MergeBaseError>
+    where
+        R: effects::FindMergeBase,
+    {
+        let key = |a: Oid, b: Oid| if a < b { (a, b) } else { (b, a) };
+        let missing = pairs
+            .iter()
+            .filter(|(a, b)| a != b)
+            .map(|(a, b)| key(*a, *b))
+            .filter(|pair| !self.merge_bases.contains_key(pair))
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect::<Vec<_>>();
+        for MergeBase { a, b, base } in repo.merge_bases(&missing)? {
+            self.merge_bases.insert(key(a, b), base);
+        }
+
+        pairs
+            .iter()
+            .map(|(a, b)| {
+                if a == b {
+                    return Ok(MergeBase::trivial(*a));
+                }
+                match self.merge_bases.get(&key(*a, *b)) {
+                    Some(base) => Ok(MergeBase {
+                        a: *a,
+                        b: *b,
+                        base: *base,
+                    }),
+                    None => {
+                        #[derive(thiserror::Error, Debug)]
+                        #[error("merge base was not computed")]
+                        struct MissingMergeBase;
+
+                        Err(effects::MergeBaseError::new(*a, *b, MissingMergeBase))
+                    }
+                }
+            })
+            .collect()
+    }
+
     fn find_merge_bases(&mut self) -> ControlFlow<(), impl Iterator<Item = (Oid, Oid)>> {
         match self.commit_quorum.next_candidate() {
             Some(candidate) => ControlFlow::Continue(candidate),
@@ -321,19 +352,85 @@ impl<'a, 'b> QuorumFinder<'a, 'b> {
     fn find_quorum(
         self,
     ) -> (
-        Result<Quorum<'a>, quorum::CommitQuorumFailure>,
-        Result<Quorum<'a>, quorum::TagQuorumFailure>,
+        Result<Object, quorum::CommitQuorumFailure>,
+        Result<Object, quorum::TagQuorumFailure>,
     ) {
-        let commit = self.commit_quorum.find_quorum().map(|id| Quorum {
-            refname: self.refname.clone(),
-            object: Object::Commit { id },
-        });
-        let tag = self.tag_quorum.find_quorum().map(|id| Quorum {
-            refname: self.refname.clone(),
-            object: Object::Tag { id },
-        });
+        let commit = self
+            .commit_quorum
+            .find_quorum()
+            .map(|id| Object::Commit { id });
+        let tag = self.tag_quorum.find_quorum().map(|id| Object::Tag { id });
         (commit, tag)
     }
+
+    /// Find the quorum, using `repo` to find the merge bases of the commits.
+    /// The `refname` is only used for reporting errors.
+    fn run<R>(mut self, repo: &R, refname: &str) -> Result<Object, QuorumError>
+    where
+        R: effects::FindMergeBase,
+    {
+        while let ControlFlow::Continue(pairs) = self.find_merge_bases() {
+            let pairs = pairs.collect::<Vec<_>>();
+            let bases = self.merge_bases(repo, &pairs)?;
+            self.found_merge_bases(bases.into_iter());
+        }
+        let refname = refname.to_owned();
+        let threshold = (**self.rule.threshold()).into();
+        let voters = std::mem::take(&mut self.voters);
+
+        match self.find_quorum() {
+            (Ok(commit), Err(_)) => Ok(commit),
+            (Err(_), Ok(tag)) => Ok(tag),
+            (Ok(_), Ok(_)) => Err(QuorumError::DifferentTypes { refname }),
+            (Err(ec), Err(eq)) => Err(Self::convert_failures(ec, eq, refname, threshold, &voters)),
+        }
+    }
+
+    fn convert_failures(
+        commit: CommitQuorumFailure,
+        tag: TagQuorumFailure,
+        refname: String,
+        threshold: usize,
+        voters: &BTreeMap<Oid, BTreeSet<Did>>,
+    ) -> QuorumError {
+        match (commit, tag) {
+            (CommitQuorumFailure::NoCandidates, TagQuorumFailure::NoCandidates) => {
+                QuorumError::NoCandidates { refname, threshold }
+            }
+            (CommitQuorumFailure::NoCandidates, TagQuorumFailure::DivergingTags { candidates }) => {
+                QuorumError::DivergingTags {
+                    refname,
+                    threshold,
+                    candidates,
+                }
+            }
+            (
+                CommitQuorumFailure::DivergingCommits {
+                    base,
+                    longest,
+                    candidate,
+                },
+                _,
+            ) => QuorumError::DivergingCommits {
+                refname,
+                threshold,
+                base,
+                longest,
+                head: candidate,
+                voters: [longest, candidate]
+                    .into_iter()
+                    .map(|oid| (oid, voters.get(&oid).cloned().unwrap_or_default()))
+                    .collect(),
+            },
+            (CommitQuorumFailure::NoMergeBase { a, b }, _) => {
+                #[derive(thiserror::Error, Debug)]
+                #[error("no existing merge base found for commit quorum")]
+                struct NoMergeBase;
+
+                effects::MergeBaseError::new(a, b, NoMergeBase).into()
+            }
+        }
+    }
 }
 
 /// Record a merge base between `a` and `b`.
@@ -349,7 +446,6 @@ pub struct MergeBase {
 
 impl MergeBase {
     /// The merge base of the same commit is the commit itself.
-    #[cfg(test)]
     pub fn trivial(oid: Oid) -> Self {
         Self {
             a: oid,
@@ -515,30 +611,81 @@ mod tests {
         threshold: usize,
         repo: &crate::git::raw::Repository,
     ) -> Result<Oid, QuorumError> {
+        quorum_with(heads, threshold, repo, repo)
+    }
+
+    /// Test helper to construct a Canonical, using `effects` for the
+    /// computation, and get the quorum
+    fn quorum_with<R>(
+        heads: &[crate::git::Oid],
+        threshold: usize,
+        repo: &crate::git::raw::Repository,
+        effects: &R,
+    ) -> Result<Oid, QuorumError>
+    where
+        R: effects::Ancestry + effects::FindMergeBase + effects::FindObjects,
+    {
+        let refname = git::refs::branch(crate::git::fmt::RefStr::try_from_str("master").unwrap());
+        let (_, rule) = delegates(&refname, heads, 0, threshold, repo);
+
+        Canonical::new(refname, &rule, effects)
+            .find_objects()
+            .unwrap()
+            .quorum()
+            .map(|Quorum { object, .. }| object.id())
+    }
+
+    /// Test helper to construct a Canonical with a candidate voting for
+    /// `object`, and get the quorum with convergence. The candidate is a
+    /// delegate without a reference.
+    fn quorum_with_convergence(
+        heads: &[crate::git::Oid],
+        object: Object,
+        threshold: usize,
+        repo: &crate::git::raw::Repository,
+    ) -> Result<QuorumWithConvergence<'static>, QuorumError> {
         let refname = git::refs::branch(crate::git::fmt::RefStr::try_from_str("master").unwrap());
+        let (delegates, rule) = delegates(&refname, heads, 1, threshold, repo);
+        let candidate = *delegates.last().unwrap();
 
+        Canonical::new(refname, &rule, repo)
+            .find_objects()
+            .unwrap()
+            .with_convergence(candidate, object)
+            .quorum()
+    }
+
+    /// Test helper to point the `refname` of a delegate to each of the
+    /// `heads`, and create a rule for these delegates, and `extra` delegates
+    /// without a reference.
+    fn delegates(
+        refname: &Qualified,
+        heads: &[crate::git::Oid],
+        extra: usize,
+        threshold: usize,
+        repo: &crate::git::raw::Repository,
+    ) -> (Vec<Did>, ValidRule) {
         let mut delegates = Vec::new();
-        for (i, head) in heads.iter().enumerate() {
+        for i in 0..heads.len() + extra {
             let signer = Device::mock_from_seed([(i + 1) as u8; 32]);
             let did = Did::from(signer.public_key());
             delegates.push(did);
-            let ns = git::fmt::Component::from(signer.public_key());
-            repo.reference(refname.with_namespace(ns).as_str(), head.into(), true, "")
-                .unwrap();
+
+            if let Some(head) = heads.get(i) {
+                let ns = git::fmt::Component::from(signer.public_key());
+                repo.reference(refname.with_namespace(ns).as_str(), head.into(), true, "")
+                    .unwrap();
+            }
         }
 
         let rule: RawRule = crate::git::canonical::rules::Rule::new(
             crate::git::canonical::rules::Allowed::Delegates,
-            threshold,
+            crate::git::canonical::rules::Threshold::Absolute(threshold),
         );
-        let delegates = crate::identity::doc::Delegates::new(delegates).unwrap();
-        let rule = rule.validate(&mut || delegates.clone()).unwrap();
+        let resolved = crate::identity::doc::Delegates::new(delegates.clone()).unwrap();
+        let rule = rule.validate(&mut || resolved.clone()).unwrap();
 
-        Canonical::new(refname, &rule, repo)
-            .find_objects()
-            .unwrap()
-            .quorum()
-            .map(|Quorum { object, .. }| object.id())
+        (delegates, rule)
     }
 
     fn commit(id: &str) -> Object {
@@ -553,6 +700,33 @@ mod tests {
         }
     }
 
+    /// Test helper to preview the quorum of `objects`, each voted for by a
+    /// different delegate.
+    fn preview(
+        objects: &[Object],
+        threshold: usize,
+        merge_bases: &[MergeBase],
+    ) -> Result<Object, QuorumError> {
+        let objects = objects
+            .iter()
+            .enumerate()
+            .map(|(i, object)| {
+                let signer = Device::mock_from_seed([(i + 1) as u8; 32]);
+                (Did::from(signer.public_key()), *object)
+            })
+            .collect::<BTreeMap<_, _>>();
+        let delegates =
+            crate::identity::doc::Delegates::new(objects.keys().copied().collect::<Vec<_>>())
+                .unwrap();
+        let rule: RawRule = crate::git::canonical::rules::Rule::new(
+            crate::git::canonical::rules::Allowed::Delegates,
+            crate::git::canonical::rules::Threshold::Absolute(threshold),
+        );
+        let rule = rule.validate(&mut || delegates.clone()).unwrap();
+
+        preview_quorum(&objects, &rule, merge_bases)
+    }
+
     #[test]
     fn test_quorum_properties() {
         let tmp = tempfile::tempdir().unwrap();
@@ -587,6 +761,114 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_quorum_merge_base_cache() {
+        use std::cell::RefCell;
+
+        /// Records the calls to [`effects::FindMergeBase::merge_base`].
+        struct Counting<'r> {
+            repo: &'r git::raw::Repository,
+            calls: RefCell<Vec<(Oid, Oid)>>,
+        }
+
+        impl effects::FindMergeBase for Counting<'_> {
+            fn merge_base(&self, a: Oid, b: Oid) -> Result<MergeBase, effects::MergeBaseError> {
+                self.calls.borrow_mut().push((a, b));
+                effects::FindMergeBase::merge_base(self.repo, a, b)
+            }
+        }
+
+        impl effects::Ancestry for Counting<'_> {
+            fn graph_ahead_behind(
+                &self,
+                commit: Oid,
+                upstream: Oid,
+            ) -> Result<GraphAheadBehind, effects::GraphDescendant> {
+                effects::Ancestry::graph_ahead_behind(self.repo, commit, upstream)
+            }
+        }
+
+        impl effects::FindObjects for Counting<'_> {
+            fn find_objects<'a, 'b, I>(
+                &self,
+                refname: &Qualified<'a>,
+                dids: I,
+            ) -> Result<FoundObjects, effects::FindObjectsError>
+            where
+                I: Iterator<Item = &'b Did>,
+            {
+                effects::FindObjects::find_objects(self.repo, refname, dids)
+            }
+        }
+
+        let tmp = tempfile::tempdir().unwrap();
+        let (repo, c0) = fixtures::repository(tmp.path());
+        let c0: crate::git::Oid = c0.into();
+        let c1 = fixtures::commit("C1", &[c0.into()], &repo);
+        let c2 = fixtures::commit("C2", &[c1.into()], &repo);
+        let b2 = fixtures::commit("B2", &[c1.into()], &repo);
+        let heads = [c1, c2, b2, c2, c1, b2, c0];
+        let counting = Counting {
+            repo: &repo,
+            calls: RefCell::new(Vec::new()),
+        };
+
+        assert_eq!(quorum_with(&heads, 3, &repo, &counting).unwrap(), c1);
+
+        let calls = counting.calls.into_inner();
+        let distinct = calls
+            .iter()
+            .map(|(a, b)| if a < b { (*a, *b) } else { (*b, *a) })
+            .collect::<BTreeSet<_>>();
+        // All pairs of the distinct heads
+        assert_eq!(distinct.len(), 6);
+        assert_eq!(calls.len(), distinct.len());
+        assert!(calls.iter().all(|(a, b)| a != b));
+    }
+
+    #[test]
+    fn test_tag_convergence() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (repo, c0) = fixtures::repository(tmp.path());
+        let t1 = fixtures::tag("v1", "", c0, &repo);
+        let t2 = fixtures::tag("v2", "", c0, &repo);
+
+        // The candidate votes for the same tag as the other delegate.
+        let QuorumWithConvergence { quorum, converges } =
+            quorum_with_convergence(&[t1], Object::Tag { id: t1 }, 1, &repo).unwrap();
+        assert!(converges);
+        assert_eq!(quorum.object, Object::Tag { id: t1 });
+
+        // The candidate votes for a different tag.
+        let QuorumWithConvergence { quorum, converges } =
+            quorum_with_convergence(&[t1], Object::Tag { id: t2 }, 1, &repo).unwrap();
+        assert!(!converges);
+        assert_eq!(quorum.object, Object::Tag { id: t1 });
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_find_objects_parallel() {
+        use effects::FindObjects as _;
+
+        let tmp = tempfile::tempdir().unwrap();
+        let (repo, c0) = fixtures::repository(tmp.path());
+        let c1 = fixtures::commit("C1", &[c0], &repo);
+        let t1 = fixtures::tag("v1", "", c0, &repo);
+        let refname = git::refs::branch(crate::git::fmt::RefStr::try_from_str("master").unwrap());
+        let (dids, _) = delegates(&refname, &[c0.into(), c1, t1, c1], 3, 1, &repo);
+
+        let serial = repo.find_objects(&refname, dids.iter()).unwrap();
+        let parallel = effects::Parallel(&repo)
+            .find_objects(&refname, dids.iter())
+            .unwrap();
+
+        assert_eq!(serial, parallel);
+        assert_eq!(serial.objects.len(), 4);
+        assert_eq!(serial.missing_refs.len(), 3);
+        assert!(serial.missing_objects.is_empty());
+    }
+
     #[test]
     fn test_quorum_different_types() {
         let tmp = tempfile::tempdir().unwrap();
@@ -834,6 +1116,141 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_preview_quorum_linear() {
+        let c0 = commit("f2de534b5e81d7c6e2dcaf58c3dd91573c0a0354");
+        let c1 = commit("bfb1a513e420eade90b0e6be64117b861b16ecb5");
+        let c2 = commit("8fc5160702365f231c77732a8fa162379e54f57a");
+        //   C2
+        //   |
+        //  C1
+        //  |
+        // C0
+        let merge_bases = [
+            MergeBase {
+                a: c2.id(),
+                b: c1.id(),
+                base: c1.id(),
+            },
+            MergeBase {
+                a: c2.id(),
+                b: c0.id(),
+                base: c0.id(),
+            },
+            MergeBase {
+                a: c1.id(),
+                b: c0.id(),
+                base: c0.id(),
+            },
+        ];
+
+        assert_eq!(preview(&[c1, c2], 1, &merge_bases).unwrap(), c2);
+        assert_eq!(preview(&[c1, c2], 2, &merge_bases).unwrap(), c1);
+        assert_eq!(preview(&[c0, c1, c2], 3, &merge_bases).unwrap(), c0);
+        assert_eq!(preview(&[c2, c2, c1], 1, &merge_bases).unwrap(), c2);
+        assert_eq!(preview(&[c1, c1, c2], 2, &merge_bases).unwrap(), c1);
+    }
+
+    #[test]
+    fn test_preview_quorum_two_way_fork() {
+        let c0 = commit("f2de534b5e81d7c6e2dcaf58c3dd91573c0a0354");
+        let c1 = commit("bfb1a513e420eade90b0e6be64117b861b16ecb5");
+        let c2 = commit("8fc5160702365f231c77732a8fa162379e54f57a");
+        let b2 = commit("037a148170e3d41524b7c482a4798e5c2daeaa00");
+
+        // B2 C2
+        //   \|
+        //   C1
+        //   |
+        //  C0
+        let merge_bases = [
+            MergeBase {
+                a: b2.id(),
+                b: c2.id(),
+                base: c1.id(),
+            },
+            MergeBase {
+                a: c2.id(),
+                b: c1.id(),
+                base: c1.id(),
+            },
+            MergeBase {
+                a: b2.id(),
+                b: c1.id(),
+                base: c1.id(),
+            },
+            MergeBase {
+                a: c1.id(),
+                b: c0.id(),
+                base: c0.id(),
+            },
+        ];
+
+        assert_matches!(
+            preview(&[c1, c2, b2], 1, &merge_bases),
+            Err(QuorumError::DivergingCommits { .. })
+        );
+        assert_matches!(
+            preview(&[b2, c2], 1, &merge_bases),
+            Err(QuorumError::DivergingCommits { .. })
+        );
+        assert_matches!(
+            preview(&[b2, b2, c2, c2], 2, &merge_bases),
+            Err(QuorumError::DivergingCommits { .. })
+        );
+        assert_eq!(preview(&[c1, c2, b2], 2, &merge_bases).unwrap(), c1);
+        assert_eq!(preview(&[c1, c2, b2], 3, &merge_bases).unwrap(), c1);
+        assert_eq!(preview(&[b2, b2, c2], 2, &merge_bases).unwrap(), b2);
+        assert_eq!(preview(&[b2, c2, c2], 2, &merge_bases).unwrap(), c2);
+    }
+
+    #[test]
+    fn test_diverging_commits_voters() {
+        let c1 = commit("bfb1a513e420eade90b0e6be64117b861b16ecb5");
+        let c2 = commit("8fc5160702365f231c77732a8fa162379e54f57a");
+        let b2 = commit("037a148170e3d41524b7c482a4798e5c2daeaa00");
+        let merge_bases = [MergeBase {
+            a: b2.id(),
+            b: c2.id(),
+            base: c1.id(),
+        }];
+        let did = |seed: u8| Did::from(Device::mock_from_seed([seed; 32]).public_key());
+
+        // B2 C2
+        //   \|
+        //   C1
+        let err = preview(&[c2, b2, b2], 1, &merge_bases).unwrap_err();
+        let voters = err.voters().unwrap();
+
+        assert_eq!(
+            *voters,
+            BTreeMap::from_iter([
+                (c2.id(), BTreeSet::from_iter([did(1)])),
+                (b2.id(), BTreeSet::from_iter([did(2), did(3)])),
+            ])
+        );
+        assert_matches!(
+            err,
+            QuorumError::DivergingCommits { base, .. } if base == c1.id()
+        );
+        assert_eq!(
+            preview(&[c2, b2], 2, &merge_bases).unwrap_err().voters(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_preview_quorum_missing_merge_base() {
+        let c1 = commit("bfb1a513e420eade90b0e6be64117b861b16ecb5");
+        let c2 = commit("8fc5160702365f231c77732a8fa162379e54f57a");
+        let t1 = tag("0480391dd7312d35c79a455ec5d004657260b358");
+
+        assert_matches!(preview(&[c1, c2], 1, &[]), Err(QuorumError::MergeBase(_)));
+        // Identical commits and tags don't need any merge bases.
+        assert_eq!(preview(&[c1, c1], 2, &[]).unwrap(), c1);
+        assert_eq!(preview(&[t1, t1], 2, &[]).unwrap(), t1);
+    }
+
     #[test]
     fn test_commit_quorum_three_way_fork() {
         let c1 = commit("bfb1a513e420eade90b0e6be64117b861b16ecb5");
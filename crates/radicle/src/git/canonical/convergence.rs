@@ -39,7 +39,7 @@ where
     ///    b. That [`Object`]'s type matches type of the [`Candidate`].
     ///
     ///   2. If the [`Object`] is a tag, then ensure the [`Candidate`] object is
-    ///      a tag.
+    ///      a tag, which only converges if it is the identical tag object.
     ///   3. Always skip a vote that is the same as the [`Candidate`].
     pub fn check<'a, I>(self, voters: I) -> Result<Option<(Did, Object)>, error::ConvergesError>
     where
@@ -56,6 +56,7 @@ where
                         converges = true;
                         continue;
                     }
+                    Effect::TagDiverges => continue,
                     Effect::SkipSelf => continue,
                 },
                 ControlFlow::Break(ConvergenceMismatch { expected, found }) => {
@@ -83,8 +84,11 @@ struct Candidate {
 enum Effect {
     /// Perform a check of the commit graph using the `commit` and `upstream`.
     GraphCheck { commit: Oid, upstream: Oid },
-    /// Mark that tags always converge – there is no ancestry check.
+    /// Mark that the tags converge, since they are the same tag object – there
+    /// is no ancestry check.
     TagConverges,
+    /// Mark that the tags diverge, since they are different tag objects.
+    TagDiverges,
     /// Skip the [`Did`] since it is the same as the [`Candidate`].
     SkipSelf,
 }
@@ -119,7 +123,13 @@ impl Candidate {
             (Object::Commit { id: commit }, Object::Commit { id: upstream }) => {
                 ControlFlow::Continue(Effect::GraphCheck { commit, upstream })
             }
-            (Object::Tag { .. }, Object::Tag { .. }) => ControlFlow::Continue(Effect::TagConverges),
+            (Object::Tag { id: candidate }, Object::Tag { id: other }) => {
+                if candidate == other {
+                    ControlFlow::Continue(Effect::TagConverges)
+                } else {
+                    ControlFlow::Continue(Effect::TagDiverges)
+                }
+            }
         }
     }
 }
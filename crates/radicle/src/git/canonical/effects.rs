@@ -119,6 +119,14 @@ pub struct MissingObject {
 pub trait FindMergeBase {
     /// Produce the [`MergeBase`] of commits `a` and `b`.
     fn merge_base(&self, a: Oid, b: Oid) -> Result<MergeBase, MergeBaseError>;
+
+    /// Produce the [`MergeBase`] of each pair of commits in `pairs`, in the
+    /// same order.
+    ///
+    /// By default, [`FindMergeBase::merge_base`] is called for each pair.
+    fn merge_bases(&self, pairs: &[(Oid, Oid)]) -> Result<Vec<MergeBase>, MergeBaseError> {
+        pairs.iter().map(|(a, b)| self.merge_base(*a, *b)).collect()
+    }
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -183,6 +191,31 @@ impl FindMergeBase for git::raw::Repository {
                 base: base.into(),
             })
     }
+
+    fn merge_bases(&self, pairs: &[(Oid, Oid)]) -> Result<Vec<MergeBase>, MergeBaseError> {
+        // Pairs are often repeated, or in reverse order, so only look up each
+        // distinct pair once.
+        let mut found = BTreeMap::new();
+        let mut bases = Vec::with_capacity(pairs.len());
+
+        for (a, b) in pairs.iter().copied() {
+            if a == b {
+                bases.push(MergeBase { a, b, base: a });
+                continue;
+            }
+            let key = if a < b { (a, b) } else { (b, a) };
+            let base = match found.get(&key) {
+                Some(base) => *base,
+                None => {
+                    let MergeBase { base, .. } = FindMergeBase::merge_base(self, a, b)?;
+                    found.insert(key, base);
+                    base
+                }
+            };
+            bases.push(MergeBase { a, b, base });
+        }
+        Ok(bases)
+    }
 }
 
 impl Ancestry for git::raw::Repository {
@@ -210,40 +243,76 @@ impl FindObjects for git::raw::Repository {
     where
         I: Iterator<Item = &'b Did>,
     {
-        let mut objects = BTreeMap::new();
-        let mut missing_refs = BTreeSet::new();
-        let mut missing_objects = BTreeMap::new();
-        for did in dids {
-            let name = &refname.with_namespace(did.as_key().into());
-            let reference = match self.find_reference(name.as_str()) {
-                Ok(reference) => reference,
-                Err(e) if e.is_not_found() => {
-                    missing_refs.insert(name.to_owned());
-                    continue;
-                }
-                Err(e) => {
-                    return Err(FindObjectsError::find_reference(name.to_owned(), e));
-                }
-            };
-            let Some(oid) = reference.target().map(Oid::from) else {
-                log::warn!(target: "radicle", "Missing target for reference `{name}`");
-                continue;
-            };
-            let object = match self.find_object(oid.into(), None) {
-                Ok(object) => Object::new(&object).ok_or_else(|| {
+        Lookup::collect(dids.map(|did| Lookup::find(self, refname, did)))
+    }
+}
+
+/// The result of looking up the object of a single [`Did`].
+enum Lookup {
+    /// The object was found.
+    Found(Did, Object),
+    /// The reference is missing.
+    MissingRef(git::fmt::Namespaced<'static>),
+    /// The reference was found, but the object it points to is missing.
+    MissingObject(Did, Oid),
+    /// The reference is symbolic, and is skipped.
+    Skipped,
+}
+
+impl Lookup {
+    /// Look up the object of the `refname` in the namespace of the `did`.
+    fn find(
+        repo: &git::raw::Repository,
+        refname: &Qualified,
+        did: &Did,
+    ) -> Result<Self, FindObjectsError> {
+        let name = &refname.with_namespace(did.as_key().into());
+        let reference = match repo.find_reference(name.as_str()) {
+            Ok(reference) => reference,
+            Err(e) if e.is_not_found() => return Ok(Self::MissingRef(name.to_owned())),
+            Err(e) => {
+                return Err(FindObjectsError::find_reference(name.to_owned(), e));
+            }
+        };
+        let Some(oid) = reference.target().map(Oid::from) else {
+            log::warn!(target: "radicle", "Missing target for reference `{name}`");
+            return Ok(Self::Skipped);
+        };
+        match repo.find_object(oid.into(), None) {
+            Ok(object) => Object::new(&object)
+                .map(|object| Self::Found(*did, object))
+                .ok_or_else(|| {
                     FindObjectsError::invalid_object_type(
                         *did,
                         oid,
                         object.kind().map(|kind| kind.to_string()),
                     )
                 }),
-                Err(err) if err.is_not_found() => {
-                    missing_objects.insert(*did, oid);
-                    continue;
+            Err(err) if err.is_not_found() => Ok(Self::MissingObject(*did, oid)),
+            Err(err) => Err(FindObjectsError::find_object(oid, err)),
+        }
+    }
+
+    /// Collect the lookups into [`FoundObjects`], stopping at the first error.
+    fn collect(
+        lookups: impl IntoIterator<Item = Result<Self, FindObjectsError>>,
+    ) -> Result<FoundObjects, FindObjectsError> {
+        let mut objects = BTreeMap::new();
+        let mut missing_refs = BTreeSet::new();
+        let mut missing_objects = BTreeMap::new();
+        for lookup in lookups {
+            match lookup? {
+                Self::Found(did, object) => {
+                    objects.insert(did, object);
                 }
-                Err(err) => Err(FindObjectsError::find_object(oid, err)),
-            };
-            objects.insert(*did, object?);
+                Self::MissingRef(name) => {
+                    missing_refs.insert(name);
+                }
+                Self::MissingObject(did, oid) => {
+                    missing_objects.insert(did, oid);
+                }
+                Self::Skipped => {}
+            }
         }
         Ok(FoundObjects {
             objects,
@@ -252,3 +321,69 @@ impl FindObjects for git::raw::Repository {
         })
     }
 }
+
+/// A Git repository that finds the objects of each [`Did`] in parallel, see
+/// [`FindObjects`].
+///
+/// Since a [`git::raw::Repository`] can't be shared between threads, the
+/// repository is re-opened by each worker thread. The result is identical to
+/// finding the objects with the repository itself.
+#[cfg(feature = "rayon")]
+pub struct Parallel<'r>(pub &'r git::raw::Repository);
+
+#[cfg(feature = "rayon")]
+impl FindObjects for Parallel<'_> {
+    fn find_objects<'a, 'b, I>(
+        &self,
+        refname: &Qualified<'a>,
+        dids: I,
+    ) -> Result<FoundObjects, FindObjectsError>
+    where
+        I: Iterator<Item = &'b Did>,
+    {
+        use rayon::prelude::*;
+
+        let path = self.0.path();
+        let dids = dids.collect::<Vec<_>>();
+        // Lookups are collected in the order of the `dids`, so that the same
+        // error is returned as when finding the objects serially.
+        let lookups = dids
+            .into_par_iter()
+            .map_init(
+                || git::raw::Repository::open(path),
+                |repo, did| match repo {
+                    Ok(repo) => Lookup::find(repo, refname, did),
+                    Err(e) => Err(FindObjectsError::other(git::raw::Error::new(
+                        e.code(),
+                        e.class(),
+                        e.message(),
+                    ))),
+                },
+            )
+            .collect::<Vec<_>>();
+
+        Lookup::collect(lookups)
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl FindMergeBase for Parallel<'_> {
+    fn merge_base(&self, a: Oid, b: Oid) -> Result<MergeBase, MergeBaseError> {
+        FindMergeBase::merge_base(self.0, a, b)
+    }
+
+    fn merge_bases(&self, pairs: &[(Oid, Oid)]) -> Result<Vec<MergeBase>, MergeBaseError> {
+        FindMergeBase::merge_bases(self.0, pairs)
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl Ancestry for Parallel<'_> {
+    fn graph_ahead_behind(
+        &self,
+        commit: Oid,
+        upstream: Oid,
+    ) -> Result<GraphAheadBehind, GraphDescendant> {
+        Ancestry::graph_ahead_behind(self.0, commit, upstream)
+    }
+}
@@ -1,6 +1,9 @@
+use std::collections::{BTreeMap, BTreeSet};
+
 use thiserror::Error;
 
 use crate::git::Oid;
+use crate::prelude::Did;
 
 use super::{effects, ObjectType};
 pub use effects::{FindObjectsError, MergeBaseError};
@@ -22,6 +25,9 @@ pub enum QuorumError {
         base: Oid,
         longest: Oid,
         head: Oid,
+        /// The delegates whose references point to each of the diverging
+        /// commits, ie. `longest` and `head`.
+        voters: BTreeMap<Oid, BTreeSet<Did>>,
     },
     #[error("could not determine target tag for canonical reference '{refname}', found multiple candidates with threshold {threshold}")]
     DivergingTags {
@@ -31,6 +37,17 @@ pub enum QuorumError {
     },
 }
 
+impl QuorumError {
+    /// For [`QuorumError::DivergingCommits`], the delegates whose references
+    /// point to each of the diverging commits.
+    pub fn voters(&self) -> Option<&BTreeMap<Oid, BTreeSet<Did>>> {
+        match self {
+            Self::DivergingCommits { voters, .. } => Some(voters),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Error)]
 #[error("the object {oid} is of unexpected type {found} and was expected to be {expected}")]
 pub struct MismatchedObject {
@@ -12,6 +12,7 @@
 use core::fmt;
 use std::cmp::Ordering;
 use std::collections::BTreeMap;
+use std::str::FromStr;
 use std::sync::LazyLock;
 
 use nonempty::NonEmpty;
@@ -22,7 +23,7 @@ use thiserror::Error;
 use crate::git;
 use crate::git::canonical;
 use crate::git::canonical::Canonical;
-use crate::git::fmt::refspec::QualifiedPattern;
+use crate::git::fmt::refspec::{PatternStr, PatternString, QualifiedPattern};
 use crate::git::fmt::Qualified;
 use crate::git::fmt::{refname, RefString};
 use crate::identity::{doc, Did};
@@ -32,12 +33,12 @@ const ASTERISK: char = '*';
 static REFS_RAD: LazyLock<RefString> = LazyLock::new(|| refname!("refs/rad"));
 
 /// Private trait to ensure that not any `Rule` can be deserialized.
-/// Implementations are provided for `Allowed` and `usize` so that `RawRule`s
-/// can be deserialized, while `ValidRule`s cannot – preventing deserialization
-/// bugs for that type.
+/// Implementations are provided for `Allowed` and `Threshold` so that
+/// `RawRule`s can be deserialized, while `ValidRule`s cannot – preventing
+/// deserialization bugs for that type.
 trait Sealed {}
 impl Sealed for Allowed {}
-impl Sealed for usize {}
+impl Sealed for Threshold {}
 
 /// A `Pattern` is a `QualifiedPattern` reference, however, it disallows any
 /// references under the `refs/rad` hierarchy.
@@ -80,6 +81,22 @@ impl<'a> TryFrom<Qualified<'a>> for Pattern {
     }
 }
 
+impl FromStr for Pattern {
+    type Err = PatternError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let pattern = PatternString::try_from(s).map_err(|source| PatternError::Invalid {
+            pattern: s.to_owned(),
+            source,
+        })?;
+        let pattern: &PatternStr = &pattern;
+        let pattern = QualifiedPattern::from_patternstr(pattern)
+            .ok_or_else(|| PatternError::Unqualified(s.to_owned()))?;
+
+        Self::try_from(pattern)
+    }
+}
+
 impl Pattern {
     /// Check if the `refname` matches the rule's `refspec`.
     pub fn matches(&self, refname: &Qualified) -> bool {
@@ -175,83 +192,142 @@ impl PartialOrd for Pattern {
 /// ```
 impl Ord for Pattern {
     fn cmp(&self, other: &Self) -> Ordering {
-        #[derive(Debug, Clone, Copy)]
-        #[repr(i8)]
-        enum ComponentOrdering {
-            MatchLength(Ordering),
-            Lexicographic(Ordering),
-        }
+        self.specificity()
+            .cmp(&other.specificity())
+            // (3.)
+            .then_with(|| self.components().cmp(other.components()))
+    }
+}
 
-        impl ComponentOrdering {
-            fn merge(&mut self, other: Self) {
-                *self = match (*self, other) {
-                    (Self::Lexicographic(Ordering::Equal), Self::Lexicographic(other)) => {
-                        Self::Lexicographic(other)
-                    }
-                    (Self::Lexicographic(_), Self::MatchLength(other)) => Self::MatchLength(other),
-                    (Self::MatchLength(Ordering::Equal), Self::MatchLength(other)) => {
-                        Self::MatchLength(other)
-                    }
-                    (clone, _) => clone,
-                }
-            }
-        }
+impl Pattern {
+    /// Compute the [`Specificity`] of this pattern, which determines its
+    /// ordering, see [`Ord`] for [`Pattern`].
+    pub fn specificity(&self) -> Specificity {
+        Specificity::of(self.0.as_str())
+    }
 
-        impl From<ComponentOrdering> for Ordering {
-            fn from(value: ComponentOrdering) -> Self {
-                match value {
-                    ComponentOrdering::MatchLength(ordering) => ordering,
-                    ComponentOrdering::Lexicographic(ordering) => ordering,
-                }
-            }
-        }
+    /// Check if this pattern matches every reference in its category, e.g.
+    /// `refs/heads/*`, which matches all branches.
+    pub fn is_catch_all(&self) -> bool {
+        self.specificity().is_catch_all()
+    }
 
-        impl Default for ComponentOrdering {
-            /// The weakest value of Self, which will be absorbed by any
-            /// other in [`ComponentOrdering::merge`].
-            fn default() -> Self {
-                Self::Lexicographic(Ordering::Equal)
-            }
-        }
+    fn components(&self) -> std::str::Split<'_, char> {
+        self.0.as_str().split('/')
+    }
+}
 
-        use git::fmt::refspec::Component;
-
-        fn cmp_component(lhs: Component<'_>, rhs: Component<'_>) -> ComponentOrdering {
-            let (l, r) = (lhs.as_str(), rhs.as_str());
-            match (l.find(ASTERISK), r.find(ASTERISK)) {
-                // (2.a.)
-                (Some(_), None) => ComponentOrdering::MatchLength(Ordering::Greater),
-                // (2.a.)
-                (None, Some(_)) => ComponentOrdering::MatchLength(Ordering::Less),
-                (Some(li), Some(ri)) => {
-                    if li != ri {
-                        // (2.b.A)
-                        ComponentOrdering::MatchLength(li.cmp(&ri).reverse())
-                    } else if l.len() != r.len() {
-                        // (2.b.B)
-                        ComponentOrdering::MatchLength(l.len().cmp(&r.len()).reverse())
-                    } else {
-                        // (3.)
-                        ComponentOrdering::Lexicographic(l.cmp(r))
-                    }
-                }
-                // (3.)
-                (None, None) => ComponentOrdering::Lexicographic(l.cmp(r)),
-            }
+/// The specificity of a [`Pattern`], made up of its number of components and
+/// the position of the asterisk within them.
+///
+/// Specificities are ordered like their patterns, with more specific
+/// patterns comparing as less, except that specificities do not fall back to
+/// the lexicographic ordering of components. Thus, two patterns with equal
+/// specificity are equally specific.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Specificity {
+    components: Vec<ComponentSpecificity>,
+}
+
+impl Specificity {
+    fn of(pattern: &str) -> Self {
+        Self {
+            components: pattern
+                .split('/')
+                .map(|c| ComponentSpecificity {
+                    star: c.find(ASTERISK),
+                    len: c.len(),
+                })
+                .collect(),
         }
+    }
 
-        let mut result = ComponentOrdering::default();
-        let mut lhs = self.0.components();
-        let mut rhs = other.0.components();
-        loop {
-            match (lhs.next(), rhs.next()) {
-                (None, Some(_)) => return Ordering::Greater, // (1.)
-                (Some(_), None) => return Ordering::Less,    // (1.)
-                (Some(lhs), Some(rhs)) => {
-                    result.merge(cmp_component(lhs, rhs));
-                }
-                (None, None) => return result.into(),
-            }
+    /// The number of components of the pattern.
+    pub fn components(&self) -> usize {
+        self.components.len()
+    }
+
+    /// The position of the asterisk in the pattern, as the index of the
+    /// component containing it and its offset within the component.
+    /// Returns `None` if the pattern does not contain an asterisk.
+    pub fn star(&self) -> Option<(usize, usize)> {
+        self.components
+            .iter()
+            .enumerate()
+            .find_map(|(i, c)| c.star.map(|offset| (i, offset)))
+    }
+
+    /// Check if the pattern matches exactly one reference.
+    pub fn is_exact(&self) -> bool {
+        self.star().is_none()
+    }
+
+    /// Check if the pattern matches every reference in its category, see
+    /// [`Pattern::is_catch_all`].
+    pub fn is_catch_all(&self) -> bool {
+        matches!(
+            self.components.as_slice(),
+            [
+                ComponentSpecificity { star: None, .. },
+                ComponentSpecificity { star: None, .. },
+                ComponentSpecificity {
+                    star: Some(0),
+                    len: 1
+                },
+            ]
+        )
+    }
+}
+
+impl PartialOrd for Specificity {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Specificity {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // (1.)
+        other
+            .components
+            .len()
+            .cmp(&self.components.len())
+            // (2.)
+            .then_with(|| {
+                self.components
+                    .iter()
+                    .zip(&other.components)
+                    .map(|(lhs, rhs)| lhs.cmp(rhs))
+                    .find(|ordering| ordering.is_ne())
+                    .unwrap_or(Ordering::Equal)
+            })
+    }
+}
+
+/// The specificity of a single path component of a [`Pattern`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct ComponentSpecificity {
+    /// Offset of the asterisk in the component, if any.
+    star: Option<usize>,
+    /// Length of the component.
+    len: usize,
+}
+
+impl PartialOrd for ComponentSpecificity {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ComponentSpecificity {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.star, other.star) {
+            // (2.a.)
+            (Some(_), None) => Ordering::Greater,
+            (None, Some(_)) => Ordering::Less,
+            // (2.b.A), then (2.b.B)
+            (Some(l), Some(r)) => r.cmp(&l).then_with(|| other.len.cmp(&self.len)),
+            (None, None) => Ordering::Equal,
         }
     }
 }
@@ -259,7 +335,7 @@ impl Ord for Pattern {
 /// A [`Rule`] that can be serialized and deserialized safely.
 ///
 /// Should be converted to a [`ValidRule`] via [`Rule::validate`].
-pub type RawRule = Rule<Allowed, usize>;
+pub type RawRule = Rule<Allowed, Threshold>;
 
 impl RawRule {
     /// Validate the `Rule` into a form that can be used for calculating
@@ -285,7 +361,20 @@ impl RawRule {
                 ResolvedDelegates::Set(valid)
             }
         };
-        let threshold = doc::Threshold::new(threshold, &allow)?;
+        let threshold = match threshold {
+            Threshold::Absolute(t) => ResolvedThreshold::Absolute(doc::Threshold::new(t, &allow)?),
+            Threshold::Percent(percent) => {
+                if !(1..=100).contains(&percent) {
+                    return Err(ValidationError::Percent(percent));
+                }
+                // Round up, so that eg. `51%` is always a majority.
+                let t = (usize::from(percent) * allow.len()).div_ceil(100);
+                ResolvedThreshold::Percent {
+                    percent,
+                    threshold: doc::Threshold::new(t, &allow)?,
+                }
+            }
+        };
         Ok(Rule {
             allow,
             threshold,
@@ -381,7 +470,7 @@ impl IntoIterator for RawRules {
 /// This is due to the fact that the `allow` field may have a value of
 /// `delegates`. In those cases the value needs to be looked up via the identity
 /// document and validated.
-pub type ValidRule = Rule<ResolvedDelegates, doc::Threshold>;
+pub type ValidRule = Rule<ResolvedDelegates, ResolvedThreshold>;
 
 impl ValidRule {
     /// Initialize a `ValidRule` for the default branch, given by `name`. The
@@ -410,7 +499,7 @@ impl ValidRule {
             allow: ResolvedDelegates::Delegates(doc::Delegates::from(did)),
             // N.B. this needs to be the minimum since we only have one
             // delegate.
-            threshold: doc::Threshold::MIN,
+            threshold: ResolvedThreshold::Absolute(doc::Threshold::MIN),
             extensions: json::Map::new(),
         };
         Ok((pattern, rule))
@@ -468,6 +557,138 @@ impl From<Did> for Allowed {
     }
 }
 
+/// The threshold of votes required by a `RawRule`.
+///
+/// The threshold is either an absolute number of votes, or a percentage of the
+/// allowed DIDs. A percentage is serialized as a string, eg. `"51%"`, and
+/// allows the threshold to adjust as the set of allowed DIDs changes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(into = "ThresholdRepr", try_from = "ThresholdRepr")]
+pub enum Threshold {
+    /// An absolute number of votes.
+    ///
+    /// # Validation
+    ///
+    /// The threshold, `t`, must satisfy:
+    /// ```text
+    /// 1 <= t <= allowed.len()
+    /// ```
+    Absolute(usize),
+    /// A percentage of the allowed DIDs, rounded up to the nearest number of
+    /// votes.
+    ///
+    /// # Validation
+    ///
+    /// The percentage, `p`, must satisfy:
+    /// ```text
+    /// 1 <= p <= 100
+    /// ```
+    Percent(u8),
+}
+
+impl From<usize> for Threshold {
+    fn from(t: usize) -> Self {
+        Self::Absolute(t)
+    }
+}
+
+impl fmt::Display for Threshold {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Absolute(t) => write!(f, "{t}"),
+            Self::Percent(p) => write!(f, "{p}%"),
+        }
+    }
+}
+
+impl FromStr for Threshold {
+    type Err = ThresholdParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let result = match s.strip_suffix('%') {
+            Some(p) => p.parse().map(Self::Percent),
+            None => s.parse().map(Self::Absolute),
+        };
+        result.map_err(|_| ThresholdParseError(s.to_owned()))
+    }
+}
+
+/// The serialized form of a [`Threshold`].
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum ThresholdRepr {
+    Absolute(usize),
+    Percent(String),
+}
+
+impl From<Threshold> for ThresholdRepr {
+    fn from(t: Threshold) -> Self {
+        match t {
+            Threshold::Absolute(t) => Self::Absolute(t),
+            Threshold::Percent(_) => Self::Percent(t.to_string()),
+        }
+    }
+}
+
+impl TryFrom<ThresholdRepr> for Threshold {
+    type Error = ThresholdParseError;
+
+    fn try_from(repr: ThresholdRepr) -> Result<Self, Self::Error> {
+        match repr {
+            ThresholdRepr::Absolute(t) => Ok(Self::Absolute(t)),
+            ThresholdRepr::Percent(p) if p.ends_with('%') => p.parse(),
+            ThresholdRepr::Percent(p) => Err(ThresholdParseError(p)),
+        }
+    }
+}
+
+/// A marker `enum` that is used in a [`ValidRule`].
+///
+/// It ensures that a rule that has been deserialized, resolving a percentage
+/// threshold to a number of votes, is still serialized back to the percentage –
+/// as opposed to serializing it to the number of votes.
+///
+/// The variants mirror the [`Threshold::Absolute`] and [`Threshold::Percent`]
+/// variants.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(into = "Threshold")]
+pub enum ResolvedThreshold {
+    Absolute(doc::Threshold),
+    Percent {
+        percent: u8,
+        threshold: doc::Threshold,
+    },
+}
+
+impl From<ResolvedThreshold> for Threshold {
+    fn from(t: ResolvedThreshold) -> Self {
+        match t {
+            ResolvedThreshold::Absolute(t) => Self::Absolute(t.into()),
+            ResolvedThreshold::Percent { percent, .. } => Self::Percent(percent),
+        }
+    }
+}
+
+impl std::ops::Deref for ResolvedThreshold {
+    type Target = doc::Threshold;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            ResolvedThreshold::Absolute(t) => t,
+            ResolvedThreshold::Percent { threshold, .. } => threshold,
+        }
+    }
+}
+
+impl fmt::Display for ResolvedThreshold {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Absolute(t) => write!(f, "{t}"),
+            Self::Percent { percent, threshold } => write!(f, "{threshold} ({percent}%)"),
+        }
+    }
+}
+
 /// A marker `enum` that is used in a [`ValidRule`].
 ///
 /// It ensures that a rule that has been deserialized, resolving the `delegates`
@@ -534,6 +755,50 @@ impl MatchedRule<'_> {
     }
 }
 
+/// An explanation of how a reference name is resolved against a set of
+/// [`Rules`].
+///
+/// Can be constructed by using [`Rules::explain`].
+#[derive(Debug)]
+pub struct RuleExplanation<'a, 'b> {
+    refname: Qualified<'b>,
+    candidates: Vec<(&'a Pattern, &'a ValidRule)>,
+}
+
+impl<'a> RuleExplanation<'a, '_> {
+    /// Return the reference name that was explained.
+    pub fn refname(&self) -> &Qualified<'_> {
+        &self.refname
+    }
+
+    /// Return all the rules whose pattern matched the reference name, ordered
+    /// from most specific to least specific.
+    pub fn candidates(&self) -> &[(&'a Pattern, &'a ValidRule)] {
+        &self.candidates
+    }
+
+    /// Return the rule that applies to the reference name, i.e. the most
+    /// specific candidate, if any.
+    pub fn selected(&self) -> Option<(&'a Pattern, &'a ValidRule)> {
+        self.candidates.first().copied()
+    }
+
+    /// Return `true` if the reference name matched none of the rules.
+    pub fn is_empty(&self) -> bool {
+        self.candidates.is_empty()
+    }
+
+    /// Return the allowed DIDs of the selected rule.
+    pub fn allowed(&self) -> Option<&'a doc::Delegates> {
+        self.selected().map(|(_, rule)| &**rule.allowed())
+    }
+
+    /// Return the [`doc::Threshold`] of the selected rule.
+    pub fn threshold(&self) -> Option<&'a doc::Threshold> {
+        self.selected().map(|(_, rule)| &**rule.threshold())
+    }
+}
+
 /// A set of valid [`Rule`]s, where the set of DIDs and threshold are fully
 /// resolved and valid. Since the rules are constructed via a `BTreeMap`, they
 /// cannot be duplicated.
@@ -628,6 +893,23 @@ impl Rules {
             .filter(move |(pattern, _)| pattern.matches(&refname_cloned))
     }
 
+    /// Check whether any rule matches the given `refname`, ie. whether the
+    /// reference is made canonical by a quorum of delegates.
+    pub fn governs(&self, refname: &Qualified) -> bool {
+        self.matches(refname).next().is_some()
+    }
+
+    /// Explain which rules match the given `refname`, and which one of them
+    /// is used for making it canonical.
+    ///
+    /// This is useful for debugging a set of rules with overlapping patterns.
+    pub fn explain<'b>(&self, refname: &Qualified<'b>) -> RuleExplanation<'_, 'b> {
+        RuleExplanation {
+            refname: refname.clone(),
+            candidates: self.matches(refname).collect(),
+        }
+    }
+
     /// Match given refname, take the most specific rule, and prepare evaluation
     /// as [`Canonical`]
     ///
@@ -656,7 +938,7 @@ impl Rules {
 /// for serialization and deserialization, however, the use of
 /// [`Rule::validate`] should be used to get a valid rule.
 ///
-/// The [`Rule::threshold`], similarly, allows for [`doc::Threshold`] to be used, and
+/// The [`Rule::threshold`], similarly, allows for [`Threshold`] to be used, and
 /// [`Rule::validate`] should be used to get a valid rule.
 // N.b. it's safe to derive `Serialize` since we only allow constructing a
 // `Rule` via `Rule::validate`, and we seal `Deserialize` by ensuring that only
@@ -715,6 +997,13 @@ impl<D, T> Rule<D, T> {
 
 #[derive(Debug, Error)]
 pub enum PatternError {
+    #[error("invalid ref pattern '{pattern}': {source}")]
+    Invalid {
+        pattern: String,
+        source: git::fmt::Error,
+    },
+    #[error("ref pattern '{0}' must start with `refs/`")]
+    Unqualified(String),
     #[error("cannot create rule for '{pattern}' since references under '{prefix}' are protected")]
     ProtectedRef {
         prefix: RefString,
@@ -730,8 +1019,14 @@ pub enum ValidationError {
     Delegates(#[from] doc::DelegatesError),
     #[error("cannot create rule for reserved `rad` references '{pattern}'")]
     RadRef { pattern: QualifiedPattern<'static> },
+    #[error("threshold percentage must be between 1% and 100%, found {0}%")]
+    Percent(u8),
 }
 
+#[derive(Debug, Error)]
+#[error("invalid threshold '{0}', expected a number or a percentage, eg. '51%'")]
+pub struct ThresholdParseError(String);
+
 #[derive(Debug, Error)]
 pub enum CanonicalError {
     #[error(transparent)]
@@ -760,7 +1055,7 @@ mod tests {
 
     use super::*;
 
-    fn roundtrip(rule: &Rule<Allowed, usize>) {
+    fn roundtrip(rule: &RawRule) {
         let json = serde_json::to_string(rule).unwrap();
         assert_eq!(
             *rule,
@@ -790,17 +1085,54 @@ mod tests {
             .into()
     }
 
+    #[test]
+    fn test_pattern_from_str() {
+        assert_eq!(
+            "refs/heads/*".parse::<Pattern>().unwrap(),
+            pattern(qualified_pattern!("refs/heads/*"))
+        );
+        assert_eq!(
+            "refs/tags/v1.*".parse::<Pattern>().unwrap(),
+            pattern(qualified_pattern!("refs/tags/v1.*"))
+        );
+        assert_eq!(
+            "refs/heads/main".parse::<Pattern>().unwrap(),
+            Pattern::try_from(git::fmt::qualified!("refs/heads/main")).unwrap()
+        );
+        assert!(matches!(
+            "heads/*".parse::<Pattern>(),
+            Err(PatternError::Unqualified(_))
+        ));
+        assert!(matches!(
+            "refs/*/*".parse::<Pattern>(),
+            Err(PatternError::Invalid { .. })
+        ));
+    }
+
+    #[test]
+    fn test_pattern_from_str_protected() {
+        for s in ["refs/rad/*", "refs/rad/id", "refs/rad/sigrefs"] {
+            assert!(
+                matches!(
+                    s.parse::<Pattern>(),
+                    Err(PatternError::ProtectedRef { ref prefix, .. }) if prefix.as_str() == "refs/rad"
+                ),
+                "{s} must be protected"
+            );
+        }
+    }
+
     #[test]
     fn test_roundtrip() {
-        let rule1 = Rule::new(Allowed::Delegates, 1);
-        let rule2 = Rule::new(Allowed::Delegates, 1);
-        let rule3 = Rule::new(Allowed::Delegates, 1);
+        let rule1 = Rule::new(Allowed::Delegates, Threshold::Absolute(1));
+        let rule2 = Rule::new(Allowed::Delegates, Threshold::Absolute(1));
+        let rule3 = Rule::new(Allowed::Delegates, Threshold::Absolute(1));
         let mut rule4 = Rule::new(
             Allowed::Set(nonempty![
                 did("did:key:z6MkpQTLwr8QyADGmBGAMsGttvWzP4PojUMs4hREZW5T5E3K"),
                 did("did:key:z6MknG1nYDftMYUQ7eTBSGgqB2PL1xK5Pif33J3sRym3e8ye"),
             ]),
-            2,
+            Threshold::Absolute(2),
         );
         rule4.add_extensions(
             serde_json::json!({
@@ -815,6 +1147,78 @@ mod tests {
         roundtrip(&rule2);
         roundtrip(&rule3);
         roundtrip(&rule4);
+        roundtrip(&Rule::new(Allowed::Delegates, Threshold::Percent(51)));
+    }
+
+    #[test]
+    fn test_threshold_percent() {
+        let doc = arbitrary::gen::<Doc>(1);
+        let delegates = |n: usize| {
+            Allowed::Set(
+                NonEmpty::from_vec(
+                    [
+                        "did:key:z6MkpQTLwr8QyADGmBGAMsGttvWzP4PojUMs4hREZW5T5E3K",
+                        "did:key:z6MknG1nYDftMYUQ7eTBSGgqB2PL1xK5Pif33J3sRym3e8ye",
+                        "did:key:z6MknLWe8A7UJxvTfY36JcB8XrP1KTLb5HFTX38hEmdY3b56",
+                        "did:key:z6Mkq2E5Se5H9gk1DsL1EMwR2t4CqSg3GFkNN2UeG4FNqXoP",
+                    ]
+                    .into_iter()
+                    .take(n)
+                    .map(did)
+                    .collect(),
+                )
+                .unwrap(),
+            )
+        };
+        let resolve = |n: usize, percent: u8| {
+            Rule::new(delegates(n), Threshold::Percent(percent))
+                .validate(&mut || resolve_from_doc(&doc))
+                .map(|rule| usize::from(**rule.threshold()))
+        };
+
+        assert_eq!(resolve(4, 51).unwrap(), 3);
+        assert_eq!(resolve(4, 50).unwrap(), 2);
+        assert_eq!(resolve(4, 26).unwrap(), 2);
+        assert_eq!(resolve(4, 25).unwrap(), 1);
+        assert_eq!(resolve(4, 100).unwrap(), 4);
+        assert_eq!(resolve(3, 33).unwrap(), 1);
+        assert_eq!(resolve(3, 34).unwrap(), 2);
+        assert_eq!(resolve(3, 67).unwrap(), 3);
+        assert_eq!(resolve(1, 1).unwrap(), 1);
+        assert_eq!(resolve(1, 100).unwrap(), 1);
+
+        assert!(matches!(resolve(4, 0), Err(ValidationError::Percent(0))));
+        assert!(matches!(
+            resolve(4, 101),
+            Err(ValidationError::Percent(101))
+        ));
+
+        // The percentage is preserved when serializing a valid rule.
+        let rule = Rule::new(delegates(4), Threshold::Percent(51))
+            .validate(&mut || resolve_from_doc(&doc))
+            .unwrap();
+        let json = serde_json::to_value(&rule).unwrap();
+        assert_eq!(json["threshold"], "51%");
+        assert_eq!(
+            RawRule::from(rule),
+            Rule::new(delegates(4), Threshold::Percent(51))
+        );
+    }
+
+    #[test]
+    fn test_threshold_deserialization() {
+        let threshold = |json: &str| serde_json::from_str::<Threshold>(json);
+
+        assert_eq!(threshold("2").unwrap(), Threshold::Absolute(2));
+        assert_eq!(threshold(r#""51%""#).unwrap(), Threshold::Percent(51));
+        assert!(threshold(r#""51""#).is_err());
+        assert!(threshold(r#""%""#).is_err());
+        assert!(threshold(r#""256%""#).is_err());
+        assert!(threshold("-1").is_err());
+
+        assert_eq!("3".parse::<Threshold>().unwrap(), Threshold::Absolute(3));
+        assert_eq!("75%".parse::<Threshold>().unwrap(), Threshold::Percent(75));
+        assert_eq!(Threshold::Percent(75).to_string(), "75%");
     }
 
     #[test]
@@ -856,7 +1260,7 @@ mod tests {
                         did("did:key:z6MkpQTLwr8QyADGmBGAMsGttvWzP4PojUMs4hREZW5T5E3K"),
                         did("did:key:z6MknG1nYDftMYUQ7eTBSGgqB2PL1xK5Pif33J3sRym3e8ye"),
                     ]),
-                    2,
+                    Threshold::Absolute(2),
                 ),
             ),
             (
@@ -867,7 +1271,7 @@ mod tests {
                         did("did:key:z6Mkq2E5Se5H9gk1DsL1EMwR2t4CqSg3GFkNN2UeG4FNqXoP"),
                         did("did:key:z6MkqRmXW5fbP9hJ1Y8j2N4CgVdJ2XJ6TsyXYf3FQ2NJgXax")
                     ]),
-                    2,
+                    Threshold::Absolute(2),
                 ),
             ),
             (
@@ -876,12 +1280,12 @@ mod tests {
                     Allowed::Set(nonempty![did(
                         "did:key:z6MkhH7ENYE62JAjTiRZPU71MGZ6xCwnbyHHWfrBu3fr6PVG"
                     )]),
-                    1,
+                    Threshold::Absolute(1),
                 ),
             ),
             (
                 pattern(qualified_pattern!("refs/heads/release/*")),
-                Rule::new(Allowed::Delegates, 1),
+                Rule::new(Allowed::Delegates, Threshold::Absolute(1)),
             ),
         ]
         .into_iter()
@@ -892,6 +1296,62 @@ mod tests {
         assert_eq!(expected, rules)
     }
 
+    #[test]
+    fn test_specificity() {
+        let main = pattern(qualified_pattern!("refs/heads/main"));
+        let heads = pattern(qualified_pattern!("refs/heads/*"));
+
+        assert!(main.specificity() < heads.specificity());
+        assert!(main < heads);
+
+        assert_eq!(main.specificity().components(), 3);
+        assert_eq!(main.specificity().star(), None);
+        assert!(main.specificity().is_exact());
+        assert_eq!(heads.specificity().star(), Some((2, 0)));
+        assert_eq!(
+            pattern(qualified_pattern!("refs/tags/v*/latest"))
+                .specificity()
+                .star(),
+            Some((2, 1))
+        );
+
+        assert!(heads.is_catch_all());
+        assert!(pattern(qualified_pattern!("refs/tags/*")).is_catch_all());
+        assert!(!pattern(qualified_pattern!("refs/heads/a*")).is_catch_all());
+        assert!(!pattern(qualified_pattern!("refs/heads/release/*")).is_catch_all());
+        assert!(!pattern(qualified_pattern!("refs/tags/*/latest")).is_catch_all());
+        assert!(!main.is_catch_all());
+    }
+
+    #[test]
+    fn test_specificity_consistent_with_order() {
+        let patterns = [
+            pattern(qualified_pattern!("refs/heads/*")),
+            pattern(qualified_pattern!("refs/heads/main")),
+            pattern(qualified_pattern!("refs/heads/dev")),
+            pattern(qualified_pattern!("refs/heads/a*")),
+            pattern(qualified_pattern!("refs/heads/aa*")),
+            pattern(qualified_pattern!("refs/heads/a*b")),
+            pattern(qualified_pattern!("refs/heads/a/*/c")),
+            pattern(qualified_pattern!("refs/heads/a/b/*")),
+            pattern(qualified_pattern!("refs/tags/*")),
+            pattern(qualified_pattern!("refs/tags/*/v1.0.0")),
+            pattern(qualified_pattern!("refs/tags/release/candidates/*")),
+        ];
+
+        for lhs in &patterns {
+            for rhs in &patterns {
+                let specificity = lhs.specificity().cmp(&rhs.specificity());
+                // Patterns only fall back to lexicographic ordering when
+                // equally specific.
+                if specificity.is_ne() {
+                    assert_eq!(lhs.cmp(rhs), specificity, "{lhs} and {rhs}");
+                }
+                assert_eq!(lhs.cmp(rhs), rhs.cmp(lhs).reverse(), "{lhs} and {rhs}");
+            }
+        }
+    }
+
     #[test]
     fn test_order() {
         assert!(
@@ -1011,7 +1471,7 @@ mod tests {
   "quux": 5
 }
 "#;
-        let rule = serde_json::from_str::<Rule<Allowed, usize>>(example).unwrap();
+        let rule = serde_json::from_str::<RawRule>(example).unwrap();
         assert!(!rule.extensions().is_empty());
         let extensions = rule.extensions();
         assert_eq!(
@@ -1030,11 +1490,11 @@ mod tests {
         let delegates = Allowed::Set(doc.delegates().as_ref().clone());
         let threshold = doc.majority();
 
-        let rule = Rule::new(delegates, threshold);
+        let rule = Rule::new(delegates, Threshold::Absolute(threshold));
         let result = rule.validate(&mut || resolve_from_doc(&doc));
         assert!(result.is_ok(), "failed to validate doc: {result:?}");
 
-        let rule = Rule::new(Allowed::Delegates, 1);
+        let rule = Rule::new(Allowed::Delegates, Threshold::Absolute(1));
         let result = rule.validate(&mut || resolve_from_doc(&doc));
         assert!(result.is_ok(), "failed to validate doc: {result:?}");
     }
@@ -1045,19 +1505,22 @@ mod tests {
         let pattern = pattern(qualified_pattern!("refs/heads/main"));
 
         assert!(matches!(
-            Rule::new(Allowed::Delegates, 256).validate(&mut || resolve_from_doc(&doc)),
+            Rule::new(Allowed::Delegates, Threshold::Absolute(256))
+                .validate(&mut || resolve_from_doc(&doc)),
             Err(ValidationError::Threshold(_))
         ));
 
         let threshold = doc.delegates().len().saturating_add(1);
         assert!(matches!(
-            Rule::new(Allowed::Delegates, threshold).validate(&mut || resolve_from_doc(&doc)),
+            Rule::new(Allowed::Delegates, Threshold::Absolute(threshold))
+                .validate(&mut || resolve_from_doc(&doc)),
             Err(ValidationError::Threshold(_))
         ));
 
         let delegates = NonEmpty::from_vec(arbitrary::vec::<Did>(256)).unwrap();
         assert!(matches!(
-            Rule::new(delegates.into(), 1).validate(&mut || resolve_from_doc(&doc)),
+            Rule::new(delegates.into(), Threshold::Absolute(1))
+                .validate(&mut || resolve_from_doc(&doc)),
             Err(ValidationError::Delegates(_))
         ));
 
@@ -1072,11 +1535,11 @@ mod tests {
                 )])
                 .unwrap(),
             ),
-            threshold: doc::Threshold::MIN,
+            threshold: ResolvedThreshold::Absolute(doc::Threshold::MIN),
             extensions: json::Map::new(),
         };
         assert_eq!(
-            Rule::new(delegates.into(), 1)
+            Rule::new(delegates.into(), Threshold::Absolute(1))
                 .validate(&mut || resolve_from_doc(&doc))
                 .unwrap(),
             expected,
@@ -1084,17 +1547,23 @@ mod tests {
 
         // Duplicate rules are overwritten
         let rules = vec![
-            (pattern.clone(), Rule::new(Allowed::Delegates, 1)),
             (
                 pattern.clone(),
-                Rule::new(doc.delegates().as_ref().clone().into(), 1),
+                Rule::new(Allowed::Delegates, Threshold::Absolute(1)),
+            ),
+            (
+                pattern.clone(),
+                Rule::new(
+                    doc.delegates().as_ref().clone().into(),
+                    Threshold::Absolute(1),
+                ),
             ),
         ];
         let expected = [(
             pattern,
             Rule::new(
                 ResolvedDelegates::Set(doc.delegates().clone()),
-                doc::Threshold::MIN,
+                ResolvedThreshold::Absolute(doc::Threshold::MIN),
             ),
         )]
         .into_iter()
@@ -1105,6 +1574,93 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_governs() {
+        let alice = did("did:key:z6MknLWe8A7UJxvTfY36JcB8XrP1KTLb5HFTX38hEmdY3b56");
+        let rules = [
+            ValidRule::default_branch(alice, git::fmt::refname!("master").as_ref()).unwrap(),
+            (
+                pattern(qualified_pattern!("refs/tags/release/*")),
+                Rule::new(
+                    ResolvedDelegates::Delegates(doc::Delegates::from(alice)),
+                    ResolvedThreshold::Absolute(doc::Threshold::MIN),
+                ),
+            ),
+        ]
+        .into_iter()
+        .collect::<Rules>();
+
+        assert!(rules.governs(&git::fmt::qualified!("refs/heads/master")));
+        assert!(rules.governs(&git::fmt::qualified!("refs/tags/release/v1.0")));
+        assert!(!rules.governs(&git::fmt::qualified!("refs/heads/feature")));
+        assert!(!rules.governs(&git::fmt::qualified!("refs/tags/v1.0")));
+        assert!(!Rules::default().governs(&git::fmt::qualified!("refs/heads/master")));
+    }
+
+    #[test]
+    fn test_explain() {
+        let doc = arbitrary::gen::<Doc>(1);
+        let alice = did("did:key:z6MknLWe8A7UJxvTfY36JcB8XrP1KTLb5HFTX38hEmdY3b56");
+        let bob = did("did:key:z6MkpQTLwr8QyADGmBGAMsGttvWzP4PojUMs4hREZW5T5E3K");
+        let eve = did("did:key:z6MknG1nYDftMYUQ7eTBSGgqB2PL1xK5Pif33J3sRym3e8ye");
+
+        let all = pattern(qualified_pattern!("refs/tags/*"));
+        let releases = pattern(qualified_pattern!("refs/tags/release/*"));
+        let exact = pattern(qualified_pattern!("refs/tags/release/v1.0"));
+        let heads = pattern(qualified_pattern!("refs/heads/*"));
+        let rules = Rules::from_raw(
+            [
+                (
+                    all.clone(),
+                    Rule::new(Allowed::Delegates, Threshold::Absolute(1)),
+                ),
+                (
+                    releases.clone(),
+                    Rule::new(nonempty![alice, bob].into(), Threshold::Absolute(2)),
+                ),
+                (
+                    exact.clone(),
+                    Rule::new(nonempty![eve].into(), Threshold::Absolute(1)),
+                ),
+                (heads, Rule::new(Allowed::Delegates, Threshold::Absolute(1))),
+            ],
+            &mut || resolve_from_doc(&doc),
+        )
+        .unwrap();
+
+        let explanation = rules.explain(&git::fmt::qualified!("refs/tags/release/v1.0"));
+        let candidates = explanation
+            .candidates()
+            .iter()
+            .map(|(pattern, _)| *pattern)
+            .collect::<Vec<_>>();
+        assert_eq!(candidates, vec![&exact, &releases, &all]);
+        assert_eq!(explanation.selected().map(|(p, _)| p), Some(&exact));
+        assert_eq!(
+            explanation
+                .allowed()
+                .map(|ds| ds.iter().collect::<Vec<_>>()),
+            Some(vec![&eve])
+        );
+        assert_eq!(explanation.threshold(), Some(&doc::Threshold::MIN));
+
+        let explanation = rules.explain(&git::fmt::qualified!("refs/tags/release/v2.0"));
+        let candidates = explanation
+            .candidates()
+            .iter()
+            .map(|(pattern, _)| *pattern)
+            .collect::<Vec<_>>();
+        assert_eq!(candidates, vec![&releases, &all]);
+        assert_eq!(explanation.selected().map(|(p, _)| p), Some(&releases));
+        assert_eq!(explanation.threshold().map(|t| usize::from(*t)), Some(2));
+
+        let explanation = rules.explain(&git::fmt::qualified!("refs/notes/v1.0"));
+        assert!(explanation.is_empty());
+        assert!(explanation.selected().is_none());
+        assert!(explanation.allowed().is_none());
+        assert!(explanation.threshold().is_none());
+    }
+
     #[test]
     fn test_canonical() {
         let tempdir = tempfile::tempdir().unwrap();
@@ -1180,18 +1736,18 @@ mod tests {
             [
                 (
                     pattern(qualified_pattern!("refs/tags/*")),
-                    Rule::new(Allowed::Delegates, 1),
+                    Rule::new(Allowed::Delegates, Threshold::Absolute(1)),
                 ),
                 (
                     pattern(qualified_pattern!("refs/tags/release/*")),
-                    Rule::new(Allowed::Delegates, 1),
+                    Rule::new(Allowed::Delegates, Threshold::Absolute(1)),
                 ),
                 // Ensure that none of the other rules apply by ensuring we need
                 // both delegates to get the quorum of the
                 // `refs/tags/release/candidates/v1.0` reference
                 (
                     pattern(qualified_pattern!("refs/tags/release/candidates/*")),
-                    Rule::new(Allowed::Delegates, 2),
+                    Rule::new(Allowed::Delegates, Threshold::Absolute(2)),
                 ),
             ],
             &mut || resolve_from_doc(&doc.clone().verified().unwrap()),
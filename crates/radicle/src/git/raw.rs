@@ -23,7 +23,8 @@ pub use git2::{
 
 // Re-exports that are used by other crates in the workspace, but *not* this crate.
 pub use git2::{
-    AnnotatedCommit, Diff, DiffFindOptions, DiffOptions, DiffStats, MergeAnalysis, MergeOptions,
+    AnnotatedCommit, Delta, Diff, DiffFindOptions, DiffOptions, DiffStats, MergeAnalysis,
+    MergeOptions,
 };
 
 // Re-exports for `radicle-cli`.
@@ -747,7 +747,7 @@ impl Doc {
         let pattern = rules::Pattern::try_from(git::refs::branch(refname).to_owned())?;
         let rule = rules::Rule::new(
             rules::ResolvedDelegates::Delegates(self.delegates.clone()),
-            self.threshold,
+            rules::ResolvedThreshold::Absolute(self.threshold),
         );
         Ok((pattern, rule))
     }
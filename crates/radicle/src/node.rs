@@ -849,6 +849,16 @@ pub enum Error {
     },
     #[error("received empty response for command")]
     EmptyResponse,
+    #[error("command `{command}` is not supported by the node")]
+    UnknownCommand { command: String },
+}
+
+impl From<UnsupportedCommand> for Error {
+    fn from(UnsupportedCommand { command }: UnsupportedCommand) -> Self {
+        Self::UnknownCommand {
+            command: command.to_owned(),
+        }
+    }
 }
 
 impl Error {
@@ -856,6 +866,20 @@ impl Error {
     pub fn is_connection_err(&self) -> bool {
         matches!(self, Self::Connect { .. })
     }
+
+    /// Check if the error is due to the node not knowing the command, eg. because
+    /// it runs an older version.
+    pub fn is_unknown_command(&self) -> bool {
+        matches!(self, Self::UnknownCommand { .. })
+    }
+}
+
+/// Error returned by the [`Handle`] methods that a handle doesn't implement.
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+#[error("command `{command}` is not supported")]
+pub struct UnsupportedCommand {
+    /// The name of the command.
+    pub command: &'static str,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -873,7 +897,7 @@ pub trait Handle: Clone + Sync + Send {
     type Events: IntoIterator<Item = Self::Event>;
     type Event;
     /// The error returned by all methods.
-    type Error: std::error::Error + Send + Sync + 'static;
+    type Error: std::error::Error + From<UnsupportedCommand> + Send + Sync + 'static;
 
     /// Get the local Node ID.
     fn nid(&self) -> Result<NodeId, Self::Error>;
@@ -921,6 +945,15 @@ pub trait Handle: Clone + Sync + Send {
     fn follow(&mut self, id: NodeId, alias: Option<Alias>) -> Result<bool, Self::Error>;
     /// Un-seed the given repo and delete it from storage.
     fn unseed(&mut self, id: RepoId) -> Result<bool, Self::Error>;
+    /// Block the given repo, refusing to fetch it from, or serve it to, other
+    /// nodes. The block persists across restarts.
+    fn block(&mut self, _id: RepoId) -> Result<bool, Self::Error> {
+        Err(UnsupportedCommand { command: "block" }.into())
+    }
+    /// Unblock the given repo.
+    fn unblock(&mut self, _id: RepoId) -> Result<bool, Self::Error> {
+        Err(UnsupportedCommand { command: "unblock" }.into())
+    }
     /// Unfollow the given peer.
     fn unfollow(&mut self, id: NodeId) -> Result<bool, Self::Error>;
 
@@ -953,6 +986,22 @@ pub trait Handle: Clone + Sync + Send {
     fn subscribe(&self, timeout: time::Duration) -> Result<Self::Events, Self::Error>;
     /// Return debug information as a JSON value.
     fn debug(&self) -> Result<json::Value, Self::Error>;
+    /// Return a snapshot of the node's metrics as a JSON value.
+    fn metrics(&self) -> Result<json::Value, Self::Error> {
+        Err(UnsupportedCommand { command: "metrics" }.into())
+    }
+    /// Return the time elapsed since the node started. This is measured with a
+    /// monotonic clock, and isn't affected by changes to the system time.
+    fn uptime(&self) -> Result<time::Duration, Self::Error> {
+        Err(UnsupportedCommand { command: "uptime" }.into())
+    }
+    /// Set the maximum level of the node's logs, taking effect immediately.
+    fn set_log_level(&mut self, _level: log::Level) -> Result<(), Self::Error> {
+        Err(UnsupportedCommand {
+            command: "setLogLevel",
+        }
+        .into())
+    }
 }
 
 /// Iterator of results `T` when passing a [`Command`] to [`Node::call`].
@@ -990,6 +1039,9 @@ impl<T: DeserializeOwned> Iterator for LineIter<T> {
                 };
                 match result {
                     CommandResult::Okay(result) => Some(Ok(result)),
+                    CommandResult::UnknownCommand { command, .. } => {
+                        Some(Err(Error::UnknownCommand { command }))
+                    }
                     CommandResult::Error { reason } => Some(Err(Error::Command { reason })),
                 }
             }
@@ -1227,6 +1279,20 @@ impl Handle for Node {
         Ok(response.updated)
     }
 
+    fn block(&mut self, rid: RepoId) -> Result<bool, Error> {
+        let mut lines = self.call::<Success>(Command::Block { rid }, DEFAULT_TIMEOUT)?;
+        let response = lines.next().ok_or(Error::EmptyResponse)??;
+
+        Ok(response.updated)
+    }
+
+    fn unblock(&mut self, rid: RepoId) -> Result<bool, Error> {
+        let mut lines = self.call::<Success>(Command::Unblock { rid }, DEFAULT_TIMEOUT)?;
+        let response = lines.next().ok_or(Error::EmptyResponse)??;
+
+        Ok(response.updated)
+    }
+
     fn announce_refs_for(
         &mut self,
         rid: RepoId,
@@ -1291,6 +1357,29 @@ impl Handle for Node {
         Ok(debug)
     }
 
+    fn metrics(&self) -> Result<json::Value, Self::Error> {
+        let metrics = self
+            .call::<json::Value>(Command::Metrics, DEFAULT_TIMEOUT)?
+            .next()
+            .ok_or(Error::EmptyResponse {})??;
+
+        Ok(metrics)
+    }
+
+    fn uptime(&self) -> Result<time::Duration, Self::Error> {
+        self.call::<time::Duration>(Command::Uptime, DEFAULT_TIMEOUT)?
+            .next()
+            .ok_or(Error::EmptyResponse)?
+    }
+
+    fn set_log_level(&mut self, level: log::Level) -> Result<(), Error> {
+        let level = level.into();
+        for line in self.call::<Success>(Command::SetLogLevel { level }, DEFAULT_TIMEOUT)? {
+            line?;
+        }
+        Ok(())
+    }
+
     fn shutdown(self) -> Result<(), Error> {
         for line in self.call::<Success>(Command::Shutdown, DEFAULT_TIMEOUT)? {
             line?;
@@ -133,6 +133,14 @@ pub enum Command {
     #[serde(rename_all = "camelCase")]
     Unseed { rid: RepoId },
 
+    /// Block the given repository.
+    #[serde(rename_all = "camelCase")]
+    Block { rid: RepoId },
+
+    /// Unblock the given repository.
+    #[serde(rename_all = "camelCase")]
+    Unblock { rid: RepoId },
+
     /// Follow the given node.
     #[serde(rename_all = "camelCase")]
     Follow {
@@ -160,6 +168,16 @@ pub enum Command {
     /// Get node debug information.
     Debug,
 
+    /// Get a snapshot of the node's metrics.
+    Metrics,
+
+    /// Get the time elapsed since the node started.
+    Uptime,
+
+    /// Set the node's log level, without restarting it.
+    #[serde(rename_all = "camelCase")]
+    SetLogLevel { level: super::config::LogLevel },
+
     /// Get the node's NID.
     NodeId,
 
@@ -168,6 +186,11 @@ pub enum Command {
 
     /// Subscribe to events.
     Subscribe,
+
+    /// A command the node doesn't know, eg. because it was added in a later
+    /// version. The node responds with [`CommandResult::UnknownCommand`].
+    #[serde(other)]
+    Unknown,
 }
 
 impl Command {
@@ -203,6 +226,16 @@ impl Default for ConnectOptions {
 pub enum CommandResult<T> {
     /// Response on node socket indicating that a command was carried out successfully.
     Okay(T),
+    /// Response on node socket indicating that the command isn't known to the node.
+    /// Clients that don't know this response read it as an [`CommandResult::Error`].
+    UnknownCommand {
+        /// The reason for the error.
+        #[serde(rename = "error")]
+        reason: String,
+        /// The name of the unknown command.
+        #[serde(rename = "unknownCommand")]
+        command: String,
+    },
     /// Response on node socket indicating that an error occurred.
     Error {
         /// The reason for the error.
@@ -259,6 +292,16 @@ impl CommandResult<()> {
             reason: err.to_string(),
         }
     }
+
+    /// Create an "unknown command" result.
+    pub fn unknown_command(command: impl ToString) -> Self {
+        let command = command.to_string();
+
+        Self::UnknownCommand {
+            reason: format!("unknown command `{command}`"),
+            command,
+        }
+    }
 }
 
 impl<T: Serialize> CommandResult<T> {
@@ -308,6 +351,20 @@ mod test {
             .unwrap(),
             "{\"error\":\"entity not found\"}"
         );
+        assert_eq!(
+            json::to_string(&CommandResult::unknown_command("frobnicate")).unwrap(),
+            "{\"error\":\"unknown command `frobnicate`\",\"unknownCommand\":\"frobnicate\"}"
+        );
+        assert_eq!(
+            json::from_str::<CommandResult<time::Duration>>(
+                "{\"error\":\"unknown command `uptime`\",\"unknownCommand\":\"uptime\"}"
+            )
+            .unwrap(),
+            CommandResult::UnknownCommand {
+                reason: String::from("unknown command `uptime`"),
+                command: String::from("uptime"),
+            }
+        );
 
         json::from_str::<CommandResult<State>>(
             &serde_json::to_string(&CommandResult::Okay(State::Connected {
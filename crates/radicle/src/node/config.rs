@@ -147,6 +147,45 @@ pub struct Limits {
 
     /// Channel limits.
     pub fetch_pack_receive: FetchPackSizeLimit,
+
+    /// What to do when a peer sends data faster than it can be processed.
+    pub inbox_overflow: InboxOverflow,
+
+    /// Maximum number of bytes received from a peer that are buffered before
+    /// being processed. Must be at least [`LimitInboxSize::MIN`].
+    pub inbox_size: LimitInboxSize,
+
+    /// Maximum number of bytes of a gossip message sent to a peer, once
+    /// framed. Larger inventory announcements of ours are split into several
+    /// announcements, and other larger messages are dropped, since peers would
+    /// exceed their inbox size receiving them, and disconnect.
+    pub max_frame_size: LimitFrameSize,
+
+    /// Number of fetches waiting for a worker, above which new fetches are
+    /// deferred, and fetch requests from peers are declined, until the
+    /// workers catch up.
+    pub max_queued_fetches: LimitQueuedFetches,
+
+    /// How long to wait for active fetches to finish when shutting down.
+    pub shutdown_grace: LimitShutdownGrace,
+}
+
+/// Policy for when the inbox of a peer is full.
+///
+/// Default: reject
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum InboxOverflow {
+    /// Disconnect the peer.
+    #[default]
+    Reject,
+    /// Hold back incoming gossip messages, dropping the oldest message if the
+    /// backlog is full. Other data is never dropped.
+    DropOldest,
+    /// Hold back incoming gossip messages, dropping the newest message if the
+    /// backlog is full. Other data is never dropped.
+    DropNewest,
 }
 
 /// Limiter for byte streams.
@@ -239,6 +278,80 @@ impl Default for FetchPackSizeLimit {
     }
 }
 
+/// TCP keepalive settings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct KeepAlive {
+    /// How long a connection must be idle before keepalive probes are sent.
+    #[serde(with = "crate::serde_ext::localtime::duration")]
+    #[cfg_attr(
+        feature = "schemars",
+        schemars(with = "crate::schemars_ext::localtime::LocalDuration")
+    )]
+    pub time: LocalDuration,
+    /// Time between keepalive probes.
+    #[serde(with = "crate::serde_ext::localtime::duration")]
+    #[cfg_attr(
+        feature = "schemars",
+        schemars(with = "crate::schemars_ext::localtime::LocalDuration")
+    )]
+    pub interval: LocalDuration,
+    /// Number of unanswered probes before the connection is dropped.
+    /// Not supported on Windows.
+    pub retries: u32,
+}
+
+impl Default for KeepAlive {
+    fn default() -> Self {
+        Self {
+            time: LocalDuration::from_secs(30),
+            interval: LocalDuration::from_secs(10),
+            retries: 3,
+        }
+    }
+}
+
+/// Reconnection settings for persistent peers.
+///
+/// The delay before reconnecting starts at `min_delay`, and doubles with every
+/// failed attempt, up to `max_delay`. A random jitter is applied to each delay.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Reconnect {
+    /// Delay before the first reconnection attempt.
+    #[serde(with = "crate::serde_ext::localtime::duration")]
+    #[cfg_attr(
+        feature = "schemars",
+        schemars(with = "crate::schemars_ext::localtime::LocalDuration")
+    )]
+    pub min_delay: LocalDuration,
+    /// Upper bound on the delay between reconnection attempts.
+    #[serde(with = "crate::serde_ext::localtime::duration")]
+    #[cfg_attr(
+        feature = "schemars",
+        schemars(with = "crate::schemars_ext::localtime::LocalDuration")
+    )]
+    pub max_delay: LocalDuration,
+}
+
+impl Reconnect {
+    /// Default delay before the first reconnection attempt.
+    pub const MIN_DELAY: LocalDuration = LocalDuration::from_secs(3);
+    /// Default upper bound on the delay between reconnection attempts.
+    pub const MAX_DELAY: LocalDuration = LocalDuration::from_mins(60);
+}
+
+impl Default for Reconnect {
+    fn default() -> Self {
+        Self {
+            min_delay: Self::MIN_DELAY,
+            max_delay: Self::MAX_DELAY,
+        }
+    }
+}
+
 /// Connection limits.
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(default, rename_all = "camelCase")]
@@ -249,6 +362,13 @@ pub struct ConnectionLimits {
 
     /// Max outbound connections. Note that this can be higher than the *target* number.
     pub outbound: LimitConnectionsOutbound,
+
+    /// Max inbound connections accepted from a single IP address, per minute.
+    /// Connections beyond this rate are dropped before the handshake.
+    pub max_inbound_per_ip_per_min: LimitConnectionsInboundPerIp,
+
+    /// Time to wait until a network connection is considered inactive.
+    pub timeout: LimitConnectionTimeout,
 }
 
 /// Rate limits for a single connection.
@@ -456,6 +576,11 @@ pub struct Config {
     /// Default seeding policy.
     #[serde(default)]
     pub seeding_policy: DefaultSeedingPolicy,
+    /// Whether to compress git data on fetch streams with peers that support
+    /// it. If disabled, the node also stops advertising support for compressed
+    /// data, so that peers don't compress the data they send.
+    #[serde(default = "default_compression")]
+    pub compression: bool,
     /// Extra fields that aren't supported.
     #[serde(flatten, skip_serializing)]
     pub extra: json::Map<String, json::Value>,
@@ -464,9 +589,24 @@ pub struct Config {
     /// public key will be used as the Node ID.
     ///
     /// A decryption password cannot be configured, but passed at runtime via
-    /// the environment variable `RAD_PASSPHRASE`.
+    /// the environment variable `RAD_PASSPHRASE`, or obtained by running
+    /// [`Config::passphrase_command`].
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub secret: Option<std::path::PathBuf>,
+    /// Command to run to obtain the passphrase of the secret key, eg. from a
+    /// secrets manager. The command is run by the shell, and its output is
+    /// used as the passphrase. Overridden by the environment variable
+    /// `RAD_PASSPHRASE_CMD`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub passphrase_command: Option<String>,
+    /// TCP keepalive settings for peer connections.
+    /// If not set, defaults are used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keepalive: Option<KeepAlive>,
+    /// Reconnection backoff for persistent peers.
+    /// If not set, defaults are used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reconnect: Option<Reconnect>,
 }
 
 impl Config {
@@ -492,8 +632,12 @@ impl Config {
             workers: Workers::default(),
             log: LogLevel::default(),
             seeding_policy: DefaultSeedingPolicy::default(),
+            compression: default_compression(),
             extra: json::Map::default(),
             secret: None,
+            passphrase_command: None,
+            keepalive: None,
+            reconnect: None,
         }
     }
 
@@ -524,10 +668,23 @@ impl Config {
     }
 
     pub fn features(&self) -> node::Features {
-        node::Features::SEED
+        let features = node::Features::SEED
+            .with(node::Features::VERSIONS)
+            .with(node::Features::RATE_LIMITS);
+
+        if self.compression {
+            features.with(node::Features::COMPRESSION)
+        } else {
+            features
+        }
     }
 }
 
+/// Compression is enabled unless configured otherwise.
+fn default_compression() -> bool {
+    true
+}
+
 #[derive(Clone, Copy, Debug, Display, Deserialize, Serialize, From)]
 #[serde(transparent)]
 #[display("{0}")]
@@ -607,6 +764,54 @@ impl From<LimitGossipMaxAge> for LocalDuration {
     }
 }
 
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, Eq, PartialEq)]
+#[serde(transparent)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct LimitShutdownGrace(
+    #[serde(with = "crate::serde_ext::localtime::duration")]
+    #[cfg_attr(
+        feature = "schemars",
+        schemars(with = "crate::schemars_ext::localtime::LocalDuration")
+    )]
+    localtime::LocalDuration,
+);
+
+impl Default for LimitShutdownGrace {
+    fn default() -> Self {
+        Self(localtime::LocalDuration::from_secs(10))
+    }
+}
+
+impl From<LimitShutdownGrace> for LocalDuration {
+    fn from(value: LimitShutdownGrace) -> Self {
+        value.0
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, Eq, PartialEq)]
+#[serde(transparent)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct LimitConnectionTimeout(
+    #[serde(with = "crate::serde_ext::localtime::duration")]
+    #[cfg_attr(
+        feature = "schemars",
+        schemars(with = "crate::schemars_ext::localtime::LocalDuration")
+    )]
+    localtime::LocalDuration,
+);
+
+impl Default for LimitConnectionTimeout {
+    fn default() -> Self {
+        Self(localtime::LocalDuration::from_secs(6))
+    }
+}
+
+impl From<LimitConnectionTimeout> for LocalDuration {
+    fn from(value: LimitConnectionTimeout) -> Self {
+        value.0
+    }
+}
+
 macro_rules! wrapper {
     ($name:ident, $type:ty, $default:expr $(, $derive:ty)*) => {
         #[derive(Clone, Debug, Deserialize, Display, Serialize, From $(, $derive)*)]
@@ -631,6 +836,7 @@ macro_rules! wrapper {
 wrapper!(Workers, usize, 8, Copy);
 wrapper!(LimitConnectionsInbound, usize, 128, Copy);
 wrapper!(LimitConnectionsOutbound, usize, 16, Copy);
+wrapper!(LimitConnectionsInboundPerIp, usize, 60, Copy);
 wrapper!(LimitRoutingMaxSize, usize, 1000, Copy);
 wrapper!(LimitFetchConcurrency, usize, 1, Copy);
 wrapper!(
@@ -642,6 +848,8 @@ wrapper!(
     }
 );
 wrapper!(LimitMaxOpenFiles, usize, 4096, Copy);
+wrapper!(LimitFrameSize, usize, 1024 * 1024 * 2, Copy);
+wrapper!(LimitQueuedFetches, usize, 512, Copy);
 wrapper!(
     LimitRateOutbound,
     RateLimit,
@@ -651,6 +859,48 @@ wrapper!(
     }
 );
 
+/// Maximum number of bytes received from a peer that are buffered before being
+/// processed.
+#[derive(Clone, Copy, Debug, Display, Serialize, From)]
+#[display("{0}")]
+#[serde(transparent)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct LimitInboxSize(usize);
+
+impl LimitInboxSize {
+    /// Smallest inbox size. Smaller inboxes can't hold the largest frames sent
+    /// by peers, ie. gossip messages of up to 64 KiB, along with their framing.
+    pub const MIN: usize = 1024 * 128;
+}
+
+impl Default for LimitInboxSize {
+    fn default() -> Self {
+        Self(1024 * 1024 * 2)
+    }
+}
+
+impl From<LimitInboxSize> for usize {
+    fn from(value: LimitInboxSize) -> Self {
+        value.0
+    }
+}
+
+impl<'de> Deserialize<'de> for LimitInboxSize {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let size = usize::deserialize(deserializer)?;
+        if size < Self::MIN {
+            return Err(serde::de::Error::custom(format!(
+                "inbox size of {size} byte(s) is less than the minimum of {} byte(s)",
+                Self::MIN
+            )));
+        }
+        Ok(Self(size))
+    }
+}
+
 #[cfg(test)]
 #[allow(clippy::unwrap_used)]
 mod test {
@@ -691,4 +941,22 @@ mod test {
         );
         assert_eq!(config.limits.connection.outbound.0, 1337);
     }
+
+    #[test]
+    fn inbox_size() {
+        use super::{Config, LimitInboxSize};
+        use serde_json::json;
+
+        let config = |size: usize| {
+            serde_json::from_value::<Config>(json!({
+                "alias": "example",
+                "limits": { "inboxSize": size },
+            }))
+        };
+        assert_eq!(
+            usize::from(config(LimitInboxSize::MIN).unwrap().limits.inbox_size),
+            LimitInboxSize::MIN
+        );
+        assert!(config(LimitInboxSize::MIN - 1).is_err());
+    }
 }
@@ -74,6 +74,31 @@ pub enum Event {
         /// The reason why the connection was terminated.
         reason: String,
     },
+    /// The node is attempting to establish a connection to another node.
+    ConnectionAttempted {
+        /// The node identifier of the other node.
+        nid: NodeId,
+        /// The address being connected to.
+        addr: node::Address,
+    },
+    /// The node has established a connection it attempted to another node.
+    ConnectionSucceeded {
+        /// The node identifier of the other node.
+        nid: NodeId,
+        /// The address connected to.
+        addr: node::Address,
+    },
+    /// A connection attempt to another node has failed, either because the
+    /// other node couldn't be reached, or because the connection conflicted
+    /// with an existing one.
+    ConnectionFailed {
+        /// The node identifier of the other node.
+        nid: NodeId,
+        /// The address that failed to connect.
+        addr: node::Address,
+        /// The reason why the attempt failed.
+        reason: String,
+    },
     /// The local node has received changes to Git references from its
     /// local user. In other words, the local user has pushed to the
     /// node, updated COBs, or otherwise updated refs in their local node.
@@ -135,6 +160,68 @@ pub enum Event {
     },
 }
 
+impl Event {
+    /// The other node involved in the event, if any.
+    pub fn nid(&self) -> Option<&NodeId> {
+        match self {
+            Self::RefsFetched { remote, .. } | Self::RefsSynced { remote, .. } => Some(remote),
+            Self::SeedDiscovered { nid, .. }
+            | Self::SeedDropped { nid, .. }
+            | Self::PeerConnected { nid }
+            | Self::PeerDisconnected { nid, .. }
+            | Self::ConnectionAttempted { nid, .. }
+            | Self::ConnectionSucceeded { nid, .. }
+            | Self::ConnectionFailed { nid, .. }
+            | Self::InventoryAnnounced { nid, .. }
+            | Self::RefsAnnounced { nid, .. }
+            | Self::NodeAnnounced { nid, .. } => Some(nid),
+            Self::UploadPack(
+                UploadPack::Done { remote, .. }
+                | UploadPack::Write { remote, .. }
+                | UploadPack::Error { remote, .. }
+                | UploadPack::PackProgress { remote, .. },
+            ) => Some(remote),
+            Self::LocalRefsAnnounced { .. } | Self::CanonicalRefUpdated { .. } => None,
+        }
+    }
+
+    /// The repository the event is about, if any.
+    pub fn rid(&self) -> Option<&RepoId> {
+        match self {
+            Self::RefsFetched { rid, .. }
+            | Self::RefsSynced { rid, .. }
+            | Self::SeedDiscovered { rid, .. }
+            | Self::SeedDropped { rid, .. }
+            | Self::LocalRefsAnnounced { rid, .. }
+            | Self::RefsAnnounced { rid, .. }
+            | Self::CanonicalRefUpdated { rid, .. } => Some(rid),
+            Self::UploadPack(
+                UploadPack::Done { rid, .. }
+                | UploadPack::Write { rid, .. }
+                | UploadPack::Error { rid, .. }
+                | UploadPack::PackProgress { rid, .. },
+            ) => Some(rid),
+            Self::PeerConnected { .. }
+            | Self::PeerDisconnected { .. }
+            | Self::ConnectionAttempted { .. }
+            | Self::ConnectionSucceeded { .. }
+            | Self::ConnectionFailed { .. }
+            | Self::InventoryAnnounced { .. }
+            | Self::NodeAnnounced { .. } => None,
+        }
+    }
+
+    /// The Git object the event is about, if there is a single one.
+    pub fn oid(&self) -> Option<&Oid> {
+        match self {
+            Self::RefsSynced { at, .. } => Some(at),
+            Self::LocalRefsAnnounced { refs, .. } => Some(&refs.at),
+            Self::CanonicalRefUpdated { target, .. } => Some(target),
+            _ => None,
+        }
+    }
+}
+
 impl From<upload_pack::UploadPack> for Event {
     fn from(value: upload_pack::UploadPack) -> Self {
         Self::UploadPack(value)
@@ -14,6 +14,17 @@ impl Features {
     /// `SEED` is the base feature set all seed nodes must support.
     pub const SEED: Features = Features(0b00000001);
 
+    /// `COMPRESSION` means the node accepts compressed git data on fetch streams.
+    pub const COMPRESSION: Features = Features(0b00000010);
+
+    /// `VERSIONS` means the node negotiates the protocol version with its peers
+    /// when connecting.
+    pub const VERSIONS: Features = Features(0b00000100);
+
+    /// `RATE_LIMITS` means the node understands a notice sent before it is
+    /// disconnected for sending data too fast, and backs off accordingly.
+    pub const RATE_LIMITS: Features = Features(0b00001000);
+
     /// Returns [`Features`] with the other features added.
     #[must_use]
     pub fn with(self, other: Features) -> Features {
@@ -67,6 +67,11 @@ impl<T> Config<T> {
         Self { policy, store }
     }
 
+    /// Set the default policy.
+    pub fn set_default_policy(&mut self, policy: SeedingPolicy) {
+        self.policy = policy;
+    }
+
     /// Check if a repository is seeded.
     pub fn is_seeding(&self, rid: &RepoId) -> Result<bool, Error> {
         self.seed_policy(rid).map(|entry| entry.policy.is_allow())
@@ -113,6 +113,23 @@ impl ReplicationFactor {
         }
     }
 
+    /// Check if `count` replicas satisfy the replication factor.
+    ///
+    /// For [`ReplicationFactor::MustReach`], the factor must be reached, while
+    /// for [`ReplicationFactor::Range`], the upper bound must be reached.
+    pub fn satisfied_by(&self, count: usize) -> bool {
+        self.remaining(count) == 0
+    }
+
+    /// Get the number of replicas remaining, given `count` replicas, for the
+    /// replication factor to be satisfied, see
+    /// [`ReplicationFactor::satisfied_by`].
+    pub fn remaining(&self, count: usize) -> usize {
+        self.upper_bound()
+            .unwrap_or_else(|| self.lower_bound())
+            .saturating_sub(count)
+    }
+
     /// Set the minimum target of the [`ReplicationFactor`] to a new value.
     ///
     /// If the original value is smaller than the new value, then the original
@@ -175,4 +192,38 @@ mod test {
             ReplicationFactor::Range(ReplicationRange { lower: 1, upper: 3 })
         );
     }
+
+    #[test]
+    fn replicas_satisfied_by_must_reach() {
+        let replicas = ReplicationFactor::must_reach(3);
+        assert!(!replicas.satisfied_by(2));
+        assert!(replicas.satisfied_by(3));
+        assert!(replicas.satisfied_by(4));
+        assert_eq!(replicas.remaining(0), 3);
+        assert_eq!(replicas.remaining(2), 1);
+        assert_eq!(replicas.remaining(3), 0);
+        assert_eq!(replicas.remaining(4), 0);
+    }
+
+    #[test]
+    fn replicas_satisfied_by_range() {
+        let replicas = ReplicationFactor::range(2, 5);
+        assert!(!replicas.satisfied_by(2));
+        assert!(!replicas.satisfied_by(4));
+        assert!(replicas.satisfied_by(5));
+        assert_eq!(replicas.remaining(2), 3);
+        assert_eq!(replicas.remaining(5), 0);
+        assert_eq!(replicas.remaining(6), 0);
+    }
+
+    #[test]
+    fn replicas_satisfied_by_zero() {
+        let replicas = ReplicationFactor::must_reach(0);
+        assert!(replicas.satisfied_by(0));
+        assert_eq!(replicas.remaining(0), 0);
+
+        let replicas = ReplicationFactor::range(0, 2);
+        assert!(!replicas.satisfied_by(0));
+        assert_eq!(replicas.remaining(0), 2);
+    }
 }
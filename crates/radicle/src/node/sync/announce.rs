@@ -13,6 +13,9 @@ pub struct Announcer {
     target: Target,
     synced: BTreeMap<NodeId, SyncStatus>,
     to_sync: BTreeSet<NodeId>,
+    failed: BTreeMap<NodeId, FailureReason>,
+    priority: Vec<NodeId>,
+    on_progress: Option<ProgressHook>,
 }
 
 impl Announcer {
@@ -79,6 +82,9 @@ impl Announcer {
                 .map(|nid| (nid, SyncStatus::AlreadySynced))
                 .collect(),
             to_sync,
+            failed: BTreeMap::new(),
+            priority: config.priority,
+            on_progress: None,
         };
         match announcer.is_target_reached() {
             None => Ok(announcer),
@@ -101,6 +107,17 @@ impl Announcer {
         }
     }
 
+    /// Call `f` with the [`Progress`] of the [`Announcer`] whenever it changes,
+    /// i.e. whenever [`Announcer::synced_with`] or [`Announcer::failed_with`]
+    /// mark a node that was not already marked.
+    pub fn on_progress<F>(mut self, f: F) -> Self
+    where
+        F: FnMut(&Progress) + 'static,
+    {
+        self.on_progress = Some(ProgressHook(Box::new(f)));
+        self
+    }
+
     /// Mark the `node` as synchronized, with the given `duration` it took to
     /// synchronize with.
     ///
@@ -118,8 +135,37 @@ impl Announcer {
             return ControlFlow::Continue(self.progress());
         }
         self.to_sync.remove(&node);
-        self.synced.insert(node, SyncStatus::Synced { duration });
-        self.finished()
+        self.failed.remove(&node);
+        let duplicate = self
+            .synced
+            .insert(node, SyncStatus::Synced { duration })
+            .is_some();
+        self.transition(!duplicate)
+    }
+
+    /// Mark the `node` as failed, for the given `reason`, e.g. the node could
+    /// not be connected to. The node will no longer be synchronized with.
+    ///
+    /// Failing the local node, or a node that is already synchronized, is
+    /// ignored.
+    ///
+    /// If the target for the [`Announcer`] has been reached, then a [`Success`] is
+    /// returned via [`ControlFlow::Break`]. Otherwise, [`Progress`] is returned
+    /// via [`ControlFlow::Continue`].
+    ///
+    /// Use [`Announcer::can_continue`] to check if there are any nodes left to
+    /// synchronize with.
+    pub fn failed_with(
+        &mut self,
+        node: NodeId,
+        reason: FailureReason,
+    ) -> ControlFlow<Success, Progress> {
+        if node == self.local_node || self.synced.contains_key(&node) {
+            return ControlFlow::Continue(self.progress());
+        }
+        self.to_sync.remove(&node);
+        let duplicate = self.failed.insert(node, reason).is_some();
+        self.transition(!duplicate)
     }
 
     /// Complete the [`Announcer`] process returning a [`AnnouncerResult`].
@@ -130,18 +176,56 @@ impl Announcer {
     pub fn timed_out(self) -> AnnouncerResult {
         match self.is_target_reached() {
             None => TimedOut {
+                preferred: self.success_counts().preferred,
                 synced: self.synced,
                 timed_out: self.to_sync,
+                failed: self.failed,
             }
             .into(),
             Some(outcome) => Success {
                 outcome,
                 synced: self.synced,
+                failed: self.failed,
             }
             .into(),
         }
     }
 
+    /// Drive the [`Announcer`] to completion, by repeatedly asking `next` for a
+    /// node that was synchronized with, and the time it took, given the nodes
+    /// left to synchronize with. The node is then marked as synchronized, see
+    /// [`Announcer::synced_with`].
+    ///
+    /// The result is [`AnnouncerResult::Success`] once the target is reached,
+    /// [`AnnouncerResult::TimedOut`] if `next` returns `None` before that, and
+    /// [`AnnouncerResult::NoNodes`] if there are no nodes left to synchronize
+    /// with.
+    ///
+    /// Note that `next` should return one of the given nodes, since otherwise
+    /// the process may not make progress.
+    pub fn drive<F>(mut self, mut next: F) -> AnnouncerResult
+    where
+        F: FnMut(&BTreeSet<NodeId>) -> Option<(NodeId, time::Duration)>,
+    {
+        loop {
+            let to_sync = self.to_sync();
+            if to_sync.is_empty() {
+                return NoNodes {
+                    preferred: self.success_counts().preferred,
+                    synced: self.synced,
+                    failed: self.failed,
+                }
+                .into();
+            }
+            let Some((node, duration)) = next(&to_sync) else {
+                return self.timed_out();
+            };
+            if let ControlFlow::Break(success) = self.synced_with(node, duration) {
+                return success.into();
+            }
+        }
+    }
+
     /// Check if the [`Announcer`] can continue synchronizing with more nodes.
     /// If there are no more nodes, then [`NoNodes`] is returned in the
     /// [`ControlFlow::Break`], otherwise the [`Announcer`] is returned as-is in
@@ -154,7 +238,9 @@ impl Announcer {
     pub fn can_continue(self) -> ControlFlow<NoNodes, Self> {
         if self.to_sync.is_empty() {
             ControlFlow::Break(NoNodes {
+                preferred: self.success_counts().preferred,
                 synced: self.synced,
+                failed: self.failed,
             })
         } else {
             ControlFlow::Continue(self)
@@ -170,6 +256,25 @@ impl Announcer {
             .collect()
     }
 
+    /// Get all the nodes to be synchronized with, in the order they should be
+    /// attempted.
+    ///
+    /// The preferred seeds come first, followed by the prioritized nodes, in
+    /// the order given by [`AnnouncerConfig::with_priority`], and then the rest
+    /// of the nodes.
+    pub fn to_sync_ordered(&self) -> Vec<NodeId> {
+        let mut to_sync = self.to_sync();
+        let mut ordered = Vec::with_capacity(to_sync.len());
+
+        for node in self.target.preferred_seeds.iter().chain(&self.priority) {
+            if to_sync.remove(node) {
+                ordered.push(*node);
+            }
+        }
+        ordered.extend(to_sync);
+        ordered
+    }
+
     /// Get the [`Target`] of the [`Announcer`].
     pub fn target(&self) -> &Target {
         &self.target
@@ -186,6 +291,18 @@ impl Announcer {
         }
     }
 
+    /// Check if the target is reached, after marking a node. If `changed`,
+    /// then the progress hook is called.
+    fn transition(&mut self, changed: bool) -> ControlFlow<Success, Progress> {
+        if changed {
+            let progress = self.progress();
+            if let Some(ProgressHook(f)) = self.on_progress.as_mut() {
+                f(&progress);
+            }
+        }
+        self.finished()
+    }
+
     fn finished(&self) -> ControlFlow<Success, Progress> {
         let progress = self.progress();
         self.is_target_reached()
@@ -193,6 +310,7 @@ impl Announcer {
                 ControlFlow::Break(Success {
                     outcome,
                     synced: self.synced.clone(),
+                    failed: self.failed.clone(),
                 })
             })
     }
@@ -214,12 +332,12 @@ impl Announcer {
                 return None;
             }
             let replicas = self.target.replicas();
-            let min = replicas.lower_bound();
+            if !replicas.satisfied_by(synced) {
+                return None;
+            }
             match replicas.upper_bound() {
-                None => (synced >= min)
-                    .then_some(SuccessfulOutcome::MinReplicationFactor { preferred, synced }),
-                Some(max) => (synced >= max)
-                    .then_some(SuccessfulOutcome::MaxReplicationFactor { preferred, synced }),
+                None => Some(SuccessfulOutcome::MinReplicationFactor { preferred, synced }),
+                Some(_) => Some(SuccessfulOutcome::MaxReplicationFactor { preferred, synced }),
             }
         }
     }
@@ -237,6 +355,15 @@ impl Announcer {
     }
 }
 
+/// The callback registered via [`Announcer::on_progress`].
+struct ProgressHook(Box<dyn FnMut(&Progress)>);
+
+impl fmt::Debug for ProgressHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ProgressHook").finish_non_exhaustive()
+    }
+}
+
 #[derive(Default)]
 struct SuccessCounts {
     preferred: usize,
@@ -267,6 +394,7 @@ pub struct AnnouncerConfig {
     preferred_seeds: BTreeSet<NodeId>,
     synced: BTreeSet<NodeId>,
     unsynced: BTreeSet<NodeId>,
+    priority: Vec<NodeId>,
 }
 
 impl AnnouncerConfig {
@@ -288,6 +416,7 @@ impl AnnouncerConfig {
             // of assuming they haven't been yet.
             synced: BTreeSet::new(),
             unsynced: network.allowed,
+            priority: Vec::new(),
         }
     }
 
@@ -317,8 +446,20 @@ impl AnnouncerConfig {
             preferred_seeds,
             synced,
             unsynced,
+            priority: Vec::new(),
         }
     }
+
+    /// Set the nodes that should be synchronized with before any other
+    /// non-preferred nodes, in descending order of priority, e.g. to try
+    /// one's own infrastructure before falling back to public seeds.
+    ///
+    /// This only affects the order of [`Announcer::to_sync_ordered`], and not
+    /// the target of the [`Announcer`].
+    pub fn with_priority(mut self, priority: Vec<NodeId>) -> Self {
+        self.priority = priority;
+        self
+    }
 }
 
 /// Result of running an [`Announcer`] process.
@@ -343,7 +484,16 @@ impl AnnouncerResult {
         match self {
             AnnouncerResult::Success(Success { synced, .. }) => synced,
             AnnouncerResult::TimedOut(TimedOut { synced, .. }) => synced,
-            AnnouncerResult::NoNodes(NoNodes { synced }) => synced,
+            AnnouncerResult::NoNodes(NoNodes { synced, .. }) => synced,
+        }
+    }
+
+    /// Get the failed nodes, regardless of the result.
+    pub fn failed(&self) -> &BTreeMap<NodeId, FailureReason> {
+        match self {
+            AnnouncerResult::Success(Success { failed, .. }) => failed,
+            AnnouncerResult::TimedOut(TimedOut { failed, .. }) => failed,
+            AnnouncerResult::NoNodes(NoNodes { failed, .. }) => failed,
         }
     }
 
@@ -352,6 +502,111 @@ impl AnnouncerResult {
         let synced = self.synced();
         synced.contains_key(node)
     }
+
+    /// Get statistics of the time it took to synchronize with nodes,
+    /// regardless of the result. Returns `None` if no nodes were synchronized
+    /// with during the process.
+    pub fn durations(&self) -> Option<DurationStats> {
+        DurationStats::new(self.synced())
+    }
+
+    /// Summarize the result, eg. to report it to the user.
+    pub fn summary(&self) -> AnnounceSummary {
+        let (status, preferred, timed_out, outcome) = match self {
+            AnnouncerResult::Success(Success { outcome, .. }) => (
+                AnnounceStatus::Success,
+                outcome.preferred(),
+                0,
+                Some(*outcome),
+            ),
+            AnnouncerResult::TimedOut(TimedOut {
+                preferred,
+                timed_out,
+                ..
+            }) => (AnnounceStatus::TimedOut, *preferred, timed_out.len(), None),
+            AnnouncerResult::NoNodes(NoNodes { preferred, .. }) => {
+                (AnnounceStatus::NoNodes, *preferred, 0, None)
+            }
+        };
+        let durations = self
+            .synced()
+            .iter()
+            .filter_map(|(nid, status)| match status {
+                SyncStatus::AlreadySynced => None,
+                SyncStatus::Synced { duration } => Some((
+                    *nid,
+                    u64::try_from(duration.as_millis()).unwrap_or(u64::MAX),
+                )),
+            })
+            .collect();
+
+        AnnounceSummary {
+            status,
+            synced: self.synced().len(),
+            preferred,
+            timed_out,
+            failed: self.failed().len(),
+            durations,
+            outcome,
+        }
+    }
+}
+
+/// How an [`Announcer`] process ended, see [`AnnouncerResult`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AnnounceStatus {
+    /// The target was reached.
+    Success,
+    /// The process timed out before reaching the target.
+    TimedOut,
+    /// There were no nodes left to synchronize with.
+    NoNodes,
+}
+
+/// Summary of an [`AnnouncerResult`], rendered as a single line for humans,
+/// or serialized as JSON.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnnounceSummary {
+    /// How the process ended.
+    pub status: AnnounceStatus,
+    /// Number of synchronized nodes, including nodes that were already
+    /// synchronized.
+    pub synced: usize,
+    /// Number of synchronized preferred seeds.
+    pub preferred: usize,
+    /// Number of nodes that timed out.
+    pub timed_out: usize,
+    /// Number of nodes that failed.
+    pub failed: usize,
+    /// Time it took to synchronize with each node, in milliseconds. Nodes
+    /// that were already synchronized are not included.
+    pub durations: BTreeMap<NodeId, u64>,
+    /// The target that was reached, if any.
+    pub outcome: Option<SuccessfulOutcome>,
+}
+
+impl fmt::Display for AnnounceSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = match self.status {
+            AnnounceStatus::Success => "target reached",
+            AnnounceStatus::TimedOut => "timed out",
+            AnnounceStatus::NoNodes => "no more nodes",
+        };
+        write!(
+            f,
+            "{status}: synced with {} node(s) ({} preferred)",
+            self.synced, self.preferred
+        )?;
+        if self.timed_out > 0 {
+            write!(f, ", {} timed out", self.timed_out)?;
+        }
+        if self.failed > 0 {
+            write!(f, ", {} failed", self.failed)?;
+        }
+        Ok(())
+    }
 }
 
 impl From<Success> for AnnouncerResult {
@@ -374,7 +629,9 @@ impl From<NoNodes> for AnnouncerResult {
 
 #[derive(Debug)]
 pub struct NoNodes {
+    preferred: usize,
     synced: BTreeMap<NodeId, SyncStatus>,
+    failed: BTreeMap<NodeId, FailureReason>,
 }
 
 impl NoNodes {
@@ -382,12 +639,19 @@ impl NoNodes {
     pub fn synced(&self) -> &BTreeMap<NodeId, SyncStatus> {
         &self.synced
     }
+
+    /// Get the set of failed nodes
+    pub fn failed(&self) -> &BTreeMap<NodeId, FailureReason> {
+        &self.failed
+    }
 }
 
 #[derive(Debug)]
 pub struct TimedOut {
+    preferred: usize,
     synced: BTreeMap<NodeId, SyncStatus>,
     timed_out: BTreeSet<NodeId>,
+    failed: BTreeMap<NodeId, FailureReason>,
 }
 
 impl TimedOut {
@@ -400,12 +664,18 @@ impl TimedOut {
     pub fn timed_out(&self) -> &BTreeSet<NodeId> {
         &self.timed_out
     }
+
+    /// Get the set of failed nodes
+    pub fn failed(&self) -> &BTreeMap<NodeId, FailureReason> {
+        &self.failed
+    }
 }
 
 #[derive(Debug)]
 pub struct Success {
     outcome: SuccessfulOutcome,
     synced: BTreeMap<NodeId, SyncStatus>,
+    failed: BTreeMap<NodeId, FailureReason>,
 }
 
 impl Success {
@@ -418,6 +688,68 @@ impl Success {
     pub fn synced(&self) -> &BTreeMap<NodeId, SyncStatus> {
         &self.synced
     }
+
+    /// Get the set of failed nodes.
+    pub fn failed(&self) -> &BTreeMap<NodeId, FailureReason> {
+        &self.failed
+    }
+
+    /// Get statistics of the time it took to synchronize with nodes, see
+    /// [`AnnouncerResult::durations`].
+    pub fn durations(&self) -> Option<DurationStats> {
+        DurationStats::new(&self.synced)
+    }
+}
+
+/// Statistics of the time it took to synchronize with nodes.
+///
+/// Only nodes synchronized with during the [`Announcer`] process are counted,
+/// not the ones that were already synchronized. Percentiles use the
+/// nearest-rank method, ie. they are always one of the measured durations.
+///
+/// Durations are serialized in milliseconds, like [`AnnounceSummary::durations`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DurationStats {
+    /// Number of durations measured.
+    pub count: usize,
+    /// Shortest duration.
+    #[serde(with = "crate::serde_ext::duration::millis")]
+    pub min: time::Duration,
+    /// Median duration.
+    #[serde(with = "crate::serde_ext::duration::millis")]
+    pub median: time::Duration,
+    /// 90th percentile duration.
+    #[serde(with = "crate::serde_ext::duration::millis")]
+    pub p90: time::Duration,
+    /// Longest duration.
+    #[serde(with = "crate::serde_ext::duration::millis")]
+    pub max: time::Duration,
+}
+
+impl DurationStats {
+    fn new(synced: &BTreeMap<NodeId, SyncStatus>) -> Option<Self> {
+        let mut durations = synced
+            .values()
+            .filter_map(|status| match status {
+                SyncStatus::AlreadySynced => None,
+                SyncStatus::Synced { duration } => Some(*duration),
+            })
+            .collect::<Vec<_>>();
+        durations.sort_unstable();
+
+        let (&min, &max) = (durations.first()?, durations.last()?);
+        // Nearest-rank percentile, for `p` in `1..=100`.
+        let percentile = |p: usize| durations[(p * durations.len()).div_ceil(100) - 1];
+
+        Some(Self {
+            count: durations.len(),
+            min,
+            median: percentile(50),
+            p90: percentile(90),
+            max,
+        })
+    }
 }
 
 /// Error in constructing the [`Announcer`].
@@ -488,6 +820,24 @@ pub enum SyncStatus {
     Synced { duration: time::Duration },
 }
 
+/// The reason that synchronizing with a node failed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FailureReason {
+    /// A connection to the node could not be established.
+    Connection(String),
+    /// The node failed to fetch the announced references.
+    Fetch(String),
+}
+
+impl fmt::Display for FailureReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FailureReason::Connection(reason) => write!(f, "connection failed: {reason}"),
+            FailureReason::Fetch(reason) => write!(f, "fetch failed: {reason}"),
+        }
+    }
+}
+
 /// Progress of the [`Announcer`] process.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct Progress {
@@ -561,7 +911,8 @@ impl Target {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
 pub enum SuccessfulOutcome {
     MinReplicationFactor {
         preferred: usize,
@@ -573,10 +924,22 @@ pub enum SuccessfulOutcome {
     },
     PreferredNodes {
         preferred: usize,
+        #[serde(rename = "totalNodesSynced")]
         total_nodes_synced: usize,
     },
 }
 
+impl SuccessfulOutcome {
+    /// The number of preferred seeds that are synchronized.
+    fn preferred(&self) -> usize {
+        match self {
+            Self::MinReplicationFactor { preferred, .. }
+            | Self::MaxReplicationFactor { preferred, .. }
+            | Self::PreferredNodes { preferred, .. } => *preferred,
+        }
+    }
+}
+
 #[allow(clippy::unwrap_used)]
 #[cfg(test)]
 mod test {
@@ -774,6 +1137,117 @@ mod test {
         )
     }
 
+    #[test]
+    fn announcer_drive_min_replication_target() {
+        let local = arbitrary::gen::<NodeId>(0);
+        let seeds = arbitrary::set::<NodeId>(10..=10);
+        let unsynced = seeds.iter().skip(3).copied().collect::<BTreeSet<_>>();
+        let preferred_seeds = seeds.iter().take(2).copied().collect::<BTreeSet<_>>();
+        let config = AnnouncerConfig::public(
+            local,
+            ReplicationFactor::must_reach(3),
+            preferred_seeds.clone(),
+            BTreeSet::new(),
+            unsynced.clone(),
+        );
+        let announcer = Announcer::new(config).unwrap();
+        let t = time::Duration::from_secs(1);
+        // Sync with one preferred seed, and then only with the other nodes.
+        let mut nodes = preferred_seeds.iter().take(1).chain(unsynced.iter());
+
+        let result = announcer.drive(|to_sync| {
+            let node = nodes.next()?;
+            assert!(to_sync.contains(node));
+            Some((*node, t))
+        });
+
+        match result {
+            AnnouncerResult::Success(success) => {
+                assert_eq!(
+                    success.outcome(),
+                    SuccessfulOutcome::MinReplicationFactor {
+                        preferred: 1,
+                        synced: 3,
+                    }
+                );
+                assert_eq!(success.synced().len(), 3);
+            }
+            unexpected => panic!("Expected AnnouncerResult::Success, found: {unexpected:#?}"),
+        }
+    }
+
+    #[test]
+    fn announcer_drive_max_replication_target() {
+        let local = arbitrary::gen::<NodeId>(0);
+        let seeds = arbitrary::set::<NodeId>(10..=10);
+        let unsynced = seeds.iter().skip(3).copied().collect::<BTreeSet<_>>();
+        let preferred_seeds = seeds.iter().take(2).copied().collect::<BTreeSet<_>>();
+        let config = AnnouncerConfig::public(
+            local,
+            ReplicationFactor::range(3, 6),
+            preferred_seeds.clone(),
+            BTreeSet::new(),
+            unsynced.clone(),
+        );
+        let announcer = Announcer::new(config).unwrap();
+        let t = time::Duration::from_secs(1);
+
+        // Don't sync with preferred so that we don't hit that target.
+        let result = announcer.drive(|to_sync| {
+            to_sync
+                .iter()
+                .find(|node| !preferred_seeds.contains(*node))
+                .map(|node| (*node, t))
+        });
+
+        match result {
+            AnnouncerResult::Success(success) => {
+                assert_eq!(
+                    success.outcome(),
+                    SuccessfulOutcome::MaxReplicationFactor {
+                        preferred: 0,
+                        synced: 6,
+                    }
+                );
+            }
+            unexpected => panic!("Expected AnnouncerResult::Success, found: {unexpected:#?}"),
+        }
+    }
+
+    #[test]
+    fn announcer_drive_timed_out() {
+        let local = arbitrary::gen::<NodeId>(0);
+        let seeds = arbitrary::set::<NodeId>(10..=10);
+        let unsynced = seeds.iter().skip(2).copied().collect::<BTreeSet<_>>();
+        let preferred_seeds = seeds.iter().take(2).copied().collect::<BTreeSet<_>>();
+        let config = AnnouncerConfig::public(
+            local,
+            ReplicationFactor::must_reach(11),
+            preferred_seeds.clone(),
+            BTreeSet::new(),
+            unsynced.clone(),
+        );
+        let announcer = Announcer::new(config).unwrap();
+        let to_sync = announcer.to_sync();
+        let t = time::Duration::from_secs(1);
+        // Simulate only being able to reach some of the nodes.
+        let mut nodes = unsynced.iter().take(5);
+
+        match announcer.drive(|_| nodes.next().map(|node| (*node, t))) {
+            AnnouncerResult::TimedOut(timeout) => {
+                assert_eq!(timeout.synced.len(), 5);
+                assert_eq!(
+                    timeout.timed_out,
+                    to_sync
+                        .difference(&timeout.synced.keys().copied().collect())
+                        .copied()
+                        .collect()
+                );
+            }
+            unexpected => panic!("Expected AnnouncerResult::TimedOut, found: {unexpected:#?}"),
+        }
+    }
+
     #[test]
     fn announcer_preferred_seeds_or_replica_factor() {
         let local = arbitrary::gen::<NodeId>(0);
@@ -1614,4 +2088,342 @@ mod test {
         // After removing local node from all sets, we should get NoSeeds error
         assert_matches!(Announcer::new(config), Err(AnnouncerError::NoSeeds));
     }
+
+    #[test]
+    fn to_sync_ordered_by_priority() {
+        let local = arbitrary::gen::<NodeId>(0);
+        let seeds = arbitrary::set::<NodeId>(6..=6)
+            .into_iter()
+            .collect::<Vec<_>>();
+        let preferred_seeds = [seeds[5]].into_iter().collect::<BTreeSet<_>>();
+        // The last node is already synced, and must not be returned
+        let priority = vec![seeds[3], local, seeds[1], seeds[4]];
+        let synced = [seeds[4]].into_iter().collect::<BTreeSet<_>>();
+        let unsynced = seeds.iter().copied().collect::<BTreeSet<_>>();
+
+        let config = AnnouncerConfig::public(
+            local,
+            ReplicationFactor::must_reach(5),
+            preferred_seeds,
+            synced,
+            unsynced,
+        )
+        .with_priority(priority);
+        let announcer = Announcer::new(config).unwrap();
+        let ordered = announcer.to_sync_ordered();
+
+        assert_eq!(ordered[..3], [seeds[5], seeds[3], seeds[1]]);
+        assert_eq!(
+            ordered.iter().copied().collect::<BTreeSet<_>>(),
+            announcer.to_sync(),
+        );
+        assert_eq!(ordered.len(), announcer.to_sync().len());
+    }
+
+    #[test]
+    fn all_nodes_failed() {
+        let local = arbitrary::gen::<NodeId>(0);
+        let seeds = arbitrary::set::<NodeId>(3..=3)
+            .into_iter()
+            .collect::<Vec<_>>();
+        let unsynced = seeds.iter().copied().collect::<BTreeSet<_>>();
+
+        let config = AnnouncerConfig::public(
+            local,
+            ReplicationFactor::must_reach(2),
+            BTreeSet::new(),
+            BTreeSet::new(),
+            unsynced,
+        );
+        let mut announcer = Announcer::new(config).unwrap();
+
+        let duration = time::Duration::from_secs(1);
+        assert_matches!(
+            announcer.synced_with(seeds[0], duration),
+            ControlFlow::Continue(_)
+        );
+        // Failing a synced node, or the local node, is ignored
+        assert_matches!(
+            announcer.failed_with(seeds[0], FailureReason::Fetch("timeout".to_owned())),
+            ControlFlow::Continue(progress) if progress.synced() == 1
+        );
+        assert_matches!(
+            announcer.failed_with(local, FailureReason::Fetch("timeout".to_owned())),
+            ControlFlow::Continue(progress) if progress.unsynced() == 2
+        );
+
+        for node in &seeds[1..] {
+            assert_matches!(
+                announcer.failed_with(*node, FailureReason::Connection("refused".to_owned())),
+                ControlFlow::Continue(_)
+            );
+        }
+        assert!(announcer.to_sync().is_empty());
+
+        let result: AnnouncerResult = match announcer.can_continue() {
+            ControlFlow::Break(no_nodes) => no_nodes.into(),
+            ControlFlow::Continue(_) => panic!("there should be no more nodes to sync with"),
+        };
+        assert_matches!(result, AnnouncerResult::NoNodes(_));
+        assert!(result.is_synced(&seeds[0]));
+        assert_eq!(
+            result.failed(),
+            &seeds[1..]
+                .iter()
+                .map(|node| (*node, FailureReason::Connection("refused".to_owned())))
+                .collect::<BTreeMap<_, _>>()
+        );
+    }
+
+    #[test]
+    fn on_progress_called_once_per_transition() {
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        let local = arbitrary::gen::<NodeId>(0);
+        let seeds = arbitrary::set::<NodeId>(4..=4)
+            .into_iter()
+            .collect::<Vec<_>>();
+        let unsynced = seeds.iter().copied().collect::<BTreeSet<_>>();
+
+        let config = AnnouncerConfig::public(
+            local,
+            ReplicationFactor::must_reach(3),
+            BTreeSet::new(),
+            BTreeSet::new(),
+            unsynced,
+        );
+        let calls = Rc::new(RefCell::new(Vec::new()));
+        let mut announcer = Announcer::new(config).unwrap().on_progress({
+            let calls = calls.clone();
+            move |progress| calls.borrow_mut().push(*progress)
+        });
+        let duration = time::Duration::from_secs(1);
+
+        assert_matches!(
+            announcer.synced_with(seeds[0], duration),
+            ControlFlow::Continue(_)
+        );
+        // Duplicate and local syncs are ignored
+        assert_matches!(
+            announcer.synced_with(seeds[0], duration),
+            ControlFlow::Continue(_)
+        );
+        assert_matches!(
+            announcer.synced_with(local, duration),
+            ControlFlow::Continue(_)
+        );
+        assert_matches!(
+            announcer.failed_with(local, FailureReason::Fetch("timeout".to_owned())),
+            ControlFlow::Continue(_)
+        );
+        assert_eq!(calls.borrow().len(), 1);
+
+        assert_matches!(
+            announcer.failed_with(seeds[1], FailureReason::Fetch("timeout".to_owned())),
+            ControlFlow::Continue(_)
+        );
+        assert_matches!(
+            announcer.failed_with(seeds[1], FailureReason::Fetch("timeout".to_owned())),
+            ControlFlow::Continue(_)
+        );
+        assert_eq!(calls.borrow().len(), 2);
+
+        assert_matches!(
+            announcer.synced_with(seeds[2], duration),
+            ControlFlow::Continue(_)
+        );
+        assert_eq!(calls.borrow().len(), 3);
+        assert_eq!(calls.borrow().last(), Some(&announcer.progress()));
+    }
+
+    #[test]
+    fn summary() {
+        let local = arbitrary::gen::<NodeId>(0);
+        let seeds = arbitrary::set::<NodeId>(4..=4)
+            .into_iter()
+            .collect::<Vec<_>>();
+        let second = time::Duration::from_secs(1);
+        let failure = || FailureReason::Connection("refused".to_owned());
+
+        // The target is reached once the preferred seed is synced.
+        let mut announcer = Announcer::new(AnnouncerConfig::public(
+            local,
+            ReplicationFactor::must_reach(2),
+            [seeds[0]].into(),
+            BTreeSet::new(),
+            [seeds[1], seeds[2]].into(),
+        ))
+        .unwrap();
+        assert_matches!(
+            announcer.synced_with(seeds[1], second),
+            ControlFlow::Continue(_)
+        );
+        let ControlFlow::Break(success) = announcer.synced_with(seeds[0], second * 2) else {
+            panic!("target should be reached");
+        };
+        let summary = AnnouncerResult::from(success).summary();
+
+        assert_eq!(summary.status, AnnounceStatus::Success);
+        assert_eq!((summary.synced, summary.preferred), (2, 1));
+        assert_eq!((summary.timed_out, summary.failed), (0, 0));
+        assert_eq!(
+            summary.durations,
+            [(seeds[0], 2000), (seeds[1], 1000)].into()
+        );
+        assert_eq!(
+            summary.outcome,
+            Some(SuccessfulOutcome::PreferredNodes {
+                preferred: 1,
+                total_nodes_synced: 2
+            })
+        );
+        assert_eq!(
+            summary.to_string(),
+            "target reached: synced with 2 node(s) (1 preferred)"
+        );
+        let json = serde_json::to_value(&summary).unwrap();
+        assert_eq!(json["timedOut"], 0);
+        assert_eq!(
+            json["outcome"],
+            serde_json::json!({ "type": "preferredNodes", "preferred": 1, "totalNodesSynced": 2 })
+        );
+
+        // Some nodes are left when timing out.
+        let mut announcer = Announcer::new(AnnouncerConfig::public(
+            local,
+            ReplicationFactor::must_reach(3),
+            BTreeSet::new(),
+            BTreeSet::new(),
+            [seeds[0], seeds[1], seeds[2]].into(),
+        ))
+        .unwrap();
+        assert_matches!(
+            announcer.synced_with(seeds[0], second),
+            ControlFlow::Continue(_)
+        );
+        assert_matches!(
+            announcer.failed_with(seeds[1], failure()),
+            ControlFlow::Continue(_)
+        );
+        let summary = announcer.timed_out().summary();
+
+        assert_eq!(summary.status, AnnounceStatus::TimedOut);
+        assert_eq!((summary.synced, summary.preferred), (1, 0));
+        assert_eq!((summary.timed_out, summary.failed), (1, 1));
+        assert_eq!(summary.durations, [(seeds[0], 1000)].into());
+        assert_eq!(summary.outcome, None);
+        assert_eq!(
+            summary.to_string(),
+            "timed out: synced with 1 node(s) (0 preferred), 1 timed out, 1 failed"
+        );
+
+        // All nodes failed, besides the one that was already synced.
+        let mut announcer = Announcer::new(AnnouncerConfig::public(
+            local,
+            ReplicationFactor::must_reach(3),
+            BTreeSet::new(),
+            [seeds[3]].into(),
+            [seeds[1], seeds[2]].into(),
+        ))
+        .unwrap();
+        for seed in [seeds[1], seeds[2]] {
+            assert_matches!(
+                announcer.failed_with(seed, failure()),
+                ControlFlow::Continue(_)
+            );
+        }
+        let ControlFlow::Break(no_nodes) = announcer.can_continue() else {
+            panic!("no nodes should be left");
+        };
+        let summary = AnnouncerResult::from(no_nodes).summary();
+
+        assert_eq!(summary.status, AnnounceStatus::NoNodes);
+        assert_eq!((summary.synced, summary.preferred), (1, 0));
+        assert_eq!((summary.timed_out, summary.failed), (0, 2));
+        assert!(summary.durations.is_empty());
+        assert_eq!(summary.outcome, None);
+        assert_eq!(
+            summary.to_string(),
+            "no more nodes: synced with 1 node(s) (0 preferred), 2 failed"
+        );
+    }
+
+    #[test]
+    fn duration_stats() {
+        let seeds = arbitrary::set::<NodeId>(11..=11)
+            .into_iter()
+            .collect::<Vec<_>>();
+        let secs = time::Duration::from_secs;
+        let stats = |synced: &[(NodeId, SyncStatus)]| {
+            DurationStats::new(&synced.iter().cloned().collect::<BTreeMap<_, _>>())
+        };
+
+        // Nothing synced during the process.
+        assert_eq!(stats(&[]), None);
+        assert_eq!(stats(&[(seeds[0], SyncStatus::AlreadySynced)]), None);
+
+        let one = stats(&[
+            (seeds[0], SyncStatus::AlreadySynced),
+            (seeds[1], SyncStatus::Synced { duration: secs(3) }),
+        ])
+        .unwrap();
+        assert_eq!(one.count, 1);
+        assert_eq!(
+            (one.min, one.median, one.p90, one.max),
+            (secs(3), secs(3), secs(3), secs(3))
+        );
+
+        // One slow seed, out of ten.
+        let mut synced = (1..=9)
+            .map(|i| {
+                (
+                    seeds[i],
+                    SyncStatus::Synced {
+                        duration: secs(i as u64),
+                    },
+                )
+            })
+            .collect::<Vec<_>>();
+        synced.push((seeds[10], SyncStatus::Synced { duration: secs(60) }));
+        synced.push((seeds[0], SyncStatus::AlreadySynced));
+
+        let ten = stats(&synced).unwrap();
+        assert_eq!(ten.count, 10);
+        assert_eq!(ten.min, secs(1));
+        assert_eq!(ten.median, secs(5));
+        assert_eq!(ten.p90, secs(9));
+        assert_eq!(ten.max, secs(60));
+        assert_eq!(
+            serde_json::to_value(ten).unwrap(),
+            serde_json::json!({
+                "count": 10,
+                "min": 1000,
+                "median": 5000,
+                "p90": 9000,
+                "max": 60000,
+            })
+        );
+
+        // The same statistics are available from the result.
+        let mut announcer = Announcer::new(AnnouncerConfig::public(
+            arbitrary::gen::<NodeId>(0),
+            ReplicationFactor::must_reach(2),
+            BTreeSet::new(),
+            BTreeSet::new(),
+            [seeds[0], seeds[1]].into(),
+        ))
+        .unwrap();
+        assert_matches!(
+            announcer.synced_with(seeds[0], secs(1)),
+            ControlFlow::Continue(_)
+        );
+        let ControlFlow::Break(success) = announcer.synced_with(seeds[1], secs(4)) else {
+            panic!("target should be reached");
+        };
+        let durations = success.durations().unwrap();
+        assert_eq!((durations.min, durations.median), (secs(1), secs(1)));
+        assert_eq!((durations.p90, durations.max), (secs(4), secs(4)));
+        assert_eq!(AnnouncerResult::from(success).durations(), Some(durations));
+    }
 }
@@ -190,10 +190,13 @@ impl Fetcher {
             })
         } else {
             let replicas = self.target.replicas();
+            if !replicas.satisfied_by(succeeded) {
+                return None;
+            }
             let min = replicas.lower_bound();
             match replicas.upper_bound() {
-                None => (succeeded >= min).then_some(SuccessfulOutcome::MinReplicas { succeeded }),
-                Some(max) => (succeeded >= max).then_some(SuccessfulOutcome::MaxReplicas {
+                None => Some(SuccessfulOutcome::MinReplicas { succeeded }),
+                Some(max) => Some(SuccessfulOutcome::MaxReplicas {
                     succeeded,
                     min,
                     max,
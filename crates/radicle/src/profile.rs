@@ -46,6 +46,8 @@ pub mod env {
     pub const RAD_SOCKET: &str = "RAD_SOCKET";
     /// Passphrase for the encrypted radicle secret key.
     pub const RAD_PASSPHRASE: &str = "RAD_PASSPHRASE";
+    /// Command to run to obtain the passphrase for the encrypted radicle secret key.
+    pub const RAD_PASSPHRASE_CMD: &str = "RAD_PASSPHRASE_CMD";
     /// RNG seed. Must be convertible to a `u64`.
     pub const RAD_RNG_SEED: &str = "RAD_RNG_SEED";
     /// Private key seed. Used for generating deterministic keypairs.
@@ -113,6 +115,11 @@ pub mod env {
         Some(super::Passphrase::from(passphrase))
     }
 
+    /// Get the command to obtain the radicle passphrase from the environment.
+    pub fn passphrase_command() -> Option<String> {
+        var(RAD_PASSPHRASE_CMD).ok().filter(|cmd| !cmd.is_empty())
+    }
+
     /// Get a random number generator from the environment.
     pub fn rng() -> fastrand::Rng {
         if let Ok(seed) = var(RAD_RNG_SEED) {
@@ -1,8 +1,13 @@
 //! Compression utilities for bandwidth and storage optimization
 //!
-//! Provides zstd compression for reducing data transfer and storage costs
+//! Provides zstd compression for reducing data transfer and storage costs,
+//! as well as a pass-through mode to measure the overhead of the layer itself.
+//! Small objects of similar structure can share context through a trained
+//! [`Dictionary`]
 
+use std::fs;
 use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -13,13 +18,123 @@ pub enum CompressionError {
     CompressionFailed(String),
     #[error("Decompression failed: {0}")]
     DecompressionFailed(String),
+    #[error("Decompressed data exceeds the limit of {0} bytes")]
+    LimitExceeded(usize),
+    #[error("Dictionary training failed: {0}")]
+    TrainingFailed(String),
+    #[error("Invalid dictionary file {0:?}")]
+    InvalidDictionary(PathBuf),
+    #[error("Unsupported dictionary version {version} in {path:?}")]
+    UnsupportedDictionaryVersion { path: PathBuf, version: u8 },
+}
+
+/// Compression algorithm of a [`CompressionLayer`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Algorithm {
+    /// zstd, at the given compression level (1-22)
+    Zstd { level: i32 },
+    /// Pass-through: data is left unchanged, which is useful to benchmark
+    /// the overhead of compression
+    None,
+}
+
+impl Default for Algorithm {
+    fn default() -> Self {
+        Self::Zstd { level: 3 }
+    }
+}
+
+/// Output of a compression or decompression, along with the sizes of the
+/// original and compressed data
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Measured {
+    /// The compressed data when compressing, or the original data when decompressing
+    pub data: Vec<u8>,
+    /// Size of the original data, in bytes
+    pub original_size: usize,
+    /// Size of the compressed data, in bytes
+    pub compressed_size: usize,
+}
+
+/// A zstd dictionary, trained on samples of small, similarly structured data
+/// such as refs blobs or tree entries
+///
+/// Data compressed with a dictionary can only be decompressed with the same
+/// dictionary, which is why it is persisted alongside storage
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dictionary {
+    bytes: Vec<u8>,
+}
+
+impl Dictionary {
+    /// Version of the persisted dictionary format
+    pub const VERSION: u8 = 1;
+    /// Name of the dictionary file, in the storage directory
+    pub const FILE_NAME: &'static str = "compression.dict";
+    /// Maximum size of a trained dictionary, in bytes
+    pub const MAX_SIZE: usize = 16 * 1024;
+    /// Header of the persisted dictionary, followed by the version
+    const MAGIC: &'static [u8] = b"RADZDICT";
+
+    /// Create a dictionary from raw zstd dictionary bytes
+    pub fn from_bytes(bytes: Vec<u8>) -> Self {
+        Self { bytes }
+    }
+
+    /// The raw zstd dictionary bytes
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Path of the dictionary file in the given storage directory
+    pub fn path(storage: &Path) -> PathBuf {
+        storage.join(Self::FILE_NAME)
+    }
+
+    /// Persist the dictionary in the given storage directory, replacing any
+    /// previous one. Returns the path of the dictionary file
+    pub fn save(&self, storage: &Path) -> Result<PathBuf, CompressionError> {
+        let path = Self::path(storage);
+        let tmp = path.with_extension("tmp");
+        let mut file = fs::File::create(&tmp)?;
+
+        file.write_all(Self::MAGIC)?;
+        file.write_all(&[Self::VERSION])?;
+        file.write_all(&self.bytes)?;
+        file.sync_all()?;
+        fs::rename(&tmp, &path)?;
+
+        Ok(path)
+    }
+
+    /// Load the dictionary persisted in the given storage directory, if any
+    pub fn load(storage: &Path) -> Result<Option<Self>, CompressionError> {
+        let path = Self::path(storage);
+        let contents = match fs::read(&path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let Some(rest) = contents.strip_prefix(Self::MAGIC) else {
+            return Err(CompressionError::InvalidDictionary(path));
+        };
+        match rest.split_first() {
+            Some((&Self::VERSION, bytes)) => Ok(Some(Self::from_bytes(bytes.to_vec()))),
+            Some((&version, _)) => {
+                Err(CompressionError::UnsupportedDictionaryVersion { path, version })
+            }
+            None => Err(CompressionError::InvalidDictionary(path)),
+        }
+    }
 }
 
 /// Compression layer using zstd
 ///
 /// Provides fast compression with excellent compression ratios
 pub struct CompressionLayer {
-    level: i32,
+    algorithm: Algorithm,
+    dictionary: Option<Dictionary>,
+    limit: Option<usize>,
 }
 
 impl CompressionLayer {
@@ -31,38 +146,152 @@ impl CompressionLayer {
     ///   - 3-7: Balanced (recommended)
     ///   - 8-22: Slower, higher compression
     pub fn new(level: i32) -> Self {
-        let level = level.clamp(1, 22);
-        Self { level }
+        Self::with_algorithm(Algorithm::Zstd { level })
+    }
+
+    /// Create a compression layer using the given algorithm
+    ///
+    /// zstd levels are clamped to 1-22, as with [`CompressionLayer::new`]
+    pub fn with_algorithm(algorithm: Algorithm) -> Self {
+        let algorithm = match algorithm {
+            Algorithm::Zstd { level } => Algorithm::Zstd {
+                level: level.clamp(1, 22),
+            },
+            Algorithm::None => Algorithm::None,
+        };
+        Self {
+            algorithm,
+            dictionary: None,
+            limit: None,
+        }
+    }
+
+    /// Use the given dictionary for zstd compression and decompression
+    ///
+    /// The dictionary is ignored by [`Algorithm::None`]
+    pub fn with_dictionary(mut self, dictionary: Dictionary) -> Self {
+        self.dictionary = Some(dictionary);
+        self
+    }
+
+    /// Limit the size of data decompressed in one piece, by
+    /// [`CompressionLayer::decompress`], to `limit` bytes
+    ///
+    /// Decompressing data that exceeds the limit fails with
+    /// [`CompressionError::LimitExceeded`], which guards against small inputs
+    /// that decompress to very large outputs
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Train a dictionary on samples of small, similarly structured data
+    pub fn train_dictionary(samples: &[&[u8]]) -> Result<Dictionary, CompressionError> {
+        let bytes = zstd::dict::from_samples(samples, Dictionary::MAX_SIZE)
+            .map_err(|e| CompressionError::TrainingFailed(e.to_string()))?;
+
+        Ok(Dictionary::from_bytes(bytes))
     }
 
     /// Create a compression layer with default settings (level 3)
     pub fn default_level() -> Self {
-        Self::new(3)
+        Self::with_algorithm(Algorithm::default())
     }
 
-    /// Compress data using zstd
-    pub fn compress(&self, data: &[u8]) -> Result<Vec<u8>, CompressionError> {
-        let compressed = zstd::encode_all(data, self.level)
-            .map_err(|e| CompressionError::CompressionFailed(e.to_string()))?;
+    /// The algorithm used by this layer
+    pub fn algorithm(&self) -> Algorithm {
+        self.algorithm
+    }
 
-        Ok(compressed)
+    /// The dictionary used by this layer, if any
+    pub fn dictionary(&self) -> Option<&Dictionary> {
+        self.dictionary.as_ref()
     }
 
-    /// Decompress zstd data
+    /// The limit on the size of decompressed data, if any
+    pub fn limit(&self) -> Option<usize> {
+        self.limit
+    }
+
+    /// The dictionary bytes passed to zstd, which are empty without a dictionary
+    fn dictionary_bytes(&self) -> &[u8] {
+        self.dictionary
+            .as_ref()
+            .map(Dictionary::as_bytes)
+            .unwrap_or_default()
+    }
+
+    /// Compress data
+    pub fn compress(&self, data: &[u8]) -> Result<Vec<u8>, CompressionError> {
+        self.compress_measured(data).map(|m| m.data)
+    }
+
+    /// Decompress data
     pub fn decompress(&self, compressed: &[u8]) -> Result<Vec<u8>, CompressionError> {
-        let decompressed = zstd::decode_all(compressed)
-            .map_err(|e| CompressionError::DecompressionFailed(e.to_string()))?;
+        self.decompress_measured(compressed).map(|m| m.data)
+    }
+
+    /// Compress data, recording the original and compressed sizes
+    pub fn compress_measured(&self, data: &[u8]) -> Result<Measured, CompressionError> {
+        let compressed = match self.algorithm {
+            Algorithm::Zstd { level } => {
+                zstd::bulk::Compressor::with_dictionary(level, self.dictionary_bytes())
+                    .and_then(|mut compressor| compressor.compress(data))
+                    .map_err(|e| CompressionError::CompressionFailed(e.to_string()))?
+            }
+            Algorithm::None => data.to_vec(),
+        };
+
+        Ok(Measured {
+            original_size: data.len(),
+            compressed_size: compressed.len(),
+            data: compressed,
+        })
+    }
 
-        Ok(decompressed)
+    /// Decompress data, recording the original and compressed sizes
+    pub fn decompress_measured(&self, compressed: &[u8]) -> Result<Measured, CompressionError> {
+        let decompressed = match self.algorithm {
+            Algorithm::Zstd { .. } => {
+                let mut decompressed = Vec::new();
+                // Read one byte past the limit, to tell whether it was exceeded.
+                let max = self.limit.map_or(u64::MAX, |limit| limit as u64 + 1);
+
+                zstd::Decoder::with_dictionary(compressed, self.dictionary_bytes())
+                    .and_then(|decoder| decoder.take(max).read_to_end(&mut decompressed))
+                    .map_err(|e| CompressionError::DecompressionFailed(e.to_string()))?;
+                decompressed
+            }
+            Algorithm::None => compressed.to_vec(),
+        };
+        if let Some(limit) = self.limit.filter(|limit| decompressed.len() > *limit) {
+            return Err(CompressionError::LimitExceeded(limit));
+        }
+
+        Ok(Measured {
+            original_size: decompressed.len(),
+            compressed_size: compressed.len(),
+            data: decompressed,
+        })
     }
 
     /// Compress data with a streaming encoder
     pub fn compress_stream<R: Read, W: Write>(
         &self,
         reader: R,
-        writer: W,
+        mut writer: W,
     ) -> Result<u64, CompressionError> {
-        let mut encoder = zstd::Encoder::new(writer, self.level)
+        let level = match self.algorithm {
+            Algorithm::Zstd { level } => level,
+            Algorithm::None => {
+                let bytes_written =
+                    std::io::copy(&mut std::io::BufReader::new(reader), &mut writer)?;
+                writer.flush()?;
+
+                return Ok(bytes_written);
+            }
+        };
+        let mut encoder = zstd::Encoder::with_dictionary(writer, level, self.dictionary_bytes())
             .map_err(|e| CompressionError::CompressionFailed(e.to_string()))?;
 
         let bytes_written = std::io::copy(&mut std::io::BufReader::new(reader), &mut encoder)?;
@@ -77,11 +306,19 @@ impl CompressionLayer {
     /// Decompress data with a streaming decoder
     pub fn decompress_stream<R: Read, W: Write>(
         &self,
-        reader: R,
+        mut reader: R,
         writer: W,
     ) -> Result<u64, CompressionError> {
-        let mut decoder = zstd::Decoder::new(reader)
-            .map_err(|e| CompressionError::DecompressionFailed(e.to_string()))?;
+        if self.algorithm == Algorithm::None {
+            let bytes_read = std::io::copy(&mut reader, &mut std::io::BufWriter::new(writer))?;
+
+            return Ok(bytes_read);
+        }
+        let mut decoder = zstd::Decoder::with_dictionary(
+            std::io::BufReader::new(reader),
+            self.dictionary_bytes(),
+        )
+        .map_err(|e| CompressionError::DecompressionFailed(e.to_string()))?;
 
         let bytes_read = std::io::copy(&mut decoder, &mut std::io::BufWriter::new(writer))?;
 
@@ -122,6 +359,14 @@ impl CompressionStats {
         self.operations += 1;
     }
 
+    /// Record the sizes of a compression or decompression
+    pub fn record(&mut self, measured: &Measured) {
+        self.record_compression(
+            measured.original_size as u64,
+            measured.compressed_size as u64,
+        );
+    }
+
     pub fn average_ratio(&self) -> f64 {
         if self.total_bytes_in == 0 {
             return 0.0;
@@ -225,6 +470,151 @@ mod tests {
         assert_eq!(stats.total_savings(), 2100);
     }
 
+    #[test]
+    fn test_algorithm_roundtrip() {
+        let data = b"This is a test string that will be compressed by each algorithm.".repeat(10);
+
+        for algorithm in [
+            Algorithm::Zstd { level: 1 },
+            Algorithm::Zstd { level: 19 },
+            Algorithm::None,
+        ] {
+            let compressor = CompressionLayer::with_algorithm(algorithm);
+
+            let compressed = compressor.compress_measured(&data).unwrap();
+            assert_eq!(compressed.original_size, data.len());
+            assert_eq!(compressed.compressed_size, compressed.data.len());
+
+            let decompressed = compressor.decompress_measured(&compressed.data).unwrap();
+            assert_eq!(decompressed.original_size, data.len());
+            assert_eq!(decompressed.compressed_size, compressed.data.len());
+            assert_eq!(decompressed.data, data, "{algorithm:?}");
+
+            let mut streamed = Vec::new();
+            compressor
+                .compress_stream(data.as_slice(), &mut streamed)
+                .unwrap();
+            let mut restored = Vec::new();
+            compressor
+                .decompress_stream(streamed.as_slice(), &mut restored)
+                .unwrap();
+            assert_eq!(restored, data, "{algorithm:?}");
+        }
+    }
+
+    #[test]
+    fn test_limit() {
+        let data = vec![0; 64 * 1024];
+
+        for algorithm in [Algorithm::Zstd { level: 3 }, Algorithm::None] {
+            let compressor = CompressionLayer::with_algorithm(algorithm);
+            let compressed = compressor.compress(&data).unwrap();
+
+            let exact = CompressionLayer::with_algorithm(algorithm).with_limit(data.len());
+            assert_eq!(
+                exact.decompress(&compressed).unwrap(),
+                data,
+                "{algorithm:?}"
+            );
+
+            let below = CompressionLayer::with_algorithm(algorithm).with_limit(data.len() - 1);
+            assert!(
+                matches!(
+                    below.decompress(&compressed),
+                    Err(CompressionError::LimitExceeded(limit)) if limit == data.len() - 1
+                ),
+                "{algorithm:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_algorithm_none() {
+        let compressor = CompressionLayer::with_algorithm(Algorithm::None);
+        let data = b"Hello, World! This is a test string that should compress well.";
+
+        let compressed = compressor.compress_measured(data).unwrap();
+        assert_eq!(compressed.data, data);
+        assert_eq!(compressed.original_size, compressed.compressed_size);
+        assert_eq!(compressor.decompress(data).unwrap(), data);
+
+        let mut stats = CompressionStats::new();
+        stats.record(&compressed);
+        assert_eq!(stats.total_savings(), 0);
+    }
+
+    #[test]
+    fn test_algorithm_clamped() {
+        let compressor = CompressionLayer::with_algorithm(Algorithm::Zstd { level: 99 });
+        assert_eq!(compressor.algorithm(), Algorithm::Zstd { level: 22 });
+        assert_eq!(
+            CompressionLayer::default().algorithm(),
+            Algorithm::default()
+        );
+    }
+
+    /// Small buffers sharing most of their structure, like refs blobs
+    fn samples(range: std::ops::Range<usize>) -> Vec<Vec<u8>> {
+        range
+            .map(|i| {
+                format!(
+                    "{:040x} refs/heads/feature/{i}\n{:040x} refs/rad/sigrefs\n{:040x} refs/rad/id\n",
+                    i * 7919,
+                    i * 104729,
+                    i % 3,
+                )
+                .into_bytes()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_dictionary() {
+        let training = samples(0..2000);
+        let training = training.iter().map(Vec::as_slice).collect::<Vec<_>>();
+        let dictionary = CompressionLayer::train_dictionary(&training).unwrap();
+
+        let plain = CompressionLayer::default();
+        let trained = CompressionLayer::default().with_dictionary(dictionary);
+
+        let (mut plain_size, mut trained_size) = (0, 0);
+        for sample in samples(2000..2100) {
+            plain_size += plain.compress(&sample).unwrap().len();
+
+            let compressed = trained.compress(&sample).unwrap();
+            trained_size += compressed.len();
+            assert_eq!(trained.decompress(&compressed).unwrap(), sample);
+        }
+        assert!(
+            trained_size < plain_size,
+            "{trained_size} bytes with a dictionary, {plain_size} bytes without"
+        );
+    }
+
+    #[test]
+    fn test_dictionary_persistence() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dictionary = Dictionary::from_bytes(b"dictionary".to_vec());
+
+        assert_eq!(Dictionary::load(tmp.path()).unwrap(), None);
+
+        let path = dictionary.save(tmp.path()).unwrap();
+        assert_eq!(path, tmp.path().join(Dictionary::FILE_NAME));
+        assert_eq!(Dictionary::load(tmp.path()).unwrap(), Some(dictionary));
+
+        fs::write(&path, b"RADZDICT\x02dictionary").unwrap();
+        assert!(matches!(
+            Dictionary::load(tmp.path()),
+            Err(CompressionError::UnsupportedDictionaryVersion { version: 2, .. })
+        ));
+
+        fs::write(&path, b"dictionary").unwrap();
+        assert!(matches!(
+            Dictionary::load(tmp.path()),
+            Err(CompressionError::InvalidDictionary(_))
+        ));
+    }
+
     #[test]
     fn test_different_compression_levels() {
         let data = b"This is a test string that will be compressed at different levels.".repeat(10);
@@ -4,10 +4,12 @@
 //! - Secret scanning in commits and repositories
 //! - Vulnerability detection in dependencies
 //! - Compression for bandwidth and storage optimization
+//! - SARIF output for CI code scanning
 
 pub mod secrets;
 pub mod vulnerabilities;
 pub mod compression;
+pub mod sarif;
 
 pub use secrets::SecretScanner;
 pub use vulnerabilities::VulnerabilityScanner;
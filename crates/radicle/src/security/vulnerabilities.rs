@@ -2,7 +2,10 @@
 //!
 //! Scans dependencies for known security vulnerabilities
 
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
+
+use semver::{Version, VersionReq};
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -15,6 +18,9 @@ pub enum VulnerabilityError {
     LockfileParse(String),
 }
 
+/// Upstream advisory database, fetched by [`VulnerabilityScanner::update_database`].
+pub const ADVISORY_DB_URL: &str = "https://github.com/rustsec/advisory-db";
+
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct Vulnerability {
     pub id: String,
@@ -90,27 +96,208 @@ pub struct VulnerabilityScanner {
 struct Advisory {
     id: String,
     package: String,
-    affected_versions: String,
+    /// Version requirements of releases that fixed the vulnerability.
+    patched: Vec<VersionReq>,
+    /// Version requirements of releases that were never affected.
+    unaffected: Vec<VersionReq>,
+    /// Kind of informational advisory, eg. `unmaintained`, if it isn't about
+    /// a vulnerability.
+    informational: Option<String>,
     severity: Severity,
     title: String,
     description: String,
     url: Option<String>,
 }
 
+impl Advisory {
+    /// Check whether a package version is affected by this advisory
+    ///
+    /// Every version that isn't patched or unaffected is affected.
+    fn affects(&self, version: &Version) -> bool {
+        !self
+            .patched
+            .iter()
+            .chain(&self.unaffected)
+            .any(|req| req.matches(version))
+    }
+
+    /// Parse an advisory in the RustSec format: a Markdown file with TOML
+    /// front matter, whose first heading is the title
+    ///
+    /// Returns `None` for withdrawn advisories.
+    fn parse(content: &str) -> Result<Option<Self>, String> {
+        let content = content
+            .trim_start()
+            .strip_prefix("```toml")
+            .ok_or("missing TOML front matter")?;
+        let (front, body) = content
+            .split_once("```")
+            .ok_or("unterminated TOML front matter")?;
+
+        let mut section = String::new();
+        let mut fields = HashMap::new();
+        let mut lines = front.lines().map(str::trim);
+
+        while let Some(line) = lines.next() {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                section = name.to_owned();
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let mut value = value.trim().to_owned();
+
+            // Arrays may span multiple lines.
+            if value.starts_with('[') {
+                while !value.contains(']') {
+                    let Some(next) = lines.next() else {
+                        return Err(format!("unterminated array `{}`", key.trim()));
+                    };
+                    value.push_str(next);
+                }
+            }
+            fields.insert(format!("{section}.{}", key.trim()), value);
+        }
+        if fields.contains_key("advisory.withdrawn") {
+            return Ok(None);
+        }
+
+        let string = |key: &str| fields.get(key).map(|v| v.trim_matches('"').to_owned());
+        let list = |key: &str| -> Result<Vec<VersionReq>, String> {
+            fields
+                .get(key)
+                .map(|v| v.split('"').skip(1).step_by(2).collect::<Vec<_>>())
+                .unwrap_or_default()
+                .into_iter()
+                .map(|req| {
+                    VersionReq::parse(req)
+                        .map_err(|e| format!("invalid version requirement `{req}`: {e}"))
+                })
+                .collect()
+        };
+        let id = string("advisory.id").ok_or("missing `advisory.id`")?;
+        let package = string("advisory.package").ok_or("missing `advisory.package`")?;
+        let severity = match string("advisory.severity") {
+            Some(s) => match s.to_lowercase().as_str() {
+                "low" => Severity::Low,
+                "medium" => Severity::Medium,
+                "high" => Severity::High,
+                "critical" => Severity::Critical,
+                other => return Err(format!("unknown severity `{other}`")),
+            },
+            None => string("advisory.cvss")
+                .map(|cvss| cvss_severity(&cvss))
+                .unwrap_or(Severity::Medium),
+        };
+
+        let mut body = body.trim().lines();
+        let title = body
+            .by_ref()
+            .find_map(|l| l.strip_prefix("# "))
+            .unwrap_or(&id)
+            .trim()
+            .to_owned();
+        let description = body.collect::<Vec<_>>().join("\n").trim().to_owned();
+
+        Ok(Some(Self {
+            patched: list("versions.patched")?,
+            unaffected: list("versions.unaffected")?,
+            informational: string("advisory.informational"),
+            severity,
+            title,
+            description,
+            url: string("advisory.url"),
+            id,
+            package,
+        }))
+    }
+}
+
 impl VulnerabilityScanner {
     /// Create a new vulnerability scanner
     ///
-    /// In production, this would fetch the RustSec advisory database
+    /// Uses the advisory database at [`VulnerabilityScanner::default_database_path`]
+    /// if it was fetched, and no advisories otherwise. Never accesses the network.
     pub fn new() -> Result<Self, VulnerabilityError> {
-        Ok(Self {
-            advisories: Self::load_advisories()?,
-        })
+        match Self::default_database_path() {
+            Some(path) if path.join("crates").is_dir() => Self::from_database(&path),
+            _ => Ok(Self {
+                advisories: Vec::new(),
+            }),
+        }
     }
 
-    fn load_advisories() -> Result<Vec<Advisory>, VulnerabilityError> {
-        // Simplified: In production, fetch from rustsec.org
-        // For now, return empty list - will be populated when integrating cargo-audit
-        Ok(Vec::new())
+    /// Create a vulnerability scanner from a local copy of the advisory database
+    ///
+    /// The database is a directory in the layout of the RustSec advisory database,
+    /// ie. `crates/<package>/<id>.md`. Advisories that can't be parsed are
+    /// skipped, with a warning. Never accesses the network.
+    pub fn from_database(path: &Path) -> Result<Self, VulnerabilityError> {
+        let crates = path.join("crates");
+        if !crates.is_dir() {
+            return Err(VulnerabilityError::Io(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("Advisory database not found: {}", path.display()),
+            )));
+        }
+        let mut advisories = Vec::new();
+
+        for entry in walkdir::WalkDir::new(&crates).sort_by_file_name() {
+            let entry = entry.map_err(std::io::Error::from)?;
+            let path = entry.path();
+
+            if !entry.file_type().is_file() || path.extension() != Some("md".as_ref()) {
+                continue;
+            }
+            let content = std::fs::read_to_string(path)?;
+            match Advisory::parse(&content) {
+                Ok(advisory) => advisories.extend(advisory),
+                Err(reason) => {
+                    log::warn!(target: "radicle", "Skipping invalid advisory {}: {reason}", path.display());
+                }
+            }
+        }
+
+        Ok(Self { advisories })
+    }
+
+    /// Fetch or refresh the advisory database at the given path
+    ///
+    /// Clones [`ADVISORY_DB_URL`] if there is no database yet.
+    pub fn update_database(path: &Path) -> Result<(), VulnerabilityError> {
+        let output = if path.join(".git").exists() {
+            crate::git::run(Some(path), ["pull", "--ff-only", "--quiet"])?
+        } else {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            crate::git::run(
+                None,
+                [
+                    "clone".as_ref(),
+                    "--depth=1".as_ref(),
+                    "--quiet".as_ref(),
+                    ADVISORY_DB_URL.as_ref(),
+                    path.as_os_str(),
+                ],
+            )?
+        };
+
+        if !output.status.success() {
+            return Err(VulnerabilityError::DatabaseFetch(
+                String::from_utf8_lossy(&output.stderr).to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Default location of the advisory database, in the user's cache directory
+    pub fn default_database_path() -> Option<PathBuf> {
+        dirs::cache_dir().map(|dir| dir.join("secular").join("advisory-db"))
     }
 
     /// Scan a Cargo.lock file for vulnerabilities
@@ -130,8 +317,25 @@ impl VulnerabilityScanner {
 
         // Check each package against advisories
         for package in packages {
+            let Ok(version) = Version::parse(&package.version) else {
+                report.warnings.push(format!(
+                    "{} has an invalid version `{}`",
+                    package.name, package.version
+                ));
+                continue;
+            };
             for advisory in &self.advisories {
-                if advisory.package == package.name && self.version_matches(&package.version, &advisory.affected_versions) {
+                if advisory.package != package.name || !advisory.affects(&version) {
+                    continue;
+                }
+                // Informational advisories, eg. about unmaintained crates, are
+                // not vulnerabilities.
+                if let Some(kind) = &advisory.informational {
+                    report.warnings.push(format!(
+                        "{} {} is {kind}: {} ({})",
+                        package.name, package.version, advisory.title, advisory.id
+                    ));
+                } else {
                     report.vulnerabilities.push(Vulnerability {
                         id: advisory.id.clone(),
                         package: package.name.clone(),
@@ -187,6 +391,61 @@ impl VulnerabilityScanner {
         Ok(reports)
     }
 
+    /// Scan all Cargo.lock files under a workspace root, merging the results
+    ///
+    /// Lockfiles in paths ignored by git, eg. build directories, are skipped.
+    /// Vulnerabilities reported by multiple lockfiles are only included once.
+    /// Lockfiles that fail to scan are reported as warnings.
+    pub fn scan_workspace(&self, root: &Path) -> Result<ScanReport, VulnerabilityError> {
+        let repo = git2::Repository::discover(root).ok();
+        let workdir = repo
+            .as_ref()
+            .and_then(|r| r.workdir())
+            .and_then(|w| dunce::canonicalize(w).ok());
+        let root = dunce::canonicalize(root)?;
+
+        let is_ignored = |path: &Path| -> bool {
+            let (Some(repo), Some(workdir)) = (&repo, &workdir) else {
+                return false;
+            };
+            path.strip_prefix(workdir)
+                .ok()
+                .and_then(|p| repo.is_path_ignored(p).ok())
+                .unwrap_or(false)
+        };
+
+        let mut report = ScanReport::new(root.clone());
+        let mut seen = HashSet::new();
+        let lockfiles = walkdir::WalkDir::new(&root)
+            .sort_by_file_name()
+            .into_iter()
+            .filter_entry(|e| {
+                e.depth() == 0
+                    || !(e.file_name().to_string_lossy().starts_with('.') || is_ignored(e.path()))
+            })
+            .filter_map(Result::ok)
+            .filter(|e| e.file_type().is_file() && e.file_name() == "Cargo.lock");
+
+        for lockfile in lockfiles {
+            match self.scan_cargo_lock(lockfile.path()) {
+                Ok(scanned) => {
+                    report.warnings.extend(scanned.warnings);
+                    report.vulnerabilities.extend(
+                        scanned
+                            .vulnerabilities
+                            .into_iter()
+                            .filter(|v| seen.insert(v.id.clone())),
+                    );
+                }
+                Err(e) => report
+                    .warnings
+                    .push(format!("{}: {e}", lockfile.path().display())),
+            }
+        }
+
+        Ok(report)
+    }
+
     fn parse_cargo_lock(&self, content: &str) -> Result<Vec<Package>, VulnerabilityError> {
         let mut packages = Vec::new();
 
@@ -226,10 +485,27 @@ impl VulnerabilityScanner {
         Ok(packages)
     }
 
-    fn version_matches(&self, version: &str, pattern: &str) -> bool {
-        // Simplified version matching
-        // In production, use semver crate for proper version matching
-        version == pattern || pattern == "*"
+}
+
+/// Approximate the severity of a CVSS v3 vector, eg. `CVSS:3.1/AV:N/AC:L/...`,
+/// from its attack vector and impact metrics
+fn cvss_severity(vector: &str) -> Severity {
+    let metrics = vector
+        .split('/')
+        .filter_map(|m| m.split_once(':'))
+        .collect::<HashMap<_, _>>();
+    let impacts = ["C", "I", "A"].map(|m| metrics.get(m).copied().unwrap_or("N"));
+    let high = impacts.iter().filter(|i| **i == "H").count();
+    let network = metrics.get("AV") == Some(&"N");
+
+    if network && high == impacts.len() {
+        Severity::Critical
+    } else if high > 0 {
+        Severity::High
+    } else if impacts.contains(&"L") {
+        Severity::Medium
+    } else {
+        Severity::Low
     }
 }
 
@@ -297,4 +573,184 @@ version = "2.0.0"
         assert_eq!(packages[0].name, "example");
         assert_eq!(packages[0].version, "1.0.0");
     }
+
+    #[test]
+    fn test_workspace_scanning() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        let lockfile = |name: &str, version: &str| {
+            format!("[[package]]\nname = \"{name}\"\nversion = \"{version}\"\n")
+        };
+        git2::Repository::init(root).unwrap();
+
+        std::fs::create_dir_all(root.join("crates/nested")).unwrap();
+        std::fs::create_dir_all(root.join("target/package")).unwrap();
+        std::fs::write(root.join(".gitignore"), "target/\n").unwrap();
+        std::fs::write(
+            root.join("Cargo.lock"),
+            lockfile("example", "1.0.0") + &lockfile("shared", "0.1.0"),
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("crates/nested/Cargo.lock"),
+            lockfile("shared", "0.1.0") + &lockfile("nested", "2.0.0"),
+        )
+        .unwrap();
+        std::fs::write(root.join("target/package/Cargo.lock"), lockfile("ignored", "1.0.0"))
+            .unwrap();
+
+        let advisory = |id: &str, package: &str, severity| Advisory {
+            id: id.to_owned(),
+            package: package.to_owned(),
+            patched: Vec::new(),
+            unaffected: Vec::new(),
+            informational: None,
+            severity,
+            title: format!("Vulnerability in {package}"),
+            description: String::new(),
+            url: None,
+        };
+        let scanner = VulnerabilityScanner {
+            advisories: vec![
+                advisory("RUSTSEC-0000-0001", "example", Severity::High),
+                advisory("RUSTSEC-0000-0002", "shared", Severity::Critical),
+                advisory("RUSTSEC-0000-0003", "nested", Severity::Low),
+                advisory("RUSTSEC-0000-0004", "ignored", Severity::Critical),
+            ],
+        };
+
+        let report = scanner.scan_workspace(root).unwrap();
+        let mut ids = report
+            .vulnerabilities
+            .iter()
+            .map(|v| v.id.as_str())
+            .collect::<Vec<_>>();
+        ids.sort();
+
+        assert_eq!(
+            ids,
+            vec!["RUSTSEC-0000-0001", "RUSTSEC-0000-0002", "RUSTSEC-0000-0003"]
+        );
+        assert_eq!(report.critical_count(), 1);
+        assert_eq!(report.high_count(), 1);
+        assert!(report.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_affects() {
+        let advisory = |patched: &[&str], unaffected: &[&str]| Advisory {
+            id: "RUSTSEC-0000-0001".to_owned(),
+            package: "example".to_owned(),
+            patched: patched
+                .iter()
+                .map(|r| VersionReq::parse(r).unwrap())
+                .collect(),
+            unaffected: unaffected
+                .iter()
+                .map(|r| VersionReq::parse(r).unwrap())
+                .collect(),
+            informational: None,
+            severity: Severity::High,
+            title: String::new(),
+            description: String::new(),
+            url: None,
+        };
+        let affects = |advisory: &Advisory, version: &str| {
+            advisory.affects(&Version::parse(version).unwrap())
+        };
+        let advisory = advisory(&[">= 1.2.0", "~0.9.4"], &["< 0.5.0"]);
+
+        assert!(!affects(&advisory, "1.2.3"));
+        assert!(affects(&advisory, "1.1.9"));
+        assert!(!affects(&advisory, "0.9.7"));
+        assert!(affects(&advisory, "0.9.3"));
+        assert!(affects(&advisory, "0.5.0"));
+        assert!(!affects(&advisory, "0.4.9"));
+        // Pre-releases of a patched version are not patched.
+        assert!(affects(&advisory, "1.2.0-alpha.1"));
+    }
+
+    #[test]
+    fn test_from_database() {
+        let tmp = tempfile::tempdir().unwrap();
+        let db = tmp.path().join("advisory-db");
+        let lockfile = tmp.path().join("Cargo.lock");
+        let advisory = |package: &str, id: &str, toml: &str, body: &str| {
+            let dir = db.join("crates").join(package);
+            std::fs::create_dir_all(&dir).unwrap();
+            std::fs::write(
+                dir.join(format!("{id}.md")),
+                format!("```toml\n[advisory]\nid = \"{id}\"\npackage = \"{package}\"\n{toml}```\n\n{body}"),
+            )
+            .unwrap();
+        };
+        advisory(
+            "example",
+            "RUSTSEC-2024-0001",
+            "url = \"https://rustsec.org/advisories/RUSTSEC-2024-0001\"\n\
+             cvss = \"CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H\"\n\
+             \n[versions]\npatched = [\n    \">= 1.2.0\",\n]\nunaffected = [\"< 0.5.0\"]\n",
+            "# Memory corruption in example\n\nParsing untrusted input corrupts memory.\n",
+        );
+        advisory(
+            "another",
+            "RUSTSEC-2024-0002",
+            "severity = \"high\"\n[versions]\npatched = [\">= 2.0.0\"]\n",
+            "# Fixed issue in another\n",
+        );
+        advisory(
+            "example",
+            "RUSTSEC-2024-0003",
+            "withdrawn = \"2024-02-01\"\n[versions]\npatched = []\n",
+            "# Withdrawn advisory\n",
+        );
+        advisory(
+            "example",
+            "RUSTSEC-2024-0004",
+            "[versions]\npatched = [\"not a version\"]\n",
+            "# Invalid advisory\n",
+        );
+        advisory(
+            "another",
+            "RUSTSEC-2024-0005",
+            "informational = \"unmaintained\"\n[versions]\npatched = []\n",
+            "# another is unmaintained\n",
+        );
+        std::fs::write(
+            &lockfile,
+            "[[package]]\nname = \"example\"\nversion = \"1.1.0\"\n\n\
+             [[package]]\nname = \"another\"\nversion = \"2.0.1\"\n",
+        )
+        .unwrap();
+
+        // The invalid advisory is skipped, and the withdrawn one ignored.
+        let scanner = VulnerabilityScanner::from_database(&db).unwrap();
+        assert_eq!(scanner.advisories.len(), 3);
+
+        let report = scanner.scan_cargo_lock(&lockfile).unwrap();
+        assert_eq!(report.vulnerabilities.len(), 1);
+        assert_eq!(
+            report.warnings,
+            vec!["another 2.0.1 is unmaintained: another is unmaintained (RUSTSEC-2024-0005)"]
+        );
+
+        let vuln = &report.vulnerabilities[0];
+        assert_eq!(vuln.id, "RUSTSEC-2024-0001");
+        assert_eq!(vuln.package, "example");
+        assert_eq!(vuln.version, "1.1.0");
+        assert_eq!(vuln.severity, Severity::Critical);
+        assert_eq!(vuln.title, "Memory corruption in example");
+        assert_eq!(vuln.description, "Parsing untrusted input corrupts memory.");
+        assert_eq!(
+            vuln.url.as_deref(),
+            Some("https://rustsec.org/advisories/RUSTSEC-2024-0001")
+        );
+        assert_eq!(report.critical_count(), 1);
+    }
+
+    #[test]
+    fn test_from_database_missing() {
+        let tmp = tempfile::tempdir().unwrap();
+        assert!(VulnerabilityScanner::from_database(tmp.path()).is_err());
+    }
 }
@@ -105,6 +105,31 @@ pub mod localtime {
     }
 }
 
+pub mod duration {
+    /// (De)serialize a [`std::time::Duration`] as a number of milliseconds.
+    pub mod millis {
+        use std::time::Duration;
+
+        use serde::{Deserialize, Deserializer, Serializer};
+
+        pub fn serialize<S>(value: &Duration, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            serializer.serialize_u64(u64::try_from(value.as_millis()).unwrap_or(u64::MAX))
+        }
+
+        pub fn deserialize<'de, D>(deserializer: D) -> Result<Duration, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            let millis = u64::deserialize(deserializer)?;
+
+            Ok(Duration::from_millis(millis))
+        }
+    }
+}
+
 /// Return true if the given value is the default for that type.
 pub fn is_default<T: Default + PartialEq>(t: &T) -> bool {
     t == &T::default()
@@ -309,6 +309,13 @@ impl git::canonical::effects::FindMergeBase for Repository {
     ) -> Result<git::canonical::MergeBase, git::canonical::effects::MergeBaseError> {
         git::canonical::effects::FindMergeBase::merge_base(&self.backend, a, b)
     }
+
+    fn merge_bases(
+        &self,
+        pairs: &[(Oid, Oid)],
+    ) -> Result<Vec<git::canonical::MergeBase>, git::canonical::effects::MergeBaseError> {
+        git::canonical::effects::FindMergeBase::merge_bases(&self.backend, pairs)
+    }
 }
 
 impl git::canonical::effects::FindObjects for Repository {
@@ -320,7 +327,18 @@ impl git::canonical::effects::FindObjects for Repository {
     where
         I: Iterator<Item = &'b crate::prelude::Did>,
     {
-        git::canonical::effects::FindObjects::find_objects(&self.backend, refname, dids)
+        #[cfg(feature = "rayon")]
+        {
+            git::canonical::effects::FindObjects::find_objects(
+                &git::canonical::effects::Parallel(&self.backend),
+                refname,
+                dids,
+            )
+        }
+        #[cfg(not(feature = "rayon"))]
+        {
+            git::canonical::effects::FindObjects::find_objects(&self.backend, refname, dids)
+        }
     }
 }
 
@@ -3,7 +3,8 @@
 use anyhow::{Context, Result};
 use clap::Args;
 use colored::Colorize;
-use radicle::security::VulnerabilityScanner;
+use radicle::security::vulnerabilities::Severity;
+use radicle::security::{sarif, VulnerabilityScanner};
 use std::path::PathBuf;
 
 #[derive(Args)]
@@ -24,18 +25,37 @@ pub struct AuditArgs {
     #[arg(long)]
     pub severity: Option<String>,
 
-    /// Output format (text, json)
+    /// Only report vulnerabilities at or above this severity (low, medium, high, critical)
+    #[arg(long, value_parser = parse_severity)]
+    pub min_severity: Option<Severity>,
+
+    /// Exit with an error if any vulnerability is at or above this severity
+    #[arg(long, value_parser = parse_severity)]
+    pub fail_on: Option<Severity>,
+
+    /// Path to a local advisory database to use instead of the default one
+    #[arg(long)]
+    pub database: Option<PathBuf>,
+
+    /// Output format (text, json, sarif)
     #[arg(short, long, default_value = "text")]
     pub format: String,
 }
 
 pub async fn run(args: AuditArgs) -> Result<()> {
-    println!("{}", "🔒 Auditing dependencies...".cyan().bold());
+    // SARIF output must be the only thing written to stdout.
+    let sarif = args.format == "sarif";
+    if !sarif {
+        println!("{}", "🔒 Auditing dependencies...".cyan().bold());
+    }
 
-    let scanner = VulnerabilityScanner::new()
-        .context("Failed to initialize vulnerability scanner")?;
+    let scanner = match &args.database {
+        Some(path) => VulnerabilityScanner::from_database(path),
+        None => VulnerabilityScanner::new(),
+    }
+    .context("Failed to initialize vulnerability scanner")?;
 
-    let reports = if args.recursive {
+    let mut reports = if args.recursive {
         // Scan all lockfiles in repository
         let cwd = std::env::current_dir()?;
         scanner
@@ -58,6 +78,29 @@ pub async fn run(args: AuditArgs) -> Result<()> {
             .context("Failed to scan Cargo.lock")?]
     };
 
+    // Failure is decided on all vulnerabilities, including the ones not displayed.
+    let failed = args.fail_on.as_ref().and_then(|threshold| {
+        let count = reports
+            .iter()
+            .flat_map(|r| &r.vulnerabilities)
+            .filter(|v| v.severity >= *threshold)
+            .count();
+        (count > 0).then_some((threshold, count))
+    });
+    if let Some(min) = &args.min_severity {
+        for report in &mut reports {
+            report.vulnerabilities.retain(|v| v.severity >= *min);
+        }
+    }
+
+    if sarif {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&sarif::vulnerabilities(&reports))?
+        );
+        return check_failure(failed);
+    }
+
     // Display results
     let mut total_vulns = 0;
     let mut critical_count = 0;
@@ -96,15 +139,33 @@ pub async fn run(args: AuditArgs) -> Result<()> {
         }
     }
 
+    check_failure(failed)
+}
+
+/// Fail if vulnerabilities were found at or above the `--fail-on` severity.
+fn check_failure(failed: Option<(&Severity, usize)>) -> Result<()> {
+    if let Some((threshold, count)) = failed {
+        anyhow::bail!("{count} vulnerabilities at or above {threshold} severity");
+    }
     Ok(())
 }
 
+fn parse_severity(s: &str) -> Result<Severity, String> {
+    match s.to_lowercase().as_str() {
+        "low" => Ok(Severity::Low),
+        "medium" => Ok(Severity::Medium),
+        "high" => Ok(Severity::High),
+        "critical" => Ok(Severity::Critical),
+        _ => Err(format!(
+            "invalid severity '{s}', expected one of: low, medium, high, critical"
+        )),
+    }
+}
+
 fn display_report(
     report: &radicle::security::vulnerabilities::ScanReport,
     severity_filter: Option<&str>,
 ) -> Result<()> {
-    use radicle::security::vulnerabilities::Severity;
-
     println!(
         "\n{} {}",
         "Lockfile:".cyan(),
@@ -148,6 +209,9 @@ fn display_report(
             println!("    More info: {}", url.cyan().underline());
         }
     }
+    for warning in &report.warnings {
+        println!("\n  {} {}", "⚠".yellow(), warning.dimmed());
+    }
 
     Ok(())
 }
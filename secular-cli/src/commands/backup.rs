@@ -1,13 +1,32 @@
 //! Backup and restore operations
+//!
+//! Each backup is a tar archive along with a manifest recording the object ID
+//! of every backed up file. Incremental backups only archive the files whose
+//! object ID changed since the previous backup, whose manifest they reference.
 
-use anyhow::Result;
-use clap::Args;
+use anyhow::{Context, Result};
+use clap::{Args, Subcommand};
 use colored::Colorize;
-use std::path::PathBuf;
-use std::process::Command;
+use radicle::git::Oid;
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Default node data directory.
+const DATA_DIR: &str = "/var/lib/secular";
+/// Environment variable holding the encryption passphrase.
+const PASSPHRASE_ENV: &str = "SECULAR_BACKUP_PASSPHRASE";
+/// File pointing to the manifest of the latest backup, in the backup directory.
+const LATEST_FILE: &str = "secular-backup.latest";
+/// Message signed with the node key to derive an encryption passphrase.
+const NODE_KEY_CONTEXT: &[u8] = b"secular-backup-encryption-key";
 
 #[derive(Args)]
 pub struct BackupArgs {
+    #[command(subcommand)]
+    pub command: Option<BackupCommand>,
+
     /// Backup destination (local path or gs://bucket/path)
     #[arg(short, long)]
     pub dest: Option<String>,
@@ -24,115 +43,520 @@ pub struct BackupArgs {
     #[arg(long)]
     pub full: bool,
 
-    /// Encrypt backup
+    /// Only archive files changed since the last backup
+    #[arg(long)]
+    pub incremental: bool,
+
+    /// Encrypt backup, with the passphrase in $SECULAR_BACKUP_PASSPHRASE or prompted for
     #[arg(long)]
     pub encrypt: bool,
+
+    /// Encrypt with a passphrase derived from the node key
+    #[arg(long, requires = "encrypt")]
+    pub node_key: bool,
+
+    /// Directory holding the data to back up
+    #[arg(long, default_value = DATA_DIR)]
+    pub data_dir: PathBuf,
+
+    /// Directory where backups and their manifests are kept
+    #[arg(long, default_value = ".")]
+    pub backup_dir: PathBuf,
+}
+
+#[derive(Subcommand)]
+pub enum BackupCommand {
+    /// Restore from a backup, verifying it against its manifest
+    Restore(RestoreArgs),
+}
+
+#[derive(Args)]
+pub struct RestoreArgs {
+    /// Backup archive or manifest to restore
+    pub source: PathBuf,
+
+    /// Directory to restore into
+    #[arg(long, default_value = DATA_DIR)]
+    pub target: PathBuf,
+
+    /// Decrypt with a passphrase derived from the node key
+    #[arg(long)]
+    pub node_key: bool,
+}
+
+/// How backup archives are encrypted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Encryption {
+    /// Archives are not encrypted.
+    None,
+    /// Archives are encrypted with gpg, using the given passphrase, or
+    /// prompting for one.
+    Passphrase(Option<String>),
+}
+
+/// Options for creating a backup.
+#[derive(Debug, Clone)]
+pub struct BackupOptions {
+    /// Name of the backup, used for its archive and manifest.
+    pub name: String,
+    /// Directory holding the data to back up.
+    pub data_dir: PathBuf,
+    /// Paths to back up, relative to the data directory.
+    pub paths: Vec<PathBuf>,
+    /// Directory where backups and their manifests are kept.
+    pub backup_dir: PathBuf,
+    /// Only archive files changed since the latest backup.
+    pub incremental: bool,
+    pub encryption: Encryption,
+}
+
+/// Record of the files in a backup.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Manifest {
+    /// Name of the backup.
+    pub name: String,
+    /// Name of the backup this one is based on, for incremental backups.
+    pub parent: Option<String>,
+    /// File name of the archive, in the backup directory.
+    pub archive: String,
+    /// Object ID of the archive, to detect corruption.
+    pub archive_oid: Oid,
+    /// Whether the archive is encrypted.
+    pub encrypted: bool,
+    /// Object IDs of all backed up files, including the ones in parent backups.
+    pub files: BTreeMap<String, Oid>,
+    /// Files included in this backup's archive.
+    pub archived: Vec<String>,
+}
+
+impl Manifest {
+    /// Path of the manifest of the named backup.
+    pub fn path(backup_dir: &Path, name: &str) -> PathBuf {
+        backup_dir.join(format!("{name}.manifest.json"))
+    }
+
+    /// Read a manifest.
+    pub fn read(path: &Path) -> Result<Self> {
+        let json = std::fs::read(path)
+            .with_context(|| format!("Failed to read manifest {}", path.display()))?;
+
+        serde_json::from_slice(&json).context("Invalid manifest")
+    }
+
+    /// Read the manifest of the latest backup in the backup directory, if any.
+    pub fn latest(backup_dir: &Path) -> Result<Option<Self>> {
+        match std::fs::read_to_string(backup_dir.join(LATEST_FILE)) {
+            Ok(name) => Self::read(&Self::path(backup_dir, name.trim())).map(Some),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
 }
 
 pub async fn run(args: BackupArgs) -> Result<()> {
+    if let Some(BackupCommand::Restore(restore)) = args.command {
+        return restore_backup(restore).await;
+    }
+
     if args.list {
         list_backups(args.dest).await
     } else if args.restore {
-        restore_backup(args.dest).await
+        let source = args
+            .dest
+            .ok_or_else(|| anyhow::anyhow!("Backup source required for restore"))?;
+
+        restore_backup(RestoreArgs {
+            source: PathBuf::from(source),
+            target: args.data_dir,
+            node_key: args.node_key,
+        })
+        .await
     } else {
-        create_backup(args.dest, args.full, args.encrypt).await
+        create_backup(args).await
     }
 }
 
-async fn create_backup(dest: Option<String>, full: bool, encrypt: bool) -> Result<()> {
+async fn create_backup(args: BackupArgs) -> Result<()> {
     println!("{}", "📦 Creating backup...".cyan().bold());
 
-    let data_dir = "/var/lib/secular";
     let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S");
-    let backup_name = format!("secular-backup-{}.tar.gz", timestamp);
-
-    // Create backup
-    println!("  {} Compressing data...", "→".cyan());
-
-    let mut cmd = Command::new("tar");
-    cmd.arg("-czf")
-        .arg(&backup_name)
-        .arg("-C")
-        .arg(data_dir);
-
-    if full {
-        cmd.arg(".");
+    let paths = if args.full {
+        vec![PathBuf::from(".")]
     } else {
         // Only backup critical data
-        cmd.arg("config")
-            .arg("keys")
-            .arg("repos");
-    }
-
-    let status = cmd.status()?;
-
-    if !status.success() {
-        anyhow::bail!("Failed to create backup archive");
-    }
+        ["config", "keys", "repos"].map(PathBuf::from).to_vec()
+    };
+    let encryption = if !args.encrypt {
+        Encryption::None
+    } else if args.node_key {
+        Encryption::Passphrase(Some(node_key_passphrase()?))
+    } else {
+        Encryption::Passphrase(std::env::var(PASSPHRASE_ENV).ok())
+    };
 
-    println!("  {} Created {}", "✓".green(), backup_name.cyan());
+    println!("  {} Compressing data...", "→".cyan());
 
-    // Encrypt if requested
-    if encrypt {
-        println!("  {} Encrypting backup...", "→".cyan());
-        encrypt_backup(&backup_name)?;
-    }
+    let manifest = backup(&BackupOptions {
+        name: format!("secular-backup-{timestamp}"),
+        data_dir: args.data_dir,
+        paths,
+        backup_dir: args.backup_dir.clone(),
+        incremental: args.incremental,
+        encryption,
+    })?;
+    let archive = args.backup_dir.join(&manifest.archive);
+
+    println!(
+        "  {} Created {} ({} of {} files)",
+        "✓".green(),
+        manifest.archive.cyan(),
+        manifest.archived.len(),
+        manifest.files.len()
+    );
 
     // Upload if destination specified
-    if let Some(dest) = dest {
+    if let Some(dest) = args.dest {
         println!("  {} Uploading to {}...", "→".cyan(), dest.cyan());
-        upload_backup(&backup_name, &dest)?;
+        upload_backup(&archive, &dest)?;
+        upload_backup(&Manifest::path(&args.backup_dir, &manifest.name), &dest)?;
 
-        // Clean up local copy
-        std::fs::remove_file(&backup_name)?;
+        // Clean up local copy, keeping the manifest for incremental backups
+        std::fs::remove_file(&archive)?;
         println!("  {} Backup uploaded successfully", "✓".green());
     } else {
-        println!("  {} Backup saved locally: {}", "✓".green(), backup_name.cyan());
+        println!(
+            "  {} Backup saved locally: {}",
+            "✓".green(),
+            archive.display().to_string().cyan()
+        );
     }
 
     Ok(())
 }
 
-async fn restore_backup(source: Option<String>) -> Result<()> {
+/// Create a backup, returning its manifest.
+pub fn backup(opts: &BackupOptions) -> Result<Manifest> {
+    let files = hash_files(&opts.data_dir, &opts.paths)?;
+    let parent = if opts.incremental {
+        Manifest::latest(&opts.backup_dir)?
+    } else {
+        None
+    };
+    let archived = files
+        .iter()
+        .filter(|(path, oid)| parent.as_ref().and_then(|p| p.files.get(*path)) != Some(*oid))
+        .map(|(path, _)| path.clone())
+        .collect::<Vec<_>>();
+
+    std::fs::create_dir_all(&opts.backup_dir)?;
+
+    let tarball = opts.backup_dir.join(format!("{}.tar.gz", opts.name));
+    create_archive(&opts.data_dir, &archived, &tarball)?;
+
+    let archive = match &opts.encryption {
+        Encryption::None => tarball,
+        Encryption::Passphrase(passphrase) => {
+            let encrypted = encrypt_backup(&tarball, passphrase.as_deref())?;
+            // Remove unencrypted copy
+            std::fs::remove_file(&tarball)?;
+            encrypted
+        }
+    };
+    let manifest = Manifest {
+        name: opts.name.clone(),
+        parent: parent.map(|p| p.name),
+        archive: file_name(&archive)?,
+        archive_oid: hash_file(&archive)?,
+        encrypted: opts.encryption != Encryption::None,
+        files,
+        archived,
+    };
+    std::fs::write(
+        Manifest::path(&opts.backup_dir, &opts.name),
+        serde_json::to_vec_pretty(&manifest)?,
+    )?;
+    std::fs::write(opts.backup_dir.join(LATEST_FILE), &opts.name)?;
+
+    Ok(manifest)
+}
+
+async fn restore_backup(args: RestoreArgs) -> Result<()> {
     println!("{}", "♻️  Restoring from backup...".cyan().bold());
 
-    let source = source.ok_or_else(|| anyhow::anyhow!("Backup source required for restore"))?;
+    let source = args.source.to_string_lossy();
 
-    // Download if remote
-    let backup_file = if source.starts_with("gs://") {
+    // Download if remote, along with the backups it's based on
+    let source = if source.starts_with("gs://") {
         println!("  {} Downloading backup...", "→".cyan());
-        download_backup(&source)?
+        fetch_backup(&source, Path::new("."), download_backup)?
     } else {
-        PathBuf::from(&source)
+        args.source.clone()
     };
-
-    // Decrypt if needed
-    let backup_file = if backup_file.extension().and_then(|s| s.to_str()) == Some("gpg") {
-        println!("  {} Decrypting backup...", "→".cyan());
-        decrypt_backup(&backup_file)?
+    let passphrase = if args.node_key {
+        Some(node_key_passphrase()?)
     } else {
-        backup_file
+        std::env::var(PASSPHRASE_ENV).ok()
     };
 
-    // Extract
     println!("  {} Extracting backup...", "→".cyan());
 
+    match manifest_for(&source) {
+        Some(manifest) => {
+            let manifest = Manifest::read(&manifest)?;
+            let backup_dir = source.parent().unwrap_or(Path::new("."));
+
+            restore(&manifest, backup_dir, &args.target, passphrase.as_deref())?;
+            println!(
+                "  {} Verified {} files against manifest",
+                "✓".green(),
+                manifest.files.len()
+            );
+        }
+        None => {
+            // Backups without a manifest can't be verified
+            println!(
+                "  {} No manifest found, skipping verification",
+                "⚠".yellow()
+            );
+            extract_archive(&source, &args.target, passphrase.as_deref())?;
+        }
+    }
+
+    println!("{}", "✓ Backup restored successfully!".green().bold());
+    println!("\nRestart the node: {}", "sec node restart".cyan());
+
+    Ok(())
+}
+
+/// Restore a backup and the backups it's based on into `target`, and verify
+/// the restored files against the manifest.
+pub fn restore(
+    manifest: &Manifest,
+    backup_dir: &Path,
+    target: &Path,
+    passphrase: Option<&str>,
+) -> Result<()> {
+    // Collect the chain of backups, oldest first
+    let mut chain = vec![manifest.clone()];
+    while let Some(parent) = &chain[chain.len() - 1].parent {
+        chain.push(Manifest::read(&Manifest::path(backup_dir, parent))?);
+    }
+    std::fs::create_dir_all(target)?;
+
+    for backup in chain.iter().rev() {
+        let archive = backup_dir.join(&backup.archive);
+        if hash_file(&archive)? != backup.archive_oid {
+            anyhow::bail!("Archive {} doesn't match its manifest", archive.display());
+        }
+        extract_archive(&archive, target, passphrase)?;
+    }
+
+    // Files deleted since a parent backup are in its archive, but not in the manifest
+    for path in chain.iter().flat_map(|backup| &backup.archived) {
+        if manifest.files.contains_key(path) {
+            continue;
+        }
+        match std::fs::remove_file(target.join(path)) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+            _ => {}
+        }
+    }
+
+    let restored = hash_files(target, &[PathBuf::from(".")])?;
+    let mismatched = manifest
+        .files
+        .iter()
+        .filter(|(path, oid)| restored.get(*path) != Some(*oid))
+        .count();
+    if mismatched > 0 {
+        anyhow::bail!("{mismatched} restored file(s) don't match the manifest");
+    }
+    Ok(())
+}
+
+/// Find the manifest of a backup, given its archive or manifest.
+fn manifest_for(source: &Path) -> Option<PathBuf> {
+    let name = source.file_name()?.to_str()?;
+    if name.ends_with(".manifest.json") {
+        return Some(source.to_path_buf());
+    }
+    let manifest = Manifest::path(source.parent()?, backup_name(name));
+
+    manifest.exists().then_some(manifest)
+}
+
+/// Name of a backup, given the file name of its archive or manifest.
+fn backup_name(file_name: &str) -> &str {
+    file_name
+        .trim_end_matches(".manifest.json")
+        .trim_end_matches(".gpg")
+        .trim_end_matches(".tar.gz")
+}
+
+/// Download a remote backup into `dir`, along with its manifest and the backups
+/// it's based on, returning the local path to restore from.
+///
+/// Backups without a manifest are downloaded on their own, unless they are
+/// known to be incremental.
+fn fetch_backup(
+    source: &str,
+    dir: &Path,
+    download: impl Fn(&str, &Path) -> Result<PathBuf>,
+) -> Result<PathBuf> {
+    let (remote, file_name) = source
+        .rsplit_once('/')
+        .ok_or_else(|| anyhow::anyhow!("Invalid backup location {source}"))?;
+    let name = backup_name(file_name);
+    let manifest_file = format!("{name}.manifest.json");
+
+    let Ok(path) = download(&format!("{remote}/{manifest_file}"), dir) else {
+        if file_name == manifest_file {
+            anyhow::bail!("Failed to download manifest {source}");
+        }
+        return download(source, dir);
+    };
+    let mut manifest = Manifest::read(&path)?;
+    loop {
+        download(&format!("{remote}/{}", manifest.archive), dir)?;
+
+        let Some(parent) = &manifest.parent else {
+            break;
+        };
+        let parent = download(&format!("{remote}/{parent}.manifest.json"), dir)
+            .with_context(|| {
+                format!("Incremental backup {} is missing its parent", manifest.name)
+            })?;
+        manifest = Manifest::read(&parent)?;
+    }
+    Ok(path)
+}
+
+/// Compute the object IDs of all files under the given paths, keyed by their
+/// path relative to `root`.
+fn hash_files(root: &Path, paths: &[PathBuf]) -> Result<BTreeMap<String, Oid>> {
+    let mut files = BTreeMap::new();
+
+    for path in paths {
+        let path = root.join(path);
+        if !path.exists() {
+            continue;
+        }
+        for entry in walkdir::WalkDir::new(&path) {
+            let entry = entry?;
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            let relative = entry.path().strip_prefix(root)?;
+            let relative = relative
+                .components()
+                .filter(|c| !matches!(c, std::path::Component::CurDir))
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+
+            files.insert(relative, hash_file(entry.path())?);
+        }
+    }
+    Ok(files)
+}
+
+fn hash_file(path: &Path) -> Result<Oid> {
+    let oid = git2::Oid::hash_file(git2::ObjectType::Blob, path)
+        .with_context(|| format!("Failed to hash {}", path.display()))?;
+
+    Ok(oid.into())
+}
+
+fn file_name(path: &Path) -> Result<String> {
+    path.file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .ok_or_else(|| anyhow::anyhow!("Invalid path {}", path.display()))
+}
+
+/// Archive the given files, relative to `root`.
+fn create_archive(root: &Path, files: &[String], archive: &Path) -> Result<()> {
+    let mut child = Command::new("tar")
+        .arg("-czf")
+        .arg(archive)
+        .arg("-C")
+        .arg(root)
+        .args(["--verbatim-files-from", "-T", "-"])
+        .stdin(Stdio::piped())
+        .spawn()?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        for file in files {
+            writeln!(stdin, "{file}")?;
+        }
+    }
+    if !child.wait()?.success() {
+        anyhow::bail!("Failed to create backup archive");
+    }
+    Ok(())
+}
+
+/// Extract an archive into `target`, decrypting it first if needed.
+fn extract_archive(archive: &Path, target: &Path, passphrase: Option<&str>) -> Result<()> {
+    let decrypted = if archive.extension().and_then(|s| s.to_str()) == Some("gpg") {
+        Some(decrypt_backup(archive, passphrase)?)
+    } else {
+        None
+    };
     let status = Command::new("tar")
-        .args(&[
-            "-xzf",
-            backup_file.to_str().unwrap(),
-            "-C",
-            "/var/lib/secular",
-        ])
+        .arg("-xzf")
+        .arg(decrypted.as_deref().unwrap_or(archive))
+        .arg("-C")
+        .arg(target)
         .status()?;
 
+    if let Some(decrypted) = decrypted {
+        std::fs::remove_file(decrypted)?;
+    }
     if !status.success() {
         anyhow::bail!("Failed to extract backup");
     }
+    Ok(())
+}
 
-    println!("{}", "✓ Backup restored successfully!".green().bold());
-    println!("\nRestart the node: {}", "sec node restart".cyan());
+/// Derive an encryption passphrase from the node key.
+///
+/// Ed25519 signatures are deterministic, so signing a fixed message always
+/// yields the same passphrase for the same key.
+fn node_key_passphrase() -> Result<String> {
+    use radicle::crypto::signature::Signer;
+
+    let profile = radicle::Profile::load().context("Failed to load profile")?;
+    let signer = profile.signer().context("Failed to load node key")?;
+    let signature = Signer::<radicle::crypto::Signature>::try_sign(&signer, NODE_KEY_CONTEXT)
+        .map_err(|e| anyhow::anyhow!("Failed to derive key: {e}"))?;
+
+    Ok(signature.to_string())
+}
 
+/// Run gpg, passing the passphrase through stdin if given, or letting gpg
+/// prompt for one otherwise.
+fn gpg(args: &[&std::ffi::OsStr], passphrase: Option<&str>) -> Result<()> {
+    let mut cmd = Command::new("gpg");
+    if passphrase.is_some() {
+        cmd.args([
+            "--batch",
+            "--yes",
+            "--quiet",
+            "--pinentry-mode",
+            "loopback",
+            "--passphrase-fd",
+            "0",
+        ])
+        .stdin(Stdio::piped());
+    }
+    let mut child = cmd.args(args).spawn()?;
+
+    if let (Some(passphrase), Some(mut stdin)) = (passphrase, child.stdin.take()) {
+        writeln!(stdin, "{passphrase}")?;
+    }
+    if !child.wait()?.success() {
+        anyhow::bail!("gpg failed");
+    }
     Ok(())
 }
 
@@ -176,70 +600,68 @@ async fn list_backups(location: Option<String>) -> Result<()> {
     Ok(())
 }
 
-fn encrypt_backup(backup_file: &str) -> Result<()> {
-    let encrypted_file = format!("{}.gpg", backup_file);
-
-    let status = Command::new("gpg")
-        .args(&[
-            "--symmetric",
-            "--cipher-algo",
-            "AES256",
-            "-o",
-            &encrypted_file,
-            backup_file,
-        ])
-        .status()?;
-
-    if !status.success() {
-        anyhow::bail!("Encryption failed");
-    }
-
-    // Remove unencrypted copy
-    std::fs::remove_file(backup_file)?;
-
-    Ok(())
+fn encrypt_backup(backup_file: &Path, passphrase: Option<&str>) -> Result<PathBuf> {
+    let mut encrypted_file = backup_file.as_os_str().to_owned();
+    encrypted_file.push(".gpg");
+    let encrypted_file = PathBuf::from(encrypted_file);
+
+    gpg(
+        &[
+            "--symmetric".as_ref(),
+            "--cipher-algo".as_ref(),
+            "AES256".as_ref(),
+            "-o".as_ref(),
+            encrypted_file.as_os_str(),
+            backup_file.as_os_str(),
+        ],
+        passphrase,
+    )
+    .context("Encryption failed")?;
+
+    Ok(encrypted_file)
 }
 
-fn decrypt_backup(backup_file: &PathBuf) -> Result<PathBuf> {
+fn decrypt_backup(backup_file: &Path, passphrase: Option<&str>) -> Result<PathBuf> {
     let decrypted_file = backup_file.with_extension("");
 
-    let status = Command::new("gpg")
-        .args(&[
-            "--decrypt",
-            "-o",
-            decrypted_file.to_str().unwrap(),
-            backup_file.to_str().unwrap(),
-        ])
-        .status()?;
-
-    if !status.success() {
-        anyhow::bail!("Decryption failed");
-    }
+    gpg(
+        &[
+            "--decrypt".as_ref(),
+            "-o".as_ref(),
+            decrypted_file.as_os_str(),
+            backup_file.as_os_str(),
+        ],
+        passphrase,
+    )
+    .context("Decryption failed")?;
 
     Ok(decrypted_file)
 }
 
-fn upload_backup(backup_file: &str, dest: &str) -> Result<()> {
+fn upload_backup(backup_file: &Path, dest: &str) -> Result<()> {
     if dest.starts_with("gs://") {
         // Upload to Google Cloud Storage
         let status = Command::new("gsutil")
-            .args(&["cp", backup_file, dest])
+            .arg("cp")
+            .arg(backup_file)
+            .arg(dest)
             .status()?;
 
         if !status.success() {
             anyhow::bail!("Upload to GCS failed");
         }
     } else {
-        // Copy to local destination
-        std::fs::copy(backup_file, dest)?;
+        // Copy to local destination directory
+        std::fs::create_dir_all(dest)?;
+        std::fs::copy(backup_file, Path::new(dest).join(file_name(backup_file)?))?;
     }
 
     Ok(())
 }
 
-fn download_backup(source: &str) -> Result<PathBuf> {
+fn download_backup(source: &str, dir: &Path) -> Result<PathBuf> {
     let filename = source.split('/').last().unwrap_or("backup.tar.gz");
-    let dest = PathBuf::from(filename);
+    let dest = dir.join(filename);
 
     let status = Command::new("gsutil")
         .args(&["cp", source, dest.to_str().unwrap()])
@@ -251,3 +673,155 @@ fn download_backup(source: &str) -> Result<PathBuf> {
 
     Ok(dest)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn store(root: &Path) {
+        std::fs::create_dir_all(root.join("keys")).unwrap();
+        std::fs::create_dir_all(root.join("repos/z42/objects")).unwrap();
+        std::fs::write(root.join("config"), "{}").unwrap();
+        std::fs::write(root.join("keys/radicle"), "secret").unwrap();
+        std::fs::write(root.join("repos/z42/objects/a"), vec![7; 4096]).unwrap();
+        std::fs::write(root.join("repos/z42/objects/b"), "b").unwrap();
+    }
+
+    fn options(root: &Path, backups: &Path, name: &str) -> BackupOptions {
+        BackupOptions {
+            name: name.to_owned(),
+            data_dir: root.to_path_buf(),
+            paths: vec![PathBuf::from(".")],
+            backup_dir: backups.to_path_buf(),
+            incremental: true,
+            encryption: Encryption::None,
+        }
+    }
+
+    #[test]
+    fn test_incremental_unchanged() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (root, backups) = (tmp.path().join("data"), tmp.path().join("backups"));
+        store(&root);
+
+        let first = backup(&options(&root, &backups, "first")).unwrap();
+        assert_eq!(first.parent, None);
+        assert_eq!(first.archived.len(), 4);
+
+        let second = backup(&options(&root, &backups, "second")).unwrap();
+        assert_eq!(second.parent.as_deref(), Some("first"));
+        assert!(second.archived.is_empty());
+        assert_eq!(second.files, first.files);
+
+        let size = |m: &Manifest| std::fs::metadata(backups.join(&m.archive)).unwrap().len();
+        assert!(size(&second) < 128);
+        assert!(size(&second) < size(&first));
+    }
+
+    #[test]
+    fn test_restore() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (root, backups) = (tmp.path().join("data"), tmp.path().join("backups"));
+        store(&root);
+        backup(&options(&root, &backups, "first")).unwrap();
+
+        std::fs::write(root.join("repos/z42/objects/b"), "changed").unwrap();
+        std::fs::write(root.join("repos/z42/objects/c"), "new").unwrap();
+        std::fs::remove_file(root.join("keys/radicle")).unwrap();
+        let second = backup(&options(&root, &backups, "second")).unwrap();
+        assert_eq!(
+            second.archived,
+            ["repos/z42/objects/b", "repos/z42/objects/c"]
+        );
+
+        let target = tmp.path().join("restored");
+        restore(&second, &backups, &target, None).unwrap();
+        assert_eq!(
+            hash_files(&target, &[PathBuf::from(".")]).unwrap(),
+            hash_files(&root, &[PathBuf::from(".")]).unwrap()
+        );
+        assert_eq!(
+            std::fs::read_to_string(target.join("repos/z42/objects/b")).unwrap(),
+            "changed"
+        );
+        assert!(!target.join("keys/radicle").exists());
+    }
+
+    #[test]
+    fn test_fetch_incremental() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (root, remote) = (tmp.path().join("data"), tmp.path().join("remote"));
+        store(&root);
+        backup(&options(&root, &remote, "first")).unwrap();
+        std::fs::write(root.join("repos/z42/objects/c"), "new").unwrap();
+        let second = backup(&options(&root, &remote, "second")).unwrap();
+
+        // Download by copying from the "remote" directory
+        let download = |source: &str, dir: &Path| -> Result<PathBuf> {
+            let name = source.strip_prefix("gs://bucket/").unwrap();
+            let dest = dir.join(name);
+            std::fs::copy(remote.join(name), &dest)?;
+            Ok(dest)
+        };
+        let local = tmp.path().join("local");
+        std::fs::create_dir(&local).unwrap();
+
+        let source = fetch_backup("gs://bucket/second.tar.gz", &local, download).unwrap();
+        assert_eq!(source, Manifest::path(&local, "second"));
+
+        let target = tmp.path().join("restored");
+        restore(&Manifest::read(&source).unwrap(), &local, &target, None).unwrap();
+        assert_eq!(second.files, hash_files(&target, &[PathBuf::from(".")]).unwrap());
+
+        // Incremental backups can't be restored without their parent
+        std::fs::remove_file(Manifest::path(&remote, "first")).unwrap();
+        let local = tmp.path().join("incomplete");
+        std::fs::create_dir(&local).unwrap();
+        assert!(fetch_backup("gs://bucket/second.tar.gz", &local, download).is_err());
+    }
+
+    #[test]
+    fn test_restore_corrupted() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (root, backups) = (tmp.path().join("data"), tmp.path().join("backups"));
+        store(&root);
+
+        let manifest = backup(&options(&root, &backups, "first")).unwrap();
+        std::fs::write(backups.join(&manifest.archive), "corrupted").unwrap();
+
+        let target = tmp.path().join("restored");
+        assert!(restore(&manifest, &backups, &target, None).is_err());
+    }
+
+    #[test]
+    #[ignore = "requires gpg"]
+    fn test_encrypted_roundtrip() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (root, backups) = (tmp.path().join("data"), tmp.path().join("backups"));
+        store(&root);
+
+        let gnupg = tmp.path().join("gnupg");
+        std::fs::create_dir(&gnupg).unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&gnupg, std::fs::Permissions::from_mode(0o700)).unwrap();
+        }
+        std::env::set_var("GNUPGHOME", &gnupg);
+
+        let passphrase = Encryption::Passphrase(Some("hunter2".to_owned()));
+        let manifest = backup(&BackupOptions {
+            encryption: passphrase,
+            ..options(&root, &backups, "first")
+        })
+        .unwrap();
+        assert!(manifest.encrypted);
+        assert!(manifest.archive.ends_with(".tar.gz.gpg"));
+        assert!(!backups.join("first.tar.gz").exists());
+
+        let target = tmp.path().join("restored");
+        assert!(restore(&manifest, &backups, &target, Some("wrong")).is_err());
+        restore(&manifest, &backups, &target, Some("hunter2")).unwrap();
+        assert_eq!(manifest.files, hash_files(&target, &[PathBuf::from(".")]).unwrap());
+    }
+}
@@ -3,11 +3,12 @@
 use anyhow::{anyhow, Result};
 use colored::Colorize;
 use dialoguer::{Confirm, Select};
+use std::io::{BufRead, BufReader, Write};
 use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::process::{Command, Stdio};
 use walkdir::WalkDir;
 
-pub async fn run(repo_path: Option<String>, private: bool, list: bool) -> Result<()> {
+pub async fn run(repo_path: Option<String>, private: bool, list: bool, dry_run: bool) -> Result<()> {
     if list {
         return list_available_repos();
     }
@@ -52,36 +53,42 @@ pub async fn run(repo_path: Option<String>, private: bool, list: bool) -> Result
         }
     };
 
-    // Validate it's a git repository
-    if !repo_path.join(".git").exists() {
-        return Err(anyhow!("Error: {} is not a git repository", repo_path.display()));
-    }
-
-    let repo_name = repo_path
-        .file_name()
-        .and_then(|n| n.to_str())
-        .ok_or_else(|| anyhow!("Invalid repository path"))?;
-
-    let visibility = if private { "--private" } else { "--public" };
-    let visibility_label = if private { "Private" } else { "Public" };
+    let plan = ConvertPlan {
+        private,
+        ..plan(&repo_path)?
+    };
 
     println!();
-    println!("{}", "🔄 Converting repository to Secular/Radicle...".bright_blue().bold());
-    println!("  Repository: {}", repo_name.blue());
-    println!("  Path: {}", repo_path.display().to_string().blue());
-    println!("  Visibility: {}", visibility_label.blue());
+    if dry_run {
+        println!("{}", "🔍 Conversion plan (dry run)".bright_blue().bold());
+    } else {
+        println!("{}", "🔄 Converting repository to Secular/Radicle...".bright_blue().bold());
+    }
+    println!("  Repository: {}", plan.name.blue());
+    println!("  Path: {}", plan.path.display().to_string().blue());
+    println!(
+        "  Default branch: {}",
+        plan.default_branch.as_deref().unwrap_or("(none)").blue()
+    );
+    println!("  Objects: {}", plan.objects.to_string().blue());
+    println!("  Visibility: {}", plan.visibility().blue());
     println!();
 
     // Check if already a radicle repo
-    if is_radicle_repo(&repo_path)? {
+    if plan.is_radicle {
         println!("{}", "✓ Already a radicle repository".bright_blue());
-        inspect_repo(&repo_path)?;
+        inspect_repo(&plan.path)?;
+        return Ok(());
+    }
+
+    if dry_run {
+        println!("  {}", "No changes made (dry run)".dimmed());
         return Ok(());
     }
 
     // Confirm conversion
     if !Confirm::new()
-        .with_prompt(format!("Convert {} to radicle?", repo_name))
+        .with_prompt(format!("Convert {} to radicle?", plan.name))
         .default(true)
         .interact()?
     {
@@ -89,98 +96,36 @@ pub async fn run(repo_path: Option<String>, private: bool, list: bool) -> Result
         return Ok(());
     }
 
-    // Initialize radicle
-    println!("{}", "Step 1/3: Initializing radicle repository...".blue());
-    let description = format!("{} repository", repo_name);
-
-    println!("  Running: rad init --name {} --description \"{}\" {}",
-             repo_name.dimmed(),
-             description.dimmed(),
-             visibility.dimmed());
-    println!();
-
-    let status = Command::new("rad")
-        .arg("init")
-        .arg("--name")
-        .arg(repo_name)
-        .arg("--description")
-        .arg(&description)
-        .arg(visibility)
-        .arg("--no-confirm")
-        .current_dir(&repo_path)
-        .status()?;
-
-    if !status.success() {
-        return Err(anyhow!("Failed to initialize radicle repo"));
-    }
-
-    println!();
-    println!("  {} Repository initialized", "✓".bright_green().bold());
-
-    // Push to radicle
-    println!();
-    println!("{}", "Step 2/3: Pushing to radicle network...".blue());
-    println!("  Running: git push rad main");
-    println!();
-
-    // Try main branch first, then master
-    let push_result = Command::new("git")
-        .arg("push")
-        .arg("rad")
-        .arg("main")
-        .current_dir(&repo_path)
-        .status();
-
-    let push_success = if let Ok(status) = push_result {
-        if status.success() {
-            true
-        } else {
-            // Try master branch
+    let outcome = execute(&plan, |progress| match progress {
+        Progress::Step { step, total, message } => {
             println!();
-            println!("  {} Trying master branch...", "→".blue());
-            println!("  Running: git push rad master");
+            println!("{}", format!("Step {step}/{total}: {message}...").blue());
+        }
+        Progress::Running(command) => {
+            println!("  Running: {}", command.dimmed());
             println!();
-            let master_status = Command::new("git")
-                .arg("push")
-                .arg("rad")
-                .arg("master")
-                .current_dir(&repo_path)
-                .status()?;
-            master_status.success()
         }
-    } else {
-        false
-    };
+        Progress::Objects { processed, total } => {
+            print!("\r  {} Objects processed: {processed}/{total}", "→".blue());
+            if processed == total {
+                println!();
+            }
+            std::io::stdout().flush().ok();
+        }
+    })?;
 
     println!();
-    if !push_success {
+    if !outcome.pushed {
         println!("  {} Warning: Push may have failed, but repo is initialized", "⚠".yellow());
     } else {
         println!("  {} Successfully pushed to network", "✓".bright_green().bold());
     }
 
-    // Step 3: Announce to network
-    println!();
-    println!("{}", "Step 3/3: Announcing to P2P network...".blue());
-    println!("  Running: rad sync --announce");
-    println!("  {} This may take a moment while syncing with peers...", "ℹ".blue());
-    println!();
-
-    let sync_result = Command::new("rad")
-        .arg("sync")
-        .arg("--announce")
-        .current_dir(&repo_path)
-        .status();
-
     println!();
-    if let Ok(status) = sync_result {
-        if status.success() {
-            println!("  {} Successfully announced to network", "✓".bright_green().bold());
-        } else {
-            println!("  {} Network announcement timed out (repo is still accessible)", "⚠".yellow());
-        }
+    if outcome.announced {
+        println!("  {} Successfully announced to network", "✓".bright_green().bold());
     } else {
-        println!("  {} Could not announce (repo is still accessible locally)", "⚠".yellow());
+        println!("  {} Network announcement timed out (repo is still accessible)", "⚠".yellow());
     }
 
     // Show repository info
@@ -189,7 +134,7 @@ pub async fn run(repo_path: Option<String>, private: bool, list: bool) -> Result
     println!("{}", "✓ CONVERSION COMPLETE!".bright_blue().bold());
     println!("{}", "═══════════════════════════════════════════".bright_blue());
     println!();
-    inspect_repo(&repo_path)?;
+    inspect_repo(&plan.path)?;
 
     // Get node ID
     let node_id = get_node_id()?;
@@ -205,6 +150,195 @@ pub async fn run(repo_path: Option<String>, private: bool, list: bool) -> Result
     Ok(())
 }
 
+/// What converting a repository will do.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConvertPlan {
+    /// Path to the repository working copy.
+    pub path: PathBuf,
+    /// Name the repository is initialized with.
+    pub name: String,
+    /// Branch pushed to the network, if the repository has one.
+    pub default_branch: Option<String>,
+    /// Number of objects in the repository.
+    pub objects: usize,
+    /// Whether the repository is initialized as private. Defaults to `true`.
+    pub private: bool,
+    /// Whether the repository is already a radicle repository.
+    pub is_radicle: bool,
+}
+
+impl ConvertPlan {
+    /// Visibility label of the repository.
+    pub fn visibility(&self) -> &'static str {
+        if self.private {
+            "Private"
+        } else {
+            "Public"
+        }
+    }
+}
+
+/// Conversion progress, reported by [`execute`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Progress {
+    /// A conversion step started.
+    Step {
+        step: usize,
+        total: usize,
+        message: &'static str,
+    },
+    /// A command is being run.
+    Running(String),
+    /// Objects were sent to the network.
+    Objects { processed: usize, total: usize },
+}
+
+/// Result of a conversion.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Outcome {
+    /// Whether the default branch was pushed.
+    pub pushed: bool,
+    /// Whether the repository was announced to the network.
+    pub announced: bool,
+}
+
+/// Plan the conversion of the repository at `path`, without modifying it.
+pub fn plan(path: &Path) -> Result<ConvertPlan> {
+    let repo = git2::Repository::open(path)
+        .map_err(|_| anyhow!("Error: {} is not a git repository", path.display()))?;
+    let name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or_else(|| anyhow!("Invalid repository path"))?
+        .to_owned();
+    // HEAD may point to a branch without commits yet.
+    let default_branch = repo
+        .find_reference("HEAD")?
+        .symbolic_target()
+        .and_then(|target| target.strip_prefix("refs/heads/"))
+        .map(str::to_owned);
+
+    let mut objects = 0;
+    repo.odb()?.foreach(|_| {
+        objects += 1;
+        true
+    })?;
+    let is_radicle = repo.find_remote("rad").is_ok();
+
+    Ok(ConvertPlan {
+        path: path.to_path_buf(),
+        name,
+        default_branch,
+        objects,
+        private: true,
+        is_radicle,
+    })
+}
+
+/// Convert a repository according to `plan`, reporting progress to `progress`.
+pub fn execute(plan: &ConvertPlan, mut progress: impl FnMut(Progress)) -> Result<Outcome> {
+    const STEPS: usize = 3;
+
+    let visibility = if plan.private { "--private" } else { "--public" };
+    let description = format!("{} repository", plan.name);
+
+    // Initialize radicle
+    progress(Progress::Step {
+        step: 1,
+        total: STEPS,
+        message: "Initializing radicle repository",
+    });
+    progress(Progress::Running(format!(
+        "rad init --name {} --description \"{description}\" {visibility}",
+        plan.name
+    )));
+
+    let status = Command::new("rad")
+        .arg("init")
+        .arg("--name")
+        .arg(&plan.name)
+        .arg("--description")
+        .arg(&description)
+        .arg(visibility)
+        .arg("--no-confirm")
+        .current_dir(&plan.path)
+        .status()?;
+
+    if !status.success() {
+        return Err(anyhow!("Failed to initialize radicle repo"));
+    }
+
+    // Push to radicle
+    progress(Progress::Step {
+        step: 2,
+        total: STEPS,
+        message: "Pushing to radicle network",
+    });
+    let pushed = match &plan.default_branch {
+        Some(branch) => {
+            progress(Progress::Running(format!("git push rad {branch}")));
+            push(&plan.path, branch, &mut progress)?
+        }
+        None => false,
+    };
+
+    // Announce to network
+    progress(Progress::Step {
+        step: 3,
+        total: STEPS,
+        message: "Announcing to P2P network",
+    });
+    progress(Progress::Running("rad sync --announce".to_owned()));
+
+    let announced = Command::new("rad")
+        .arg("sync")
+        .arg("--announce")
+        .current_dir(&plan.path)
+        .status()
+        .is_ok_and(|status| status.success());
+
+    Ok(Outcome { pushed, announced })
+}
+
+/// Push a branch to the `rad` remote, reporting the objects written.
+fn push(path: &Path, branch: &str, progress: &mut impl FnMut(Progress)) -> Result<bool> {
+    let mut child = Command::new("git")
+        .arg("push")
+        .arg("--progress")
+        .arg("rad")
+        .arg(branch)
+        .current_dir(path)
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    if let Some(stderr) = child.stderr.take() {
+        // Git redraws progress lines with carriage returns.
+        for line in BufReader::new(stderr).split(b'\r') {
+            let line = line?;
+            let line = String::from_utf8_lossy(&line);
+
+            for line in line.lines() {
+                match parse_progress(line) {
+                    Some((processed, total)) => progress(Progress::Objects { processed, total }),
+                    None if !line.trim().is_empty() => eprintln!("  {}", line.dimmed()),
+                    None => {}
+                }
+            }
+        }
+    }
+    Ok(child.wait()?.success())
+}
+
+/// Parse a git progress line such as `Writing objects:  50% (3/6)`.
+fn parse_progress(line: &str) -> Option<(usize, usize)> {
+    let rest = line.trim().strip_prefix("Writing objects:")?;
+    let (_, counts) = rest.split_once('(')?;
+    let (counts, _) = counts.split_once(')')?;
+    let (processed, total) = counts.split_once('/')?;
+
+    Some((processed.parse().ok()?, total.parse().ok()?))
+}
+
 fn is_radicle_repo(repo_path: &Path) -> Result<bool> {
     let output = Command::new("git")
         .arg("remote")
@@ -346,3 +480,68 @@ fn select_repository() -> Result<PathBuf> {
 
     Ok(repos[selection].clone())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Commit a file to the repository's current branch.
+    fn commit(repo: &git2::Repository, name: &str, content: &str) {
+        let sig = git2::Signature::now("anonymous", "anonymous@example.com").unwrap();
+        let blob = repo.blob(content.as_bytes()).unwrap();
+        let mut tree = repo.treebuilder(None).unwrap();
+        tree.insert(name, blob, 0o100644).unwrap();
+        let tree = repo.find_tree(tree.write().unwrap()).unwrap();
+        let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+
+        repo.commit(
+            Some("HEAD"),
+            &sig,
+            &sig,
+            "Update",
+            &tree,
+            parent.as_ref().into_iter().collect::<Vec<_>>().as_slice(),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_plan() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("acme");
+        let repo = git2::Repository::init(&path).unwrap();
+        repo.set_head("refs/heads/trunk").unwrap();
+
+        let empty = plan(&path).unwrap();
+        assert_eq!(empty.name, "acme");
+        assert_eq!(empty.default_branch.as_deref(), Some("trunk"));
+        assert_eq!(empty.objects, 0);
+        assert!(empty.private);
+        assert!(!empty.is_radicle);
+
+        // Each commit adds a blob, a tree and a commit object.
+        commit(&repo, "README", "hello");
+        commit(&repo, "README", "hello, world");
+
+        let plan = plan(&path).unwrap();
+        assert_eq!(plan.default_branch.as_deref(), Some("trunk"));
+        assert_eq!(plan.objects, 6);
+    }
+
+    #[test]
+    fn test_plan_not_a_repository() {
+        let tmp = tempfile::tempdir().unwrap();
+        assert!(plan(tmp.path()).is_err());
+    }
+
+    #[test]
+    fn test_parse_progress() {
+        assert_eq!(parse_progress("Writing objects:  50% (3/6)"), Some((3, 6)));
+        assert_eq!(
+            parse_progress("Writing objects: 100% (6/6), 512 bytes | 512.00 KiB/s, done."),
+            Some((6, 6))
+        );
+        assert_eq!(parse_progress("Counting objects: 100% (6/6), done."), None);
+        assert_eq!(parse_progress("To rad://z42hL2jL4XNk6K8oHQaSWfMgCL7ji"), None);
+    }
+}
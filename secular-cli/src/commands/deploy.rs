@@ -6,10 +6,19 @@ use colored::Colorize;
 use dialoguer::Confirm;
 use std::process::Command;
 
+use crate::utils::cost::{self, CostBreakdown, ResourceUsage};
+
+/// Port of the P2P firewall rule.
+const P2P_PORT: u16 = 8776;
+
 #[derive(Args)]
 pub struct DeployArgs {
     #[command(subcommand)]
     pub platform: DeployPlatform,
+
+    /// Print the deployment plan without acting on it
+    #[arg(long, global = true)]
+    pub dry_run: bool,
 }
 
 #[derive(Subcommand)]
@@ -49,7 +58,121 @@ pub enum DeployPlatform {
     },
 }
 
+/// A resource created by a deployment.
+#[derive(Debug, PartialEq, serde::Serialize)]
+pub struct PlannedResource {
+    /// Kind of resource, eg. `compute-instance`.
+    pub kind: &'static str,
+    /// Name of the resource.
+    pub name: String,
+    /// Human-readable details, eg. the machine type of an instance.
+    pub details: String,
+}
+
+/// What a deployment would create, and what it would cost.
+#[derive(Debug, serde::Serialize)]
+pub struct DeployPlan {
+    /// Platform deployed to, eg. `gcp`.
+    pub platform: &'static str,
+    /// Name of the deployed instance.
+    pub instance: String,
+    /// Region of the deployment, if any.
+    pub region: Option<String>,
+    /// Zone of the deployment, if any.
+    pub zone: Option<String>,
+    /// Machine type of the instance, if any.
+    pub machine_type: Option<String>,
+    /// Boot disk size, in GB, if any.
+    pub disk_size_gb: Option<u64>,
+    /// Estimated monthly costs, or `None` if the machine type's price is unknown.
+    pub costs: Option<CostBreakdown>,
+    /// Resources to be created.
+    pub resources: Vec<PlannedResource>,
+}
+
+/// Plan a deployment, without acting on it.
+pub fn plan(args: &DeployArgs) -> Result<DeployPlan> {
+    match &args.platform {
+        DeployPlatform::Gcp {
+            instance,
+            zone,
+            project: _,
+            machine_type,
+            disk_size,
+        } => {
+            let region = gcp_region(zone);
+            let disk_size_gb = parse_disk_size(disk_size)?;
+            let costs = cost::machine_hourly(machine_type).map(|machine_hourly| {
+                cost::estimate(&ResourceUsage {
+                    machine_hourly,
+                    storage_gb: disk_size_gb as f64,
+                    ..ResourceUsage::default()
+                })
+            });
+
+            Ok(DeployPlan {
+                platform: "gcp",
+                instance: instance.clone(),
+                region: Some(region.to_owned()),
+                zone: Some(zone.clone()),
+                machine_type: Some(machine_type.clone()),
+                disk_size_gb: Some(disk_size_gb),
+                costs,
+                resources: vec![
+                    PlannedResource {
+                        kind: "compute-instance",
+                        name: instance.clone(),
+                        details: format!("{machine_type} in {zone}, {disk_size_gb}GB disk"),
+                    },
+                    PlannedResource {
+                        kind: "static-ip",
+                        name: format!("{instance}-ip"),
+                        details: format!("in {region}"),
+                    },
+                    PlannedResource {
+                        kind: "firewall-rule",
+                        name: format!("{instance}-p2p"),
+                        details: format!("allow tcp:{P2P_PORT}"),
+                    },
+                ],
+            })
+        }
+        DeployPlatform::Local { port, data_dir } => {
+            let data_dir = local_data_dir(data_dir.clone());
+
+            Ok(DeployPlan {
+                platform: "local",
+                instance: format!("localhost:{port}"),
+                region: None,
+                zone: None,
+                machine_type: None,
+                disk_size_gb: None,
+                costs: Some(cost::estimate(&ResourceUsage {
+                    machine_hourly: 0.0,
+                    compute_hours: 0.0,
+                    storage_gb: 0.0,
+                    egress_gb: 0.0,
+                    static_ip: false,
+                })),
+                resources: vec![PlannedResource {
+                    kind: "data-directory",
+                    name: data_dir,
+                    details: String::from("created if missing"),
+                }],
+            })
+        }
+    }
+}
+
 pub async fn run(args: DeployArgs) -> Result<()> {
+    let plan = plan(&args)?;
+
+    if args.dry_run {
+        print_plan(&plan);
+        println!("{}", "Dry run: nothing was deployed.".yellow());
+        return Ok(());
+    }
+
     match args.platform {
         DeployPlatform::Gcp {
             instance,
@@ -57,12 +180,74 @@ pub async fn run(args: DeployArgs) -> Result<()> {
             project,
             machine_type,
             disk_size,
-        } => deploy_gcp(instance, zone, project, machine_type, disk_size).await,
+        } => deploy_gcp(&plan, instance, zone, project, machine_type, disk_size).await,
         DeployPlatform::Local { port, data_dir } => deploy_local(port, data_dir).await,
     }
 }
 
+fn print_plan(plan: &DeployPlan) {
+    println!("{}", "Deployment plan:".cyan().bold());
+    println!("  Platform:     {}", plan.platform.cyan());
+    println!("  Instance:     {}", plan.instance.cyan());
+    if let Some(region) = &plan.region {
+        println!("  Region:       {}", region.cyan());
+    }
+    if let Some(zone) = &plan.zone {
+        println!("  Zone:         {}", zone.cyan());
+    }
+    if let Some(machine_type) = &plan.machine_type {
+        println!("  Machine type: {}", machine_type.cyan());
+    }
+    if let Some(disk_size_gb) = plan.disk_size_gb {
+        println!("  Disk size:    {}", format!("{disk_size_gb}GB").cyan());
+    }
+    match &plan.costs {
+        Some(costs) => {
+            println!(
+                "  Est. cost:    {} (compute ${:.2}, storage ${:.2}, egress ${:.2}, static IP ${:.2})",
+                format!("${:.2}/month", costs.total).yellow(),
+                costs.compute,
+                costs.storage,
+                costs.egress,
+                costs.static_ip
+            );
+        }
+        None => println!("  Est. cost:    {}", "unknown for this machine type".yellow()),
+    }
+    println!();
+    println!("Resources to be created:");
+    for resource in &plan.resources {
+        println!(
+            "  {} {} {} ({})",
+            "+".green(),
+            resource.kind,
+            resource.name.cyan(),
+            resource.details
+        );
+    }
+    println!();
+}
+
+/// Region of a GCP zone, eg. `us-central1` for `us-central1-a`.
+fn gcp_region(zone: &str) -> &str {
+    zone.rsplitn(2, '-').nth(1).unwrap_or("us-central1")
+}
+
+/// Parse a disk size such as `20GB` or `20`, in GB.
+fn parse_disk_size(size: &str) -> Result<u64> {
+    let digits = size
+        .strip_suffix("GB")
+        .or_else(|| size.strip_suffix("gb"))
+        .unwrap_or(size);
+
+    digits
+        .trim()
+        .parse()
+        .with_context(|| format!("Invalid disk size `{size}`, expected eg. `20GB`"))
+}
+
 async fn deploy_gcp(
+    plan: &DeployPlan,
     instance: String,
     zone: String,
     project: Option<String>,
@@ -79,13 +264,8 @@ async fn deploy_gcp(
         .context("GCP project ID required. Set RADICLE_GCP_PROJECT or use --project")?;
 
     // Confirm deployment
-    println!("Configuration:");
     println!("  Project:      {}", project.cyan());
-    println!("  Instance:     {}", instance.cyan());
-    println!("  Zone:         {}", zone.cyan());
-    println!("  Machine type: {}", machine_type.cyan());
-    println!("  Disk size:    {}", disk_size.cyan());
-    println!();
+    print_plan(plan);
 
     if !Confirm::new()
         .with_prompt("Proceed with deployment?")
@@ -171,7 +351,7 @@ fn create_gcp_instance(
 }
 
 fn create_static_ip(instance: &str, zone: &str) -> Result<()> {
-    let region = zone.rsplitn(2, '-').nth(1).unwrap_or("us-central1");
+    let region = gcp_region(zone);
     let ip_name = format!("{}-ip", instance);
 
     // Try to create (may already exist)
@@ -216,7 +396,7 @@ fn configure_firewall(instance: &str) -> Result<()> {
             "firewall-rules",
             "create",
             &format!("{}-p2p", instance),
-            "--allow=tcp:8776",
+            &format!("--allow=tcp:{P2P_PORT}"),
             "--target-tags=secular-node",
             "--description=Secular P2P port",
         ],
@@ -281,13 +461,7 @@ fn print_deployment_summary(instance: &str, zone: &str) {
 async fn deploy_local(port: u16, data_dir: Option<String>) -> Result<()> {
     println!("{}", "🏠 Deploying locally...".cyan().bold());
 
-    let data_dir = data_dir.unwrap_or_else(|| {
-        dirs::home_dir()
-            .unwrap()
-            .join(".secular")
-            .to_string_lossy()
-            .to_string()
-    });
+    let data_dir = local_data_dir(data_dir);
 
     println!("  Port: {}", port.to_string().cyan());
     println!("  Data directory: {}", data_dir.cyan());
@@ -301,6 +475,17 @@ async fn deploy_local(port: u16, data_dir: Option<String>) -> Result<()> {
     Ok(())
 }
 
+/// Data directory of a local deployment, defaulting to `~/.secular`.
+fn local_data_dir(data_dir: Option<String>) -> String {
+    data_dir.unwrap_or_else(|| {
+        dirs::home_dir()
+            .unwrap()
+            .join(".secular")
+            .to_string_lossy()
+            .to_string()
+    })
+}
+
 fn run_command(args: &[&str], description: &str) -> Result<()> {
     println!("  {} {}", "→".cyan(), description);
 
@@ -319,3 +504,79 @@ fn run_command(args: &[&str], description: &str) -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gcp(machine_type: &str) -> DeployArgs {
+        DeployArgs {
+            platform: DeployPlatform::Gcp {
+                instance: String::from("secular-node"),
+                zone: String::from("europe-west1-b"),
+                project: None,
+                machine_type: machine_type.to_owned(),
+                disk_size: String::from("30GB"),
+            },
+            dry_run: true,
+        }
+    }
+
+    #[test]
+    fn test_plan_gcp() {
+        let plan = plan(&gcp("e2-small")).unwrap();
+
+        assert_eq!(plan.platform, "gcp");
+        assert_eq!(plan.machine_type.as_deref(), Some("e2-small"));
+        assert_eq!(plan.region.as_deref(), Some("europe-west1"));
+        assert_eq!(plan.disk_size_gb, Some(30));
+        let costs = plan.costs.unwrap();
+        assert!(costs.total > 0.0);
+        assert_eq!(costs.storage, 30.0 * cost::HDD_STORAGE_PER_GB);
+        assert_eq!(
+            plan.resources
+                .iter()
+                .map(|r| (r.kind, r.name.as_str()))
+                .collect::<Vec<_>>(),
+            [
+                ("compute-instance", "secular-node"),
+                ("static-ip", "secular-node-ip"),
+                ("firewall-rule", "secular-node-p2p"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_plan_cost() {
+        let micro = plan(&gcp("e2-micro")).unwrap().costs.unwrap();
+        let medium = plan(&gcp("e2-medium")).unwrap().costs.unwrap();
+
+        assert!(micro.total > cost::STATIC_IP_MONTHLY);
+        assert!(medium.total > micro.total);
+        assert!(medium.savings_percent < micro.savings_percent);
+        assert_eq!(plan(&gcp("n2-custom")).unwrap().costs, None);
+    }
+
+    #[test]
+    fn test_plan_local() {
+        let plan = plan(&DeployArgs {
+            platform: DeployPlatform::Local {
+                port: 8776,
+                data_dir: Some(String::from("/tmp/secular")),
+            },
+            dry_run: true,
+        })
+        .unwrap();
+
+        assert_eq!(plan.platform, "local");
+        assert_eq!(plan.costs.unwrap().total, 0.0);
+        assert_eq!(plan.resources[0].name, "/tmp/secular");
+    }
+
+    #[test]
+    fn test_parse_disk_size() {
+        assert_eq!(parse_disk_size("20GB").unwrap(), 20);
+        assert_eq!(parse_disk_size("64").unwrap(), 64);
+        assert!(parse_disk_size("1TB").is_err());
+    }
+}
@@ -7,6 +7,9 @@ use colored::Colorize;
 use std::fs;
 use std::path::Path;
 
+use crate::utils::control::ControlClient;
+use crate::utils::cost::{self, CostBreakdown, ResourceUsage};
+
 #[derive(Args)]
 pub struct MonitorArgs {
     /// Show historical metrics
@@ -18,34 +21,57 @@ pub struct MonitorArgs {
     pub format: String,
 
     /// Path to node data directory
-    #[arg(long, default_value = "/var/lib/radicle")]
+    #[arg(long, default_value = DEFAULT_DATA_DIR)]
     pub data_dir: String,
 }
 
-// Cost constants (GCP us-central1)
-const E2_MICRO_HOURLY: f64 = 0.00508;
-const HDD_STORAGE_PER_GB: f64 = 0.04;
-const EGRESS_PER_GB: f64 = 0.12;
-const STATIC_IP_MONTHLY: f64 = 2.88;
+/// Default node data directory.
+pub const DEFAULT_DATA_DIR: &str = "/var/lib/radicle";
+// Maximum number of history points reported.
+const MAX_HISTORY: usize = 30;
 
 #[derive(Debug, serde::Serialize)]
-struct ResourceMetrics {
-    uptime_hours: f64,
-    cpu_percent: f64,
-    memory_percent: f64,
-    disk_used_gb: f64,
-    disk_total_gb: f64,
-    peer_count: usize,
-    repo_count: usize,
+pub struct ResourceMetrics {
+    pub uptime_hours: f64,
+    pub cpu_percent: f64,
+    pub memory_percent: f64,
+    pub disk_used_gb: f64,
+    pub disk_total_gb: f64,
+    pub peer_count: usize,
+    pub repo_count: usize,
+}
+
+/// Estimated total monthly cost at a point in time.
+#[derive(Debug, PartialEq, serde::Serialize)]
+pub struct HistoryPoint {
+    /// Day of the estimate, as `YYYY-MM-DD`.
+    pub date: String,
+    pub cost: f64,
 }
 
+/// Resource usage and cost metrics, as output by `--format json`.
 #[derive(Debug, serde::Serialize)]
-struct CostEstimate {
-    compute: f64,
-    storage: f64,
-    egress: f64,
-    static_ip: f64,
-    total: f64,
+pub struct MonitorMetrics {
+    #[serde(flatten)]
+    pub costs: CostBreakdown,
+    /// Daily cost estimates, oldest first.
+    pub history: Vec<HistoryPoint>,
+    pub resources: ResourceMetrics,
+    pub timestamp: String,
+}
+
+/// Collect the resource usage and cost metrics of the node at `data_dir`.
+pub async fn collect(data_dir: &Path) -> Result<MonitorMetrics> {
+    let resources = collect_metrics(data_dir).await?;
+    let costs = cost::estimate(&usage(&resources));
+    let history = read_history(&data_dir.join("metrics.log"));
+
+    Ok(MonitorMetrics {
+        costs,
+        history,
+        resources,
+        timestamp: chrono::Utc::now().to_rfc3339(),
+    })
 }
 
 pub async fn run(args: MonitorArgs) -> Result<()> {
@@ -59,31 +85,21 @@ pub async fn run(args: MonitorArgs) -> Result<()> {
 async fn run_text(args: &MonitorArgs) -> Result<()> {
     print_header();
 
-    let metrics = collect_metrics(args)?;
-    print_resource_usage(&metrics);
-
-    let costs = calculate_costs(&metrics)?;
-    print_cost_estimate(&costs);
-
-    print_optimization_tips(&metrics);
+    let metrics = collect(Path::new(&args.data_dir)).await?;
+    print_resource_usage(&metrics.resources);
+    print_cost_estimate(&metrics.costs);
+    print_optimization_tips(&metrics.resources);
 
     // Save metrics for historical tracking
-    save_metrics(&metrics, &costs, args)?;
+    save_metrics(&metrics.resources, &metrics.costs, args)?;
 
     Ok(())
 }
 
 async fn run_json(args: &MonitorArgs) -> Result<()> {
-    let metrics = collect_metrics(args)?;
-    let costs = calculate_costs(&metrics)?;
+    let metrics = collect(Path::new(&args.data_dir)).await?;
 
-    let output = serde_json::json!({
-        "metrics": metrics,
-        "costs": costs,
-        "timestamp": chrono::Utc::now().to_rfc3339()
-    });
-
-    println!("{}", serde_json::to_string_pretty(&output)?);
+    println!("{}", serde_json::to_string_pretty(&metrics)?);
     Ok(())
 }
 
@@ -99,7 +115,7 @@ fn print_header() {
     println!();
 }
 
-fn collect_metrics(args: &MonitorArgs) -> Result<ResourceMetrics> {
+pub(crate) async fn collect_metrics(data_dir: &Path) -> Result<ResourceMetrics> {
     // Get uptime
     let uptime_hours = get_uptime_hours()?;
 
@@ -110,10 +126,10 @@ fn collect_metrics(args: &MonitorArgs) -> Result<ResourceMetrics> {
     let memory_percent = get_memory_usage()?;
 
     // Get disk usage
-    let (disk_used_gb, disk_total_gb) = get_disk_usage(&args.data_dir)?;
+    let (disk_used_gb, disk_total_gb) = get_disk_usage(data_dir)?;
 
     // Get peer/repo counts (if radicle node is accessible)
-    let peer_count = get_peer_count().unwrap_or(0);
+    let peer_count = get_peer_count().await.unwrap_or(0);
     let repo_count = get_repo_count().unwrap_or(0);
 
     Ok(ResourceMetrics {
@@ -143,34 +159,20 @@ fn print_resource_usage(metrics: &ResourceMetrics) {
     println!();
 }
 
-fn calculate_costs(metrics: &ResourceMetrics) -> Result<CostEstimate> {
-    // Estimate monthly hours based on current uptime
+/// Monthly resource usage of an e2-micro node, extrapolated from its current uptime.
+pub fn usage(metrics: &ResourceMetrics) -> ResourceUsage {
     let days_in_month = 30.0;
-    let monthly_hours = (metrics.uptime_hours / 24.0) * (24.0 * days_in_month);
-
-    // Compute cost
-    let compute = monthly_hours * E2_MICRO_HOURLY;
-
-    // Storage cost
-    let storage = metrics.disk_used_gb * HDD_STORAGE_PER_GB;
-
-    // Egress cost (conservative estimate)
-    let egress_gb = 10.0; // Would need actual network monitoring
-    let egress = egress_gb * EGRESS_PER_GB;
 
-    // Total
-    let total = compute + storage + egress + STATIC_IP_MONTHLY;
-
-    Ok(CostEstimate {
-        compute,
-        storage,
-        egress,
-        static_ip: STATIC_IP_MONTHLY,
-        total,
-    })
+    ResourceUsage {
+        compute_hours: (metrics.uptime_hours / 24.0) * (24.0 * days_in_month),
+        storage_gb: metrics.disk_used_gb,
+        // Would need actual network monitoring
+        egress_gb: cost::DEFAULT_EGRESS_GB,
+        ..ResourceUsage::default()
+    }
 }
 
-fn print_cost_estimate(costs: &CostEstimate) {
+fn print_cost_estimate(costs: &CostBreakdown) {
     println!("{}", "Estimated Monthly Costs:".yellow().bold());
     println!("  Compute (e2-micro): ${:.2}", costs.compute);
     println!("  Storage (HDD):      ${:.2}", costs.storage);
@@ -182,6 +184,10 @@ fn print_cost_estimate(costs: &CostEstimate) {
         "Total:".yellow().bold(),
         format!("${:.2}", costs.total).yellow().bold()
     );
+    println!(
+        "  Savings vs. unoptimized deployment: {}",
+        format!("{:.0}%", costs.savings_percent).green()
+    );
     println!();
 }
 
@@ -213,7 +219,7 @@ fn print_optimization_tips(metrics: &ResourceMetrics) {
     println!();
 }
 
-fn save_metrics(metrics: &ResourceMetrics, costs: &CostEstimate, args: &MonitorArgs) -> Result<()> {
+fn save_metrics(metrics: &ResourceMetrics, costs: &CostBreakdown, args: &MonitorArgs) -> Result<()> {
     let metrics_file = Path::new(&args.data_dir).join("metrics.log");
 
     if let Ok(parent) = metrics_file.parent().ok_or(anyhow::anyhow!("No parent")) {
@@ -238,6 +244,38 @@ fn save_metrics(metrics: &ResourceMetrics, costs: &CostEstimate, args: &MonitorA
     Ok(())
 }
 
+/// Read the daily cost history saved by [`save_metrics`], keeping the last
+/// estimate of each day.
+fn read_history(metrics_file: &Path) -> Vec<HistoryPoint> {
+    let Ok(content) = fs::read_to_string(metrics_file) else {
+        return Vec::new();
+    };
+    let mut history: Vec<HistoryPoint> = Vec::new();
+
+    for line in content.lines() {
+        let fields: Vec<&str> = line.split(',').collect();
+        let (Some(timestamp), Some(total)) = (fields.first(), fields.get(5)) else {
+            continue;
+        };
+        let (Ok(timestamp), Ok(cost)) = (timestamp.parse::<i64>(), total.parse::<f64>()) else {
+            continue;
+        };
+        let Some(time) = chrono::DateTime::from_timestamp(timestamp, 0) else {
+            continue;
+        };
+        let date = time.format("%Y-%m-%d").to_string();
+
+        match history.last_mut() {
+            Some(last) if last.date == date => last.cost = cost,
+            _ => history.push(HistoryPoint { date, cost }),
+        }
+    }
+    let skip = history.len().saturating_sub(MAX_HISTORY);
+    history.drain(..skip);
+
+    history
+}
+
 // Helper functions for system metrics
 fn get_uptime_hours() -> Result<f64> {
     #[cfg(target_os = "linux")]
@@ -282,11 +320,12 @@ fn get_memory_usage() -> Result<f64> {
     Ok(mem_str.trim().parse().unwrap_or(0.0))
 }
 
-fn get_disk_usage(data_dir: &str) -> Result<(f64, f64)> {
+fn get_disk_usage(data_dir: &Path) -> Result<(f64, f64)> {
     use std::process::Command;
 
     let output = Command::new("df")
-        .args(&["-BG", data_dir])
+        .arg("-BG")
+        .arg(data_dir)
         .output()?;
 
     let output_str = String::from_utf8_lossy(&output.stdout);
@@ -302,13 +341,81 @@ fn get_disk_usage(data_dir: &str) -> Result<(f64, f64)> {
     Ok((0.0, 20.0))
 }
 
-fn get_peer_count() -> Result<usize> {
-    // Would integrate with radicle node API
-    // For now, return placeholder
-    Ok(0)
+/// Number of peers connected to the local node.
+async fn get_peer_count() -> Result<usize> {
+    let node = ControlClient::new(radicle::profile::home()?.socket());
+    let sessions = node.sessions().await?;
+
+    Ok(sessions.iter().filter(|s| s.is_connected()).count())
 }
 
 fn get_repo_count() -> Result<usize> {
     // Would integrate with radicle node API
     Ok(0)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_keys() {
+        let resources = ResourceMetrics {
+            uptime_hours: 2.0,
+            cpu_percent: 12.5,
+            memory_percent: 40.0,
+            disk_used_gb: 1.5,
+            disk_total_gb: 10.0,
+            peer_count: 3,
+            repo_count: 7,
+        };
+        let metrics = MonitorMetrics {
+            costs: cost::estimate(&usage(&resources)),
+            history: vec![HistoryPoint {
+                date: String::from("2023-11-14"),
+                cost: 8.53,
+            }],
+            resources,
+            timestamp: String::from("2023-11-14T22:13:20+00:00"),
+        };
+        let json = serde_json::to_value(&metrics).unwrap();
+
+        for key in [
+            "compute",
+            "storage",
+            "egress",
+            "static_ip",
+            "total",
+            "savings_percent",
+            "history",
+            "resources",
+            "timestamp",
+        ] {
+            assert!(json.get(key).is_some(), "missing key `{key}`");
+        }
+        assert_eq!(
+            json["history"],
+            serde_json::json!([{ "date": "2023-11-14", "cost": 8.53 }])
+        );
+        assert_eq!(json["resources"]["peer_count"], 3);
+        assert_eq!(json["resources"]["repo_count"], 7);
+        assert_eq!(json["timestamp"], "2023-11-14T22:13:20+00:00");
+    }
+
+    #[test]
+    fn test_read_history() {
+        let tmp = tempfile::tempdir().unwrap();
+        let file = tmp.path().join("metrics.log");
+        let day = 24 * 3600;
+        let log = (0..40)
+            .map(|i| format!("{},1.0,3.6,0.8,1.2,{i}.0\n", 1700000000 + i * day))
+            .collect::<String>();
+        fs::write(&file, log + "garbage\n").unwrap();
+
+        let history = read_history(&file);
+        assert_eq!(history.len(), MAX_HISTORY);
+        assert_eq!(history[0].cost, 10.0);
+        assert_eq!(history.last().unwrap().cost, 39.0);
+        assert!(read_history(&tmp.path().join("missing.log")).is_empty());
+    }
+}
@@ -4,6 +4,9 @@ use anyhow::Result;
 use clap::Subcommand;
 use colored::Colorize;
 use std::process::Command;
+use std::str::FromStr;
+
+use crate::utils::control::ControlClient;
 
 #[derive(Subcommand)]
 pub enum NodeCommands {
@@ -55,6 +58,16 @@ pub enum NodeCommands {
         lines: usize,
     },
 
+    /// Dump the node's internal state, including peer connections
+    Debug,
+
+    /// Change the log level of the running node, without restarting it
+    LogLevel {
+        /// Log level (error, warn, info, debug or trace)
+        #[arg(value_parser = parse_log_level)]
+        level: log::Level,
+    },
+
     /// Announce repositories to the network
     Announce {
         /// Repository path (defaults to current directory)
@@ -73,6 +86,8 @@ pub async fn run(cmd: NodeCommands) -> Result<()> {
         NodeCommands::Repos => list_repos().await,
         NodeCommands::Storage { detailed } => show_storage(detailed).await,
         NodeCommands::Logs { follow, lines } => show_logs(follow, lines).await,
+        NodeCommands::Debug => debug_node().await,
+        NodeCommands::LogLevel { level } => set_log_level(level).await,
         NodeCommands::Announce { path } => announce_repos(path).await,
     }
 }
@@ -249,7 +264,57 @@ async fn show_logs(follow: bool, lines: usize) -> Result<()> {
     Ok(())
 }
 
+async fn debug_node() -> Result<()> {
+    use anyhow::Context;
+
+    if !is_node_running()? {
+        println!("{}", "Error: Node is not running".red());
+        anyhow::bail!("Node not running");
+    }
+
+    // The node answers with a JSON snapshot of its state over the control socket
+    let status = Command::new("rad")
+        .args(&["node", "debug"])
+        .status()
+        .context("Failed to execute 'rad node debug'")?;
+
+    if !status.success() {
+        anyhow::bail!("Failed to get debug information from the node");
+    }
+
+    Ok(())
+}
+
+async fn set_log_level(level: log::Level) -> Result<()> {
+    use anyhow::Context;
+
+    let profile = radicle::Profile::load().context("Failed to load profile")?;
+    let node = ControlClient::new(profile.socket());
+
+    if !node.is_running().await {
+        println!("{}", "Error: Node is not running".red());
+        anyhow::bail!("Node not running");
+    }
+
+    node.set_log_level(level)
+        .await
+        .context("Failed to set the node's log level")?;
+
+    println!(
+        "{}",
+        format!("✓ Log level set to {}", level.as_str().to_lowercase()).green()
+    );
+
+    Ok(())
+}
+
 // Helper functions
+fn parse_log_level(s: &str) -> Result<log::Level, String> {
+    log::Level::from_str(s).map_err(|_| {
+        format!("invalid log level `{s}`, expected one of: error, warn, info, debug, trace")
+    })
+}
+
 fn is_systemd_available() -> Result<bool> {
     Ok(which::which("systemctl").is_ok())
 }
@@ -329,3 +394,16 @@ async fn announce_repos(path: Option<String>) -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_log_level() {
+        assert_eq!(parse_log_level("debug"), Ok(log::Level::Debug));
+        assert_eq!(parse_log_level("WARN"), Ok(log::Level::Warn));
+        assert!(parse_log_level("verbose").is_err());
+        assert!(parse_log_level("").is_err());
+    }
+}
@@ -1,31 +1,255 @@
 //! Cost optimization analysis and recommendations
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use colored::Colorize;
+use serde::{Deserialize, Serialize};
 use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::monitor;
+use crate::utils::cost::{self, CostBreakdown, ResourceUsage};
+
+/// Path of the node configuration edited by optimizations.
+const CONFIG_PATH: &str = "/var/lib/secular/config.toml";
+
+pub async fn run(dry_run: bool, revert: bool) -> Result<()> {
+    let config = Path::new(CONFIG_PATH);
+
+    if revert {
+        let reverted = revert_changes(config)?;
+
+        println!("{}", "↩ Reverted configuration changes:".cyan().bold());
+        print_change_set(&reverted);
+        println!("Restart node: {}", "sec node restart".cyan());
+
+        return Ok(());
+    }
 
-pub async fn run(dry_run: bool) -> Result<()> {
     println!("{}", "💰 Analyzing cost optimization opportunities...".cyan().bold());
     println!();
 
-    let recommendations = analyze_system()?;
-
-    print_recommendations(&recommendations);
+    let content = read_config(config)?;
+    let recommendations = analyze_system(&content)?;
+    let before = monitor::collect_metrics(Path::new(monitor::DEFAULT_DATA_DIR))
+        .await
+        .map(|metrics| monitor::usage(&metrics))
+        .unwrap_or_default();
+    let after = optimized(&before, &recommendations);
+    let changes = ChangeSet::plan(&content, &recommendations, &before);
+
+    print_recommendations(
+        &recommendations,
+        &cost::estimate(&before),
+        &cost::estimate(&after),
+    );
+    if !changes.is_empty() {
+        println!();
+        println!("{}", "Configuration changes:".yellow().bold());
+        print_change_set(&changes);
+    }
 
-    if !dry_run {
+    if !dry_run && !recommendations.is_empty() {
         println!();
         if dialoguer::Confirm::new()
             .with_prompt("Apply recommended optimizations?")
             .default(false)
             .interact()?
         {
-            apply_optimizations(&recommendations)?;
+            apply_optimizations(config, &recommendations, &changes)?;
         }
     }
 
     Ok(())
 }
 
+/// A single configuration edit made by an optimization.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Change {
+    /// Top-level configuration key.
+    pub key: String,
+    /// Value before the edit, or `None` if the key was unset.
+    pub old: Option<String>,
+    /// Value after the edit.
+    pub new: String,
+    /// Why the edit is made.
+    pub rationale: String,
+    /// Estimated monthly saving, in USD.
+    pub saving: f64,
+}
+
+/// Configuration edits made by optimizations. Applied edits are recorded in
+/// a revert manifest, next to the configuration.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChangeSet {
+    pub changes: Vec<Change>,
+}
+
+impl ChangeSet {
+    /// Configuration edits needed by the given optimizations, given the
+    /// current configuration `content` and resource usage.
+    fn plan(content: &str, optimizations: &[Optimization], usage: &ResourceUsage) -> Self {
+        let total = cost::estimate(usage).total;
+        let changes = optimizations
+            .iter()
+            .filter_map(|opt| {
+                let (key, new) = opt.action.config()?;
+                let old = config_value(content, key);
+                if old.as_deref() == Some(new) {
+                    return None;
+                }
+                let optimized = optimized(usage, std::slice::from_ref(opt));
+
+                Some(Change {
+                    key: key.to_owned(),
+                    old,
+                    new: new.to_owned(),
+                    rationale: opt.description.clone(),
+                    saving: total - cost::estimate(&optimized).total,
+                })
+            })
+            .collect();
+
+        Self { changes }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Apply the edits to the configuration at `config`, and record them in
+    /// its revert manifest, after any edits recorded previously.
+    fn apply(&self, config: &Path) -> Result<()> {
+        if self.is_empty() {
+            return Ok(());
+        }
+        let mut content = read_config(config)?;
+        for change in &self.changes {
+            content = set_config_value(&content, &change.key, Some(&change.new));
+        }
+
+        let manifest = manifest_path(config);
+        let mut applied = match fs::read(&manifest) {
+            Ok(bytes) => serde_json::from_slice::<ChangeSet>(&bytes)
+                .with_context(|| format!("Invalid revert manifest {}", manifest.display()))?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => ChangeSet::default(),
+            Err(e) => return Err(e.into()),
+        };
+        applied.changes.extend(self.changes.iter().cloned());
+
+        fs::write(&manifest, serde_json::to_vec_pretty(&applied)?)?;
+        fs::write(config, content)?;
+
+        Ok(())
+    }
+}
+
+/// Restore the values recorded in the revert manifest of the configuration
+/// at `config`, and remove the manifest. Values that were changed since are
+/// left untouched. Returns the reverted edits.
+fn revert_changes(config: &Path) -> Result<ChangeSet> {
+    let manifest = manifest_path(config);
+    let bytes = match fs::read(&manifest) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            anyhow::bail!("Nothing to revert: {} not found", manifest.display())
+        }
+        Err(e) => return Err(e.into()),
+    };
+    let applied: ChangeSet = serde_json::from_slice(&bytes)
+        .with_context(|| format!("Invalid revert manifest {}", manifest.display()))?;
+
+    let mut content = read_config(config)?;
+    let mut reverted = ChangeSet::default();
+    for change in applied.changes.into_iter().rev() {
+        if config_value(&content, &change.key).as_deref() != Some(&change.new) {
+            eprintln!(
+                "{} `{}` was changed since it was optimized, leaving it as is",
+                "⚠".yellow(),
+                change.key
+            );
+            continue;
+        }
+        content = set_config_value(&content, &change.key, change.old.as_deref());
+        reverted.changes.push(change);
+    }
+    fs::write(config, content)?;
+    fs::remove_file(&manifest)?;
+
+    Ok(reverted)
+}
+
+/// Path of the revert manifest of the configuration at `config`.
+fn manifest_path(config: &Path) -> PathBuf {
+    config.with_file_name("optimize-revert.json")
+}
+
+/// Read the configuration at `config`, which is empty if it doesn't exist.
+fn read_config(config: &Path) -> Result<String> {
+    match fs::read_to_string(config) {
+        Ok(content) => Ok(content),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(String::new()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Value of a top-level `key = value` entry of a TOML configuration.
+fn config_value(content: &str, key: &str) -> Option<String> {
+    content
+        .lines()
+        .take_while(|line| !line.trim_start().starts_with('['))
+        .filter_map(|line| line.split_once('='))
+        .find(|(k, _)| k.trim() == key)
+        .map(|(_, v)| v.trim().to_owned())
+}
+
+/// Set, or remove if `value` is `None`, a top-level `key = value` entry of a
+/// TOML configuration. Other lines are left as they are.
+fn set_config_value(content: &str, key: &str, value: Option<&str>) -> String {
+    let mut lines = content.lines().map(str::to_owned).collect::<Vec<_>>();
+    // New top-level entries must come before the first table.
+    let tables = lines
+        .iter()
+        .position(|line| line.trim_start().starts_with('['))
+        .unwrap_or(lines.len());
+    let existing = lines[..tables]
+        .iter()
+        .position(|line| line.split_once('=').is_some_and(|(k, _)| k.trim() == key));
+
+    match (existing, value) {
+        (Some(i), Some(value)) => lines[i] = format!("{key} = {value}"),
+        (Some(i), None) => {
+            lines.remove(i);
+        }
+        (None, Some(value)) => lines.insert(tables, format!("{key} = {value}")),
+        (None, None) => {}
+    }
+
+    let mut content = lines.join("\n");
+    if !content.is_empty() {
+        content.push('\n');
+    }
+    content
+}
+
+fn print_change_set(changes: &ChangeSet) {
+    if changes.is_empty() {
+        println!("  (none)");
+        return;
+    }
+    for change in &changes.changes {
+        println!(
+            "  {} {}: {} → {} {}",
+            "~".yellow(),
+            change.key.cyan(),
+            change.old.as_deref().unwrap_or("(unset)").red(),
+            change.new.green(),
+            format!("[~${:.2}/month]", change.saving).yellow()
+        );
+        println!("      {}", change.rationale.dimmed());
+    }
+}
+
 #[derive(Debug)]
 struct Optimization {
     category: String,
@@ -38,16 +262,50 @@ struct Optimization {
 enum OptimizationAction {
     EnableIdleShutdown,
     EnableCompression,
-    ArchiveOldData,
+    ArchiveOldData { gb: f64 },
     ReduceReplication,
     OptimizeDiskType,
 }
 
-fn analyze_system() -> Result<Vec<Optimization>> {
+impl OptimizationAction {
+    /// Top-level configuration key and value set by the optimization, if any.
+    fn config(&self) -> Option<(&'static str, &'static str)> {
+        match self {
+            Self::EnableIdleShutdown => Some(("idle_timeout", "600")),
+            Self::EnableCompression => Some(("enable_compression", "true")),
+            Self::ArchiveOldData { .. } | Self::ReduceReplication | Self::OptimizeDiskType => None,
+        }
+    }
+
+    /// Expected effect of the optimization on the monthly resource usage.
+    fn apply(&self, usage: &mut ResourceUsage) {
+        match self {
+            Self::EnableIdleShutdown => usage.compute_hours *= 0.3,
+            Self::EnableCompression => usage.egress_gb *= 0.4,
+            // Cold storage costs 4% of standard storage.
+            Self::ArchiveOldData { gb } => {
+                usage.storage_gb = (usage.storage_gb - gb * 0.96).max(0.0)
+            }
+            Self::ReduceReplication => usage.egress_gb *= 0.7,
+            Self::OptimizeDiskType => {}
+        }
+    }
+}
+
+/// Resource usage once all the given optimizations are applied.
+fn optimized(usage: &ResourceUsage, optimizations: &[Optimization]) -> ResourceUsage {
+    let mut usage = usage.clone();
+    for opt in optimizations {
+        opt.action.apply(&mut usage);
+    }
+    usage
+}
+
+fn analyze_system(config: &str) -> Result<Vec<Optimization>> {
     let mut optimizations = Vec::new();
 
     // Check idle shutdown
-    if !is_idle_shutdown_enabled()? {
+    if config_value(config, "idle_timeout").is_none() {
         optimizations.push(Optimization {
             category: "Compute".to_string(),
             description: "Enable idle shutdown to reduce uptime costs by 70%".to_string(),
@@ -57,7 +315,7 @@ fn analyze_system() -> Result<Vec<Optimization>> {
     }
 
     // Check compression
-    if !is_compression_enabled()? {
+    if config_value(config, "enable_compression").as_deref() != Some("true") {
         optimizations.push(Optimization {
             category: "Bandwidth".to_string(),
             description: "Enable compression to reduce bandwidth costs by 50-70%".to_string(),
@@ -76,7 +334,7 @@ fn analyze_system() -> Result<Vec<Optimization>> {
                 old_data_gb
             ),
             savings_percent: 40.0,
-            action: OptimizationAction::ArchiveOldData,
+            action: OptimizationAction::ArchiveOldData { gb: old_data_gb },
         });
     }
 
@@ -95,7 +353,11 @@ fn analyze_system() -> Result<Vec<Optimization>> {
     Ok(optimizations)
 }
 
-fn print_recommendations(recommendations: &[Optimization]) {
+fn print_recommendations(
+    recommendations: &[Optimization],
+    before: &CostBreakdown,
+    after: &CostBreakdown,
+) {
     if recommendations.is_empty() {
         println!("{}", "✓ System is already optimized!".green().bold());
         return;
@@ -108,7 +370,6 @@ fn print_recommendations(recommendations: &[Optimization]) {
     );
     println!();
 
-    let mut total_savings = 0.0;
     for (i, opt) in recommendations.iter().enumerate() {
         let savings_str = if opt.savings_percent > 50.0 {
             format!("~{}%", opt.savings_percent).green().bold()
@@ -123,36 +384,51 @@ fn print_recommendations(recommendations: &[Optimization]) {
             opt.description,
             savings_str
         );
-
-        total_savings += opt.savings_percent;
     }
 
+    println!();
+    println!("{}", "Estimated monthly costs:".yellow().bold());
+    for (component, before, after) in [
+        ("Compute", before.compute, after.compute),
+        ("Storage", before.storage, after.storage),
+        ("Egress", before.egress, after.egress),
+        ("Static IP", before.static_ip, after.static_ip),
+    ] {
+        println!("  {:<10} ${:.2} → ${:.2}", component, before, after);
+    }
+    println!(
+        "  {:<10} ${:.2} → {}",
+        "Total",
+        before.total,
+        format!("${:.2}", after.total).green().bold()
+    );
     println!();
     println!(
         "Potential total savings: {}",
-        format!("~{:.0}%", total_savings / recommendations.len() as f64)
+        format!("~{:.0}%", cost::savings_percent(after.total, before.total))
             .green()
             .bold()
     );
 }
 
-fn apply_optimizations(optimizations: &[Optimization]) -> Result<()> {
+fn apply_optimizations(
+    config: &Path,
+    optimizations: &[Optimization],
+    changes: &ChangeSet,
+) -> Result<()> {
     println!();
     println!("{}", "Applying optimizations...".cyan());
 
+    changes.apply(config)?;
+
     for opt in optimizations {
         print!("  {} {} ... ", "→".cyan(), opt.description);
 
         match &opt.action {
-            OptimizationAction::EnableIdleShutdown => {
-                enable_idle_shutdown()?;
-                println!("{}", "✓".green());
-            }
-            OptimizationAction::EnableCompression => {
-                enable_compression()?;
+            OptimizationAction::EnableIdleShutdown | OptimizationAction::EnableCompression => {
                 println!("{}", "✓".green());
             }
-            OptimizationAction::ArchiveOldData => {
+            OptimizationAction::ArchiveOldData { .. } => {
                 archive_old_data()?;
                 println!("{}", "✓".green());
             }
@@ -168,30 +444,15 @@ fn apply_optimizations(optimizations: &[Optimization]) -> Result<()> {
 
     println!();
     println!("{}", "✓ Optimizations applied!".green().bold());
+    if !changes.is_empty() {
+        println!("Revert with: {}", "sec optimize --revert".cyan());
+    }
     println!("Restart node: {}", "sec node restart".cyan());
 
     Ok(())
 }
 
 // Helper functions
-fn is_idle_shutdown_enabled() -> Result<bool> {
-    let config_path = "/var/lib/secular/config.toml";
-    if let Ok(content) = fs::read_to_string(config_path) {
-        Ok(content.contains("idle_timeout"))
-    } else {
-        Ok(false)
-    }
-}
-
-fn is_compression_enabled() -> Result<bool> {
-    let config_path = "/var/lib/secular/config.toml";
-    if let Ok(content) = fs::read_to_string(config_path) {
-        Ok(content.contains("enable_compression = true"))
-    } else {
-        Ok(false)
-    }
-}
-
 fn check_old_data() -> Result<f64> {
     // Simplified - would check actual file dates
     Ok(0.0)
@@ -202,16 +463,6 @@ fn is_over_replicating() -> Result<bool> {
     Ok(false)
 }
 
-fn enable_idle_shutdown() -> Result<()> {
-    update_config("idle_timeout", "600")?;
-    Ok(())
-}
-
-fn enable_compression() -> Result<()> {
-    update_config("enable_compression", "true")?;
-    Ok(())
-}
-
 fn archive_old_data() -> Result<()> {
     // Would implement actual archival
     Ok(())
@@ -222,15 +473,139 @@ fn optimize_replication() -> Result<()> {
     Ok(())
 }
 
-fn update_config(key: &str, value: &str) -> Result<()> {
-    let config_path = "/var/lib/secular/config.toml";
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    if let Ok(mut content) = fs::read_to_string(config_path) {
-        if !content.contains(key) {
-            content.push_str(&format!("\n{} = {}\n", key, value));
-            fs::write(config_path, content)?;
+    fn optimization(action: OptimizationAction) -> Optimization {
+        Optimization {
+            category: String::from("Test"),
+            description: String::from("Test"),
+            savings_percent: 0.0,
+            action,
         }
     }
 
-    Ok(())
+    #[test]
+    fn test_optimized_costs() {
+        let usage = ResourceUsage {
+            storage_gb: 20.0,
+            ..ResourceUsage::default()
+        };
+        let before = cost::estimate(&usage);
+
+        let after = cost::estimate(&optimized(
+            &usage,
+            &[optimization(OptimizationAction::EnableIdleShutdown)],
+        ));
+        assert!((after.compute - before.compute * 0.3).abs() < 1e-9);
+        assert_eq!(after.storage, before.storage);
+        assert_eq!(after.egress, before.egress);
+
+        let after = cost::estimate(&optimized(
+            &usage,
+            &[
+                optimization(OptimizationAction::EnableCompression),
+                optimization(OptimizationAction::ArchiveOldData { gb: 100.0 }),
+                optimization(OptimizationAction::OptimizeDiskType),
+            ],
+        ));
+        assert!((after.egress - before.egress * 0.4).abs() < 1e-9);
+        assert_eq!(after.storage, 0.0);
+        assert_eq!(after.compute, before.compute);
+        assert!(after.total < before.total);
+        assert!(cost::savings_percent(after.total, before.total) > 0.0);
+    }
+
+    const CONFIG: &str = "# Secular node\nenable_compression = false\n\n[node]\nport = 8776\n";
+
+    #[test]
+    fn test_dry_run() {
+        let tmp = tempfile::tempdir().unwrap();
+        let config = tmp.path().join("config.toml");
+        fs::write(&config, CONFIG).unwrap();
+
+        let content = read_config(&config).unwrap();
+        let recommendations = analyze_system(&content).unwrap();
+        let changes = ChangeSet::plan(&content, &recommendations, &ResourceUsage::default());
+
+        assert_eq!(
+            changes
+                .changes
+                .iter()
+                .map(|c| (c.key.as_str(), c.old.as_deref(), c.new.as_str()))
+                .collect::<Vec<_>>(),
+            [
+                ("idle_timeout", None, "600"),
+                ("enable_compression", Some("false"), "true"),
+            ]
+        );
+        assert!(changes.changes.iter().all(|c| c.saving > 0.0));
+        assert_eq!(fs::read_to_string(&config).unwrap(), CONFIG);
+        assert!(!manifest_path(&config).exists());
+    }
+
+    #[test]
+    fn test_apply_revert() {
+        let tmp = tempfile::tempdir().unwrap();
+        let config = tmp.path().join("config.toml");
+        fs::write(&config, CONFIG).unwrap();
+
+        let content = read_config(&config).unwrap();
+        let recommendations = analyze_system(&content).unwrap();
+        let changes = ChangeSet::plan(&content, &recommendations, &ResourceUsage::default());
+        changes.apply(&config).unwrap();
+
+        let applied = fs::read_to_string(&config).unwrap();
+        assert_eq!(
+            config_value(&applied, "idle_timeout").as_deref(),
+            Some("600")
+        );
+        assert_eq!(
+            config_value(&applied, "enable_compression").as_deref(),
+            Some("true")
+        );
+        assert!(applied.contains("[node]\nport = 8776\n"));
+        assert!(analyze_system(&applied).unwrap().is_empty());
+
+        let reverted = revert_changes(&config).unwrap();
+        assert_eq!(reverted.changes.len(), 2);
+        assert_eq!(fs::read_to_string(&config).unwrap(), CONFIG);
+        assert!(!manifest_path(&config).exists());
+        assert!(revert_changes(&config).is_err());
+    }
+
+    #[test]
+    fn test_revert_skips_changed_values() {
+        let tmp = tempfile::tempdir().unwrap();
+        let config = tmp.path().join("config.toml");
+        fs::write(&config, CONFIG).unwrap();
+
+        let content = read_config(&config).unwrap();
+        let recommendations = analyze_system(&content).unwrap();
+        ChangeSet::plan(&content, &recommendations, &ResourceUsage::default())
+            .apply(&config)
+            .unwrap();
+
+        // The user tunes the idle timeout after optimizing.
+        let content = read_config(&config).unwrap();
+        fs::write(
+            &config,
+            set_config_value(&content, "idle_timeout", Some("1200")),
+        )
+        .unwrap();
+
+        let reverted = revert_changes(&config).unwrap();
+        let content = read_config(&config).unwrap();
+
+        assert_eq!(reverted.changes.len(), 1);
+        assert_eq!(
+            config_value(&content, "idle_timeout").as_deref(),
+            Some("1200")
+        );
+        assert_eq!(
+            config_value(&content, "enable_compression").as_deref(),
+            Some("false")
+        );
+    }
 }
@@ -3,6 +3,14 @@
 use anyhow::{Context, Result};
 use clap::Subcommand;
 use colored::Colorize;
+use radicle::node::address::Store as _;
+use radicle::node::config::LimitConnectionTimeout;
+use radicle::node::{Address, ConnectOptions, ConnectResult, NodeId};
+use radicle::Profile;
+use radicle_node::LocalDuration;
+use std::time::Duration;
+
+use crate::utils::control::ControlClient;
 
 // Use blue theme throughout
 const PRIMARY_COLOR: &str = "blue";
@@ -10,6 +18,11 @@ const SUCCESS_COLOR: &str = "bright blue";
 const ACCENT_COLOR: &str = "cyan";
 use std::process::Command;
 
+/// Delay before the first connection retry.
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+/// Maximum delay between connection retries.
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
 #[derive(Subcommand)]
 pub enum PeerCommands {
     /// List all peers
@@ -30,6 +43,18 @@ pub enum PeerCommands {
 
         /// Peer's Node ID (did:key:z6Mk... or z6Mk...)
         node_id: String,
+
+        /// Address to connect to (host:port), instead of the peer's known addresses
+        #[arg(long)]
+        address: Option<Address>,
+
+        /// Seconds to wait for each connection attempt
+        #[arg(long, default_value_t = LocalDuration::from(LimitConnectionTimeout::default()).as_secs())]
+        timeout: u64,
+
+        /// Number of times to retry a failed connection
+        #[arg(long, default_value_t = 3)]
+        retries: u32,
     },
 
     /// Peer-specific commands: secular peer <NAME> <action>
@@ -41,7 +66,10 @@ pub async fn run(cmd: PeerCommands) -> Result<()> {
     match cmd {
         PeerCommands::List { detailed } => list_peers(detailed).await,
         PeerCommands::All => list_all_peers().await,
-        PeerCommands::Add { node_id, name } => add_peer(&node_id, &name).await,
+        PeerCommands::Add { node_id, name, address, timeout, retries } => {
+            add_peer(&node_id, &name).await?;
+            connect_peer(&node_id, &name, address, Duration::from_secs(timeout), retries).await
+        }
         PeerCommands::PeerAction(args) => {
             if args.is_empty() {
                 anyhow::bail!("Usage: secular peer <NAME> [status|repos|remove]");
@@ -138,6 +166,91 @@ async fn add_peer(node_id: &str, name: &str) -> Result<()> {
     Ok(())
 }
 
+/// Connect to a newly added peer, retrying failed attempts with exponential backoff.
+async fn connect_peer(
+    node_id: &str,
+    name: &str,
+    address: Option<Address>,
+    timeout: Duration,
+    retries: u32,
+) -> Result<()> {
+    let node_id = node_id.trim();
+    let nid: NodeId = node_id
+        .strip_prefix("did:key:")
+        .unwrap_or(node_id)
+        .parse()
+        .context("Invalid Node ID")?;
+    let profile = Profile::load().context("Failed to load profile")?;
+    let addrs = match address {
+        Some(addr) => vec![addr],
+        None => profile
+            .database()?
+            .addresses_of(&nid)?
+            .into_iter()
+            .map(|ka| ka.addr)
+            .collect(),
+    };
+
+    if addrs.is_empty() {
+        println!("\n{}", "No known addresses for this peer, skipping connection".dimmed());
+        println!("  {}", format!("secular peer add --name {} --address <host:port> {}", name, node_id).cyan());
+        return Ok(());
+    }
+
+    let node = ControlClient::new(profile.socket());
+    let mut last_error = String::new();
+
+    println!("\n{}", format!("Connecting to '{}'...", name).blue());
+
+    for attempt in 0..=retries {
+        if attempt > 0 {
+            let delay = backoff(attempt - 1);
+            println!(
+                "  {} Retrying in {}s (attempt {}/{})",
+                "→".blue(),
+                delay.as_secs(),
+                attempt + 1,
+                retries + 1
+            );
+            tokio::time::sleep(delay).await;
+        }
+        for addr in &addrs {
+            let opts = ConnectOptions { persistent: true, timeout };
+
+            match node.connect(nid, addr.clone(), opts).await {
+                Ok(ConnectResult::Connected) => {
+                    println!("  {}", format!("✓ Connected to {}", addr).bright_blue().bold());
+                    return Ok(());
+                }
+                Ok(ConnectResult::Disconnected { reason }) => {
+                    last_error = format!("{}: {}", addr, reason);
+                }
+                Err(e) if e.is_connection_err() => {
+                    anyhow::bail!("Failed to connect to peer: node is not running (start it with `secular node start`)");
+                }
+                Err(e) => {
+                    last_error = format!("{}: {}", addr, e);
+                }
+            }
+            println!("  {} {}", "✗".yellow(), last_error.dimmed());
+        }
+    }
+
+    anyhow::bail!(
+        "Peer '{}' was added, but connecting failed after {} attempt(s). Last error: {}",
+        name,
+        retries + 1,
+        last_error
+    )
+}
+
+/// Delay before the given retry, starting at zero, doubling up to [`MAX_BACKOFF`].
+fn backoff(retry: u32) -> Duration {
+    INITIAL_BACKOFF
+        .checked_mul(2u32.saturating_pow(retry))
+        .map_or(MAX_BACKOFF, |delay| delay.min(MAX_BACKOFF))
+}
+
 async fn remove_peer(name: &str) -> Result<()> {
     println!("{}", format!("Removing peer '{}'...", name).blue());
 
@@ -589,3 +702,16 @@ async fn list_all_peers() -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff() {
+        let delays = (0..8).map(|retry| backoff(retry).as_secs()).collect::<Vec<_>>();
+
+        assert_eq!(delays, [1, 2, 4, 8, 16, 30, 30, 30]);
+        assert_eq!(backoff(u32::MAX), MAX_BACKOFF);
+    }
+}
@@ -3,9 +3,17 @@
 use anyhow::{Context, Result};
 use clap::Subcommand;
 use colored::Colorize;
+use radicle::node::sync::{Announcer, AnnouncerConfig, AnnouncerError, AnnouncerResult, ReplicationFactor};
+use radicle::node::{Handle, Node, NodeId};
+use radicle::Profile;
+use std::collections::BTreeSet;
 use std::process::Command;
+use std::time::Duration;
 use walkdir::WalkDir;
 
+/// Time to wait for seeds to sync when announcing with a replication target.
+const SYNC_TIMEOUT: Duration = Duration::from_secs(9);
+
 #[derive(Subcommand)]
 pub enum RepoCommands {
     /// Push changes to a friend
@@ -59,6 +67,18 @@ pub enum RepoCommands {
         /// Fetch from network
         #[arg(short, long)]
         fetch: bool,
+
+        /// Number of seeds to sync with (implies --announce)
+        #[arg(long, value_name = "N", value_parser = parse_replicas, conflicts_with = "replicas_range")]
+        replicas: Option<ReplicationFactor>,
+
+        /// Range of seeds to sync with, e.g. 2..5 (implies --announce)
+        #[arg(long, value_name = "MIN..MAX", value_parser = parse_replicas_range)]
+        replicas_range: Option<ReplicationFactor>,
+
+        /// Preferred seed to sync with, may be repeated (implies --announce)
+        #[arg(long = "seed", value_name = "NID")]
+        seeds: Vec<NodeId>,
     },
 
     /// Initialize a Radicle repository
@@ -132,7 +152,17 @@ pub async fn run(cmd: RepoCommands) -> Result<()> {
     match cmd {
         RepoCommands::Push { friend, repo, branch, path } => push_to_friend(&friend, repo, branch, path).await,
         RepoCommands::Pull { friend, repo, branch, path } => pull_from_friend(&friend, repo, branch, path).await,
-        RepoCommands::Sync { path, announce, fetch } => sync_repo(path, announce, fetch).await,
+        RepoCommands::Sync { path, announce, fetch, replicas, replicas_range, seeds } => {
+            match replicas.or(replicas_range) {
+                None if seeds.is_empty() => sync_repo(path, announce, fetch).await,
+                replicas => {
+                    if fetch {
+                        sync_repo(path.clone(), false, true).await?;
+                    }
+                    announce_repo(path, replicas.unwrap_or_default(), seeds.into_iter().collect())
+                }
+            }
+        }
         RepoCommands::Init { name, description, private, path } => {
             init_repo(&name, description, private, path).await
         }
@@ -277,6 +307,105 @@ async fn sync_repo(path: Option<String>, announce: bool, fetch: bool) -> Result<
     Ok(())
 }
 
+/// Announce the repository to the network through the node, until the
+/// replication target is reached or the sync times out.
+fn announce_repo(path: Option<String>, replicas: ReplicationFactor, seeds: BTreeSet<NodeId>) -> Result<()> {
+    println!("{}", "Announcing to Radicle network...".blue());
+
+    let profile = Profile::load().context("Failed to load profile")?;
+    let (_, rid) = match &path {
+        Some(p) => radicle::rad::at(p)?,
+        None => radicle::rad::cwd()?,
+    };
+    let me = *profile.id();
+    let mut node = Node::new(profile.socket());
+
+    let (synced, unsynced) = node
+        .seeds_for(rid, [me])
+        .context("Failed to query seeds, is the node running?")?
+        .iter()
+        .fold((BTreeSet::new(), BTreeSet::new()), |(mut synced, mut unsynced), seed| {
+            if seed.is_synced() {
+                synced.insert(seed.nid);
+            } else {
+                unsynced.insert(seed.nid);
+            }
+            (synced, unsynced)
+        });
+
+    match replicas.upper_bound() {
+        Some(max) => println!("  Replicas: {}", format!("{}..{}", replicas.lower_bound(), max).dimmed()),
+        None => println!("  Replicas: {}", replicas.lower_bound().to_string().dimmed()),
+    }
+    if !seeds.is_empty() {
+        println!("  Preferred seeds: {}", seeds.len().to_string().dimmed());
+    }
+
+    let config = AnnouncerConfig::public(me, replicas, seeds, synced, unsynced);
+    let announcer = match Announcer::new(config) {
+        Ok(announcer) => announcer,
+        Err(AnnouncerError::AlreadySynced(result)) => {
+            println!("{}", format!("✓ Already in sync with {} seed(s)", result.synced()).bright_blue());
+            return Ok(());
+        }
+        Err(e) => anyhow::bail!("Sync failed: {}", e),
+    };
+    let result = node.announce(rid, [me], SYNC_TIMEOUT, announcer, |nid, progress| {
+        println!("  {} Synced with {} ({} seed(s))", "✓".bright_blue(), nid, progress.synced());
+    })?;
+
+    report_announcement(&result);
+
+    Ok(())
+}
+
+fn report_announcement(result: &AnnouncerResult) {
+    let synced = result.synced().len();
+
+    match result {
+        AnnouncerResult::Success(_) => {
+            println!("{}", format!("✓ Sync complete! Synced with {} seed(s)", synced).bright_blue());
+        }
+        AnnouncerResult::TimedOut(timed_out) => {
+            println!(
+                "  {} Timed out after syncing with {} seed(s), {} seed(s) did not respond",
+                "⚠".yellow(),
+                synced,
+                timed_out.timed_out().len()
+            );
+        }
+        AnnouncerResult::NoNodes(_) => {
+            println!("  {} Ran out of seeds after syncing with {} seed(s)", "⚠".yellow(), synced);
+        }
+    }
+    for (nid, reason) in result.failed() {
+        println!("  {} {}: {}", "✗".red(), nid, reason);
+    }
+}
+
+/// Parse a `--replicas` value.
+fn parse_replicas(s: &str) -> Result<ReplicationFactor, String> {
+    match s.parse::<usize>() {
+        Ok(0) => Err("replicas must be at least 1".to_owned()),
+        Ok(n) => Ok(ReplicationFactor::must_reach(n)),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Parse a `--replicas-range` value of the form `MIN..MAX`.
+fn parse_replicas_range(s: &str) -> Result<ReplicationFactor, String> {
+    let (min, max) = s
+        .split_once("..")
+        .ok_or_else(|| format!("expected MIN..MAX, got '{}'", s))?;
+    let min = parse_replicas(min)?.lower_bound();
+    let max = parse_replicas(max)?.lower_bound();
+
+    if min > max {
+        return Err(format!("minimum {} is greater than maximum {}", min, max));
+    }
+    Ok(ReplicationFactor::range(min, max))
+}
+
 async fn init_repo(name: &str, description: Option<String>, private: bool, path: Option<String>) -> Result<()> {
     println!("{}", format!("Initializing repository '{}'...", name).blue());
 
@@ -696,3 +825,62 @@ fn get_current_branch() -> Result<String> {
         anyhow::bail!("Not in a git repository")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[derive(Parser)]
+    struct Cli {
+        #[command(subcommand)]
+        command: RepoCommands,
+    }
+
+    fn sync(args: &[&str]) -> Result<(Option<ReplicationFactor>, Vec<NodeId>), clap::Error> {
+        let cli = Cli::try_parse_from(["secular", "sync"].iter().chain(args))?;
+        match cli.command {
+            RepoCommands::Sync { replicas, replicas_range, seeds, .. } => Ok((replicas.or(replicas_range), seeds)),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_replicas() {
+        assert_eq!(sync(&[]).unwrap(), (None, vec![]));
+        assert_eq!(sync(&["--replicas", "5"]).unwrap().0, Some(ReplicationFactor::must_reach(5)));
+        assert!(sync(&["--replicas", "0"]).is_err());
+        assert!(sync(&["--replicas", "many"]).is_err());
+    }
+
+    #[test]
+    fn test_replicas_range() {
+        let (replicas, _) = sync(&["--replicas-range", "2..5"]).unwrap();
+        let replicas = replicas.unwrap();
+
+        assert_eq!(replicas, ReplicationFactor::range(2, 5));
+        assert_eq!(replicas.lower_bound(), 2);
+        assert_eq!(replicas.upper_bound(), Some(5));
+
+        // A range with equal bounds must reach exactly that many seeds.
+        assert_eq!(
+            sync(&["--replicas-range", "3..3"]).unwrap().0,
+            Some(ReplicationFactor::must_reach(3))
+        );
+        assert!(sync(&["--replicas-range", "5..2"]).is_err());
+        assert!(sync(&["--replicas-range", "5"]).is_err());
+        assert!(sync(&["--replicas-range", "0..2"]).is_err());
+        assert!(sync(&["--replicas", "2", "--replicas-range", "2..5"]).is_err());
+    }
+
+    #[test]
+    fn test_seeds() {
+        let alice = "z6MknSLrJoTcukLrE435hVNQT4JUhbvWLX4kUzqkEStBU8Vi";
+        let bob = "z6MksFqXN3Yhqk8pTJdUGLwATkRfQvwZXPqR2qMEhbS9wzpT";
+        let (replicas, seeds) = sync(&["--seed", alice, "--seed", bob]).unwrap();
+
+        assert_eq!(replicas, None);
+        assert_eq!(seeds, vec![alice.parse().unwrap(), bob.parse::<NodeId>().unwrap()]);
+        assert!(sync(&["--seed", "alice"]).is_err());
+    }
+}
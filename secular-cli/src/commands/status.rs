@@ -2,9 +2,13 @@
 
 use anyhow::Result;
 use colored::Colorize;
+use radicle::node::NodeId;
+use radicle::Profile;
+
+use crate::utils::control::ControlClient;
 
 pub async fn run(detailed: bool, format: &str) -> Result<()> {
-    let status = collect_status()?;
+    let status = collect(detailed).await?;
 
     match format {
         "json" => print_json(&status),
@@ -16,50 +20,64 @@ pub async fn run(detailed: bool, format: &str) -> Result<()> {
 }
 
 #[derive(Debug, serde::Serialize)]
-struct SystemStatus {
-    node: NodeStatus,
-    deployment: DeploymentStatus,
-    security: SecurityStatus,
-    costs: CostStatus,
+pub struct Status {
+    /// Whether the node is running and responding on its control socket.
+    pub node_running: bool,
+    /// Node status, if it is running.
+    pub node: Option<NodeStatus>,
+    pub deployment: DeploymentStatus,
+    pub security: SecurityStatus,
+    pub costs: CostStatus,
 }
 
 #[derive(Debug, serde::Serialize)]
-struct NodeStatus {
-    running: bool,
-    uptime_hours: f64,
-    peers: usize,
-    repos: usize,
+pub struct NodeStatus {
+    pub uptime_hours: f64,
+    /// Number of connected peers.
+    pub peers: usize,
+    /// Number of seeded repositories.
+    pub repos: usize,
+    /// Connected peers, in detailed status only.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub connected: Option<Vec<NodeId>>,
 }
 
 #[derive(Debug, serde::Serialize)]
-struct DeploymentStatus {
-    platform: String,
-    region: Option<String>,
-    instance_type: Option<String>,
+pub struct DeploymentStatus {
+    pub platform: String,
+    pub region: Option<String>,
+    pub instance_type: Option<String>,
 }
 
 #[derive(Debug, serde::Serialize)]
-struct SecurityStatus {
-    secret_scanning: bool,
-    vulnerability_scanning: bool,
-    last_scan: Option<String>,
+pub struct SecurityStatus {
+    pub secret_scanning: bool,
+    pub vulnerability_scanning: bool,
+    pub last_scan: Option<String>,
 }
 
 #[derive(Debug, serde::Serialize)]
-struct CostStatus {
-    current_month: f64,
-    projected: f64,
-    optimization_level: String,
-}
-
-fn collect_status() -> Result<SystemStatus> {
-    Ok(SystemStatus {
-        node: NodeStatus {
-            running: is_node_running()?,
-            uptime_hours: get_uptime()?,
-            peers: 0,
-            repos: 0,
-        },
+pub struct CostStatus {
+    pub current_month: f64,
+    pub projected: f64,
+    pub optimization_level: String,
+}
+
+/// Collect the system status, querying the node over its control socket.
+///
+/// A node that isn't running is reported with `node_running: false`.
+pub async fn collect(detailed: bool) -> Result<Status> {
+    let profile = Profile::load().ok();
+    let socket = match &profile {
+        Some(profile) => profile.socket(),
+        None => radicle::profile::home()?.socket(),
+    };
+    let repos = profile.as_ref().map(seeded_repos).unwrap_or(0);
+    let node = node_status(&ControlClient::new(socket), repos, detailed).await?;
+
+    Ok(Status {
+        node_running: node.is_some(),
+        node,
         deployment: DeploymentStatus {
             platform: detect_platform(),
             region: detect_region(),
@@ -78,7 +96,47 @@ fn collect_status() -> Result<SystemStatus> {
     })
 }
 
-fn print_text(status: &SystemStatus, detailed: bool) {
+/// Query the node for its status, returning `None` if it isn't running.
+async fn node_status(
+    node: &ControlClient,
+    repos: usize,
+    detailed: bool,
+) -> Result<Option<NodeStatus>> {
+    if !node.is_running().await {
+        return Ok(None);
+    }
+    let connected = node
+        .sessions()
+        .await?
+        .into_iter()
+        .filter(|s| s.is_connected())
+        .map(|s| s.nid)
+        .collect::<Vec<_>>();
+    let uptime = node.uptime().await?;
+
+    Ok(Some(NodeStatus {
+        uptime_hours: uptime.as_secs_f64() / 3600.0,
+        peers: connected.len(),
+        repos,
+        connected: detailed.then_some(connected),
+    }))
+}
+
+/// Number of repositories the profile's seeding policy allows.
+fn seeded_repos(profile: &Profile) -> usize {
+    let Ok(policies) = profile.policies() else {
+        return 0;
+    };
+    let Ok(seeds) = policies.seed_policies() else {
+        return 0;
+    };
+    seeds
+        .filter_map(Result::ok)
+        .filter(|seed| seed.policy.is_allow())
+        .count()
+}
+
+fn print_text(status: &Status, detailed: bool) {
     println!("{}", "━".repeat(60).blue());
     println!("{}", "  Secular System Status".blue().bold());
     println!("{}", "━".repeat(60).blue());
@@ -86,15 +144,18 @@ fn print_text(status: &SystemStatus, detailed: bool) {
 
     // Node status
     println!("{}", "Node:".cyan().bold());
-    let status_str = if status.node.running {
-        "Running".green()
+    if let Some(node) = &status.node {
+        println!("  Status:   {}", "Running".green());
+        println!("  Uptime:   {:.1} hours", node.uptime_hours);
+        println!("  Peers:    {}", node.peers);
+        println!("  Repos:    {}", node.repos);
+
+        for nid in node.connected.iter().flatten() {
+            println!("    {}", nid.to_string().dimmed());
+        }
     } else {
-        "Stopped".red()
-    };
-    println!("  Status:   {}", status_str);
-    println!("  Uptime:   {:.1} hours", status.node.uptime_hours);
-    println!("  Peers:    {}", status.node.peers);
-    println!("  Repos:    {}", status.node.repos);
+        println!("  Status:   {}", "Stopped".red());
+    }
     println!();
 
     // Deployment
@@ -143,13 +204,12 @@ fn print_text(status: &SystemStatus, detailed: bool) {
     }
 }
 
-fn print_json(status: &SystemStatus) {
+fn print_json(status: &Status) {
     println!("{}", serde_json::to_string_pretty(status).unwrap());
 }
 
-fn print_yaml(status: &SystemStatus) {
-    // Would use serde_yaml, but keeping it simple for now
-    println!("{:#?}", status);
+fn print_yaml(status: &Status) {
+    print!("{}", serde_yaml::to_string(status).unwrap());
 }
 
 fn print_detailed_status() {
@@ -160,36 +220,6 @@ fn print_detailed_status() {
     println!();
 }
 
-fn is_node_running() -> Result<bool> {
-    use std::process::Command;
-
-    let output = Command::new("pgrep")
-        .args(&["-f", "radicle-node"])
-        .output()?;
-
-    Ok(output.status.success())
-}
-
-fn get_uptime() -> Result<f64> {
-    #[cfg(target_os = "linux")]
-    {
-        use std::fs;
-
-        let uptime_str = fs::read_to_string("/proc/uptime")?;
-        let uptime_seconds: f64 = uptime_str
-            .split_whitespace()
-            .next()
-            .and_then(|s| s.parse().ok())
-            .unwrap_or(0.0);
-        Ok(uptime_seconds / 3600.0)
-    }
-
-    #[cfg(not(target_os = "linux"))]
-    {
-        Ok(0.0)
-    }
-}
-
 fn detect_platform() -> String {
     if std::path::Path::new("/var/run/google.instance").exists() {
         "Google Cloud Platform".to_string()
@@ -209,3 +239,77 @@ fn detect_instance_type() -> Option<String> {
     // Would query metadata service
     None
 }
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::UnixListener;
+
+    const CONNECTED: &str = "z6MksmpU5b1dS7oaqF2bHXhQi1DWy2hB7Mh9CuN7y1DN6QSz";
+    const DISCONNECTED: &str = "z6MknSLrJoTcukLrE435hVNQT4JUhbvWLX4kUzqkEStBU8Vi";
+
+    /// Serve canned responses to node commands on a control socket.
+    async fn mock_node(listener: UnixListener) {
+        loop {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut line = String::new();
+            BufReader::new(&mut stream)
+                .read_line(&mut line)
+                .await
+                .unwrap();
+
+            let command: serde_json::Value = serde_json::from_str(&line).unwrap();
+            let response = match command["command"].as_str().unwrap() {
+                "status" => serde_json::json!({}),
+                "sessions" => serde_json::json!([
+                    {
+                        "nid": CONNECTED,
+                        "link": "outbound",
+                        "addr": "seed.example.com:8776",
+                        "state": { "connected": { "since": 1699983994, "fetching": [] } }
+                    },
+                    {
+                        "nid": DISCONNECTED,
+                        "link": "inbound",
+                        "addr": "127.0.0.1:8776",
+                        "state": { "disconnected": { "since": 1699983994, "retryAt": 1699984994 } }
+                    }
+                ]),
+                "uptime" => serde_json::json!({ "secs": 5400, "nanos": 0 }),
+                other => serde_json::json!({ "error": format!("unexpected command {other}") }),
+            };
+            let line = format!("{response}\n");
+            stream.write_all(line.as_bytes()).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_node_status() {
+        let tmp = tempfile::tempdir().unwrap();
+        let socket = tmp.path().join("control.sock");
+        let listener = UnixListener::bind(&socket).unwrap();
+        tokio::spawn(mock_node(listener));
+
+        let node = ControlClient::new(&socket);
+        let status = node_status(&node, 3, false).await.unwrap().unwrap();
+
+        assert_eq!(status.uptime_hours, 1.5);
+        assert_eq!(status.peers, 1);
+        assert_eq!(status.repos, 3);
+        assert!(status.connected.is_none());
+
+        let status = node_status(&node, 3, true).await.unwrap().unwrap();
+        let connected = status.connected.unwrap();
+        assert_eq!(connected.len(), 1);
+        assert_eq!(connected[0].to_string(), CONNECTED);
+    }
+
+    #[tokio::test]
+    async fn test_node_not_running() {
+        let tmp = tempfile::tempdir().unwrap();
+        let node = ControlClient::new(tmp.path().join("control.sock"));
+
+        assert!(node_status(&node, 0, true).await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,8 @@
+//! Secular library
+//!
+//! The commands of the `secular` CLI, along with the functions they use to
+//! collect node status and metrics, so that other front-ends, eg. the desktop
+//! GUI, report the same numbers.
+
+pub mod commands;
+pub mod utils;
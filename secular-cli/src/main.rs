@@ -7,10 +7,7 @@ use clap::{Parser, Subcommand};
 use colored::Colorize;
 use std::process;
 
-mod commands;
-mod utils;
-
-use commands::*;
+use secular::commands::*;
 
 #[derive(Parser)]
 #[command(
@@ -92,6 +89,10 @@ enum Commands {
         /// List available repositories
         #[arg(short, long)]
         list: bool,
+
+        /// Show what would be converted without making changes
+        #[arg(long)]
+        dry_run: bool,
     },
 
     /// Optimize configuration for cost savings
@@ -99,6 +100,10 @@ enum Commands {
         /// Run optimization analysis only (don't apply)
         #[arg(long)]
         dry_run: bool,
+
+        /// Revert the configuration changes of previous optimizations
+        #[arg(long, conflicts_with = "dry_run")]
+        revert: bool,
     },
 
     /// Show status of deployment and node
@@ -154,8 +159,13 @@ async fn main() {
         Commands::Peer(cmd) => peer::run(cmd).await,
         Commands::Repos(cmd) => repo::run(cmd).await,
         Commands::Backup(args) => backup::run(args).await,
-        Commands::Convert { path, public, list } => convert::run(path, !public, list).await,
-        Commands::Optimize { dry_run } => optimize::run(dry_run).await,
+        Commands::Convert {
+            path,
+            public,
+            list,
+            dry_run,
+        } => convert::run(path, !public, list, dry_run).await,
+        Commands::Optimize { dry_run, revert } => optimize::run(dry_run, revert).await,
         Commands::Status { detailed, format } => status::run(detailed, &format).await,
         Commands::Completions { shell } => {
             completions::generate(shell);
@@ -0,0 +1,238 @@
+//! Async client for the node's control socket
+//!
+//! Speaks the same protocol as [`radicle::node::Node`], without blocking the
+//! tokio runtime: every command is written as a line of JSON on a new
+//! connection, and answered with one or more lines of JSON, each a
+//! [`CommandResult`].
+
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use radicle::node::{
+    Address, Command, CommandResult, ConnectOptions, ConnectResult, NodeId, Session, Success,
+    DEFAULT_TIMEOUT,
+};
+use serde::de::DeserializeOwned;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::UnixStream;
+
+/// Control client errors.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("failed to connect to node control socket {path}: {source}")]
+    Connect { path: PathBuf, source: io::Error },
+    #[error("control socket i/o error: {0}")]
+    Io(#[from] io::Error),
+    #[error("timed out waiting for the node to respond")]
+    TimedOut,
+    #[error("invalid response from node `{response}`: {source}")]
+    InvalidJson {
+        response: String,
+        source: serde_json::Error,
+    },
+    #[error("node error: {reason}")]
+    Command { reason: String },
+    #[error("empty response from node")]
+    EmptyResponse,
+}
+
+impl Error {
+    /// Whether the node couldn't be reached, eg. because it isn't running.
+    pub fn is_connection_err(&self) -> bool {
+        matches!(self, Self::Connect { .. })
+    }
+}
+
+/// Async client for the node's control socket.
+#[derive(Debug, Clone)]
+pub struct ControlClient {
+    socket: PathBuf,
+    timeout: Duration,
+}
+
+/// Responses to a command sent with [`ControlClient::send`].
+pub struct Responses {
+    stream: BufReader<UnixStream>,
+    timeout: Duration,
+}
+
+impl ControlClient {
+    /// Client for the control socket at `socket`.
+    pub fn new(socket: impl AsRef<Path>) -> Self {
+        Self {
+            socket: socket.as_ref().to_path_buf(),
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+
+    /// Set how long to wait for each response.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Send a command, returning the responses to it.
+    pub async fn send(&self, command: &Command) -> Result<Responses, Error> {
+        let mut stream =
+            UnixStream::connect(&self.socket)
+                .await
+                .map_err(|source| Error::Connect {
+                    path: self.socket.clone(),
+                    source,
+                })?;
+        let mut line = Vec::new();
+        command.to_writer(&mut line)?;
+        stream.write_all(&line).await?;
+
+        Ok(Responses {
+            stream: BufReader::new(stream),
+            timeout: self.timeout,
+        })
+    }
+
+    /// Send a command, and receive its first response.
+    pub async fn call<T: DeserializeOwned>(&self, command: &Command) -> Result<T, Error> {
+        self.send(command)
+            .await?
+            .recv()
+            .await?
+            .ok_or(Error::EmptyResponse)
+    }
+
+    /// Whether the node is running, and responds to commands.
+    pub async fn is_running(&self) -> bool {
+        self.call::<Success>(&Command::Status).await.is_ok()
+    }
+
+    /// The node's peer sessions.
+    pub async fn sessions(&self) -> Result<Vec<Session>, Error> {
+        self.call(&Command::Sessions).await
+    }
+
+    /// Time elapsed since the node started.
+    pub async fn uptime(&self) -> Result<Duration, Error> {
+        self.call(&Command::Uptime).await
+    }
+
+    /// Set the node's log level.
+    pub async fn set_log_level(&self, level: log::Level) -> Result<(), Error> {
+        let level = level.into();
+        self.call::<Success>(&Command::SetLogLevel { level })
+            .await
+            .map(drop)
+    }
+
+    /// Connect to a peer, waiting up to the connection timeout in `opts`.
+    pub async fn connect(
+        &self,
+        nid: NodeId,
+        addr: Address,
+        opts: ConnectOptions,
+    ) -> Result<ConnectResult, Error> {
+        let client = self.clone().with_timeout(opts.timeout);
+        let command = Command::Connect {
+            addr: (nid, addr).into(),
+            opts,
+        };
+        client.call(&command).await
+    }
+}
+
+impl Responses {
+    /// Receive the next response, or `None` once the node closed the
+    /// connection.
+    pub async fn recv<T: DeserializeOwned>(&mut self) -> Result<Option<T>, Error> {
+        let mut line = String::new();
+        let read = tokio::time::timeout(self.timeout, self.stream.read_line(&mut line))
+            .await
+            .map_err(|_| Error::TimedOut)??;
+        if read == 0 {
+            return Ok(None);
+        }
+
+        match serde_json::from_str(&line) {
+            Ok(CommandResult::Okay(result)) => Ok(Some(result)),
+            Ok(CommandResult::Error { reason }) => Err(Error::Command { reason }),
+            Err(source) => Err(Error::InvalidJson {
+                response: line.trim_end().to_owned(),
+                source,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tokio::net::UnixListener;
+
+    /// Answer commands on a control socket with known responses.
+    async fn mock_node(listener: UnixListener) {
+        loop {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut stream = BufReader::new(stream);
+            let mut line = String::new();
+            stream.read_line(&mut line).await.unwrap();
+
+            let command: serde_json::Value = serde_json::from_str(&line).unwrap();
+            let name = command["command"].as_str().unwrap().to_owned();
+            let responses = match name.as_str() {
+                "status" => vec![json!({})],
+                "uptime" => vec![json!({ "secs": 5400, "nanos": 0 })],
+                "setLogLevel" => vec![json!({})],
+                // Echo the command back, twice.
+                "announceInventory" => vec![command.clone(), command],
+                other => vec![json!({ "error": format!("unexpected command {other}") })],
+            };
+            for response in responses {
+                let line = format!("{response}\n");
+                stream.get_mut().write_all(line.as_bytes()).await.unwrap();
+            }
+        }
+    }
+
+    fn client() -> (tempfile::TempDir, ControlClient) {
+        let tmp = tempfile::tempdir().unwrap();
+        let socket = tmp.path().join("control.sock");
+        let listener = UnixListener::bind(&socket).unwrap();
+        tokio::spawn(mock_node(listener));
+
+        (tmp, ControlClient::new(socket))
+    }
+
+    #[tokio::test]
+    async fn test_call() {
+        let (_tmp, client) = client();
+
+        assert!(client.is_running().await);
+        assert_eq!(client.uptime().await.unwrap(), Duration::from_secs(5400));
+        client.set_log_level(log::Level::Debug).await.unwrap();
+        assert!(matches!(
+            client.sessions().await,
+            Err(Error::Command { reason }) if reason == "unexpected command sessions"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_send_recv() {
+        let (_tmp, client) = client();
+        let mut responses = client.send(&Command::AnnounceInventory).await.unwrap();
+
+        for _ in 0..2 {
+            let echo: serde_json::Value = responses.recv().await.unwrap().unwrap();
+            assert_eq!(echo, json!({ "command": "announceInventory" }));
+        }
+        assert!(responses.recv::<Success>().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_not_running() {
+        let tmp = tempfile::tempdir().unwrap();
+        let client = ControlClient::new(tmp.path().join("control.sock"));
+
+        assert!(!client.is_running().await);
+        assert!(client.uptime().await.unwrap_err().is_connection_err());
+    }
+}
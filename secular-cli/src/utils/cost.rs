@@ -0,0 +1,171 @@
+//! Cost estimation, shared by `monitor`, `optimize` and `deploy`
+//!
+//! Prices are those of GCP in us-central1.
+
+use serde::Serialize;
+
+/// Hourly prices of GCP machine types.
+const MACHINE_HOURLY: &[(&str, f64)] = &[
+    ("e2-micro", 0.00508),
+    ("e2-small", 0.01675),
+    ("e2-medium", 0.03351),
+    ("e2-standard-2", 0.06701),
+    ("e2-standard-4", 0.13402),
+];
+/// Monthly price of standard persistent disk (HDD), per GB.
+pub const HDD_STORAGE_PER_GB: f64 = 0.04;
+/// Price of egress traffic, per GB.
+pub const EGRESS_PER_GB: f64 = 0.12;
+/// Monthly price of a reserved static IP.
+pub const STATIC_IP_MONTHLY: f64 = 2.88;
+/// Hours in a month.
+pub const MONTHLY_HOURS: f64 = 24.0 * 30.0;
+/// Conservative estimate of monthly egress, in GB, until traffic is measured.
+pub const DEFAULT_EGRESS_GB: f64 = 10.0;
+/// Monthly cost of an unoptimized deployment, ie. an always-on e2-medium with
+/// SSD storage, which savings are measured against.
+pub const BASELINE_MONTHLY: f64 = 30.0;
+
+/// Hourly price of a GCP machine type, if known.
+pub fn machine_hourly(machine_type: &str) -> Option<f64> {
+    MACHINE_HOURLY
+        .iter()
+        .find(|(name, _)| *name == machine_type)
+        .map(|(_, hourly)| *hourly)
+}
+
+/// Monthly resource usage of a deployment.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ResourceUsage {
+    /// Hourly price of the machine.
+    pub machine_hourly: f64,
+    /// Hours the machine runs, per month.
+    pub compute_hours: f64,
+    /// Disk space, in GB.
+    pub storage_gb: f64,
+    /// Egress traffic per month, in GB.
+    pub egress_gb: f64,
+    /// Whether a static IP is reserved.
+    pub static_ip: bool,
+}
+
+impl Default for ResourceUsage {
+    /// An always-on e2-micro, without storage, with the default egress estimate.
+    fn default() -> Self {
+        Self {
+            machine_hourly: machine_hourly("e2-micro").unwrap_or_default(),
+            compute_hours: MONTHLY_HOURS,
+            storage_gb: 0.0,
+            egress_gb: DEFAULT_EGRESS_GB,
+            static_ip: true,
+        }
+    }
+}
+
+/// Estimated monthly costs, in USD.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CostBreakdown {
+    pub compute: f64,
+    pub storage: f64,
+    pub egress: f64,
+    pub static_ip: f64,
+    pub total: f64,
+    /// Percentage saved compared to [`BASELINE_MONTHLY`].
+    pub savings_percent: f64,
+}
+
+/// Estimate the monthly costs of the given resource usage.
+pub fn estimate(usage: &ResourceUsage) -> CostBreakdown {
+    let compute = usage.compute_hours * usage.machine_hourly;
+    let storage = usage.storage_gb * HDD_STORAGE_PER_GB;
+    let egress = usage.egress_gb * EGRESS_PER_GB;
+    let static_ip = if usage.static_ip {
+        STATIC_IP_MONTHLY
+    } else {
+        0.0
+    };
+    let total = compute + storage + egress + static_ip;
+
+    CostBreakdown {
+        compute,
+        storage,
+        egress,
+        static_ip,
+        total,
+        savings_percent: savings_percent(total, BASELINE_MONTHLY),
+    }
+}
+
+/// Percentage saved by a monthly cost of `total` compared to `baseline`, or
+/// zero if it isn't cheaper.
+pub fn savings_percent(total: f64, baseline: f64) -> f64 {
+    if baseline <= 0.0 {
+        return 0.0;
+    }
+    ((1.0 - total / baseline) * 100.0).max(0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate() {
+        let usage = ResourceUsage {
+            machine_hourly: 0.5,
+            compute_hours: 10.0,
+            storage_gb: 50.0,
+            egress_gb: 10.0,
+            static_ip: true,
+        };
+        let costs = estimate(&usage);
+
+        assert_eq!(costs.compute, 5.0);
+        assert_eq!(costs.storage, 50.0 * HDD_STORAGE_PER_GB);
+        assert_eq!(costs.egress, 10.0 * EGRESS_PER_GB);
+        assert_eq!(costs.static_ip, STATIC_IP_MONTHLY);
+        assert_eq!(
+            costs.total,
+            costs.compute + costs.storage + costs.egress + costs.static_ip
+        );
+
+        let doubled = estimate(&ResourceUsage {
+            egress_gb: 20.0,
+            ..usage.clone()
+        });
+        assert_eq!(doubled.egress, costs.egress * 2.0);
+        assert_eq!(doubled.compute, costs.compute);
+
+        let without_ip = estimate(&ResourceUsage {
+            static_ip: false,
+            ..usage
+        });
+        assert_eq!(without_ip.static_ip, 0.0);
+        assert_eq!(
+            without_ip.total,
+            costs.compute + costs.storage + costs.egress
+        );
+    }
+
+    #[test]
+    fn test_savings_percent() {
+        assert_eq!(savings_percent(30.0, 30.0), 0.0);
+        assert_eq!(savings_percent(7.5, 30.0), 75.0);
+        assert_eq!(savings_percent(0.0, 30.0), 100.0);
+        assert_eq!(savings_percent(45.0, 30.0), 0.0);
+        assert_eq!(savings_percent(10.0, 0.0), 0.0);
+
+        let costs = estimate(&ResourceUsage::default());
+        assert_eq!(
+            costs.savings_percent,
+            savings_percent(costs.total, BASELINE_MONTHLY)
+        );
+        assert!(costs.savings_percent > 0.0);
+    }
+
+    #[test]
+    fn test_machine_hourly() {
+        assert_eq!(machine_hourly("e2-micro"), Some(0.00508));
+        assert_eq!(machine_hourly("n2-custom"), None);
+    }
+}
@@ -1,4 +1,6 @@
 //! Utility functions
 
 pub mod colors;
+pub mod control;
+pub mod cost;
 pub mod platform;
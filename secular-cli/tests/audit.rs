@@ -0,0 +1,95 @@
+use std::path::Path;
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+/// Write an advisory database with a single high severity advisory for `example`,
+/// and a lockfile depending on the affected version.
+fn fixture(dir: &Path) {
+    let advisories = dir.join("advisory-db").join("crates").join("example");
+    std::fs::create_dir_all(&advisories).unwrap();
+    std::fs::write(
+        advisories.join("RUSTSEC-2024-0001.md"),
+        "```toml\n\
+         [advisory]\n\
+         id = \"RUSTSEC-2024-0001\"\n\
+         package = \"example\"\n\
+         severity = \"high\"\n\
+         \n\
+         [versions]\n\
+         patched = [\">= 1.2.0\"]\n\
+         ```\n\
+         \n\
+         # Memory corruption in example\n",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("Cargo.lock"),
+        "[[package]]\nname = \"example\"\nversion = \"1.1.0\"\n",
+    )
+    .unwrap();
+}
+
+fn audit(dir: &Path) -> Command {
+    let mut cmd = Command::cargo_bin("secular").unwrap();
+    cmd.current_dir(dir)
+        .arg("audit")
+        .arg("--database")
+        .arg(dir.join("advisory-db"));
+    cmd
+}
+
+#[test]
+fn test_audit_fail_on_high() {
+    let tmp = tempfile::tempdir().unwrap();
+    fixture(tmp.path());
+
+    audit(tmp.path())
+        .args(["--fail-on", "high"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("RUSTSEC-2024-0001"))
+        .stderr(predicate::str::contains("at or above HIGH severity"));
+}
+
+#[test]
+fn test_audit_fail_on_below_threshold() {
+    let tmp = tempfile::tempdir().unwrap();
+    fixture(tmp.path());
+
+    audit(tmp.path())
+        .args(["--fail-on", "critical"])
+        .assert()
+        .success();
+    audit(tmp.path()).assert().success();
+}
+
+#[test]
+fn test_audit_min_severity() {
+    let tmp = tempfile::tempdir().unwrap();
+    fixture(tmp.path());
+
+    audit(tmp.path())
+        .args(["--min-severity", "critical"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("RUSTSEC-2024-0001").not());
+
+    // Vulnerabilities that aren't displayed still fail the audit.
+    audit(tmp.path())
+        .args(["--min-severity", "critical", "--fail-on", "high"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_audit_invalid_severity() {
+    let tmp = tempfile::tempdir().unwrap();
+    fixture(tmp.path());
+
+    audit(tmp.path())
+        .args(["--fail-on", "severe"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid severity"));
+}
@@ -76,14 +76,19 @@ async fn scan_for_secrets(path: String) -> Result<ScanResult, String> {
 
 #[tauri::command]
 async fn get_system_status() -> Result<SystemStatus, String> {
-    // In production, integrate with actual node
+    // Shares its collection with `secular status`, which queries the node.
+    let status = secular::commands::status::collect(false)
+        .await
+        .map_err(|e| e.to_string())?;
+    let node = status.node.as_ref();
+
     Ok(SystemStatus {
-        node_running: true,
-        uptime_hours: 12.5,
-        peers: 3,
-        repos: 5,
-        current_cost: 5.42,
-        projected_cost: 7.20,
+        node_running: status.node_running,
+        uptime_hours: node.map_or(0.0, |n| n.uptime_hours),
+        peers: node.map_or(0, |n| n.peers),
+        repos: node.map_or(0, |n| n.repos),
+        current_cost: status.costs.current_month,
+        projected_cost: status.costs.projected,
     })
 }
 
@@ -109,21 +114,15 @@ async fn deploy_to_cloud(config: DeployConfig) -> Result<String, String> {
 
 #[tauri::command]
 async fn get_cost_metrics() -> Result<serde_json::Value, String> {
-    Ok(serde_json::json!({
-        "compute": 3.65,
-        "storage": 0.80,
-        "egress": 2.40,
-        "static_ip": 2.88,
-        "total": 9.73,
-        "savings_percent": 68,
-        "history": [
-            { "date": "2025-10-01", "cost": 8.20 },
-            { "date": "2025-10-08", "cost": 9.10 },
-            { "date": "2025-10-15", "cost": 8.85 },
-            { "date": "2025-10-22", "cost": 9.50 },
-            { "date": "2025-10-29", "cost": 9.73 },
-        ]
-    }))
+    use secular::commands::monitor;
+    use std::path::Path;
+
+    // Shares its collection with `secular monitor`, for consistent numbers.
+    let metrics = monitor::collect(Path::new(monitor::DEFAULT_DATA_DIR))
+        .await
+        .map_err(|e| e.to_string())?;
+
+    serde_json::to_value(metrics).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -132,14 +131,8 @@ async fn audit_dependencies(path: String) -> Result<serde_json::Value, String> {
     use std::path::PathBuf;
 
     let scanner = VulnerabilityScanner::new().map_err(|e| e.to_string())?;
-    let lockfile = PathBuf::from(path).join("Cargo.lock");
-
-    if !lockfile.exists() {
-        return Err("Cargo.lock not found".to_string());
-    }
-
     let report = scanner
-        .scan_cargo_lock(&lockfile)
+        .scan_workspace(&PathBuf::from(path))
         .map_err(|e| e.to_string())?;
 
     Ok(serde_json::json!({